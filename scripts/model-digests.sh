@@ -0,0 +1,22 @@
+#!/usr/bin/env bash
+# Print the SHA-256 of every model file in the embedding registry
+# (src/embed/models.rs), as `onnx_sha256` / `tokenizer_sha256` lines to paste
+# into each entry. Needs network access; files are streamed, not kept.
+set -euo pipefail
+
+registry="$(dirname "$0")/../src/embed/models.rs"
+
+grep -oE '(id|onnx_url|tokenizer_url): "[^"]+"' "$registry" | while IFS= read -r line; do
+    field="${line%%:*}"
+    value="${line#*: \"}"
+    value="${value%\"}"
+    case "$field" in
+        id)
+            echo "# $value"
+            ;;
+        onnx_url | tokenizer_url)
+            digest="$(curl -fsSL "$value" | sha256sum | cut -d' ' -f1)"
+            echo "        ${field%_url}_sha256: Some(\"$digest\"),"
+            ;;
+    esac
+done
@@ -68,6 +68,19 @@ impl CapabilityPayload {
 /// Verify and decode a capability token.
 /// Token format: base64url(payload_json).base64url(ed25519_signature)
 pub fn verify_capability_token(token: &str) -> Result<CapabilityPayload, String> {
+    let payload = decode_capability_token(token)?;
+
+    // Check expiry
+    if payload.is_expired() {
+        return Err("Token expired".into());
+    }
+
+    Ok(payload)
+}
+
+/// Check the signature and decode the payload without rejecting expired tokens,
+/// so callers can tell "expired" apart from "missing" or "forged".
+pub fn decode_capability_token(token: &str) -> Result<CapabilityPayload, String> {
     let parts: Vec<&str> = token.splitn(2, '.').collect();
     if parts.len() != 2 {
         return Err("Invalid token format".into());
@@ -86,15 +99,7 @@ pub fn verify_capability_token(token: &str) -> Result<CapabilityPayload, String>
     verifying_key.verify(&payload_bytes, &signature).map_err(|_| "Invalid signature")?;
 
     // Decode payload
-    let payload: CapabilityPayload = serde_json::from_slice(&payload_bytes)
-        .map_err(|e| format!("Invalid payload JSON: {e}"))?;
-
-    // Check expiry
-    if payload.is_expired() {
-        return Err("Token expired".into());
-    }
-
-    Ok(payload)
+    serde_json::from_slice(&payload_bytes).map_err(|e| format!("Invalid payload JSON: {e}"))
 }
 
 fn base64url_decode(input: &str) -> Result<Vec<u8>, String> {
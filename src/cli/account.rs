@@ -16,6 +16,7 @@ struct UserProfile {
     tier_expires_at: Option<String>,
     memory_count: u64,
     device_count: u64,
+    storage_bytes: Option<u64>,
     limits: Limits,
 }
 
@@ -28,27 +29,109 @@ struct Limits {
     consolidation: bool,
 }
 
-pub async fn run(cfg: &Config) -> Result<()> {
-    println!("ctxovrflw account\n");
+/// Share of the memory limit at which `account` starts warning.
+const USAGE_WARN_RATIO: f64 = 0.8;
+
+/// Local vault usage measured against the tier limit.
+struct LocalUsage {
+    count: usize,
+    max: Option<usize>,
+    last_sync: Option<String>,
+}
+
+impl LocalUsage {
+    fn load(cfg: &Config) -> Result<Self> {
+        let conn = crate::db::open()?;
+        Ok(Self {
+            count: crate::db::memories::count(&conn)?,
+            max: cfg.effective_max_memories(),
+            last_sync: crate::db::memories::last_synced_at(&conn)?,
+        })
+    }
+
+    fn ratio(&self) -> Option<f64> {
+        self.max.filter(|m| *m > 0).map(|m| self.count as f64 / m as f64)
+    }
+
+    fn usage_label(&self) -> String {
+        match (self.max, self.ratio()) {
+            (Some(max), Some(ratio)) => format!("{} / {} ({:.0}%)", self.count, max, ratio * 100.0),
+            _ => format!("{} / unlimited", self.count),
+        }
+    }
+
+    fn warning(&self) -> Option<String> {
+        let ratio = self.ratio()?;
+        if ratio < USAGE_WARN_RATIO {
+            return None;
+        }
+        let max = self.max.unwrap_or_default();
+        Some(if self.count >= max {
+            format!("Memory limit reached ({}/{}) — the cloud will reject new memories on push.", self.count, max)
+        } else {
+            format!("Using {:.0}% of your {}-memory limit — pushes are rejected once it is reached.", ratio * 100.0, max)
+        })
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "memories": self.count,
+            "max_memories": self.max,
+            "percent_used": self.ratio().map(|r| (r * 1000.0).round() / 10.0),
+            "last_sync": self.last_sync,
+        })
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        b if b >= 1_048_576 => format!("{:.1} MB", b as f64 / 1_048_576.0),
+        b if b >= 1024 => format!("{:.1} KB", b as f64 / 1024.0),
+        b => format!("{b} B"),
+    }
+}
+
+fn print_local_usage(local: &LocalUsage) {
+    println!("  Usage:           {}", local.usage_label());
+    println!("  Last sync:       {}", local.last_sync.as_deref().unwrap_or("never"));
+    if let Some(warning) = local.warning() {
+        println!("\n  ⚠ {warning}");
+        println!("    Free space with `ctxovrflw forget` or upgrade: https://ctxovrflw.dev/pricing");
+    }
+}
+
+pub async fn run(cfg: &Config, json: bool) -> Result<()> {
+    let local = LocalUsage::load(cfg)?;
 
     if !cfg.is_logged_in() {
+        if json {
+            let out = serde_json::json!({
+                "logged_in": false,
+                "tier": cfg.tier,
+                "local": local.to_json(),
+                "cloud": null,
+                "warnings": local.warning().into_iter().collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&out)?);
+            return Ok(());
+        }
+
+        println!("ctxovrflw account\n");
         println!("  Not logged in.");
         println!("  Run: ctxovrflw login\n");
 
         // Still show local stats
-        let conn = crate::db::open()?;
-        let count = crate::db::memories::count(&conn)?;
-        let max = cfg.effective_max_memories()
-            .map(|m| m.to_string())
-            .unwrap_or_else(|| "unlimited".to_string());
-
         println!("  Local tier:      {:?}", cfg.tier);
-        println!("  Local memories:  {}/{}", count, max);
+        print_local_usage(&local);
         return Ok(());
     }
 
+    if !json {
+        println!("ctxovrflw account\n");
+    }
+
     let api_key = cfg.api_key.as_ref().unwrap();
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
 
     let resp = client
         .get(format!("{}/v1/auth/profile", cfg.cloud_url))
@@ -58,6 +141,17 @@ pub async fn run(cfg: &Config) -> Result<()> {
 
     if !resp.status().is_success() {
         let status = resp.status();
+        if json {
+            let out = serde_json::json!({
+                "logged_in": true,
+                "tier": cfg.tier,
+                "local": local.to_json(),
+                "cloud": { "error": format!("HTTP {status}") },
+                "warnings": local.warning().into_iter().collect::<Vec<_>>(),
+            });
+            println!("{}", serde_json::to_string_pretty(&out)?);
+            return Ok(());
+        }
         println!("  Failed to fetch account info (HTTP {status})");
         println!("  Your API key may be invalid. Try: ctxovrflw logout && ctxovrflw login");
         return Ok(());
@@ -81,11 +175,31 @@ pub async fn run(cfg: &Config) -> Result<()> {
             updated_cfg.capability_token = Some(ct);
         }
         updated_cfg.save()?;
-        if cfg.tier != cloud_tier {
+        if cfg.tier != cloud_tier && !json {
             println!("  ✓ Tier updated locally: {:?} → {:?}\n", cfg.tier, updated_cfg.tier);
         }
     }
 
+    if json {
+        let out = serde_json::json!({
+            "logged_in": true,
+            "tier": cloud_tier,
+            "local": local.to_json(),
+            "cloud": {
+                "email": u.email,
+                "memories": u.memory_count,
+                "max_memories": (u.limits.max_memories >= 0).then_some(u.limits.max_memories),
+                "devices": u.device_count,
+                "max_devices": (u.limits.max_devices >= 0).then_some(u.limits.max_devices),
+                "storage_bytes": u.storage_bytes,
+                "cloud_sync": u.limits.cloud_sync,
+            },
+            "warnings": local.warning().into_iter().collect::<Vec<_>>(),
+        });
+        println!("{}", serde_json::to_string_pretty(&out)?);
+        return Ok(());
+    }
+
     // Tier display
     let tier_label = match u.tier.as_str() {
         "pro" => "Pro ⭐",
@@ -116,6 +230,9 @@ pub async fn run(cfg: &Config) -> Result<()> {
     println!();
     println!("  Memories:        {} / {}", u.memory_count, memories_limit);
     println!("  Devices:         {} / {}", u.device_count, devices_limit);
+    if let Some(bytes) = u.storage_bytes {
+        println!("  Cloud storage:   {}", format_bytes(bytes));
+    }
     println!();
     println!("  Cloud sync:      {}", if u.limits.cloud_sync { "enabled ✓" } else { "disabled" });
     println!("  Synthesis:       {}", if u.limits.context_synthesis { "enabled ✓" } else { "—" });
@@ -123,9 +240,7 @@ pub async fn run(cfg: &Config) -> Result<()> {
 
     // Local state
     println!();
-    let conn = crate::db::open()?;
-    let local_count = crate::db::memories::count(&conn)?;
-    println!("  Local memories:  {}", local_count);
+    println!("  Local memories:  {}", local.count);
     println!("  Device ID:       {}", cfg.device_id.as_deref().unwrap_or("—"));
 
     if cfg.is_encrypted() {
@@ -133,6 +248,9 @@ pub async fn run(cfg: &Config) -> Result<()> {
         println!("  Encryption:      {key_status}");
     }
 
+    println!();
+    print_local_usage(&local);
+
     if u.tier == "free" && !u.limits.cloud_sync {
         println!("\n  💡 Upgrade for cloud sync: https://ctxovrflw.dev/pricing");
     }
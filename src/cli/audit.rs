@@ -0,0 +1,50 @@
+use anyhow::Result;
+
+use crate::config::Config;
+
+/// Show the MCP tool-call audit log, newest first, or export it as JSON lines.
+pub fn run(
+    cfg: &Config,
+    agent: Option<&str>,
+    tool: Option<&str>,
+    since: Option<&str>,
+    limit: usize,
+    json: bool,
+) -> Result<()> {
+    let conn = crate::db::open()?;
+    let limit = (limit > 0).then_some(limit);
+    let entries = crate::db::audit::list(&conn, agent, tool, since, limit)?;
+
+    if json {
+        for entry in &entries {
+            println!("{}", serde_json::to_string(entry)?);
+        }
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No audit log entries.");
+        if !cfg.audit_log {
+            println!("Audit logging is off — set `audit_log = true` in config.toml to record MCP tool calls.");
+        }
+        return Ok(());
+    }
+
+    for entry in entries.iter().rev() {
+        let arguments = entry.arguments.to_string();
+        let preview: String = arguments.chars().take(100).collect();
+        let ellipsis = if arguments.chars().count() > 100 { "…" } else { "" };
+        println!(
+            "{}  {:<14} {:<6} agent={}  {preview}{ellipsis}",
+            entry.at,
+            entry.tool,
+            entry.outcome,
+            entry.agent_id.as_deref().unwrap_or("-"),
+        );
+    }
+    if !cfg.audit_log {
+        println!();
+        println!("Audit logging is currently off — set `audit_log = true` in config.toml to resume recording.");
+    }
+    Ok(())
+}
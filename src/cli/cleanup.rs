@@ -0,0 +1,41 @@
+use anyhow::Result;
+use chrono::SecondsFormat;
+use crate::config::Config;
+
+/// Soft-delete memories of `types` that have gone untouched since `older_than`.
+/// Deletions are ordinary tombstones: restorable until purged, pushed on sync.
+pub fn run(cfg: &Config, older_than: &str, types: &[String], dry_run: bool) -> Result<()> {
+    let before = crate::validation::parse_time(older_than)
+        .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+        .map_err(|e| anyhow::anyhow!("--older-than: {e}"))?;
+    let types = crate::db::memories::parse_types(types)?;
+
+    let conn = crate::db::open()?;
+    let stale = crate::db::memories::list_stale(&conn, &before, &types)?;
+    let type_names = types.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(", ");
+    if stale.is_empty() {
+        println!("No {type_names} memories untouched since {}.", &before[..10]);
+        return Ok(());
+    }
+
+    for memory in &stale {
+        let preview: String = memory.content.chars().take(80).collect();
+        let ellipsis = if memory.content.chars().count() > 80 { "…" } else { "" };
+        println!("[{}] ({}, updated {}) {preview}{ellipsis}", memory.id, memory.memory_type, memory.updated_at.get(..10).unwrap_or(""));
+    }
+    println!();
+
+    if dry_run {
+        println!("Would delete {} memories untouched since {}. Run without --dry-run to delete them.", stale.len(), &before[..10]);
+        return Ok(());
+    }
+
+    let mut deleted = 0;
+    for memory in &stale {
+        if crate::db::memories::delete(&conn, &memory.id, cfg.forget_penalty())? {
+            deleted += 1;
+        }
+    }
+    println!("Deleted {deleted} memories. See them with `ctxovrflw forget --list`; `ctxovrflw restore <id>` brings one back.");
+    Ok(())
+}
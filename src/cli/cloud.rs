@@ -0,0 +1,69 @@
+use anyhow::Result;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+
+/// What `GET /v1/health` reports. Every field is optional so older or
+/// self-hosted backends that return less still pass the reachability check.
+#[derive(serde::Deserialize, Default)]
+struct HealthResponse {
+    status: Option<String>,
+    version: Option<String>,
+    /// Oldest client version the server accepts syncs from.
+    min_client_version: Option<String>,
+}
+
+/// Check the cloud endpoint (`sync --check`): reachable, what version it runs
+/// and whether it accepts this client. Nothing is sent but the health probe.
+pub async fn check(cfg: &Config) -> Result<()> {
+    println!("Cloud endpoint: {}", cfg.cloud_url);
+
+    let started = Instant::now();
+    let resp = crate::config::http_client()?
+        .get(format!("{}/v1/health", cfg.cloud_url))
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await;
+    let resp = match resp {
+        Ok(resp) => resp,
+        Err(e) => {
+            println!("  ✗ Unreachable: {e}");
+            anyhow::bail!("cloud endpoint {} is unreachable", cfg.cloud_url);
+        }
+    };
+    let elapsed = started.elapsed().as_millis();
+    let status = resp.status();
+    if !status.is_success() {
+        println!("  ✗ /v1/health returned {status} ({elapsed} ms)");
+        if status == reqwest::StatusCode::NOT_FOUND {
+            println!("    Is this a ctxovrflw backend? Check the URL, including any path prefix.");
+        }
+        anyhow::bail!("cloud endpoint {} failed its health check", cfg.cloud_url);
+    }
+    println!("  ✓ Reachable ({elapsed} ms)");
+
+    let health: HealthResponse = resp.json().await.unwrap_or_default();
+    if let Some(state) = health.status.as_deref()
+        && state != "ok"
+    {
+        println!("  ⚠ Server reports status: {state}");
+    }
+    println!("  Server version: {}", health.version.as_deref().unwrap_or("unknown"));
+
+    let client_version = env!("CARGO_PKG_VERSION");
+    match health.min_client_version.as_deref() {
+        Some(min) if super::update::is_newer(min) => {
+            println!("  ✗ This client (v{client_version}) is older than the server's minimum (v{min})");
+            println!("    Run `ctxovrflw update` before syncing.");
+            anyhow::bail!("client v{client_version} is not compatible with {}", cfg.cloud_url);
+        }
+        Some(min) => println!("  ✓ Compatible: client v{client_version}, server accepts v{min} and newer"),
+        None => println!("  ✓ Compatible: server sets no minimum client version"),
+    }
+
+    if !cfg.is_logged_in() {
+        println!();
+        println!("Not logged in — run `ctxovrflw login` (with the same --cloud-url) before syncing.");
+    }
+    Ok(())
+}
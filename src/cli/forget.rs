@@ -1,7 +1,7 @@
 use anyhow::Result;
 use crate::config::Config;
 
-pub async fn run(_cfg: &Config, id: &str, dry_run: bool) -> Result<()> {
+pub async fn run(cfg: &Config, id: &str, dry_run: bool) -> Result<()> {
     let conn = crate::db::open()?;
 
     if dry_run {
@@ -14,7 +14,7 @@ pub async fn run(_cfg: &Config, id: &str, dry_run: bool) -> Result<()> {
         return Ok(());
     }
 
-    if crate::db::memories::delete(&conn, id)? {
+    if crate::db::memories::delete(&conn, id, cfg.forget_penalty())? {
         println!("Deleted memory {id}.");
     } else {
         println!("Memory {id} not found.");
@@ -22,3 +22,29 @@ pub async fn run(_cfg: &Config, id: &str, dry_run: bool) -> Result<()> {
 
     Ok(())
 }
+
+/// List tombstoned memories that `restore` can still bring back.
+pub async fn list(cfg: &Config) -> Result<()> {
+    let conn = crate::db::open()?;
+    let deleted = crate::db::memories::list_deleted(&conn, 100)?;
+
+    if deleted.is_empty() {
+        println!("No deleted memories to restore.");
+        return Ok(());
+    }
+
+    let window = match cfg.tombstone_retention_days {
+        0 => "until the next sync".to_string(),
+        days => format!("{days} days after sync"),
+    };
+    println!("Deleted memories (restorable until purged — {window}, 1 day if never synced):");
+    println!();
+    for memory in &deleted {
+        let preview: String = memory.content.chars().take(80).collect();
+        println!("[{}] (deleted {}) {}", memory.id, memory.updated_at, preview);
+    }
+    println!();
+    println!("Restore with: ctxovrflw restore <id>");
+
+    Ok(())
+}
@@ -1,20 +1,48 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 
 use crate::db;
 use crate::db::graph;
 
 /// Build knowledge graph from existing memories by extracting entities
-/// from subject fields and namespaced tags.
-pub fn build() -> Result<()> {
+/// from subject fields, namespaced tags and (with `entity_extraction`) content.
+pub fn build(cfg: &crate::config::Config) -> Result<()> {
     let conn = db::open()?;
     let memories = load_all_memories(&conn)?;
-
     let total = memories.len();
-    let mut entities_created = 0usize;
-    let mut relations_created = 0usize;
-    let mut skipped = 0usize;
 
     println!("Scanning {} memories for graph entities...\n", total);
+    let report = extract_all(&conn, cfg, &memories)?;
+    println!("\n");
+
+    let entity_count = graph::count_entities(&conn).unwrap_or(0);
+    let relation_count = graph::count_relations(&conn).unwrap_or(0);
+
+    println!("✓ Graph built from {} memories", total);
+    println!("  Entities: {} new (total in graph: {})", report.entities_created, entity_count);
+    println!("  Relations: {} new (total in graph: {})", report.relations_created, relation_count);
+    println!("  Skipped: {} (no subject, tags or content entities to extract)", report.skipped);
+
+    Ok(())
+}
+
+/// What a build added to the graph.
+#[derive(Debug, Default)]
+struct BuildReport {
+    /// Entities inserted; re-running over known memories upserts, adding none
+    entities_created: usize,
+    relations_created: usize,
+    /// Memories with nothing to extract
+    skipped: usize,
+}
+
+/// Extract entities and `mentioned_in` links from every memory. New entity and
+/// relation counts come from the graph itself, so upserts of existing rows
+/// (or the same entity named by many memories) aren't counted.
+fn extract_all(conn: &rusqlite::Connection, cfg: &crate::config::Config, memories: &[MemoryRecord]) -> Result<BuildReport> {
+    let total = memories.len();
+    let entities_before = graph::count_entities(conn)?;
+    let relations_before = graph::count_relations(conn)?;
+    let mut report = BuildReport::default();
 
     for (i, mem) in memories.iter().enumerate() {
         let mut extracted = false;
@@ -28,9 +56,8 @@ pub fn build() -> Result<()> {
             };
 
             if !entity_name.is_empty() {
-                match graph::upsert_entity(&conn, &entity_name, &entity_type, None) {
+                match graph::upsert_entity(conn, &entity_name, &entity_type, None) {
                     Ok(entity) => {
-                        entities_created += 1;
                         extracted = true;
 
                         // Create a "memory" entity for this memory and link them
@@ -41,22 +68,20 @@ pub fn build() -> Result<()> {
                         };
                         let meta = serde_json::json!({ "preview": short_content });
                         if let Ok(mem_entity) = graph::upsert_entity(
-                            &conn,
+                            conn,
                             &mem.id,
                             "memory",
                             Some(&meta),
                         ) {
-                            if graph::upsert_relation(
-                                &conn,
+                            let _ = graph::upsert_relation(
+                                conn,
                                 &entity.id,
                                 &mem_entity.id,
                                 "mentioned_in",
                                 1.0,
                                 Some(&mem.id),
                                 None,
-                            ).is_ok() {
-                                relations_created += 1;
-                            }
+                            );
                         }
                     }
                     Err(_) => {}
@@ -70,16 +95,22 @@ pub fn build() -> Result<()> {
                 let ns = ns.trim().to_lowercase();
                 let value = value.trim().to_string();
                 if !value.is_empty() && !ns.is_empty() {
-                    if graph::upsert_entity(&conn, &value, &ns, None).is_ok() {
-                        entities_created += 1;
+                    if graph::upsert_entity(conn, &value, &ns, None).is_ok() {
                         extracted = true;
                     }
                 }
             }
         }
 
+        // Extract from content (low confidence; reuses the entities above)
+        if let Ok(linked) = crate::entities::link_mentions(conn, &mem.id, &mem.content, cfg.entity_extraction)
+            && linked > 0
+        {
+            extracted = true;
+        }
+
         if !extracted {
-            skipped += 1;
+            report.skipped += 1;
         }
 
         // Progress indicator every 50 memories
@@ -88,18 +119,9 @@ pub fn build() -> Result<()> {
         }
     }
 
-    println!("\n");
-
-    // Deduplicate counts (upsert means some are updates, not creates)
-    let entity_count = graph::count_entities(&conn).unwrap_or(0);
-    let relation_count = graph::count_relations(&conn).unwrap_or(0);
-
-    println!("✓ Graph built from {} memories", total);
-    println!("  Entities: {} (total in graph: {})", entities_created, entity_count);
-    println!("  Relations: {} (total in graph: {})", relations_created, relation_count);
-    println!("  Skipped: {} (no subject or tags to extract)", skipped);
-
-    Ok(())
+    report.entities_created = graph::count_entities(conn)?.saturating_sub(entities_before);
+    report.relations_created = graph::count_relations(conn)?.saturating_sub(relations_before);
+    Ok(report)
 }
 
 /// Show knowledge graph statistics.
@@ -132,6 +154,39 @@ pub fn stats() -> Result<()> {
     Ok(())
 }
 
+/// Seed the graph from a JSON document of entities and relations.
+pub fn import(file: &str) -> Result<()> {
+    let raw = if file == "-" {
+        let mut buf = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf)?;
+        buf
+    } else {
+        std::fs::read_to_string(file).with_context(|| format!("Failed to read {file}"))?
+    };
+    let doc: graph::ImportDocument = serde_json::from_str(&raw).context("Invalid graph import document")?;
+
+    let conn = db::open()?;
+    let report = graph::import(&conn, &doc)?;
+
+    println!("✓ Graph import complete");
+    println!("  Entities:  {} created, {} updated", report.entities_created, report.entities_updated);
+    println!("  Relations: {} created, {} updated", report.relations_created, report.relations_updated);
+    if !report.unresolved.is_empty() {
+        println!("\n  Unresolved endpoints ({}), relations skipped:", report.unresolved.len());
+        for name in &report.unresolved {
+            println!("    {name}");
+        }
+    }
+    if !report.errors.is_empty() {
+        println!("\n  Rejected entries ({}):", report.errors.len());
+        for err in &report.errors {
+            println!("    {err}");
+        }
+    }
+
+    Ok(())
+}
+
 struct MemoryRecord {
     id: String,
     content: String,
@@ -141,7 +196,7 @@ struct MemoryRecord {
 
 fn load_all_memories(conn: &rusqlite::Connection) -> Result<Vec<MemoryRecord>> {
     let mut stmt = conn.prepare(
-        "SELECT id, content, subject, tags FROM memories WHERE deleted = 0
+        "SELECT id, content, subject, tags FROM memories WHERE deleted = 0 AND in_workspace(workspace)
          AND (expires_at IS NULL OR expires_at > datetime('now'))"
     )?;
 
@@ -158,3 +213,34 @@ fn load_all_memories(conn: &rusqlite::Connection) -> Result<Vec<MemoryRecord>> {
 
     Ok(results)
 }
+
+#[cfg(all(test, feature = "pro"))]
+mod tests {
+    use super::*;
+    use crate::db::memories::MemoryType;
+
+    #[test]
+    fn test_build_counts_only_inserted_entities() {
+        let _data = crate::config::test_data_dir();
+        let conn = db::open().unwrap();
+        let cfg = crate::config::Config::default();
+        let store = |content: &str, subject: Option<&str>, tags: &[&str]| {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            db::memories::store(&conn, content, &MemoryType::Semantic, &tags, subject, None, None, None).unwrap();
+        };
+        // Both name project:atlas; the tag repeats the subject
+        store("atlas deploys on fridays", Some("project:atlas"), &["project:atlas"]);
+        store("atlas uses postgres", Some("project:atlas"), &["lang:rust"]);
+        store("plain note", None, &[]);
+
+        let first = extract_all(&conn, &cfg, &load_all_memories(&conn).unwrap()).unwrap();
+        // atlas, rust, and one memory node per subject-bearing memory
+        assert_eq!(first.entities_created, graph::count_entities(&conn).unwrap());
+        assert_eq!(first.entities_created, 4);
+        assert_eq!(first.relations_created, 2);
+
+        let again = extract_all(&conn, &cfg, &load_all_memories(&conn).unwrap()).unwrap();
+        assert_eq!((again.entities_created, again.relations_created), (0, 0));
+        assert_eq!(again.skipped, first.skipped);
+    }
+}
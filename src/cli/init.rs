@@ -1,9 +1,10 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use console::style;
 use dialoguer::{Confirm, MultiSelect};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::config::Config;
+use super::InitScope;
 
 // ── Agent Registry ───────────────────────────────────────────
 
@@ -17,6 +18,9 @@ pub(crate) struct AgentDef {
     pub(crate) cli_install: Option<&'static str>,
     /// Global rules file path (relative to home dir)
     pub(crate) global_rules_path: Option<&'static str>,
+    /// Project-level MCP config paths (relative to the project root), used by
+    /// `init --scope project`: the first that exists, else the first listed
+    pub(crate) project_config_paths: &'static [&'static str],
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +52,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[],
         cli_install: Some("claude mcp add --transport sse --scope user ctxovrflw http://127.0.0.1:{port}/mcp/sse"),
         global_rules_path: Some(".claude/CLAUDE.md"),
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Claude Desktop",
@@ -59,6 +64,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Cursor",
@@ -66,6 +72,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[ConfigLocation::Home(".cursor/mcp.json")],
         cli_install: None,
         global_rules_path: Some(".cursorrules"),
+        project_config_paths: &[".cursor/mcp.json", ".mcp.json"],
     },
     AgentDef {
         name: "Cline",
@@ -76,6 +83,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: Some(".cline/.clinerules"),
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Roo Code",
@@ -86,6 +94,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: Some(".roo-code/.roorules"),
+        project_config_paths: &[".roo/mcp.json"],
     },
     AgentDef {
         name: "Windsurf",
@@ -93,6 +102,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[ConfigLocation::Home(".windsurf/mcp.json")],
         cli_install: None,
         global_rules_path: Some(".windsurf/.windsurfrules"),
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Continue",
@@ -103,6 +113,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Codex CLI",
@@ -113,6 +124,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: Some(".codex/codex.md"),
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Goose",
@@ -123,6 +135,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Gemini CLI",
@@ -133,6 +146,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: Some(".gemini/.gemini_rules"),
+        project_config_paths: &[".gemini/settings.json"],
     },
     AgentDef {
         name: "Antigravity",
@@ -140,6 +154,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[ConfigLocation::Home(".antigravity/mcp.json")],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Amp",
@@ -150,6 +165,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Kiro",
@@ -157,6 +173,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[ConfigLocation::Home(".kiro/mcp.json")],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[".kiro/settings/mcp.json"],
     },
     AgentDef {
         name: "OpenCode",
@@ -167,6 +184,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Trae",
@@ -174,6 +192,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[ConfigLocation::Home(".trae/mcp.json")],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[".trae/mcp.json"],
     },
     AgentDef {
         name: "Kilo Code",
@@ -184,6 +203,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "Factory (Drip)",
@@ -194,6 +214,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         ],
         cli_install: None,
         global_rules_path: None,
+        project_config_paths: &[],
     },
     AgentDef {
         name: "GitHub Copilot",
@@ -201,6 +222,8 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[],
         cli_install: None,
         global_rules_path: Some(".github/copilot-instructions.md"),
+        // VS Code only reads `servers` from `.vscode/mcp.json`, not a root `.mcp.json`
+        project_config_paths: &[".vscode/mcp.json"],
     },
     AgentDef {
         name: "OpenClaw",
@@ -208,6 +231,7 @@ pub(crate) const AGENTS: &[AgentDef] = &[
         config_paths: &[],
         cli_install: None,
         global_rules_path: Some(".openclaw/workspace/AGENTS.md"),
+        project_config_paths: &[],
     },
 ];
 
@@ -216,11 +240,15 @@ pub(crate) const AGENTS: &[AgentDef] = &[
 pub(crate) struct DetectedAgent {
     pub(crate) def: &'static AgentDef,
     pub(crate) config_path: Option<PathBuf>,
+    pub(crate) scope: InitScope,
+    /// A project-local config for this agent already exists in the current directory
+    pub(crate) project_config_exists: bool,
 }
 
-pub(crate) fn detect_agents() -> Vec<DetectedAgent> {
+pub(crate) fn detect_agents(scope: InitScope) -> Vec<DetectedAgent> {
     let home = dirs::home_dir().unwrap_or_default();
     let config_dir = dirs::config_dir().unwrap_or_default();
+    let project_root = std::env::current_dir().unwrap_or_default();
     let mut found = Vec::new();
 
     for def in AGENTS {
@@ -254,20 +282,43 @@ pub(crate) fn detect_agents() -> Vec<DetectedAgent> {
             path.exists()
         });
 
-        if !detected {
-            continue;
-        }
+        let project_paths: Vec<PathBuf> = def.project_config_paths.iter().map(|rel| project_root.join(rel)).collect();
+        let existing_project_path = project_paths.iter().find(|p| p.exists());
+        let project_config_exists = existing_project_path.is_some();
+        let project_path = existing_project_path.or(project_paths.first()).cloned();
 
-        let config_path = def.config_paths.iter().find_map(|loc| {
-            let path = resolve_config_path(loc);
-            if path.exists() || path.parent().map(|p| p.exists()).unwrap_or(false) {
-                Some(path)
-            } else {
-                None
+        let config_path = match scope {
+            InitScope::Global => {
+                if !detected {
+                    continue;
+                }
+                def.config_paths.iter().find_map(|loc| {
+                    let path = resolve_config_path(loc);
+                    if path.exists() || path.parent().map(|p| p.exists()).unwrap_or(false) {
+                        Some(path)
+                    } else {
+                        None
+                    }
+                })
             }
-        });
+            InitScope::Project => {
+                // A repo that already carries the tool's project dir counts as detected
+                let in_project = project_paths
+                    .iter()
+                    .filter_map(|p| p.parent())
+                    .any(|p| p != project_root && p.exists());
+                if !(detected || in_project) {
+                    continue;
+                }
+                // Only agents with a project-level config (or a scoped CLI) can be set up per-repo
+                if project_path.is_none() && def.cli_install.is_none() {
+                    continue;
+                }
+                project_path
+            }
+        };
 
-        found.push(DetectedAgent { def, config_path });
+        found.push(DetectedAgent { def, config_path, scope, project_config_exists });
     }
 
     found
@@ -321,7 +372,8 @@ pub(crate) fn which(cmd: &str) -> bool {
 
 // ── Installation ─────────────────────────────────────────────
 
-pub(crate) fn mcp_sse_url(cfg: &Config) -> String {
+/// The SSE endpoint, as shown to the user.
+pub(crate) fn mcp_sse_endpoint(cfg: &Config) -> String {
     if let Some(ref remote) = cfg.remote_daemon_url {
         format!("{}/mcp/sse", remote.trim_end_matches('/'))
     } else {
@@ -329,20 +381,40 @@ pub(crate) fn mcp_sse_url(cfg: &Config) -> String {
     }
 }
 
+/// The SSE URL agents are configured with. Carries the auth token as
+/// `?token=`, since not every agent can send an Authorization header.
+pub(crate) fn mcp_sse_url(cfg: &Config) -> String {
+    let url = mcp_sse_endpoint(cfg);
+    match &cfg.auth_token {
+        Some(token) => format!("{url}?token={token}"),
+        None => url,
+    }
+}
+
 pub(crate) fn sse_mcp_json(cfg: &Config) -> serde_json::Value {
     serde_json::json!({
         "url": mcp_sse_url(cfg)
     })
 }
 
+/// Build the CLI install command for an agent, honouring the init scope.
+/// Returns None for agents configured through JSON files.
+pub(crate) fn cli_install_command(agent: &DetectedAgent, cfg: &Config) -> Option<String> {
+    let cmd_template = agent.def.cli_install?;
+    let cmd = cmd_template
+        .replace("http://127.0.0.1:{port}/mcp/sse", &mcp_sse_url(cfg))
+        .replace("{port}", &cfg.port.to_string());
+    Some(match agent.scope {
+        InitScope::Global => cmd,
+        InitScope::Project => cmd.replace("--scope user", "--scope project"),
+    })
+}
+
 fn install_agent(agent: &DetectedAgent, cfg: &Config) -> Result<()> {
     let url = mcp_sse_url(cfg);
 
     // CLI-based install (e.g., Claude Code)
-    if let Some(cmd_template) = agent.def.cli_install {
-        let cmd = cmd_template
-            .replace("{port}", &cfg.port.to_string())
-            .replace("http://127.0.0.1:{port}/mcp/sse", &url);
+    if let Some(cmd) = cli_install_command(agent, cfg) {
         println!("  {} {}", style("→").dim(), style(&cmd).dim());
         let parts: Vec<&str> = cmd.split_whitespace().collect();
         if parts.len() >= 2 {
@@ -365,7 +437,7 @@ fn install_agent(agent: &DetectedAgent, cfg: &Config) -> Result<()> {
     }
 
     // No config path available — manual instructions
-    if agent.def.config_paths.is_empty() {
+    if agent.config_path.is_none() && agent.def.config_paths.is_empty() {
         println!(
             "  {} {} — add MCP server URL manually:",
             style("ℹ").blue(),
@@ -384,6 +456,23 @@ fn install_agent(agent: &DetectedAgent, cfg: &Config) -> Result<()> {
     write_mcp_config(&config_path, &mcp_entry, agent.def.name)
 }
 
+/// Top-level key the MCP config at `path` lists servers under: VS Code's
+/// `.vscode/mcp.json` uses `servers`, every other format `mcpServers`.
+pub(crate) fn servers_key(path: &Path) -> &'static str {
+    let in_vscode_dir = path.parent().and_then(|p| p.file_name()).is_some_and(|dir| dir == ".vscode");
+    if in_vscode_dir { "servers" } else { "mcpServers" }
+}
+
+/// `mcp_entry` as the config at `path` expects it — VS Code's format also
+/// needs the transport `type`.
+pub(crate) fn mcp_entry_for(path: &Path, mcp_entry: &serde_json::Value) -> serde_json::Value {
+    let mut entry = mcp_entry.clone();
+    if servers_key(path) == "servers" {
+        entry["type"] = serde_json::json!("sse");
+    }
+    entry
+}
+
 pub(crate) fn write_mcp_config(
     path: &PathBuf,
     mcp_entry: &serde_json::Value,
@@ -399,11 +488,12 @@ pub(crate) fn write_mcp_config(
         serde_json::json!({})
     };
 
-    if config.get("mcpServers").is_none() {
-        config["mcpServers"] = serde_json::json!({});
+    let key = servers_key(path);
+    if config.get(key).is_none() {
+        config[key] = serde_json::json!({});
     }
 
-    if config["mcpServers"].get("ctxovrflw").is_some() {
+    if config[key].get("ctxovrflw").is_some() {
         let overwrite = Confirm::new()
             .with_prompt(format!("  {} already configured — overwrite?", agent_name))
             .default(false)
@@ -414,7 +504,7 @@ pub(crate) fn write_mcp_config(
         }
     }
 
-    config["mcpServers"]["ctxovrflw"] = mcp_entry.clone();
+    config[key]["ctxovrflw"] = mcp_entry_for(path, mcp_entry);
 
     let formatted = serde_json::to_string_pretty(&config)?;
     std::fs::write(path, formatted)?;
@@ -621,13 +711,19 @@ pub(crate) fn replace_ctxovrflw_section(content: &str, new_rules: &str) -> Strin
 /// The bundled SKILL.md content (included at compile time from skill/SKILL.md)
 pub(crate) const SKILL_MD: &str = include_str!("../../skill/SKILL.md");
 
-/// Install the ctxovrflw Agent Skill to ~/.skills/ctxovrflw/
+/// Install the ctxovrflw Agent Skill to ~/.skills/ctxovrflw/.
+/// Returns false when the installed SKILL.md is already current.
 pub(crate) fn install_agent_skill() -> Result<bool> {
     let home = dirs::home_dir().unwrap_or_default();
     let skill_dir = home.join(".skills").join("ctxovrflw");
+    let skill_path = skill_dir.join("SKILL.md");
+
+    if std::fs::read_to_string(&skill_path).is_ok_and(|existing| existing == SKILL_MD) {
+        return Ok(false);
+    }
 
     std::fs::create_dir_all(&skill_dir)?;
-    std::fs::write(skill_dir.join("SKILL.md"), SKILL_MD)?;
+    std::fs::write(skill_path, SKILL_MD)?;
 
     // Success is handled by caller (TUI or CLI)
     Ok(true)
@@ -635,7 +731,7 @@ pub(crate) fn install_agent_skill() -> Result<bool> {
 
 // ── Main init flow ───────────────────────────────────────────
 
-pub async fn run(cfg: &Config) -> Result<()> {
+pub async fn run(cfg: &Config, scope: InitScope) -> Result<()> {
     println!();
     println!("  {}", style("🧠 ctxovrflw").bold().cyan());
     println!("  {}", style("Universal AI Context Layer").dim());
@@ -653,6 +749,12 @@ pub async fn run(cfg: &Config) -> Result<()> {
     } else {
         println!("  {} Config loaded", style("✓").green());
     }
+    // Agents are configured with the daemon's auth token in their MCP URL
+    let mut cfg = cfg.clone();
+    if !cfg.is_remote_client() {
+        cfg.ensure_auth_token()?;
+    }
+    let cfg = &cfg;
 
     // 3. Database
     let _conn = crate::db::open()?;
@@ -692,9 +794,10 @@ pub async fn run(cfg: &Config) -> Result<()> {
             let lib_name = if cfg!(target_os = "macos") { "libonnxruntime.dylib" } else { "libonnxruntime.so" };
             let mut found = false;
             if let Some(home) = dirs::home_dir() {
+                let data_dir = Config::data_dir().unwrap_or_else(|_| home.join(".ctxovrflw"));
                 let paths = [
-                    home.join(".ctxovrflw").join("lib").join(lib_name),
-                    home.join(".ctxovrflw").join("bin").join(lib_name),
+                    data_dir.join("lib").join(lib_name),
+                    data_dir.join("bin").join(lib_name),
                     home.join(".local").join("lib").join(lib_name),
                     home.join(".cargo").join("bin").join(lib_name),
                 ];
@@ -722,9 +825,13 @@ pub async fn run(cfg: &Config) -> Result<()> {
     // 5. Detect AI tools
     println!();
     println!("  {}", style("Scanning for AI tools...").bold());
+    if scope == InitScope::Project {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        println!("  {} Writing project-local configs in {}", style("ℹ").blue(), style(cwd.display()).dim());
+    }
     println!();
 
-    let agents = detect_agents();
+    let agents = detect_agents(scope);
 
     let selections: Vec<usize> = if agents.is_empty() {
         println!("  {} No AI tools detected.", style("ℹ").blue());
@@ -739,7 +846,13 @@ pub async fn run(cfg: &Config) -> Result<()> {
         // Multi-select with all checked by default
         let agent_names: Vec<String> = agents
             .iter()
-            .map(|a| a.def.name.to_string())
+            .map(|a| {
+                if a.project_config_exists {
+                    format!("{} (project config exists)", a.def.name)
+                } else {
+                    a.def.name.to_string()
+                }
+            })
             .collect();
         let defaults: Vec<bool> = agents.iter().map(|_| true).collect();
 
@@ -772,7 +885,7 @@ pub async fn run(cfg: &Config) -> Result<()> {
             println!(
                 "  {} tools connect via {}",
                 style("ℹ").blue(),
-                style(mcp_sse_url(cfg)).underlined()
+                style(mcp_sse_endpoint(cfg)).underlined()
             );
         }
         sels
@@ -957,7 +1070,7 @@ pub async fn run(cfg: &Config) -> Result<()> {
 
         if enable_cloud {
             println!();
-            if let Err(e) = crate::cli::login::run_inner(cfg, true, None).await {
+            if let Err(e) = crate::cli::login::run_inner(cfg, true, crate::cli::login::LoginMethod::Auto).await {
                 println!("  {} Cloud setup failed: {e}", style("⚠").yellow());
                 println!("  {} You can set it up later: {}", style("ℹ").blue(), style("ctxovrflw login").bold());
             }
@@ -1223,14 +1336,18 @@ async fn integrate_openclaw(cfg: &Config) -> Result<()> {
     Ok(())
 }
 
-/// Inject or update the ctxovrflw memory section in AGENTS.md
-pub(crate) fn inject_openclaw_agents_md(path: &PathBuf) -> Result<()> {
+/// Inject or update the ctxovrflw memory section in AGENTS.md.
+/// Returns false when the section is already current and nothing was written.
+pub(crate) fn inject_openclaw_agents_md(path: &PathBuf) -> Result<bool> {
     if path.exists() {
         let content = std::fs::read_to_string(path)?;
 
         if content.contains(CTXOVRFLW_RULES_MARKER) {
             // Already present — update in place
             let updated = replace_ctxovrflw_section(&content, OPENCLAW_AGENTS_SECTION);
+            if updated == content {
+                return Ok(false);
+            }
             std::fs::write(path, updated)?;
         } else {
             // Find the right place to inject: after ## Memory section if it exists, 
@@ -1280,7 +1397,7 @@ pub(crate) fn inject_openclaw_agents_md(path: &PathBuf) -> Result<()> {
         std::fs::write(path, content)?;
     }
 
-    Ok(())
+    Ok(true)
 }
 
 /// Migrate OpenClaw workspace files into ctxovrflw memories.
@@ -1529,45 +1646,47 @@ pub(crate) fn store_migrated_memory(
         embedding.as_deref(),
         None,
         None,
+        false,
     )?;
 
     Ok(())
 }
 
 pub(crate) async fn download_model() -> Result<()> {
-    let model_dir = Config::model_dir()?;
-
-    let model_url =
-        "https://huggingface.co/Xenova/all-MiniLM-L6-v2/resolve/main/onnx/model_quantized.onnx";
-    let tokenizer_url =
-        "https://huggingface.co/Xenova/all-MiniLM-L6-v2/resolve/main/tokenizer.json";
+    use crate::embed::download;
 
-    let client = reqwest::Client::builder()
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()?;
-
-    let resp = client.get(model_url).send().await?;
-    if !resp.status().is_success() {
-        anyhow::bail!("Failed to download model: HTTP {}", resp.status());
-    }
-    let model_bytes = resp.bytes().await?;
+    let model_dir = Config::model_dir()?;
+    let model = crate::embed::models::get_model("all-MiniLM-L6-v2")
+        .ok_or_else(|| anyhow::anyhow!("Default model missing from registry"))?;
+
+    let bar = indicatif::ProgressBar::new(0);
+    bar.set_style(
+        indicatif::ProgressStyle::with_template(
+            "  {bar:30.cyan/dim} {percent:>3}%  {bytes}/{total_bytes}  {bytes_per_sec}  eta {eta}",
+        )?
+        .progress_chars("━╸─"),
+    );
 
-    if model_bytes.len() < 1_000_000 {
-        anyhow::bail!(
-            "Model file too small ({} bytes) — likely a redirect/error page.",
-            model_bytes.len()
-        );
-    }
-    if model_bytes.starts_with(b"<!") || model_bytes.starts_with(b"<html") {
-        anyhow::bail!("Downloaded HTML instead of ONNX model.");
-    }
-    std::fs::write(model_dir.join("all-MiniLM-L6-v2-q8.onnx"), &model_bytes)?;
+    // Streams to a .part file and resumes if the connection drops
+    let result = download::fetch_to_file(
+        model.onnx_url,
+        model.onnx_sha256,
+        &model_dir.join("all-MiniLM-L6-v2-q8.onnx"),
+        1_000_000,
+        |done, total| {
+            if let Some(total) = total {
+                bar.set_length(total);
+            }
+            bar.set_position(done);
+        },
+    )
+    .await;
+    bar.finish_and_clear();
+    result.context("Failed to download model")?;
 
-    let resp = client.get(tokenizer_url).send().await?;
-    if !resp.status().is_success() {
-        anyhow::bail!("Failed to download tokenizer: HTTP {}", resp.status());
-    }
-    let tokenizer_bytes = resp.bytes().await?;
+    let tokenizer_bytes = download::fetch(model.tokenizer_url, model.tokenizer_sha256)
+        .await
+        .context("Failed to download tokenizer")?;
 
     if serde_json::from_slice::<serde_json::Value>(&tokenizer_bytes).is_err() {
         anyhow::bail!("Downloaded tokenizer is not valid JSON.");
@@ -1608,10 +1727,7 @@ async fn download_ort_runtime() -> Result<std::path::PathBuf> {
         "https://github.com/microsoft/onnxruntime/releases/download/v{ORT_VERSION}/{archive_name}.{ext}"
     );
 
-    let dest_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Cannot determine home directory"))?
-        .join(".ctxovrflw")
-        .join("lib");
+    let dest_dir = Config::data_dir()?.join("lib");
     std::fs::create_dir_all(&dest_dir)?;
 
     let dest_path = dest_dir.join(lib_name);
@@ -1619,9 +1735,7 @@ async fn download_ort_runtime() -> Result<std::path::PathBuf> {
         return Ok(dest_path);
     }
 
-    let client = reqwest::Client::builder()
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()?;
+    let client = crate::config::http_client()?;
 
     let resp = client.get(&url).send().await?;
     if !resp.status().is_success() {
@@ -1680,3 +1794,74 @@ async fn download_ort_runtime() -> Result<std::path::PathBuf> {
 
     Ok(dest_path)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mcp_sse_url_carries_token() {
+        let mut cfg = Config { port: 7437, ..Config::default() };
+        assert_eq!(mcp_sse_url(&cfg), "http://127.0.0.1:7437/mcp/sse");
+
+        cfg.auth_token = Some("abc123".into());
+        assert_eq!(mcp_sse_url(&cfg), "http://127.0.0.1:7437/mcp/sse?token=abc123");
+        assert_eq!(mcp_sse_endpoint(&cfg), "http://127.0.0.1:7437/mcp/sse");
+
+        cfg.remote_daemon_url = Some("http://10.0.0.5:7437/".into());
+        assert_eq!(mcp_sse_url(&cfg), "http://10.0.0.5:7437/mcp/sse?token=abc123");
+    }
+
+    #[test]
+    fn test_servers_key_by_format() {
+        assert_eq!(servers_key(Path::new("/repo/.vscode/mcp.json")), "servers");
+        assert_eq!(servers_key(Path::new("/repo/.mcp.json")), "mcpServers");
+        assert_eq!(servers_key(Path::new("/repo/.cursor/mcp.json")), "mcpServers");
+        assert_eq!(servers_key(Path::new("/home/me/.cursor/mcp.json")), "mcpServers");
+        assert_eq!(servers_key(Path::new("mcp.json")), "mcpServers");
+    }
+
+    #[test]
+    fn test_mcp_entry_for_adds_vscode_transport() {
+        let entry = serde_json::json!({ "url": "http://127.0.0.1:7437/mcp/sse" });
+        assert_eq!(
+            mcp_entry_for(Path::new("/repo/.vscode/mcp.json"), &entry),
+            serde_json::json!({ "url": "http://127.0.0.1:7437/mcp/sse", "type": "sse" })
+        );
+        assert_eq!(mcp_entry_for(Path::new("/repo/.mcp.json"), &entry), entry);
+        assert_eq!(mcp_entry_for(Path::new("/repo/.cursor/mcp.json"), &entry), entry);
+    }
+
+    #[test]
+    fn test_copilot_project_configs_use_vscode_format() {
+        let copilot = AGENTS.iter().find(|a| a.name == "GitHub Copilot").unwrap();
+        assert!(!copilot.project_config_paths.is_empty());
+        for rel in copilot.project_config_paths {
+            assert_eq!(servers_key(Path::new(rel)), "servers", "{rel}");
+        }
+        let cursor = AGENTS.iter().find(|a| a.name == "Cursor").unwrap();
+        for rel in cursor.project_config_paths {
+            assert_eq!(servers_key(Path::new(rel)), "mcpServers", "{rel}");
+        }
+    }
+
+    #[test]
+    fn test_write_mcp_config_uses_the_file_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let entry = serde_json::json!({ "url": "http://127.0.0.1:7437/mcp/sse" });
+
+        let vscode = dir.path().join(".vscode/mcp.json");
+        write_mcp_config(&vscode, &entry, "GitHub Copilot").unwrap();
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&vscode).unwrap()).unwrap();
+        assert_eq!(written["servers"]["ctxovrflw"]["type"], "sse");
+        assert!(written.get("mcpServers").is_none());
+
+        let root = dir.path().join(".mcp.json");
+        std::fs::write(&root, r#"{ "mcpServers": { "other": { "command": "x" } } }"#).unwrap();
+        write_mcp_config(&root, &entry, "Cursor").unwrap();
+        let written: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&root).unwrap()).unwrap();
+        assert_eq!(written["mcpServers"]["ctxovrflw"], entry);
+        assert_eq!(written["mcpServers"]["other"]["command"], "x");
+        assert!(written.get("servers").is_none());
+    }
+}
@@ -2,24 +2,38 @@
 //!
 //! Accepts all defaults without prompting:
 //! - Creates data dir, config, database
-//! - Downloads embedding model if missing
-//! - Detects and configures ALL found AI tools
+//! - Downloads embedding model if missing (`--model <id>` picks a non-default one)
+//! - Detects and configures ALL found AI tools (project-local configs with `--scope project`)
 //! - Installs agent rules for all supported tools
 //! - Runs OpenClaw integration if detected (AGENTS.md injection, no MEMORY.md migration)
 //! - Installs agent skill
-//! - Installs systemd service (if not already installed) and starts it
+//! - Installs the background service (if not already installed) and starts it
 //! - Does NOT enable cloud sync (requires interactive login)
 //!
 //! Designed for agents and scripts that cannot interact with prompts.
 //! All output is structured for easy parsing.
+//!
+//! Safe to re-run: steps whose state already matches (model on disk, MCP entries
+//! pointing at the right URL, current rules/skill/AGENTS.md sections) are skipped
+//! and reported as "already configured".
 
 use anyhow::Result;
 use std::path::PathBuf;
 
 use crate::config::Config;
 use super::init;
+use super::InitScope;
+
+pub async fn run(cfg: &Config, scope: InitScope, model: Option<&str>) -> Result<()> {
+    // Validate --model up front so a typo fails before anything is written
+    let model_info = match model {
+        Some(id) => Some(crate::embed::models::get_model(id).ok_or_else(|| {
+            let known: Vec<&str> = crate::embed::models::MODELS.iter().map(|m| m.id).collect();
+            anyhow::anyhow!("Unknown model '{id}'. Available: {}", known.join(", "))
+        })?),
+        None => None,
+    };
 
-pub async fn run(cfg: &Config) -> Result<()> {
     println!("ctxovrflw init (non-interactive)");
     println!();
 
@@ -32,30 +46,70 @@ pub async fn run(cfg: &Config) -> Result<()> {
         cfg.save()?;
         println!("✓ Config created");
     } else {
-        println!("✓ Config loaded");
+        println!("✓ Config (already configured)");
+    }
+    // Agents are configured with the daemon's auth token in their MCP URL
+    let mut cfg = cfg.clone();
+    if !cfg.is_remote_client() {
+        cfg.ensure_auth_token()?;
+    }
+    let cfg = &cfg;
+
+    // 2b. Model selection
+    if let Some(info) = model_info
+        && cfg.embedding_model != info.id
+    {
+        if Config::db_path()?.exists() {
+            // Existing vault: park the old vectors; the daemon re-embeds the rest
+            if let Some(switched) = super::model::switch_unattended(info.id).await?
+                && switched.missing > 0
+            {
+                println!(
+                    "ℹ {} memories need vectors for {}; the daemon embeds them in the background \
+                     (or run: ctxovrflw reindex --missing-only)",
+                    switched.missing, info.id
+                );
+            }
+        } else {
+            let mut updated = Config::load().unwrap_or_else(|_| cfg.clone());
+            updated.embedding_model = info.id.to_string();
+            updated.embedding_dim = info.dim;
+            updated.save()?;
+            crate::embed::set_embedding_dim(info.dim);
+        }
+        println!("✓ Model selected: {} ({})", info.name, info.id);
     }
 
     // 3. Database
     let _conn = crate::db::open()?;
     println!("✓ Database initialized");
 
-    // 4. Embedding model
+    // 4. Embedding model — present, roughly the expected size, and with its tokenizer
+    let active_model = model_info.or_else(|| crate::embed::models::get_model(&cfg.embedding_model));
     let model_path = crate::embed::Embedder::model_path()?;
-    let needs_download = !model_path.exists()
-        || std::fs::metadata(&model_path).map(|m| m.len() < 1_000_000).unwrap_or(true);
+    let tokenizer_path = crate::embed::Embedder::tokenizer_path()?;
+    let min_bytes = active_model
+        .map(|m| m.size_mb as u64 * 1_048_576 / 2)
+        .unwrap_or(0)
+        .max(1_000_000);
+    let needs_download = std::fs::metadata(&model_path).map(|m| m.len() < min_bytes).unwrap_or(true)
+        || !tokenizer_path.exists();
 
     if needs_download {
         println!("⬇ Downloading embedding model...");
-        init::download_model().await?;
+        match model_info {
+            Some(info) => super::model::download_model_files(info).await?,
+            None => init::download_model().await?,
+        }
         println!("✓ Model downloaded");
     } else {
         let size = std::fs::metadata(&model_path)?.len() as f64 / 1_048_576.0;
-        println!("✓ Model loaded ({size:.1} MB)");
+        println!("✓ Model ({size:.1} MB, already configured)");
     }
 
     // 5. Detect and configure ALL AI tools
     println!();
-    let agents = init::detect_agents();
+    let agents = init::detect_agents(scope);
 
     if agents.is_empty() {
         println!("ℹ No AI tools detected");
@@ -69,12 +123,30 @@ pub async fn run(cfg: &Config) -> Result<()> {
             let name = agent.def.name;
 
             // CLI install
-            if let Some(cmd_template) = agent.def.cli_install {
-                let cmd = cmd_template
-                    .replace("{port}", &cfg.port.to_string())
-                    .replace("http://127.0.0.1:{port}/mcp/sse", &url);
+            if let Some(cmd) = init::cli_install_command(agent, cfg) {
                 let parts: Vec<&str> = cmd.split_whitespace().collect();
                 if parts.len() >= 2 {
+                    match cli_registration(parts[0]) {
+                        Some(existing) if existing.contains(&url) => {
+                            println!("✓ {name} (CLI, already configured)");
+                            continue;
+                        }
+                        Some(_) => {
+                            // Registered with a stale URL — `mcp add` refuses duplicates
+                            let scope_args: Vec<&str> = parts
+                                .windows(2)
+                                .find(|w| w[0] == "--scope")
+                                .map(|w| w.to_vec())
+                                .unwrap_or_default();
+                            let _ = std::process::Command::new(parts[0])
+                                .args(["mcp", "remove", "ctxovrflw"])
+                                .args(&scope_args)
+                                .stdout(std::process::Stdio::null())
+                                .stderr(std::process::Stdio::null())
+                                .status();
+                        }
+                        None => {}
+                    }
                     let ok = std::process::Command::new(parts[0])
                         .args(&parts[1..])
                         .stdout(std::process::Stdio::null())
@@ -92,13 +164,17 @@ pub async fn run(cfg: &Config) -> Result<()> {
             }
 
             // JSON config
-            if !agent.def.config_paths.is_empty() {
+            if agent.config_path.is_some() || !agent.def.config_paths.is_empty() {
                 let config_path = agent.config_path.clone().unwrap_or_else(|| {
                     init::resolve_config_path(&agent.def.config_paths[0])
                 });
                 let mcp_entry = init::sse_mcp_json(cfg);
+                if agent.project_config_exists && scope == InitScope::Project {
+                    println!("ℹ {name}: updating existing project config");
+                }
                 match write_mcp_config_force(&config_path, &mcp_entry) {
-                    Ok(_) => println!("✓ {name} → {}", config_path.display()),
+                    Ok(true) => println!("✓ {name} → {}", config_path.display()),
+                    Ok(false) => println!("✓ {name} → {} (already configured)", config_path.display()),
                     Err(e) => println!("✗ {name}: {e}"),
                 }
                 continue;
@@ -138,7 +214,8 @@ pub async fn run(cfg: &Config) -> Result<()> {
 
             let agents_md = home.join(".openclaw/workspace/AGENTS.md");
             match init::inject_openclaw_agents_md(&agents_md) {
-                Ok(_) => println!("✓ AGENTS.md — ctxovrflw memory section injected"),
+                Ok(true) => println!("✓ AGENTS.md — ctxovrflw memory section injected"),
+                Ok(false) => println!("✓ AGENTS.md (already configured)"),
                 Err(e) => println!("✗ AGENTS.md: {e}"),
             }
 
@@ -153,7 +230,8 @@ pub async fn run(cfg: &Config) -> Result<()> {
 
     // 8. Agent skill
     match init::install_agent_skill() {
-        Ok(_) => println!("✓ Agent Skill installed"),
+        Ok(true) => println!("✓ Agent Skill installed"),
+        Ok(false) => println!("✓ Agent Skill (already configured)"),
         Err(e) => println!("✗ Agent Skill: {e}"),
     }
 
@@ -162,7 +240,7 @@ pub async fn run(cfg: &Config) -> Result<()> {
     if cfg.is_remote_client() {
         println!("✓ Using remote daemon: {}", cfg.daemon_url());
     } else if crate::daemon::is_service_installed() {
-        println!("✓ Service installed");
+        println!("✓ Service (already configured)");
         if !crate::daemon::is_service_running() {
             match crate::daemon::service_start() {
                 Ok(_) => println!("✓ Daemon started on port {}", cfg.port),
@@ -196,15 +274,30 @@ pub async fn run(cfg: &Config) -> Result<()> {
     println!();
     println!("✅ ctxovrflw is ready!");
     println!();
-    println!("  MCP endpoint: {}", init::mcp_sse_url(cfg));
+    println!("  MCP endpoint: {}", init::mcp_sse_endpoint(cfg));
     println!("  REST API:     {}/v1/", cfg.daemon_url());
     println!("  Data dir:     {}", Config::data_dir()?.display());
 
     Ok(())
 }
 
-/// Write MCP config, always overwriting existing ctxovrflw entries
-fn write_mcp_config_force(path: &PathBuf, mcp_entry: &serde_json::Value) -> Result<()> {
+/// Output of `<cli> mcp get ctxovrflw` when the agent CLI already has an entry.
+fn cli_registration(bin: &str) -> Option<String> {
+    let output = std::process::Command::new(bin)
+        .args(["mcp", "get", "ctxovrflw"])
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Write MCP config, overwriting any stale ctxovrflw entry.
+/// Returns false when the entry already matches and nothing was written.
+fn write_mcp_config_force(path: &PathBuf, mcp_entry: &serde_json::Value) -> Result<bool> {
     let mut config: serde_json::Value = if path.exists() {
         let content = std::fs::read_to_string(path)?;
         serde_json::from_str(&content).unwrap_or(serde_json::json!({}))
@@ -215,21 +308,28 @@ fn write_mcp_config_force(path: &PathBuf, mcp_entry: &serde_json::Value) -> Resu
         serde_json::json!({})
     };
 
-    if config.get("mcpServers").is_none() {
-        config["mcpServers"] = serde_json::json!({});
+    let key = init::servers_key(path);
+    let mcp_entry = init::mcp_entry_for(path, mcp_entry);
+    if config.get(key).is_none() {
+        config[key] = serde_json::json!({});
+    } else if config[key].get("ctxovrflw") == Some(&mcp_entry) {
+        return Ok(false);
     }
-    config["mcpServers"]["ctxovrflw"] = mcp_entry.clone();
+    config[key]["ctxovrflw"] = mcp_entry;
     let formatted = serde_json::to_string_pretty(&config)?;
     std::fs::write(path, formatted)?;
-    Ok(())
+    Ok(true)
 }
 
-/// Install rules, always writing (overwrite or append)
+/// Install rules: replace the marked section in place, append it, or create the file
 fn install_rules_force(path: &PathBuf, rules: &str) -> Result<String> {
     if path.exists() {
         let existing = std::fs::read_to_string(path)?;
         if existing.contains(init::CTXOVRFLW_RULES_MARKER) {
             let updated = init::replace_ctxovrflw_section(&existing, rules);
+            if updated == existing {
+                return Ok("already configured".into());
+            }
             std::fs::write(path, updated)?;
             Ok("updated".into())
         } else {
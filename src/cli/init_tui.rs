@@ -19,6 +19,7 @@ use tokio::sync::mpsc;
 
 use crate::config::Config;
 use super::init;
+use super::InitScope;
 
 // ── Constants ───────────────────────────────────────────────
 
@@ -152,6 +153,7 @@ struct App {
 
     // Config (mutable for saves)
     cfg: Config,
+    scope: InitScope,
 
     // Async channel
     async_tx: mpsc::UnboundedSender<AsyncMsg>,
@@ -172,7 +174,7 @@ struct App {
 }
 
 impl App {
-    fn new(cfg: Config) -> Self {
+    fn new(cfg: Config, scope: InitScope) -> Self {
         let (tx, rx) = mpsc::unbounded_channel();
         let mut steps = [StepStatus::Pending; 6];
         steps[0] = StepStatus::Active;
@@ -185,6 +187,7 @@ impl App {
             tick: 0,
             should_quit: false,
             cfg,
+            scope,
             async_tx: tx,
             async_rx: rx,
             detected_agents: Vec::new(),
@@ -256,6 +259,12 @@ impl App {
                 Err(e) => self.lines.push(LogLine::err(format!("Config: {e}"))),
             }
         }
+        // Agents are configured with the daemon's auth token in their MCP URL
+        if !self.cfg.is_remote_client()
+            && let Err(e) = self.cfg.ensure_auth_token()
+        {
+            self.lines.push(LogLine::err(format!("Auth token: {e}")));
+        }
 
         // Database
         match crate::db::open() {
@@ -305,7 +314,7 @@ impl App {
         self.lines.push(LogLine::header("Scanning for AI tools..."));
         self.lines.push(LogLine::blank());
 
-        self.detected_agents = init::detect_agents();
+        self.detected_agents = init::detect_agents(self.scope);
 
         if self.detected_agents.is_empty() {
             self.lines.push(LogLine::info("No AI tools detected."));
@@ -321,6 +330,9 @@ impl App {
                     .or_else(|| agent.def.cli_install.map(|_| " (CLI)".to_string()))
                     .unwrap_or_default();
                 self.lines.push(LogLine::ok(format!("Found {}{}", agent.def.name, path_info)));
+                if agent.project_config_exists && self.scope == InitScope::Project {
+                    self.lines.push(LogLine::info("Project config already exists — ctxovrflw entry will be merged"));
+                }
             }
             self.lines.push(LogLine::blank());
 
@@ -357,10 +369,7 @@ impl App {
             self.lines.push(LogLine::header(format!("─ {name}")));
 
             // Try CLI install
-            if let Some(cmd_template) = agent.def.cli_install {
-                let cmd = cmd_template
-                    .replace("{port}", &self.cfg.port.to_string())
-                    .replace("http://127.0.0.1:{port}/mcp/sse", &url);
+            if let Some(cmd) = init::cli_install_command(agent, &self.cfg) {
                 let parts: Vec<&str> = cmd.split_whitespace().collect();
                 if parts.len() >= 2 {
                     let output = std::process::Command::new(parts[0])
@@ -388,7 +397,7 @@ impl App {
             }
 
             // JSON config — check if overwrite needed
-            if !agent.def.config_paths.is_empty() {
+            if agent.config_path.is_some() || !agent.def.config_paths.is_empty() {
                 let config_path = agent.config_path.clone().unwrap_or_else(|| {
                     init::resolve_config_path(&agent.def.config_paths[0])
                 });
@@ -397,7 +406,7 @@ impl App {
                     std::fs::read_to_string(&config_path)
                         .ok()
                         .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
-                        .and_then(|v| v.get("mcpServers")?.get("ctxovrflw").cloned())
+                        .and_then(|v| v.get(init::servers_key(&config_path))?.get("ctxovrflw").cloned())
                         .is_some()
                 };
 
@@ -491,7 +500,7 @@ impl App {
             self.lines.push(LogLine::blank());
         }
 
-        let url = init::mcp_sse_url(&self.cfg);
+        let url = init::mcp_sse_endpoint(&self.cfg);
         self.lines.push(LogLine::info(format!("Tools connect via {url}")));
         self.lines.push(LogLine::blank());
 
@@ -873,15 +882,9 @@ impl App {
                                     };
                                     self.flow = FlowState::DownloadingSelectedModel;
 
-                                    let model_id = model.id.to_string();
-                                    let model_dim = model.dim;
-                                    let onnx_url = model.onnx_url.to_string();
-                                    let tokenizer_url = model.tokenizer_url.to_string();
                                     let tx = self.async_tx.clone();
                                     tokio::spawn(async move {
-                                        let result = download_model_quiet(
-                                            &model_id, model_dim, &onnx_url, &tokenizer_url
-                                        ).await;
+                                        let result = download_model_quiet(model).await;
                                         let _ = tx.send(AsyncMsg::ModelDownloaded(result));
                                     });
                                 }
@@ -1231,10 +1234,11 @@ fn write_mcp_config_quiet(path: &PathBuf, mcp_entry: &serde_json::Value) -> Resu
         serde_json::json!({})
     };
 
-    if config.get("mcpServers").is_none() {
-        config["mcpServers"] = serde_json::json!({});
+    let key = init::servers_key(path);
+    if config.get(key).is_none() {
+        config[key] = serde_json::json!({});
     }
-    config["mcpServers"]["ctxovrflw"] = mcp_entry.clone();
+    config[key]["ctxovrflw"] = init::mcp_entry_for(path, mcp_entry);
     let formatted = serde_json::to_string_pretty(&config)?;
     std::fs::write(path, formatted)?;
     Ok(())
@@ -1474,47 +1478,37 @@ fn render_tabs(f: &mut Frame, app: &App, area: Rect) {
 
 // ── Quiet model download (no stdout) ────────────────────────
 
-async fn download_model_quiet(
-    model_id: &str,
-    model_dim: usize,
-    onnx_url: &str,
-    tokenizer_url: &str,
-) -> Result<()> {
-    use crate::embed::set_embedding_dim;
+async fn download_model_quiet(model: &'static crate::embed::models::EmbeddingModel) -> Result<()> {
+    use crate::embed::{download, set_embedding_dim};
 
+    let model_id = model.id;
+    let model_dim = model.dim;
     let model_dir = Config::model_dir()?;
     let model_subdir = model_dir.join(model_id);
     std::fs::create_dir_all(&model_subdir)?;
 
-    let client = reqwest::Client::builder()
-        .redirect(reqwest::redirect::Policy::limited(10))
-        .build()?;
-
     // Download ONNX model
     let model_file = model_subdir.join("model.onnx");
     if !model_file.exists() {
-        let resp = client.get(onnx_url).send().await?;
-        if !resp.status().is_success() {
-            anyhow::bail!("HTTP {} downloading model from {}", resp.status(), onnx_url);
-        }
-        let bytes = resp.bytes().await?;
-        if bytes.len() < 100_000 {
-            anyhow::bail!("Model file too small ({} bytes)", bytes.len());
-        }
-        std::fs::write(&model_file, &bytes)?;
+        download::fetch_to_file(
+            model.onnx_url, model.onnx_sha256, &model_file, 100_000, |_, _| {},
+        )
+        .await?;
     }
 
     // Download tokenizer
     let tokenizer_file = model_subdir.join("tokenizer.json");
     if !tokenizer_file.exists() {
-        let resp = client.get(tokenizer_url).send().await?;
-        if !resp.status().is_success() {
-            anyhow::bail!("HTTP {} downloading tokenizer", resp.status());
-        }
-        let bytes = resp.bytes().await?;
+        let bytes = download::fetch(model.tokenizer_url, model.tokenizer_sha256).await?;
         std::fs::write(&tokenizer_file, &bytes)?;
     }
 
+    // Open once under the outgoing model so its vectors are on record as its own
+    let db_path = Config::db_path()?;
+    if db_path.exists() {
+        drop(crate::db::open()?);
+    }
+
     // Update config
     let mut cfg = Config::load().unwrap_or_default();
     cfg.embedding_model = model_id.to_string();
@@ -1524,44 +1518,9 @@ async fn download_model_quiet(
     // Set runtime dimension for db::open
     set_embedding_dim(model_dim);
 
-    // If there's an existing database, we need to recreate it
-    let db_path = Config::db_path()?;
+    // Reopening parks the old model's vectors; embed whatever this one lacks
     if db_path.exists() {
-        let conn = crate::db::open()?;
-        let mut stmt = conn.prepare(
-            "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at, deleted, synced_at FROM memories ORDER BY created_at"
-        )?;
-        let rows: Vec<(String, String, String, String, Option<String>, Option<String>, Option<String>, Option<String>, String, String, i32, Option<String>)> = stmt.query_map([], |row| {
-            Ok((
-                row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?,
-                row.get(4)?, row.get(5)?, row.get(6)?, row.get(7)?,
-                row.get(8)?, row.get(9)?, row.get(10)?, row.get(11)?,
-            ))
-        })?.collect::<std::result::Result<Vec<_>, _>>()?;
-        drop(stmt);
-        drop(conn);
-
-        // Nuke and recreate
-        std::fs::remove_file(&db_path)?;
-        let conn = crate::db::open()?;
-
-        for row in &rows {
-            conn.execute(
-                "INSERT INTO memories (id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at, deleted, synced_at) VALUES (?1,?2,?3,?4,?5,?6,?7,?8,?9,?10,?11,?12)",
-                rusqlite::params![row.0, row.1, row.2, row.3, row.4, row.5, row.6, row.7, row.8, row.9, row.10, row.11],
-            )?;
-        }
-
-        // Re-embed
-        let embedder_arc = crate::embed::get_or_init()?;
-        let mut embedder = embedder_arc.lock().unwrap_or_else(|e| e.into_inner());
-        for row in &rows {
-            if row.10 != 0 { continue; }
-            let embedding = embedder.embed(&row.1)?;
-            let bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
-            conn.execute("UPDATE memories SET embedding = ?1 WHERE id = ?2", rusqlite::params![bytes, row.0])?;
-            conn.execute("INSERT OR REPLACE INTO memory_vectors (id, embedding) VALUES (?1, ?2)", rusqlite::params![row.0, bytes])?;
-        }
+        crate::maintenance::embed_missing_vectors(usize::MAX)?;
     }
 
     Ok(())
@@ -1569,14 +1528,14 @@ async fn download_model_quiet(
 
 // ── Entry Point ─────────────────────────────────────────────
 
-pub async fn run(cfg: &Config) -> Result<()> {
+pub async fn run(cfg: &Config, scope: InitScope) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let mut app = App::new(cfg.clone());
+    let mut app = App::new(cfg.clone(), scope);
     app.advance();
 
     let result = run_loop(&mut terminal, &mut app).await;
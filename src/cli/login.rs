@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crate::config::Config;
 use crate::crypto;
 
@@ -9,16 +9,27 @@ struct AuthResponse {
     api_key: String,
 }
 
+/// `POST /v1/auth/device/code`. Accepts the RFC 8628 field names too.
 #[derive(serde::Deserialize)]
 struct DeviceCodeResponse {
     device_code: String,
     user_code: String,
+    #[serde(alias = "verification_uri")]
     verification_url: String,
+    /// Verification URL with the code already filled in.
+    #[serde(default, alias = "verification_uri_complete")]
+    verification_url_complete: Option<String>,
     expires_in: u64,
+    #[serde(default = "default_poll_interval")]
     interval: u64,
 }
 
-#[derive(serde::Deserialize)]
+/// Polling interval when the server doesn't send one (RFC 8628 §3.2).
+fn default_poll_interval() -> u64 {
+    5
+}
+
+#[derive(serde::Deserialize, Default)]
 struct DeviceTokenResponse {
     api_key: Option<String>,
     device_id: Option<String>,
@@ -35,28 +46,43 @@ struct ErrorResponse {
     error: String,
 }
 
+/// How `login` gets an API key.
+#[derive(Debug, Clone, Copy)]
+pub enum LoginMethod<'a> {
+    /// Device code flow in a terminal, email/password otherwise.
+    Auto,
+    /// Use this API key directly.
+    ApiKey(&'a str),
+    /// Device code flow even without a TTY (e.g. over SSH), with no
+    /// email/password fallback.
+    Device,
+}
+
 /// Run the login flow. If `inline` is true, skip the header (called from init).
-/// If `api_key_arg` is Some, skip the interactive flow and use the key directly.
-pub async fn run_inner(cfg: &Config, inline: bool, api_key_arg: Option<&str>) -> Result<()> {
+pub async fn run_inner(cfg: &Config, inline: bool, method: LoginMethod<'_>) -> Result<()> {
     if !inline {
         println!("ctxovrflw cloud login\n");
     }
 
     // Check if already logged in
-    if cfg.is_logged_in() && api_key_arg.is_none() {
+    if cfg.is_logged_in() && !matches!(method, LoginMethod::ApiKey(_)) {
         if cfg.is_encrypted() && cfg.get_cached_key().is_none() {
             println!("Logged in, but sync PIN has expired. Please re-enter it.");
             return prompt_sync_pin(cfg).await;
         }
+        if cfg.pin_verifier.as_deref().is_some_and(|v| crypto::verifier_iterations(v) < cfg.pbkdf2_iterations) {
+            println!("Logged in. Re-enter your sync PIN to upgrade its key derivation.");
+            return prompt_sync_pin(cfg).await;
+        }
         println!("Already logged in (device: {}).", cfg.device_id.as_deref().unwrap_or("?"));
         println!("To re-login, run: ctxovrflw logout");
         return Ok(());
     }
 
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let cloud_url = &cfg.cloud_url;
 
-    let (api_key, pre_device_id) = if let Some(key) = api_key_arg {
+    let (api_key, pre_device_id) = if let LoginMethod::ApiKey(key) = method {
         // ─── Direct API key auth ────────────────────────────────────────
         println!("Authenticating with API key...");
         // Verify the key works
@@ -80,9 +106,12 @@ pub async fn run_inner(cfg: &Config, inline: bool, api_key_arg: Option<&str>) ->
         cfg.save()?;
 
         (key.to_string(), None)
+    } else if matches!(method, LoginMethod::Device) {
+        // ─── Device code flow (requested explicitly) ────────────────────
+        device_code_flow(&client, cloud_url, false).await?
     } else if is_tty() {
         // ─── Device code flow (interactive TTY) ─────────────────────────
-        device_code_flow(&client, cloud_url).await?
+        device_code_flow(&client, cloud_url, true).await?
     } else {
         // ─── Fallback: email/password (non-TTY or if device flow fails) ─
         email_password_flow(&client, cloud_url).await?
@@ -184,8 +213,9 @@ pub async fn run_inner(cfg: &Config, inline: bool, api_key_arg: Option<&str>) ->
     Ok(())
 }
 
-/// Device code flow — opens browser, user enters code on website
-async fn device_code_flow(client: &reqwest::Client, cloud_url: &str) -> Result<(String, Option<String>)> {
+/// Device code flow — opens browser, user enters code on website. With
+/// `fallback`, a cloud without device auth drops to email/password.
+async fn device_code_flow(client: &reqwest::Client, cloud_url: &str, fallback: bool) -> Result<(String, Option<String>)> {
     let device_name = hostname::get()
         .map(|h| h.to_string_lossy().to_string())
         .unwrap_or_else(|_| "unknown".to_string());
@@ -198,11 +228,14 @@ async fn device_code_flow(client: &reqwest::Client, cloud_url: &str) -> Result<(
         .await?;
 
     if !resp.status().is_success() {
+        if !fallback {
+            anyhow::bail!("{cloud_url} does not support device login (HTTP {}). Use `ctxovrflw login --key <KEY>` instead.", resp.status());
+        }
         println!("Device auth not available, falling back to email/password...\n");
         return email_password_flow(client, cloud_url).await;
     }
 
-    let code_resp: DeviceCodeResponse = resp.json().await?;
+    let code_resp: DeviceCodeResponse = resp.json().await.context("Invalid device code response")?;
 
     println!("  Open this URL in your browser:\n");
     println!("    {}", code_resp.verification_url);
@@ -210,14 +243,18 @@ async fn device_code_flow(client: &reqwest::Client, cloud_url: &str) -> Result<(
     println!("    ┌──────────────┐");
     println!("    │  {}  │", code_resp.user_code);
     println!("    └──────────────┘\n");
+    if let Some(url) = &code_resp.verification_url_complete {
+        println!("  Or open this link, which has the code filled in:\n");
+        println!("    {url}\n");
+    }
 
     // Try to open browser automatically
-    let _ = open_browser(&code_resp.verification_url);
+    let _ = open_browser(code_resp.verification_url_complete.as_deref().unwrap_or(&code_resp.verification_url));
 
     println!("  Waiting for approval... (expires in {}m)", code_resp.expires_in / 60);
 
     // Poll for token
-    let interval = std::time::Duration::from_secs(code_resp.interval.max(3));
+    let mut interval = std::time::Duration::from_secs(code_resp.interval.max(3));
     let deadline = std::time::Instant::now() + std::time::Duration::from_secs(code_resp.expires_in);
 
     loop {
@@ -227,14 +264,26 @@ async fn device_code_flow(client: &reqwest::Client, cloud_url: &str) -> Result<(
 
         tokio::time::sleep(interval).await;
 
-        let resp = client
+        let resp = match client
             .post(format!("{cloud_url}/v1/auth/device/token"))
             .json(&serde_json::json!({ "device_code": code_resp.device_code }))
             .send()
-            .await?;
+            .await
+        {
+            Ok(resp) => resp,
+            // A dropped connection shouldn't throw away an approval in progress
+            Err(e) => {
+                tracing::debug!("Device token poll failed, retrying: {e}");
+                continue;
+            }
+        };
 
         let status = resp.status();
-        let body: DeviceTokenResponse = resp.json().await?;
+        if status.is_server_error() {
+            tracing::debug!("Device token poll returned {status}, retrying");
+            continue;
+        }
+        let body: DeviceTokenResponse = resp.json().await.unwrap_or_default();
 
         if let Some(api_key) = body.api_key {
             println!("\n✓ Authorized!");
@@ -246,6 +295,13 @@ async fn device_code_flow(client: &reqwest::Client, cloud_url: &str) -> Result<(
                 // Still waiting — continue polling
                 continue;
             }
+            Some("slow_down") => {
+                interval += std::time::Duration::from_secs(5);
+                continue;
+            }
+            Some("access_denied") => {
+                anyhow::bail!("Authorization was denied in the browser. Run `ctxovrflw login` to try again.");
+            }
             Some("expired_token") => {
                 anyhow::bail!("Device code expired. Run `ctxovrflw login` to try again.");
             }
@@ -338,7 +394,7 @@ struct PinActionResponse {
 /// Client derives the same key using the server-provided salt.
 async fn setup_sync_pin(cfg: &Config) -> Result<()> {
     let api_key = cfg.api_key.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in"))?;
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
 
     println!("\n🔐 Zero-Knowledge Encryption Setup");
     println!("Your memories are encrypted before leaving this device.\n");
@@ -402,10 +458,10 @@ async fn setup_sync_pin(cfg: &Config) -> Result<()> {
         let key_salt = result.key_salt.ok_or_else(|| anyhow::anyhow!("Server didn't return salt"))?;
 
         // Derive key CLIENT-SIDE — PIN never leaves this device
-        let key = crypto::derive_key(&pin, &key_salt);
+        let key = crypto::derive_key_with_iterations(&pin, &key_salt, cfg.pbkdf2_iterations);
 
         // Create a verifier: encrypt a known string with the derived key
-        let verifier = crypto::create_pin_verifier(&key)?;
+        let verifier = crypto::create_pin_verifier_with_iterations(&key, cfg.pbkdf2_iterations)?;
 
         // POST only the verifier (encrypted blob) to server — NEVER the PIN
         let store_resp = client
@@ -439,8 +495,10 @@ async fn setup_sync_pin(cfg: &Config) -> Result<()> {
         let key_salt = account_pin.key_salt.ok_or_else(|| anyhow::anyhow!("Server didn't return salt"))?;
         let stored_verifier = account_pin.pin_verifier.ok_or_else(|| anyhow::anyhow!("Server didn't return verifier"))?;
 
-        // Derive key CLIENT-SIDE — PIN never leaves this device
-        let key = crypto::derive_key(&pin, &key_salt);
+        // Derive key CLIENT-SIDE — PIN never leaves this device, with the
+        // iteration count the account's key was created with
+        let iterations = crypto::verifier_iterations(&stored_verifier);
+        let key = crypto::derive_key_with_iterations(&pin, &key_salt, iterations);
 
         // Verify by decrypting the stored verifier locally (true zero-knowledge)
         if !crypto::verify_pin(&key, &stored_verifier) {
@@ -455,17 +513,25 @@ async fn setup_sync_pin(cfg: &Config) -> Result<()> {
             anyhow::bail!("Wrong sync PIN. You've been logged out. Run `ctxovrflw login` to try again.");
         }
 
-        let verifier = crypto::create_pin_verifier(&key)?;
+        let verifier = crypto::create_pin_verifier_with_iterations(&key, iterations)?;
 
         let mut cfg = Config::load()?;
-        cfg.key_salt = Some(key_salt);
+        cfg.key_salt = Some(key_salt.clone());
         cfg.pin_verifier = Some(verifier);
         cfg.cache_key(&key)?;
 
         println!("✓ PIN verified — encryption key cached (30-day TTL)");
+        upgrade_key_derivation(&pin, &key_salt, iterations).await;
     }
 
     let cfg = Config::load()?;
+    println!();
+    if let Err(e) = crate::sync::self_test(&cfg).await {
+        println!("\n⚠ Logged in, but the sync self-test failed: {e:#}");
+        println!("  Fix the problem before syncing.");
+        println!("  Rerun the check with `ctxovrflw sync --self-test`.");
+        return Ok(());
+    }
     println!("\n✓ Ready! Cloud sync is {}.",
         if cfg.auto_sync { format!("enabled (every {}s)", cfg.sync_interval_secs) } else { "disabled".to_string() }
     );
@@ -474,11 +540,16 @@ async fn setup_sync_pin(cfg: &Config) -> Result<()> {
 }
 
 pub async fn run(cfg: &Config) -> Result<()> {
-    run_inner(cfg, false, None).await
+    run_inner(cfg, false, LoginMethod::Auto).await
 }
 
 pub async fn run_with_key(cfg: &Config, key: &str) -> Result<()> {
-    run_inner(cfg, false, Some(key)).await
+    run_inner(cfg, false, LoginMethod::ApiKey(key)).await
+}
+
+/// `login --device`: browser sign-in with a device code, even without a TTY.
+pub async fn run_device(cfg: &Config) -> Result<()> {
+    run_inner(cfg, false, LoginMethod::Device).await
 }
 
 /// Re-prompt for sync PIN when the cached key has expired.
@@ -493,18 +564,20 @@ async fn prompt_sync_pin(cfg: &Config) -> Result<()> {
 
     // If we have the salt locally, derive and verify locally
     if let (Some(salt), Some(verifier)) = (&cfg.key_salt, &cfg.pin_verifier) {
-        let key = crypto::derive_key(&pin, salt);
+        let iterations = crypto::verifier_iterations(verifier);
+        let key = crypto::derive_key_with_iterations(&pin, salt, iterations);
         if !crypto::verify_pin(&key, verifier) {
             anyhow::bail!("Wrong sync PIN.");
         }
         let mut cfg = cfg.clone();
         cfg.cache_key(&key)?;
         println!("✓ Sync PIN accepted, key cached for 30 days.");
+        upgrade_key_derivation(&pin, salt, iterations).await;
         return Ok(());
     }
 
     // Otherwise fetch salt from server and derive locally
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let resp = client
         .get(format!("{}/v1/auth/pin-verifier", cfg.cloud_url))
         .header("Authorization", format!("Bearer {api_key}"))
@@ -519,8 +592,12 @@ async fn prompt_sync_pin(cfg: &Config) -> Result<()> {
     let key_salt = vdata.key_salt.ok_or_else(|| anyhow::anyhow!("Server didn't return salt"))?;
 
     // Derive key client-side and verify locally
-    let key = crypto::derive_key(&pin, &key_salt);
-    let verifier = crypto::create_pin_verifier(&key)?;
+    let iterations = vdata
+        .pin_verifier
+        .as_deref()
+        .map_or(crypto::DEFAULT_PBKDF2_ITERATIONS, crypto::verifier_iterations);
+    let key = crypto::derive_key_with_iterations(&pin, &key_salt, iterations);
+    let verifier = crypto::create_pin_verifier_with_iterations(&key, iterations)?;
 
     // We can't verify against server's stored verifier without it, so just trust the derivation
     // and store locally. Next sync will fail if PIN is wrong.
@@ -532,6 +609,57 @@ async fn prompt_sync_pin(cfg: &Config) -> Result<()> {
     Ok(())
 }
 
+/// Re-key sync when the account's key was derived with fewer PBKDF2 iterations
+/// than `pbkdf2_iterations`: pull everything under the old key, publish a
+/// verifier for the new one (same salt), then re-push every memory encrypted
+/// with it. Failures are reported but don't fail login: before the verifier is
+/// replaced sync keeps the old key; after, the old key is refused and the
+/// re-push stays queued for the next sync.
+async fn upgrade_key_derivation(pin: &str, key_salt: &str, from: u32) {
+    let target = match Config::load() {
+        Ok(cfg) => cfg.pbkdf2_iterations,
+        Err(_) => return,
+    };
+    if from >= target {
+        return;
+    }
+    println!("Upgrading sync key derivation from {from} to {target} PBKDF2 iterations...");
+    match rekey(pin, key_salt, target).await {
+        Ok(pushed) => println!("✓ Re-encrypted {pushed} memories with the stronger key"),
+        Err(e) => println!("⚠ Key upgrade failed: {e:#}. Log in again to retry."),
+    }
+}
+
+async fn rekey(pin: &str, key_salt: &str, iterations: u32) -> Result<usize> {
+    let cfg = Config::load()?;
+    let api_key = cfg.api_key.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in"))?;
+
+    // Bring in everything still encrypted under the old key before it's replaced
+    crate::sync::run_silent(&cfg).await.context("sync before re-keying failed")?;
+
+    let key = crypto::derive_key_with_iterations(pin, key_salt, iterations);
+    let verifier = crypto::create_pin_verifier_with_iterations(&key, iterations)?;
+    let store_resp = crate::config::http_client()?
+        .post(format!("{}/v1/auth/store-verifier", cfg.cloud_url))
+        .header("Authorization", format!("Bearer {api_key}"))
+        .json(&serde_json::json!({ "pin_verifier": verifier, "key_salt": key_salt }))
+        .send()
+        .await?;
+    if !store_resp.status().is_success() {
+        anyhow::bail!("server rejected the new PIN verifier ({})", store_resp.status());
+    }
+
+    let mut cfg = Config::load()?;
+    cfg.pin_verifier = Some(verifier);
+    cfg.cache_key(&key)?;
+
+    // From here the new verifier is live, so anything not yet re-pushed stays
+    // queued and goes out with the next sync
+    crate::sync::queue_full_repush()?;
+    let (pushed, _, _) = crate::sync::run_silent(&cfg).await.context("re-pushing under the new key failed; memories stay queued for the next sync")?;
+    Ok(pushed)
+}
+
 fn is_tty() -> bool {
     atty::is(atty::Stream::Stdin) && atty::is(atty::Stream::Stdout)
 }
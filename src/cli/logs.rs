@@ -0,0 +1,131 @@
+use anyhow::Result;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+use super::LogSource;
+
+/// Daemon log file prefix. Rotated files are suffixed with a date (daemon.log.YYYY-MM-DD).
+pub const DAEMON_LOG_PREFIX: &str = "daemon.log";
+
+pub async fn run(source: LogSource, follow: bool, lines: usize) -> Result<()> {
+    let data_dir = Config::data_dir()?;
+
+    let mut targets: Vec<(&str, PathBuf)> = Vec::new();
+    if matches!(source, LogSource::All | LogSource::Daemon) {
+        match latest_daemon_log(&data_dir) {
+            Some(path) => targets.push(("daemon", path)),
+            None => {
+                println!("No daemon log found in {}", data_dir.display());
+                if crate::daemon::is_service_installed() {
+                    println!("  Service logs: {}", crate::daemon::service_logs_hint());
+                }
+            }
+        }
+    }
+    if matches!(source, LogSource::All | LogSource::Mcp) {
+        let path = Config::mcp_log_path()?;
+        if path.exists() {
+            targets.push(("mcp", path));
+        } else {
+            println!("No MCP debug log found at {}", path.display());
+        }
+    }
+
+    if targets.is_empty() {
+        return Ok(());
+    }
+
+    // Only prefix lines when several logs are interleaved
+    let prefixed = targets.len() > 1;
+
+    let mut offsets = Vec::with_capacity(targets.len());
+    for (label, path) in &targets {
+        if prefixed {
+            println!("==> {} ({}) <==", label, path.display());
+        }
+        for line in tail_lines(path, lines)? {
+            println!("{line}");
+        }
+        offsets.push(std::fs::metadata(path).map(|m| m.len()).unwrap_or(0));
+        if prefixed {
+            println!();
+        }
+    }
+
+    if !follow {
+        return Ok(());
+    }
+
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => return Ok(()),
+            _ = tokio::time::sleep(std::time::Duration::from_millis(500)) => {}
+        }
+
+        for (i, (label, path)) in targets.iter_mut().enumerate() {
+            // Daemon logs rotate daily — switch to the newest file when it appears
+            if *label == "daemon"
+                && let Some(latest) = latest_daemon_log(&data_dir)
+                && latest != *path
+            {
+                *path = latest;
+                offsets[i] = 0;
+            }
+
+            let len = match std::fs::metadata(&*path) {
+                Ok(m) => m.len(),
+                Err(_) => continue,
+            };
+            if len < offsets[i] {
+                // Truncated — start over
+                offsets[i] = 0;
+            }
+            if len == offsets[i] {
+                continue;
+            }
+
+            let mut file = std::fs::File::open(&*path)?;
+            file.seek(SeekFrom::Start(offsets[i]))?;
+            let mut buf = String::new();
+            file.read_to_string(&mut buf)?;
+
+            // Hold back a trailing partial line until it is complete
+            let complete = match buf.rfind('\n') {
+                Some(pos) => &buf[..=pos],
+                None => continue,
+            };
+            offsets[i] += complete.len() as u64;
+
+            for line in complete.lines() {
+                if prefixed {
+                    println!("[{label}] {line}");
+                } else {
+                    println!("{line}");
+                }
+            }
+        }
+    }
+}
+
+/// Newest daemon log in the data dir (plain or date-rotated).
+pub fn latest_daemon_log(data_dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(data_dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with(DAEMON_LOG_PREFIX))
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, e.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+/// Last `n` lines of a file.
+fn tail_lines(path: &Path, n: usize) -> Result<Vec<String>> {
+    let content = std::fs::read_to_string(path)?;
+    let all: Vec<&str> = content.lines().collect();
+    let start = all.len().saturating_sub(n);
+    Ok(all[start..].iter().map(|s| s.to_string()).collect())
+}
@@ -19,6 +19,7 @@ use rusqlite::{params, Connection};
 use std::collections::HashSet;
 use std::io;
 
+use super::ExportFormat;
 use crate::config::Config;
 use crate::db;
 #[cfg(feature = "pro")]
@@ -100,6 +101,15 @@ struct App {
     graph_entity_type: String,
     graph_relations: Vec<(String, String, String, String, f64, bool)>,
     graph_selected: usize,
+    /// Shown in the header when the list is a subset (e.g. `recall --interactive`)
+    scope: Option<String>,
+    /// What the subset was built from, re-run after a sync
+    scope_filter: Option<ScopeFilter>,
+    /// Set by `e`; the run loop opens $EDITOR since it owns the terminal
+    pending_edit: bool,
+    export_format: ExportFormat,
+    /// Query whose terms are highlighted in the detail view (`recall --interactive`)
+    highlight: Option<String>,
 }
 
 impl App {
@@ -136,6 +146,11 @@ impl App {
             graph_entity_type: String::new(),
             graph_relations: Vec::new(),
             graph_selected: 0,
+            scope: None,
+            scope_filter: None,
+            pending_edit: false,
+            export_format: ExportFormat::default(),
+            highlight: None,
         }
     }
 
@@ -244,7 +259,7 @@ impl App {
 fn load_memories(conn: &Connection) -> Result<Vec<MemoryRow>> {
     let mut stmt = conn.prepare(
         "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at, synced_at, deleted
-         FROM memories WHERE deleted = 0
+         FROM memories WHERE deleted = 0 AND in_workspace(workspace)
          ORDER BY created_at DESC"
     )?;
 
@@ -268,9 +283,49 @@ fn load_memories(conn: &Connection) -> Result<Vec<MemoryRow>> {
     Ok(rows)
 }
 
+/// How a scoped view picks its memories out of everything stored.
+#[derive(Debug, Clone)]
+enum ScopeFilter {
+    /// Launch filters: the subject and/or any of the tags, both when given
+    Launch { subject: Option<String>, tags: Vec<String> },
+    /// Recall results, in ranked order
+    Recall(Vec<String>),
+}
+
+impl ScopeFilter {
+    /// The memories in scope, out of `memories` as loaded. Launch filters are
+    /// re-queried, so memories pulled by a sync that match them show up too.
+    fn apply(&self, conn: &Connection, mut memories: Vec<MemoryRow>) -> Result<Vec<MemoryRow>> {
+        match self {
+            ScopeFilter::Launch { subject, tags } => {
+                if let Some(subject) = subject {
+                    let ids: HashSet<String> = db::search::by_subject(conn, subject, i64::MAX as usize)?
+                        .into_iter()
+                        .map(|m| m.id)
+                        .collect();
+                    memories.retain(|m| ids.contains(&m.id));
+                }
+                if !tags.is_empty() {
+                    let tag_refs: Vec<&str> = tags.iter().map(String::as_str).collect();
+                    let ids: HashSet<String> = db::search::by_any_tag(conn, &tag_refs, i64::MAX as usize)?
+                        .into_iter()
+                        .map(|m| m.id)
+                        .collect();
+                    memories.retain(|m| ids.contains(&m.id));
+                }
+                Ok(memories)
+            }
+            ScopeFilter::Recall(ids) => Ok(ids
+                .iter()
+                .filter_map(|id| memories.iter().position(|m| &m.id == id).map(|i| memories.swap_remove(i)))
+                .collect()),
+        }
+    }
+}
+
 // ── Entry point ─────────────────────────────────────────────────────────
 
-pub async fn run(cfg: &Config) -> Result<()> {
+pub async fn run(cfg: &Config, format: ExportFormat, subject: Option<&str>, tags: &[String]) -> Result<()> {
     let conn = db::open()?;
     let memories = load_memories(&conn)?;
 
@@ -279,6 +334,49 @@ pub async fn run(cfg: &Config) -> Result<()> {
         return Ok(());
     }
 
+    let mut scope = Vec::new();
+    if let Some(subject) = subject {
+        scope.push(format!("subject {subject}"));
+    }
+    if !tags.is_empty() {
+        scope.push(format!("tag {}", tags.join(" | ")));
+    }
+    if scope.is_empty() {
+        return run_tui(cfg, &conn, memories, None, format, None);
+    }
+
+    let filter = ScopeFilter::Launch { subject: subject.map(String::from), tags: tags.to_vec() };
+    let memories = filter.apply(&conn, memories)?;
+    if memories.is_empty() {
+        println!("No memories match {}.", scope.join(" and "));
+        return Ok(());
+    }
+    run_tui(cfg, &conn, memories, Some((scope.join(", "), filter)), format, None)
+}
+
+/// Open the browser on just the given recall results, in ranked order.
+pub async fn run_recall(cfg: &Config, query: &str, ids: &[String]) -> Result<()> {
+    let conn = db::open()?;
+    let filter = ScopeFilter::Recall(ids.to_vec());
+    let memories = filter.apply(&conn, load_memories(&conn)?)?;
+
+    if memories.is_empty() {
+        println!("No memories found for: {query}");
+        return Ok(());
+    }
+
+    let scope = Some((format!("recall \"{query}\""), filter));
+    run_tui(cfg, &conn, memories, scope, ExportFormat::default(), Some(query.to_string()))
+}
+
+fn run_tui(
+    cfg: &Config,
+    conn: &Connection,
+    memories: Vec<MemoryRow>,
+    scope: Option<(String, ScopeFilter)>,
+    export_format: ExportFormat,
+    highlight: Option<String>,
+) -> Result<()> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -287,8 +385,11 @@ pub async fn run(cfg: &Config) -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     let mut app = App::new(memories);
+    (app.scope, app.scope_filter) = scope.unzip();
+    app.export_format = export_format;
+    app.highlight = highlight;
 
-    let res = run_loop(&mut terminal, &mut app, &conn, cfg);
+    let res = run_loop(&mut terminal, &mut app, conn, cfg);
 
     // Restore terminal
     disable_raw_mode()?;
@@ -315,19 +416,234 @@ fn run_loop(
                     Mode::List => handle_list_key(app, key, conn, cfg)?,
                     Mode::Detail => handle_detail_key(app, key),
                     Mode::Search => handle_search_key(app, key),
-                    Mode::ConfirmDelete => handle_delete_key(app, key, conn)?,
+                    Mode::ConfirmDelete => handle_delete_key(app, key, conn, cfg)?,
                     Mode::Graph => handle_graph_key(app, key),
                     Mode::Syncing => {} // non-interactive, will transition back
                 }
             }
         }
 
+        if app.pending_edit {
+            app.pending_edit = false;
+            edit_selected(terminal, app, conn, cfg)?;
+        }
+
         if app.should_quit {
             return Ok(());
         }
     }
 }
 
+/// Edit the selected memory's content in $VISUAL/$EDITOR, then save and re-embed it.
+fn edit_selected(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    app: &mut App,
+    conn: &Connection,
+    cfg: &Config,
+) -> Result<()> {
+    let Some(mem) = app.selected_memory().cloned() else {
+        return Ok(());
+    };
+
+    // Private (0600), unpredictably named, and removed when dropped
+    let file = tempfile::Builder::new().prefix("ctxovrflw-").suffix(".md").tempfile()?;
+    std::fs::write(file.path(), &mem.content)?;
+    let path = file.path();
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    let status = std::process::Command::new(&editor).arg(path).status();
+    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    enable_raw_mode()?;
+    terminal.clear()?;
+
+    let edited = std::fs::read_to_string(path);
+    drop(file);
+
+    let new_content = match (status, edited) {
+        (Ok(s), Ok(text)) if s.success() => text.trim_end().to_string(),
+        (Err(e), _) => {
+            app.status_msg = Some(format!("Could not launch editor '{editor}': {e}"));
+            return Ok(());
+        }
+        _ => {
+            app.status_msg = Some("Edit cancelled".into());
+            return Ok(());
+        }
+    };
+    if new_content.is_empty() || new_content == mem.content {
+        app.status_msg = Some("No changes".into());
+        return Ok(());
+    }
+    if new_content.len() > crate::validation::MAX_CONTENT_SIZE {
+        app.status_msg = Some("Edit too large — not saved".into());
+        return Ok(());
+    }
+    let (new_content, secrets) = match crate::secrets::apply(cfg.secret_policy, &new_content) {
+        Ok(scanned) => scanned,
+        Err(msg) => {
+            app.status_msg = Some(msg);
+            return Ok(());
+        }
+    };
+
+    let embedding = if cfg.tier.semantic_search_enabled() {
+        crate::embed::get_or_init()
+            .ok()
+            .and_then(|arc| arc.acquire().embed(&new_content).ok())
+    } else {
+        None
+    };
+    if let Some(updated) =
+        db::memories::update(conn, &mem.id, Some(&new_content), None, None, None, embedding.as_deref())?
+        && let Some(row) = app.memories.iter_mut().find(|m| m.id == mem.id)
+    {
+        row.content = updated.content;
+        row.updated_at = updated.updated_at;
+    }
+    app.recalc_counts();
+    app.status_msg = Some(match (secrets.is_empty(), cfg.secret_policy) {
+        (true, _) => format!("Saved memory {}", &mem.id[..8]),
+        (false, crate::secrets::SecretPolicy::Redact) => {
+            format!("Saved memory {} — redacted likely secrets: {}", &mem.id[..8], secrets.join(", "))
+        }
+        (false, _) => format!("Saved memory {} — may contain secrets ({})", &mem.id[..8], secrets.join(", ")),
+    });
+    Ok(())
+}
+
+/// Add or remove the `pinned` tag on the highlighted memory.
+fn toggle_pin(app: &mut App, conn: &Connection, cfg: &Config) -> Result<()> {
+    let Some(mem) = app.selected_memory().cloned() else {
+        return Ok(());
+    };
+    let pinned = mem.tags.iter().any(|t| t == "pinned");
+    let mut evicted = Vec::new();
+    if !pinned {
+        match db::memories::make_room_for_pin(conn, cfg.max_pinned, cfg.pin_overflow)? {
+            db::memories::PinRoom::Full(count) => {
+                app.status_msg = Some(format!("Pin limit reached ({count} of {} pinned) — unpin one first", cfg.max_pinned));
+                return Ok(());
+            }
+            db::memories::PinRoom::Made(ids) => evicted = ids,
+        }
+        for row in app.memories.iter_mut().filter(|m| evicted.contains(&m.id)) {
+            row.tags.retain(|t| t != "pinned");
+        }
+    }
+    let tags: Vec<String> = if pinned {
+        mem.tags.iter().filter(|t| *t != "pinned").cloned().collect()
+    } else {
+        mem.tags.iter().cloned().chain(std::iter::once("pinned".to_string())).collect()
+    };
+
+    if let Some(updated) = db::memories::update(conn, &mem.id, None, Some(&tags), None, None, None)?
+        && let Some(row) = app.memories.iter_mut().find(|m| m.id == mem.id)
+    {
+        row.tags = updated.tags;
+        row.updated_at = updated.updated_at;
+    }
+    app.recalc_counts();
+    app.status_msg = Some(if evicted.is_empty() {
+        format!("{} memory {}", if pinned { "Unpinned" } else { "Pinned" }, &mem.id[..8])
+    } else {
+        format!("Pinned memory {} (pin limit reached — unpinned {} least recently used)", &mem.id[..8], evicted.len())
+    });
+    Ok(())
+}
+
+/// Write the selected memories — or, with no selection, everything the current
+/// filters show — to a timestamped file in the working directory.
+fn export_visible(app: &mut App) -> Result<()> {
+    let rows: Vec<&MemoryRow> = if app.selected.is_empty() {
+        app.filtered.iter().map(|&idx| &app.memories[idx]).collect()
+    } else {
+        app.memories.iter().filter(|m| app.selected.contains(&m.id)).collect()
+    };
+    if rows.is_empty() {
+        app.status_msg = Some("Nothing to export".into());
+        return Ok(());
+    }
+
+    let (body, ext) = match app.export_format {
+        ExportFormat::Json => (export_json(&rows)?, "json"),
+        ExportFormat::Markdown => (export_markdown(app, &rows), "md"),
+    };
+    let path = std::env::current_dir()?.join(format!(
+        "ctxovrflw-memories-{}.{ext}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    std::fs::write(&path, body)?;
+    app.status_msg = Some(format!("Exported {} memories to {}", rows.len(), path.display()));
+    Ok(())
+}
+
+fn export_json(rows: &[&MemoryRow]) -> Result<String> {
+    let memories: Vec<db::memories::Memory> = rows
+        .iter()
+        .map(|m| db::memories::Memory {
+            id: m.id.clone(),
+            content: m.content.clone(),
+            memory_type: m.memory_type.parse().unwrap_or_default(),
+            tags: m.tags.clone(),
+            subject: m.subject.clone(),
+            source: m.source.clone(),
+            agent_id: m.agent_id.clone(),
+            expires_at: m.expires_at.clone(),
+            created_at: m.created_at.clone(),
+            updated_at: m.updated_at.clone(),
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&memories)? + "\n")
+}
+
+fn export_markdown(app: &App, rows: &[&MemoryRow]) -> String {
+    let mut out = String::from("# ctxovrflw memories\n\n");
+    let mut scope = vec![format!("{} memories", rows.len())];
+    if let Some(s) = &app.scope {
+        scope.push(s.clone());
+    }
+    if app.selected.is_empty() {
+        if !app.search.is_empty() {
+            scope.push(format!("filter \"{}\"", app.search));
+        }
+        if app.sync_filter != SyncFilter::All {
+            scope.push(format!("{} only", app.sync_filter.label().to_lowercase()));
+        }
+    } else {
+        scope.push("selection".into());
+    }
+    out.push_str(&format!("_{} · exported {}_\n", scope.join(" · "), chrono::Utc::now().to_rfc3339()));
+
+    for m in rows {
+        out.push_str(&format!("\n## {} · {}", &m.id[..8.min(m.id.len())], m.memory_type));
+        if let Some(subject) = &m.subject {
+            out.push_str(&format!(" · {subject}"));
+        }
+        out.push_str("\n\n");
+        let tags = m.tags.join(", ");
+        let fields = [
+            ("ID", Some(m.id.as_str())),
+            ("Tags", Some(tags.as_str()).filter(|t| !t.is_empty())),
+            ("Source", m.source.as_deref()),
+            ("Agent", m.agent_id.as_deref()),
+            ("Created", Some(m.created_at.as_str())),
+            ("Updated", Some(m.updated_at.as_str())),
+            ("Expires", m.expires_at.as_deref()),
+        ];
+        for (label, value) in fields {
+            if let Some(value) = value {
+                out.push_str(&format!("- **{label}:** {value}\n"));
+            }
+        }
+        out.push_str(&format!("\n{}\n", m.content.trim_end()));
+    }
+    out
+}
+
 // ── Key Handlers ────────────────────────────────────────────────────────
 
 fn handle_list_key(app: &mut App, key: KeyEvent, conn: &Connection, cfg: &Config) -> Result<()> {
@@ -424,8 +740,13 @@ fn handle_list_key(app: &mut App, key: KeyEvent, conn: &Connection, cfg: &Config
 
             match sync_result {
                 Ok((pushed, pulled, pull_purged)) => {
-                    // Reload memories from DB to reflect sync changes
-                    if let Ok(fresh) = load_memories(conn) {
+                    // Reload memories from DB to reflect sync changes; a scoped
+                    // view (recall results, launch filters) re-runs its scope
+                    let fresh = load_memories(conn).and_then(|fresh| match &app.scope_filter {
+                        Some(filter) => filter.apply(conn, fresh),
+                        None => Ok(fresh),
+                    });
+                    if let Ok(fresh) = fresh {
                         app.memories = fresh;
                         app.recalc_counts();
                         app.apply_filters();
@@ -447,6 +768,11 @@ fn handle_list_key(app: &mut App, key: KeyEvent, conn: &Connection, cfg: &Config
                 app.mode = Mode::ConfirmDelete;
             }
         }
+        KeyCode::Char('e') => {
+            app.pending_edit = app.selected_memory().is_some();
+        }
+        KeyCode::Char('p') => toggle_pin(app, conn, cfg)?,
+        KeyCode::Char('x') => export_visible(app)?,
         _ => {}
     }
     Ok(())
@@ -504,7 +830,7 @@ fn handle_search_key(app: &mut App, key: KeyEvent) {
     }
 }
 
-fn handle_delete_key(app: &mut App, key: KeyEvent, conn: &Connection) -> Result<()> {
+fn handle_delete_key(app: &mut App, key: KeyEvent, conn: &Connection, cfg: &Config) -> Result<()> {
     match key.code {
         KeyCode::Char('y') | KeyCode::Char('Y') => {
             if !app.selected.is_empty() {
@@ -512,7 +838,7 @@ fn handle_delete_key(app: &mut App, key: KeyEvent, conn: &Connection) -> Result<
                 let count = app.selected.len();
                 let ids: Vec<String> = app.selected.drain().collect();
                 for id in &ids {
-                    db::memories::delete(conn, id)?;
+                    db::memories::delete(conn, id, cfg.forget_penalty())?;
                 }
                 app.memories.retain(|m| !ids.contains(&m.id));
                 app.recalc_counts();
@@ -521,7 +847,7 @@ fn handle_delete_key(app: &mut App, key: KeyEvent, conn: &Connection) -> Result<
             } else if let Some(mem) = app.selected_memory() {
                 // Single delete
                 let id = mem.id.clone();
-                db::memories::delete(conn, &id)?;
+                db::memories::delete(conn, &id, cfg.forget_penalty())?;
                 app.memories.retain(|m| m.id != id);
                 app.recalc_counts();
                 app.apply_filters();
@@ -600,6 +926,12 @@ fn render_header(f: &mut Frame, app: &App, area: Rect) {
     let mut spans = vec![
         Span::styled(" ctxovrflw ", Style::default().fg(Color::Cyan).bold()),
         Span::raw("│ "),
+    ];
+    if let Some(scope) = &app.scope {
+        spans.push(Span::styled(scope.clone(), Style::default().fg(Color::Magenta).bold()));
+        spans.push(Span::raw(" │ "));
+    }
+    spans.extend([
         Span::styled(format!("{} memories", app.total_count), Style::default().fg(Color::White)),
         Span::raw(" │ "),
         Span::styled(format!("✓{}", app.synced_count), Style::default().fg(Color::Green)),
@@ -613,7 +945,7 @@ fn render_header(f: &mut Frame, app: &App, area: Rect) {
             sync_style(app.sync_filter, app.sync_filter),
         ),
         Span::styled(" [s] ", Style::default().fg(Color::DarkGray)),
-    ];
+    ]);
 
     if !app.selected.is_empty() {
         spans.push(Span::raw("│ "));
@@ -757,6 +1089,12 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
             Span::raw(" filter  "),
             Span::styled("g", Style::default().fg(Color::DarkGray)),
             Span::raw(" graph  "),
+            Span::styled("e", Style::default().fg(Color::DarkGray)),
+            Span::raw(" edit  "),
+            Span::styled("p", Style::default().fg(Color::DarkGray)),
+            Span::raw(" pin  "),
+            Span::styled("x", Style::default().fg(Color::DarkGray)),
+            Span::raw(" export  "),
             Span::styled("d", Style::default().fg(Color::DarkGray)),
             Span::raw(" delete  "),
             Span::styled("S", Style::default().fg(Color::DarkGray)),
@@ -778,6 +1116,19 @@ fn render_footer(f: &mut Frame, app: &App, area: Rect) {
     f.render_widget(Paragraph::new(content), area);
 }
 
+/// `line` split into spans, the words matching `query` styled.
+fn highlighted_line(query: &str, line: &str) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut last = 0;
+    for range in db::search::highlight_ranges(query, line) {
+        spans.push(Span::raw(line[last..range.start].to_string()));
+        spans.push(Span::styled(line[range.clone()].to_string(), Style::default().fg(Color::Yellow).bold()));
+        last = range.end;
+    }
+    spans.push(Span::raw(line[last..].to_string()));
+    Line::from(spans)
+}
+
 fn render_detail(f: &mut Frame, app: &App, area: Rect) {
     let mem = match app.selected_memory() {
         Some(m) => m,
@@ -842,9 +1193,12 @@ fn render_detail(f: &mut Frame, app: &App, area: Rect) {
         Line::from(""),
     ];
 
-    // Add content lines
+    // Add content lines, with the recall query's terms highlighted
     for line in mem.content.lines() {
-        lines.push(Line::from(line.to_string()));
+        lines.push(match &app.highlight {
+            Some(query) => highlighted_line(query, line),
+            None => Line::from(line.to_string()),
+        });
     }
 
     let block = Block::default()
@@ -979,3 +1333,49 @@ fn centered_rect(percent_x: u16, percent_y: u16, area: Rect) -> Rect {
         ])
         .split(popup_layout[1])[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memories::MemoryType;
+
+    fn ids(rows: &[MemoryRow]) -> Vec<&str> {
+        rows.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_launch_scope_picks_up_new_matches() {
+        let data = crate::config::test_data_dir();
+        let conn = db::open_in_memory(&data).unwrap();
+        let store = |content: &str, subject: Option<&str>, tags: &[&str]| {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            db::memories::store(&conn, content, &MemoryType::Semantic, &tags, subject, None, None, None).unwrap().id
+        };
+        let tagged = store("atlas deploys on fridays", Some("project:atlas"), &["infra"]);
+        store("atlas uses postgres", Some("project:atlas"), &[]);
+        store("orion deploys nightly", Some("project:orion"), &["infra"]);
+
+        let filter = ScopeFilter::Launch { subject: Some("project:atlas".into()), tags: vec!["infra".into()] };
+        assert_eq!(ids(&filter.apply(&conn, load_memories(&conn).unwrap()).unwrap()), [tagged.as_str()]);
+
+        // A memory that arrives later (e.g. pulled by a sync) joins the scope
+        let pulled = store("atlas rotates keys quarterly", Some("project:atlas"), &["infra", "security"]);
+        let scoped = filter.apply(&conn, load_memories(&conn).unwrap()).unwrap();
+        assert_eq!(scoped.len(), 2);
+        assert!(ids(&scoped).contains(&pulled.as_str()));
+    }
+
+    #[test]
+    fn test_recall_scope_keeps_ranked_order() {
+        let data = crate::config::test_data_dir();
+        let conn = db::open_in_memory(&data).unwrap();
+        let store = |content: &str| db::memories::store(&conn, content, &MemoryType::Semantic, &[], None, None, None, None).unwrap().id;
+        let first = store("first stored");
+        let second = store("second stored");
+        store("not recalled");
+
+        let filter = ScopeFilter::Recall(vec![first.clone(), "gone".into(), second.clone()]);
+        let scoped = filter.apply(&conn, load_memories(&conn).unwrap()).unwrap();
+        assert_eq!(ids(&scoped), [first.as_str(), second.as_str()]);
+    }
+}
@@ -1,4 +1,8 @@
 pub mod account;
+pub mod audit;
+pub mod cleanup;
+pub mod cloud;
+pub mod completions;
 pub mod forget;
 #[cfg(feature = "pro")]
 pub mod graph;
@@ -6,26 +10,83 @@ pub mod init;
 pub mod init_auto;
 pub mod init_tui;
 pub mod login;
+pub mod logs;
 pub mod logout;
 pub mod memories;
+pub mod pinned;
 pub mod model;
 pub mod model_tui;
+pub mod profile;
 pub mod recall;
 pub mod reindex;
 pub mod remember;
+pub mod restore;
 pub mod status;
+pub mod tags;
+pub mod timeline;
 pub mod update;
+pub mod wipe;
+pub mod workspace;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 
 #[derive(Parser)]
 #[command(name = "ctxovrflw", about = "Universal AI context layer. One memory, every tool.")]
 #[command(version, propagate_version = true)]
 pub struct Cli {
+    /// Read across every workspace instead of only the active one
+    #[arg(long, global = true)]
+    pub all_workspaces: bool,
+
+    /// Only log errors
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+
+    /// Log more: -v for debug, -vv for trace (overrides RUST_LOG and log_level)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Cloud API base URL for this run only, e.g. a staging or self-hosted
+    /// backend (overrides `cloud_url` and CTXOVRFLW_CLOUD_URL; never saved)
+    #[arg(long, global = true, value_name = "URL")]
+    pub cloud_url: Option<String>,
+
+    /// Keep config, databases, models and logs in DIR instead of
+    /// ~/.ctxovrflw (overrides CTXOVRFLW_DATA_DIR)
+    #[arg(long, global = true, value_name = "DIR")]
+    pub data_dir: Option<std::path::PathBuf>,
+
     #[command(subcommand)]
     pub command: Command,
 }
 
+impl Cli {
+    /// Log filter chosen by `-q`/`-v`, if either was given.
+    pub fn log_filter(&self) -> Option<&'static str> {
+        match (self.quiet, self.verbose) {
+            (true, _) => Some("error"),
+            (false, 0) => None,
+            (false, 1) => Some("ctxovrflw=debug"),
+            (false, _) => Some("ctxovrflw=trace"),
+        }
+    }
+}
+
+/// Registry model IDs, so arguments naming a model complete in the shell.
+fn model_ids() -> clap::builder::PossibleValuesParser {
+    crate::embed::models::MODELS.iter().map(|m| m.id).into()
+}
+
+/// Where `init` writes MCP server configs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum InitScope {
+    /// User-level configs (e.g. ~/.cursor/mcp.json)
+    #[default]
+    Global,
+    /// Repo-local configs in the current directory (e.g. .cursor/mcp.json)
+    Project,
+}
+
 #[derive(Subcommand)]
 pub enum Command {
     /// First-time setup — detect tools, download models, configure integrations
@@ -33,13 +94,21 @@ pub enum Command {
         /// Non-interactive mode: accept all defaults, configure all detected tools
         #[arg(short = 'y', long = "yes", alias = "non-interactive")]
         non_interactive: bool,
+
+        /// Where to write MCP configs: global (user-level) or project (current directory)
+        #[arg(long, value_enum, default_value = "global")]
+        scope: InitScope,
+
+        /// Embedding model to install (see `ctxovrflw model list`); requires --yes
+        #[arg(long, requires = "non_interactive", value_parser = model_ids(), hide_possible_values = true)]
+        model: Option<String>,
     },
 
     /// Start the ctxovrflw daemon (MCP server + HTTP API)
     Start {
-        /// HTTP port for REST API (default: 7437)
-        #[arg(short, long, default_value = "7437")]
-        port: u16,
+        /// HTTP port for REST API (default: `port` from config, 7437)
+        #[arg(short, long)]
+        port: Option<u16>,
 
         /// Run in foreground (don't daemonize)
         #[arg(short, long)]
@@ -68,30 +137,131 @@ pub enum Command {
         /// Subject entity (e.g., "user", "project:myapp", "person:sarah")
         #[arg(short, long)]
         subject: Option<String>,
+
+        /// Store keyword-only, without computing an embedding (always on with
+        /// `skip_embedding = true` in config)
+        #[arg(long)]
+        skip_embedding: bool,
     },
 
     /// Semantic search across all memories
     Recall {
         /// Search query
-        query: String,
+        #[arg(required_unless_present_any = ["related", "queries", "tags"])]
+        query: Option<String>,
+
+        /// Another phrasing to search as well; repeat it (or put one per line)
+        /// and the results are merged by rank, memories several phrasings agree on first
+        #[arg(long = "query", value_name = "QUERY")]
+        queries: Vec<String>,
+
+        /// Find memories similar to this memory ID instead of searching by text
+        #[arg(long, value_name = "ID", conflicts_with_all = ["query", "queries", "explain", "interactive"])]
+        related: Option<String>,
 
         /// Max results
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Show why each result matched (source, raw score, matched terms)
+        #[arg(long)]
+        explain: bool,
+
+        /// Open the results in the memories browser to edit, pin or delete them
+        #[arg(short, long, conflicts_with = "explain")]
+        interactive: bool,
+
+        /// Only return these memory types (comma-separated, e.g. procedural,preference)
+        #[arg(short = 't', long = "type", value_delimiter = ',')]
+        types: Vec<String>,
+
+        /// Only return memories with any of these tags (repeat or comma-separate).
+        /// Without a query, lists every memory so tagged, newest first
+        #[arg(long = "tag", value_name = "TAG", value_delimiter = ',', conflicts_with = "related")]
+        tags: Vec<String>,
+
+        /// Print only memory content, one result per line (for piping)
+        #[arg(long, conflicts_with_all = ["explain", "interactive"])]
+        raw: bool,
     },
 
     /// Delete a memory
     Forget {
         /// Memory ID to delete
-        id: String,
+        #[arg(required_unless_present = "list")]
+        id: Option<String>,
+
+        /// Show what would be deleted without deleting
+        #[arg(short, long)]
+        dry_run: bool,
+
+        /// List deleted memories that can still be restored
+        #[arg(long, conflicts_with = "dry_run")]
+        list: bool,
+    },
+
+    /// Delete stale memories: older than --older-than, not updated or recalled since,
+    /// and of the given types. Pinned/policy memories are never touched.
+    Cleanup {
+        /// Age cutoff (relative like 90d / 12h, YYYY-MM-DD, or RFC 3339)
+        #[arg(long, default_value = "90d")]
+        older_than: String,
+
+        /// Memory types to clean up (comma-separated)
+        #[arg(short = 't', long = "type", value_delimiter = ',', default_value = "episodic")]
+        types: Vec<String>,
 
         /// Show what would be deleted without deleting
         #[arg(short, long)]
         dry_run: bool,
     },
 
+    /// Restore a deleted memory (before its tombstone is purged)
+    Restore {
+        /// Memory ID to restore
+        id: String,
+    },
+
+    /// List pinned memories, least recently reinforced (next to be evicted) first
+    Pinned,
+
+    /// Show episodic memories in chronological order, grouped by day or week
+    Timeline {
+        /// Start of the range (RFC 3339, YYYY-MM-DD, or relative like 2h / 7d)
+        #[arg(long, default_value = "7d")]
+        since: String,
+
+        /// End of the range, exclusive (same formats; default now)
+        #[arg(long)]
+        until: Option<String>,
+
+        /// Only memories about this subject
+        #[arg(short, long)]
+        subject: Option<String>,
+
+        /// Group by ISO week instead of by day
+        #[arg(long)]
+        weekly: bool,
+
+        /// Max memories to show (the newest are kept)
+        #[arg(short = 'n', long, default_value = "100")]
+        limit: usize,
+    },
+
     /// Browse, search, and manage memories in an interactive TUI
-    Memories,
+    Memories {
+        /// File format for the `x` (export) key
+        #[arg(long, value_enum, default_value = "markdown")]
+        format: ExportFormat,
+
+        /// Open showing only memories about this subject (e.g. "project:myapp")
+        #[arg(short, long)]
+        subject: Option<String>,
+
+        /// Open showing only memories with any of these tags (repeat or comma-separate)
+        #[arg(short, long = "tag", value_name = "TAG", value_delimiter = ',')]
+        tags: Vec<String>,
+    },
 
     /// Knowledge graph commands (Pro)
     #[cfg(feature = "pro")]
@@ -107,24 +277,145 @@ pub enum Command {
     },
 
     /// Rebuild embeddings for all memories (fixes missing semantic search results)
-    Reindex,
+    Reindex {
+        /// Only re-embed memories updated after this point (RFC 3339, YYYY-MM-DD, or relative like 2h / 7d)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Only embed memories that have no vector yet
+        #[arg(long)]
+        missing_only: bool,
+
+        /// Check memories and vectors are in step (missing / orphaned vectors) and offer to fix
+        #[arg(long, conflicts_with_all = ["since", "missing_only"])]
+        verify: bool,
+
+        /// Parallel embedding workers (each loads its own model copy, max 8)
+        #[arg(short, long, default_value = "1")]
+        jobs: usize,
+    },
+
+    /// List tags with usage counts, or rename a tag across all memories
+    Tags {
+        #[command(subcommand)]
+        action: Option<TagsAction>,
+    },
+
+    /// Manage workspaces (isolated sets of memories within one vault)
+    Workspace {
+        #[command(subcommand)]
+        action: WorkspaceAction,
+    },
+
+    /// Manage cloud identity profiles (separate credentials and database per account)
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
 
     /// Sync memories to cloud
-    Sync,
+    Sync {
+        /// List edits that conflicted between devices instead of syncing
+        #[arg(long)]
+        conflicts: bool,
+
+        /// Mark a listed conflict as reviewed
+        #[arg(long, value_name = "ID", conflicts_with = "conflicts")]
+        resolve: Option<i64>,
+
+        /// Report what would be pushed (count, payload size, graph items) without sending anything
+        #[arg(long, conflicts_with_all = ["conflicts", "resolve"])]
+        dry_run: bool,
+
+        /// Check that the cloud endpoint is reachable and compatible with this client, without syncing
+        #[arg(long, conflicts_with_all = ["conflicts", "resolve", "dry_run"])]
+        check: bool,
+
+        /// Round-trip a throwaway encrypted memory through the cloud to confirm the
+        /// sync key, server and format agree, without pushing local memories
+        #[arg(long, conflicts_with_all = ["conflicts", "resolve", "dry_run", "check"])]
+        self_test: bool,
+    },
 
     /// Show cloud account status, tier, usage
-    Account,
+    Account {
+        /// Print usage as JSON (for monitoring)
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Authenticate for cloud features
     Login {
         /// Authenticate directly with an API key
         #[arg(long)]
         key: Option<String>,
+
+        /// Sign in through the browser with a device code, even without a
+        /// terminal (e.g. over SSH); no email/password fallback
+        #[arg(long, conflicts_with = "key")]
+        device: bool,
     },
 
     /// Log out and disable cloud sync
     Logout,
 
+    /// Delete all local memories, credentials and logs (offboarding / fresh start)
+    Wipe {
+        /// Keep config.toml settings; only its cloud credentials are cleared
+        #[arg(long)]
+        keep_config: bool,
+
+        /// Also remove ctxovrflw from agent MCP configs, rules files and skills
+        #[arg(long)]
+        agents: bool,
+
+        /// First delete this account's memories from the cloud (pushes deletions)
+        #[arg(long)]
+        cloud: bool,
+
+        /// Skip the typed confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+
+    /// Show the audit log of MCP tool calls (enable with `audit_log = true`)
+    Audit {
+        /// Only calls made with this agent_id
+        #[arg(long)]
+        agent: Option<String>,
+
+        /// Only calls to this tool (e.g. remember, forget)
+        #[arg(long)]
+        tool: Option<String>,
+
+        /// Only calls at or after this time (e.g. 2025-01-31 or "2025-01-31 12:00:00", UTC)
+        #[arg(long)]
+        since: Option<String>,
+
+        /// Number of most recent entries to show (0 for all)
+        #[arg(short = 'n', long, default_value = "50")]
+        limit: usize,
+
+        /// Export entries as JSON, one object per line
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show daemon and MCP debug logs
+    Logs {
+        /// Which log to show
+        #[arg(value_enum, default_value = "all")]
+        source: LogSource,
+
+        /// Keep printing new lines as they are written
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Number of lines to show from the end of each log
+        #[arg(short = 'n', long, default_value = "50")]
+        lines: usize,
+    },
+
     /// Check for updates and self-update the binary
     Update {
         /// Just check for updates without installing
@@ -135,7 +426,14 @@ pub enum Command {
     /// Show current version and check for updates
     Version,
 
-    /// Manage the ctxovrflw systemd service
+    /// Print a shell completion script (e.g. `ctxovrflw completions zsh > ~/.zfunc/_ctxovrflw`)
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+
+    /// Manage the ctxovrflw background service (systemd / launchd / Windows Task Scheduler)
     Service {
         #[command(subcommand)]
         action: ServiceAction,
@@ -153,6 +451,12 @@ pub enum GraphAction {
     Build,
     /// Show graph statistics
     Stats,
+    /// Import entities and relations from a JSON document
+    /// ({"entities": [{name, type, metadata}], "relations": [{source, source_type, target, target_type, type, confidence}]})
+    Import {
+        /// Path to the JSON file ("-" reads stdin)
+        file: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -161,18 +465,85 @@ pub enum ModelAction {
     List,
     /// Show current model
     Current,
-    /// Switch to a different model (re-downloads and re-embeds all memories)
+    /// Switch to a different model (downloads it if needed; vectors are kept per model)
     Switch {
         /// Model ID to switch to
+        #[arg(value_parser = model_ids(), hide_possible_values = true)]
         model_id: String,
     },
 }
 
+#[derive(Subcommand)]
+pub enum TagsAction {
+    /// List tags and how many memories carry each (default)
+    List,
+    /// Rename a tag on every memory in the current workspace
+    Rename {
+        /// Existing tag
+        from: String,
+        /// New tag
+        to: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// List profiles and the account each is logged in as
+    List,
+    /// Add a profile with its own database
+    Add {
+        /// Profile name (letters, digits, '-', '_', '.')
+        name: String,
+        /// Database file to use (default: ~/.ctxovrflw/profiles/<name>/memories.db)
+        #[arg(long)]
+        db: Option<std::path::PathBuf>,
+    },
+    /// Make a profile active; login, logout, sync and account then use it
+    Switch {
+        name: String,
+    },
+    /// Forget a profile's credentials (its database is kept)
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WorkspaceAction {
+    /// List workspaces and their memory counts
+    List,
+    /// Make a workspace active (created on first write)
+    Switch {
+        /// Workspace name (letters, digits, '-', '_', '.')
+        name: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum LogSource {
+    /// Daemon and MCP logs, interleaved when following
+    All,
+    /// Daemon log (data dir daemon.log)
+    Daemon,
+    /// MCP stdio debug log (data dir mcp-debug.log)
+    Mcp,
+}
+
+/// How exported memories are written.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// One section per memory, for reading and sharing
+    #[default]
+    Markdown,
+    /// The same memory objects the HTTP API returns
+    Json,
+}
+
 #[derive(Subcommand)]
 pub enum ServiceAction {
-    /// Install ctxovrflw as a systemd user service
+    /// Install ctxovrflw as a user service that starts on login
     Install,
-    /// Remove the systemd service
+    /// Remove the background service
     Uninstall,
     /// Show service status
     Status,
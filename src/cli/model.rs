@@ -1,7 +1,5 @@
 use anyhow::{Context, Result};
-use chrono::Utc;
 use reqwest;
-use serde_json::json;
 use std::fs;
 use std::io::{self, Write};
 use std::path::PathBuf;
@@ -13,6 +11,8 @@ pub fn list() -> Result<()> {
     let cfg = Config::load().unwrap_or_default();
     let current_model = &cfg.embedding_model;
 
+    let stored = stored_vectors();
+
     println!("Available Embedding Models:\n");
 
     for model in embed::models::MODELS.iter() {
@@ -26,6 +26,10 @@ pub fn list() -> Result<()> {
         if model.requires_prefix && model.query_prefix.is_some() {
             println!("    Query prefix: \"{}\"", model.query_prefix.unwrap());
         }
+        if let Some(v) = stored.iter().find(|v| v.model_id == model.id) {
+            let note = if v.active { "" } else { " (kept — reused if you switch back)" };
+            println!("    Vectors:    {} stored{}", v.vectors, note);
+        }
         println!();
     }
 
@@ -52,6 +56,9 @@ pub fn current() -> Result<()> {
         if model.requires_prefix && model.query_prefix.is_some() {
             println!("Query prefix:  \"{}\"", model.query_prefix.unwrap());
         }
+        if let Some(v) = stored_vectors().into_iter().find(|v| v.active) {
+            println!("Vectors:       {} stored ({}, {} dims)", v.vectors, v.model_id, v.dim);
+        }
         
         // Check if model files exist
         let model_dir = Config::model_dir()?;
@@ -90,7 +97,8 @@ pub fn current() -> Result<()> {
     Ok(())
 }
 
-/// Switch to a different embedding model
+/// Switch to a different embedding model. Vectors are kept per model, so only
+/// memories this model hasn't embedded before need embedding.
 pub async fn switch(model_id: &str) -> Result<()> {
     // Validate model exists in registry
     let model_info = embed::models::get_model(model_id)
@@ -124,46 +132,29 @@ pub async fn switch(model_id: &str) -> Result<()> {
     }
     
     let current_model_id = cfg.embedding_model.clone();
-    
-    // Step 1: Download new model files
-    println!("📥 Downloading model files...");
-    download_model_files(model_info).await?;
-    
-    // Step 2: Export all data from database
-    println!("💾 Exporting existing data...");
-    let export_data = export_all_data(&current_model_id)?;
-    
-    // Step 3: Close database connection and delete database file
-    println!("🗑️  Removing old database...");
-    let db_path = Config::db_path()?;
-    if db_path.exists() {
-        fs::remove_file(&db_path)
-            .context("Failed to remove old database")?;
+    let Switched { restored, missing } = apply_switch(&mut cfg, model_info).await?;
+
+    println!("✅ Switched to model '{}'", model_id);
+    if restored > 0 {
+        println!("   {} vectors from an earlier switch reused", restored);
     }
+    println!("   Vectors for '{}' are kept — switching back is instant", current_model_id);
     
-    // Step 4: Update config with new model
-    println!("⚙️  Updating configuration...");
-    cfg.embedding_model = model_id.to_string();
-    cfg.embedding_dim = model_info.dim; // This will be recalculated on load, but set it for consistency
-    cfg.save()?;
-    
-    // Step 5: Set new embedding dimension BEFORE opening DB
-    // (db::open creates memory_vectors with embedding_dim(), which defaults to old value)
-    embed::set_embedding_dim(model_info.dim);
-    
-    println!("🏗️  Creating new database...");
-    let _conn = db::open()?; // Creates tables with correct new dimension
-    
-    // Step 6: Import all data
-    println!("📤 Importing data...");
-    import_all_data(&export_data)?;
-    
-    // Step 7: Re-embed all memories
-    println!("🔄 Re-embedding memories with new model...");
-    let reembedded_count = reembed_all_memories()?;
-    
-    println!("✅ Successfully switched to model '{}'", model_id);
-    println!("   {} memories re-embedded", reembedded_count);
+    if missing > 0 {
+        println!();
+        println!("{} memories have no vector for this model yet.", missing);
+        let embed_now = atty::is(atty::Stream::Stdin)
+            && dialoguer::Confirm::new()
+                .with_prompt("Embed them now?")
+                .default(true)
+                .interact()?;
+        if embed_now {
+            super::reindex::run(None, true, 1)?;
+        } else {
+            println!("   The daemon embeds them in the background; until then they're found by keyword search only.");
+            println!("   To do it now: ctxovrflw reindex --missing-only");
+        }
+    }
     println!();
     println!("Next steps:");
     println!("   • Restart the daemon: ctxovrflw start");
@@ -172,18 +163,78 @@ pub async fn switch(model_id: &str) -> Result<()> {
     Ok(())
 }
 
-async fn download_model_files(model_info: &embed::models::EmbeddingModel) -> Result<()> {
+/// Result of a model switch that went through.
+#[derive(Debug)]
+pub(crate) struct Switched {
+    /// Vectors for the new model reused from an earlier switch
+    pub restored: usize,
+    /// Memories with no vector for the new model yet
+    pub missing: usize,
+}
+
+/// Switch to `model_id` without prompting, for scripted callers. Fails instead
+/// of asking when the model is unknown or the daemon is running; returns `None`
+/// if the model is already active.
+pub(crate) async fn switch_unattended(model_id: &str) -> Result<Option<Switched>> {
+    let model_info = embed::models::get_model(model_id)
+        .context(format!("Model '{}' not found in registry", model_id))?;
+    let mut cfg = Config::load()?;
+    if cfg.embedding_model == model_id {
+        return Ok(None);
+    }
+    if daemon_running(&cfg).await {
+        anyhow::bail!(
+            "The ctxovrflw daemon is running; stop it first (ctxovrflw stop) to switch to '{model_id}'"
+        );
+    }
+    apply_switch(&mut cfg, model_info).await.map(Some)
+}
+
+/// Download `model_info`, make it the configured model and swap its vectors in.
+async fn apply_switch(cfg: &mut Config, model_info: &embed::models::EmbeddingModel) -> Result<Switched> {
+    // Open once under the outgoing model so its vectors are on record as its own
+    drop(db::open()?);
+
+    // Step 1: Download new model files
+    println!("📥 Downloading model files...");
+    download_model_files(model_info).await?;
+
+    // Step 2: Update config with new model
+    println!("⚙️  Updating configuration...");
+    cfg.embedding_model = model_info.id.to_string();
+    cfg.embedding_dim = model_info.dim; // This will be recalculated on load, but set it for consistency
+    cfg.save()?;
+    embed::set_embedding_dim(model_info.dim);
+
+    // Step 3: Park the old model's vectors and bring back any this model had before
+    let conn = db::open()?;
+    db::activate_configured_model(&conn, cfg)?;
+    let restored = db::vector_models(&conn)?
+        .into_iter()
+        .find(|m| m.active)
+        .map_or(0, |m| m.vectors);
+    let (missing, _) = db::memories::vector_drift(&conn)?;
+    Ok(Switched { restored, missing: missing.len() })
+}
+
+/// Models with stored vectors, without creating a database that isn't there yet.
+fn stored_vectors() -> Vec<db::VectorModel> {
+    if !Config::db_path().is_ok_and(|p| p.exists()) {
+        return Vec::new();
+    }
+    db::open().and_then(|conn| db::vector_models(&conn)).unwrap_or_default()
+}
+
+pub(crate) async fn download_model_files(model_info: &embed::models::EmbeddingModel) -> Result<()> {
     let model_dir = Config::model_dir()?;
     let model_subdir = model_dir.join(model_info.id);
     fs::create_dir_all(&model_subdir)?;
     
-    let client = reqwest::Client::new();
-    
     // Download ONNX model
     let model_file = model_subdir.join("model.onnx");
     if !model_file.exists() {
         println!("  Downloading ONNX model...");
-        download_file(&client, model_info.onnx_url, &model_file).await?;
+        download_file(model_info.onnx_url, model_info.onnx_sha256, &model_file).await?;
     } else {
         println!("  ONNX model already exists");
     }
@@ -192,7 +243,7 @@ async fn download_model_files(model_info: &embed::models::EmbeddingModel) -> Res
     let tokenizer_file = model_subdir.join("tokenizer.json");
     if !tokenizer_file.exists() {
         println!("  Downloading tokenizer...");
-        download_file(&client, model_info.tokenizer_url, &tokenizer_file).await?;
+        download_file(model_info.tokenizer_url, model_info.tokenizer_sha256, &tokenizer_file).await?;
     } else {
         println!("  Tokenizer already exists");
     }
@@ -200,297 +251,22 @@ async fn download_model_files(model_info: &embed::models::EmbeddingModel) -> Res
     Ok(())
 }
 
-async fn download_file(client: &reqwest::Client, url: &str, dest: &PathBuf) -> Result<()> {
-    let response = client
-        .get(url)
-        .send()
-        .await
-        .context(format!("Failed to fetch {}", url))?;
-    
-    if !response.status().is_success() {
-        anyhow::bail!("HTTP {} when downloading {}", response.status(), url);
-    }
-    
-    let content_length = response.content_length().unwrap_or(0);
-    
-    let bytes = response.bytes().await
-        .context("Failed to download response body")?;
-    
-    fs::write(dest, &bytes)
-        .context(format!("Failed to write file {}", dest.display()))?;
-    
-    if content_length > 0 {
-        println!("    Downloaded {} MB", content_length / 1_048_576);
+async fn download_file(
+    url: &str,
+    sha256: Option<&str>,
+    dest: &PathBuf,
+) -> Result<()> {
+    let size = embed::download::fetch_to_file(url, sha256, dest, 0, |_, _| {}).await?;
+    
+    if size >= 1_048_576 {
+        println!("    Downloaded {} MB", size / 1_048_576);
     } else {
-        println!("    Downloaded {} KB", bytes.len() / 1024);
+        println!("    Downloaded {} KB", size / 1024);
     }
     
     Ok(())
 }
 
-fn export_all_data(source_model: &str) -> Result<serde_json::Value> {
-    let conn = db::open()?;
-    
-    // Export memories
-    let memories = export_all_memories(&conn)?;
-    
-    // Export entities (Pro feature)
-    let entities = export_all_entities(&conn)?;
-    
-    // Export relations (Pro feature)  
-    let relations = export_all_relations(&conn)?;
-    
-    Ok(json!({
-        "memories": memories,
-        "entities": entities,
-        "relations": relations,
-        "exported_at": Utc::now().to_rfc3339(),
-        "source_model": source_model,
-    }))
-}
-
-fn export_all_memories(conn: &rusqlite::Connection) -> Result<Vec<serde_json::Value>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at, deleted, synced_at
-         FROM memories ORDER BY created_at"
-    )?;
-    
-    let results = stmt.query_map([], |row| {
-        Ok(json!({
-            "id": row.get::<_, String>(0)?,
-            "content": row.get::<_, String>(1)?,
-            "type": row.get::<_, String>(2)?,
-            "tags": row.get::<_, String>(3)?,
-            "subject": row.get::<_, Option<String>>(4)?,
-            "source": row.get::<_, Option<String>>(5)?,
-            "agent_id": row.get::<_, Option<String>>(6)?,
-            "expires_at": row.get::<_, Option<String>>(7)?,
-            "created_at": row.get::<_, String>(8)?,
-            "updated_at": row.get::<_, String>(9)?,
-            "deleted": row.get::<_, i32>(10)?,
-            "synced_at": row.get::<_, Option<String>>(11)?,
-        }))
-    })?.collect::<Result<Vec<_>, _>>()?;
-    
-    Ok(results)
-}
-
-#[cfg(feature = "pro")]
-fn export_all_entities(conn: &rusqlite::Connection) -> Result<Vec<serde_json::Value>> {
-    let stmt_result = conn.prepare(
-        "SELECT id, name, type, properties, created_at, updated_at
-         FROM entities ORDER BY created_at"
-    );
-    
-    let mut stmt = match stmt_result {
-        Ok(stmt) => stmt,
-        Err(_) => return Ok(vec![]), // Table doesn't exist
-    };
-    
-    let results = stmt.query_map([], |row| {
-        Ok(json!({
-            "id": row.get::<_, String>(0)?,
-            "name": row.get::<_, String>(1)?,
-            "type": row.get::<_, String>(2)?,
-            "properties": row.get::<_, String>(3)?,
-            "created_at": row.get::<_, String>(4)?,
-            "updated_at": row.get::<_, String>(5)?,
-        }))
-    })?.collect::<Result<Vec<_>, _>>()?;
-    
-    Ok(results)
-}
-
-#[cfg(not(feature = "pro"))]
-fn export_all_entities(_conn: &rusqlite::Connection) -> Result<Vec<serde_json::Value>> {
-    Ok(vec![])
-}
-
-#[cfg(feature = "pro")]
-fn export_all_relations(conn: &rusqlite::Connection) -> Result<Vec<serde_json::Value>> {
-    let stmt_result = conn.prepare(
-        "SELECT id, source_id, source_type, target_id, target_type, relation_type, properties, created_at
-         FROM relations ORDER BY created_at"
-    );
-    
-    let mut stmt = match stmt_result {
-        Ok(stmt) => stmt,
-        Err(_) => return Ok(vec![]), // Table doesn't exist
-    };
-    
-    let results = stmt.query_map([], |row| {
-        Ok(json!({
-            "id": row.get::<_, String>(0)?,
-            "source_id": row.get::<_, String>(1)?,
-            "source_type": row.get::<_, String>(2)?,
-            "target_id": row.get::<_, String>(3)?,
-            "target_type": row.get::<_, String>(4)?,
-            "relation_type": row.get::<_, String>(5)?,
-            "properties": row.get::<_, String>(6)?,
-            "created_at": row.get::<_, String>(7)?,
-        }))
-    })?.collect::<Result<Vec<_>, _>>()?;
-    
-    Ok(results)
-}
-
-#[cfg(not(feature = "pro"))]
-fn export_all_relations(_conn: &rusqlite::Connection) -> Result<Vec<serde_json::Value>> {
-    Ok(vec![])
-}
-
-fn import_all_data(export_data: &serde_json::Value) -> Result<()> {
-    let conn = db::open()?;
-    
-    // Import memories
-    let empty_memories = vec![];
-    let memories = export_data["memories"].as_array().unwrap_or(&empty_memories);
-    for memory in memories {
-        import_memory(&conn, memory)?;
-    }
-    
-    // Import entities (Pro feature)
-    let empty_entities = vec![];
-    let entities = export_data["entities"].as_array().unwrap_or(&empty_entities);
-    for entity in entities {
-        import_entity(&conn, entity)?;
-    }
-    
-    // Import relations (Pro feature)
-    let empty_relations = vec![];
-    let relations = export_data["relations"].as_array().unwrap_or(&empty_relations);
-    for relation in relations {
-        import_relation(&conn, relation)?;
-    }
-    
-    Ok(())
-}
-
-fn import_memory(conn: &rusqlite::Connection, memory: &serde_json::Value) -> Result<()> {
-    conn.execute(
-        "INSERT INTO memories (id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at, deleted, synced_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
-        rusqlite::params![
-            memory["id"].as_str().unwrap(),
-            memory["content"].as_str().unwrap(),
-            memory["type"].as_str().unwrap(),
-            memory["tags"].as_str().unwrap(),
-            memory["subject"].as_str(),
-            memory["source"].as_str(),
-            memory["agent_id"].as_str(),
-            memory["expires_at"].as_str(),
-            memory["created_at"].as_str().unwrap(),
-            memory["updated_at"].as_str().unwrap(),
-            memory["deleted"].as_i64().unwrap_or(0),
-            memory["synced_at"].as_str(),
-        ]
-    )?;
-    
-    Ok(())
-}
-
-#[cfg(feature = "pro")]
-fn import_entity(conn: &rusqlite::Connection, entity: &serde_json::Value) -> Result<()> {
-    let result = conn.execute(
-        "INSERT INTO entities (id, name, type, properties, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        rusqlite::params![
-            entity["id"].as_str().unwrap(),
-            entity["name"].as_str().unwrap(),
-            entity["type"].as_str().unwrap(),
-            entity["properties"].as_str().unwrap(),
-            entity["created_at"].as_str().unwrap(),
-            entity["updated_at"].as_str().unwrap(),
-        ]
-    );
-    
-    // Ignore errors (table might not exist)
-    let _ = result;
-    Ok(())
-}
-
-#[cfg(not(feature = "pro"))]
-fn import_entity(_conn: &rusqlite::Connection, _entity: &serde_json::Value) -> Result<()> {
-    Ok(())
-}
-
-#[cfg(feature = "pro")]
-fn import_relation(conn: &rusqlite::Connection, relation: &serde_json::Value) -> Result<()> {
-    let result = conn.execute(
-        "INSERT INTO relations (id, source_id, source_type, target_id, target_type, relation_type, properties, created_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-        rusqlite::params![
-            relation["id"].as_str().unwrap(),
-            relation["source_id"].as_str().unwrap(),
-            relation["source_type"].as_str().unwrap(),
-            relation["target_id"].as_str().unwrap(),
-            relation["target_type"].as_str().unwrap(),
-            relation["relation_type"].as_str().unwrap(),
-            relation["properties"].as_str().unwrap(),
-            relation["created_at"].as_str().unwrap(),
-        ]
-    );
-    
-    // Ignore errors (table might not exist)
-    let _ = result;
-    Ok(())
-}
-
-#[cfg(not(feature = "pro"))]
-fn import_relation(_conn: &rusqlite::Connection, _relation: &serde_json::Value) -> Result<()> {
-    Ok(())
-}
-
-fn reembed_all_memories() -> Result<usize> {
-    use rusqlite::params;
-    
-    let conn = db::open()?;
-    let embedder_arc = embed::get_or_init()?;
-    let mut embedder = embedder_arc.lock().unwrap();
-    
-    // Get all memory IDs and content
-    let mut stmt = conn.prepare(
-        "SELECT id, content FROM memories WHERE deleted = 0"
-    )?;
-    
-    let memories: Vec<(String, String)> = stmt.query_map([], |row| {
-        Ok((row.get(0)?, row.get(1)?))
-    })?.collect::<Result<Vec<_>, _>>()?;
-    
-    let total = memories.len();
-    let mut processed = 0;
-    
-    for (id, content) in memories {
-        // Generate embedding
-        let embedding = embedder.embed(&content)?;
-        let embedding_bytes: Vec<u8> = embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
-        
-        // Update memory table with new embedding
-        conn.execute(
-            "UPDATE memories SET embedding = ?1 WHERE id = ?2",
-            params![embedding_bytes, id]
-        )?;
-        
-        // Insert/update vector table
-        conn.execute(
-            "INSERT OR REPLACE INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-            params![id, embedding_bytes]
-        )?;
-        
-        processed += 1;
-        if processed % 10 == 0 || processed == total {
-            print!("\r  Progress: {}/{} memories", processed, total);
-            io::stdout().flush()?;
-        }
-    }
-    
-    if total > 0 {
-        println!(); // New line after progress
-    }
-    
-    Ok(total)
-}
-
 async fn daemon_running(cfg: &Config) -> bool {
     let daemon_url = cfg.daemon_url();
     if let Ok(client) = reqwest::Client::builder()
@@ -501,4 +277,30 @@ async fn daemon_running(cfg: &Config) -> bool {
     } else {
         false
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_switch_unattended() {
+        let _data = crate::config::test_data_dir();
+        // Nothing listens on port 1, so no daemon is "running"
+        Config { port: 1, ..Config::default() }.save().unwrap();
+        let target = embed::models::get_model("bge-small-en-v1.5").unwrap();
+        let dir = Config::model_dir().unwrap().join(target.id);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("model.onnx"), b"onnx").unwrap();
+        fs::write(dir.join("tokenizer.json"), b"{}").unwrap();
+
+        assert!(switch_unattended("no-such-model").await.is_err());
+        assert_eq!(Config::load().unwrap().embedding_model, Config::default().embedding_model);
+
+        let switched = switch_unattended(target.id).await.unwrap().expect("switched");
+        assert_eq!(switched.missing, 0);
+        assert_eq!(Config::load().unwrap().embedding_model, target.id);
+
+        // Already active: nothing to do
+        assert!(switch_unattended(target.id).await.unwrap().is_none());
+    }
+}
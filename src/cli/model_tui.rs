@@ -537,7 +537,7 @@ fn draw_confirm_popup(f: &mut Frame, app: &App) {
 
     lines.push(Line::from(vec![
         Span::styled(
-            "  🔄 All memories will be re-embedded",
+            "  🔄 Memories new to this model will be embedded",
             Style::default().fg(Color::Yellow),
         ),
     ]));
@@ -0,0 +1,32 @@
+use anyhow::Result;
+
+use crate::config::{Config, PinOverflow};
+
+/// List pinned memories, least recently reinforced first — the order in which
+/// `pin_memory` evicts them once `max_pinned` is reached.
+pub fn run(cfg: &Config) -> Result<()> {
+    let conn = crate::db::open()?;
+    let pinned = crate::db::memories::list_pinned(&conn)?;
+
+    if pinned.is_empty() {
+        println!("No pinned memories.");
+        return Ok(());
+    }
+
+    for (memory, reinforced_at) in &pinned {
+        let preview: String = memory.content.chars().take(80).collect();
+        let ellipsis = if memory.content.chars().count() > 80 { "…" } else { "" };
+        println!("{}  {reinforced_at}  {preview}{ellipsis}", &memory.id[..8]);
+    }
+    println!();
+    if cfg.max_pinned > 0 {
+        let overflow = match cfg.pin_overflow {
+            PinOverflow::Reject => "new pins are refused",
+            PinOverflow::Evict => "new pins evict the first one listed",
+        };
+        println!("{} of {} pinned; at the cap {overflow}.", pinned.len(), cfg.max_pinned);
+    } else {
+        println!("{} pinned (no cap).", pinned.len());
+    }
+    Ok(())
+}
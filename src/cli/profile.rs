@@ -0,0 +1,102 @@
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::config::{Config, Profile};
+
+pub fn list(cfg: &Config) -> Result<()> {
+    let active = Profile {
+        email: cfg.email.clone(),
+        api_key: cfg.api_key.clone(),
+        tier: cfg.tier.clone(),
+        db_path: cfg.db_path.clone(),
+        ..Profile::default()
+    };
+    let mut profiles: Vec<(&str, &Profile)> = cfg.profiles.iter().map(|(n, p)| (n.as_str(), p)).collect();
+    profiles.push((&cfg.active_profile, &active));
+    profiles.sort_by_key(|(name, _)| *name);
+
+    for (name, profile) in profiles {
+        let marker = if name == cfg.active_profile { "*" } else { " " };
+        let account = match (&profile.api_key, &profile.email) {
+            (Some(_), Some(email)) => format!("{email}, {:?}", profile.tier),
+            (Some(_), None) => format!("logged in, {:?}", profile.tier),
+            (None, _) => "not logged in".to_string(),
+        };
+        let db = match &profile.db_path {
+            Some(path) => path.display().to_string(),
+            None => Config::data_dir()?.join("memories.db").display().to_string(),
+        };
+        println!("{marker} {name} ({account})");
+        println!("    db: {db}");
+    }
+
+    Ok(())
+}
+
+pub fn add(name: &str, db: Option<PathBuf>) -> Result<()> {
+    validate_name(name)?;
+
+    let mut cfg = Config::load()?;
+    if cfg.active_profile == name || cfg.profiles.contains_key(name) {
+        anyhow::bail!("Profile '{name}' already exists");
+    }
+    let db_path = match db {
+        Some(path) => path,
+        None => Config::profile_db_path(name)?,
+    };
+    cfg.profiles.insert(name.to_string(), Profile {
+        db_path: Some(db_path.clone()),
+        ..Profile::default()
+    });
+    cfg.save()?;
+
+    println!("✓ Added profile '{name}' (db: {}).", db_path.display());
+    println!("  Switch to it with `ctxovrflw profile switch {name}`, then `ctxovrflw login`.");
+    Ok(())
+}
+
+pub fn switch(name: &str) -> Result<()> {
+    let mut cfg = Config::load()?;
+    if cfg.active_profile == name {
+        println!("Already using profile '{name}'.");
+        return Ok(());
+    }
+    cfg.switch_profile(name)?;
+    cfg.save()?;
+
+    println!("✓ Switched to profile '{name}'.");
+    if !cfg.is_logged_in() {
+        println!("  Not logged in yet — run `ctxovrflw login` to connect this profile.");
+    }
+    if crate::daemon::is_service_running() {
+        println!("  Restart the daemon to use it: ctxovrflw stop && ctxovrflw start");
+    }
+    Ok(())
+}
+
+pub fn remove(name: &str) -> Result<()> {
+    let mut cfg = Config::load()?;
+    if cfg.active_profile == name {
+        anyhow::bail!("Can't remove the active profile; switch to another one first");
+    }
+    let Some(profile) = cfg.profiles.remove(name) else {
+        anyhow::bail!("No profile named '{name}'");
+    };
+    cfg.save()?;
+
+    println!("✓ Removed profile '{name}' and its credentials.");
+    if let Some(path) = profile.db_path {
+        println!("  Its database was kept at {}", path.display());
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        anyhow::bail!("Profile name must be 1-64 characters");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        anyhow::bail!("Profile name may only contain letters, digits, '-', '_' and '.'");
+    }
+    Ok(())
+}
@@ -1,54 +1,189 @@
 use anyhow::Result;
 use crate::config::Config;
 
-pub async fn run(cfg: &Config, query: &str, limit: usize) -> Result<()> {
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    cfg: &Config,
+    queries: &[String],
+    limit: usize,
+    types: &[String],
+    tags: &[String],
+    explain: bool,
+    interactive: bool,
+    raw: bool,
+) -> Result<()> {
+    let types = crate::db::memories::parse_types(types)?;
+
+    // Each value may hold several queries, one per line
+    let mut split: Vec<String> = Vec::new();
+    for query in queries.iter().flat_map(|q| q.lines()).map(str::trim).filter(|q| !q.is_empty()) {
+        if !split.iter().any(|q| q == query) {
+            split.push(query.to_string());
+        }
+    }
+    let queries = split;
+    if queries.is_empty() {
+        anyhow::bail!("Nothing to search for — pass a query");
+    }
+    let query = &queries.join(" | ");
+
     // Sync before recall to get latest from other devices
     if cfg.is_logged_in() {
         let _ = crate::sync::run_silent(cfg).await;
     }
 
     let conn = crate::db::open()?;
+    let requested = limit;
+    // Type and tag filtering happen after ranking, so over-fetch to still fill `limit`
+    let limit = if types.is_empty() && tags.is_empty() { limit } else { limit * 4 };
 
     use crate::db::search::SearchMethod;
 
-    let (results, method) = if cfg.tier.semantic_search_enabled() {
-        match crate::embed::Embedder::new() {
-            Ok(mut embedder) => match embedder.embed(query) {
-                Ok(embedding) => {
-                    #[cfg(feature = "pro")]
-                    {
-                        match crate::db::search::hybrid_search(&conn, query, &embedding, limit) {
-                            Ok(r) if !r.is_empty() => (r, SearchMethod::Hybrid),
-                            _ => (crate::db::search::keyword_search(&conn, query, limit)?, SearchMethod::Keyword),
-                        }
-                    }
-                    #[cfg(not(feature = "pro"))]
-                    {
-                        let sem = crate::db::search::semantic_search(&conn, &embedding, limit)?;
-                        if !sem.is_empty() {
-                            (sem, SearchMethod::Semantic)
-                        } else {
-                            (crate::db::search::keyword_search(&conn, query, limit)?, SearchMethod::Keyword)
-                        }
-                    }
-                }
-                Err(_) => (crate::db::search::keyword_search(&conn, query, limit)?, SearchMethod::Keyword),
-            },
-            Err(_) => (crate::db::search::keyword_search(&conn, query, limit)?, SearchMethod::Keyword),
-        }
-    } else {
-        (crate::db::search::keyword_search(&conn, query, limit)?, SearchMethod::Keyword)
+    let (embedder, _) = crate::mcp::tools::recall_embedder(cfg);
+    let (results, method, matched, sources) = crate::mcp::tools::search_queries(embedder.as_deref(), &conn, &queries, limit)?;
+
+    let results: Vec<_> = match cfg.recall_min_score {
+        Some(threshold) if method == SearchMethod::Semantic => {
+            results.into_iter().filter(|(_, s)| *s >= threshold).collect()
+        }
+        _ => results,
     };
+    let results = crate::db::search::boost_priority(results, cfg.priority_boost);
+    let mut results = crate::db::search::boost_access(&conn, results, cfg.access_boost)?;
+    results.retain(|(m, _)| {
+        (types.is_empty() || types.contains(&m.memory_type)) && (tags.is_empty() || m.tags.iter().any(|t| tags.contains(t)))
+    });
+    results.truncate(requested);
 
     if results.is_empty() {
-        println!("No memories found for: {query}");
+        if !raw {
+            println!("No memories found for: {query}");
+        }
+        return Ok(());
+    }
+
+    if raw {
+        for (memory, _) in &results {
+            println!("{}", memory.content);
+        }
         return Ok(());
     }
 
+    if interactive {
+        let ids: Vec<String> = results.iter().map(|(m, _)| m.id.clone()).collect();
+        return crate::cli::memories::run_recall(cfg, query, &ids).await;
+    }
+
     println!("Search method: {method}\n");
 
     for (memory, score) in &results {
-        println!("[{}] (score: {:.2}, type: {}) {}", memory.id, score, memory.memory_type, memory.content);
+        let matched_query = matched.get(&memory.id).map_or(query.as_str(), |&i| queries[i].as_str());
+        println!(
+            "[{}] (score: {:.2}, type: {}) {}",
+            memory.id,
+            score,
+            memory.memory_type,
+            highlight(matched_query, &memory.content)
+        );
+        if !memory.tags.is_empty() {
+            println!("     tags: {}", memory.tags.join(", "));
+        }
+        if queries.len() > 1 && let Some(&i) = matched.get(&memory.id) {
+            println!("     matched: {}", queries[i]);
+        }
+        if explain {
+            let source = sources.get(&memory.id).copied().unwrap_or_else(|| method.into());
+            let explanation = crate::db::search::explain_match(source, *score, matched_query, &memory.content);
+            println!("     {}", explanation.summary());
+        }
+    }
+
+    Ok(())
+}
+
+/// `content` with the words matching `query` in bold yellow. Plain when
+/// stdout isn't a terminal (or NO_COLOR is set), so piped output is unchanged.
+fn highlight(query: &str, content: &str) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for range in crate::db::search::highlight_ranges(query, content) {
+        out.push_str(&content[last..range.start]);
+        out.push_str(&console::style(&content[range.clone()]).yellow().bold().to_string());
+        last = range.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+/// Memories with any of `tags`, newest first — browsing, no query or ranking.
+pub fn tagged(tags: &[String], limit: usize, types: &[String], raw: bool) -> Result<()> {
+    let types = crate::db::memories::parse_types(types)?;
+    let conn = crate::db::open()?;
+
+    let tag_refs: Vec<&str> = tags.iter().map(String::as_str).collect();
+    let fetch = if types.is_empty() { limit } else { limit * 4 };
+    let mut memories = crate::db::search::by_any_tag(&conn, &tag_refs, fetch)?;
+    // The CLI has no agent_id, so channel-private memories stay hidden
+    memories.retain(|m| {
+        (types.is_empty() || types.contains(&m.memory_type)) && crate::db::search::visible_to(m, None)
+    });
+    memories.truncate(limit);
+
+    if memories.is_empty() {
+        if !raw {
+            println!("No memories tagged: {}", tags.join(", "));
+        }
+        return Ok(());
+    }
+
+    for memory in &memories {
+        if raw {
+            println!("{}", memory.content);
+            continue;
+        }
+        println!(
+            "[{}] (updated: {}, type: {}) {}",
+            memory.id,
+            memory.updated_at.get(..10).unwrap_or(""),
+            memory.memory_type,
+            memory.content
+        );
+        println!("     tags: {}", memory.tags.join(", "));
+    }
+
+    Ok(())
+}
+
+/// Memories most similar to `id`, by its stored embedding.
+pub fn related(id: &str, limit: usize, types: &[String], raw: bool) -> Result<()> {
+    let types = crate::db::memories::parse_types(types)?;
+    let conn = crate::db::open()?;
+
+    if crate::db::memories::get(&conn, id)?.is_none() {
+        anyhow::bail!("Memory {id} not found");
+    }
+    // Type and channel-private filtering happen after ranking, so over-fetch to still fill `limit`
+    let Some(mut results) = crate::db::search::related(&conn, id, limit * 4)? else {
+        anyhow::bail!("Memory {id} has no embedding yet. Run `ctxovrflw reindex` to create one.");
+    };
+    results.retain(|(m, _)| {
+        (types.is_empty() || types.contains(&m.memory_type)) && crate::db::search::visible_to(m, None)
+    });
+    results.truncate(limit);
+
+    if results.is_empty() {
+        if !raw {
+            println!("No memories related to {id}");
+        }
+        return Ok(());
+    }
+
+    for (memory, score) in &results {
+        if raw {
+            println!("{}", memory.content);
+            continue;
+        }
+        println!("[{}] (similarity: {:.2}, type: {}) {}", memory.id, score, memory.memory_type, memory.content);
         if !memory.tags.is_empty() {
             println!("     tags: {}", memory.tags.join(", "));
         }
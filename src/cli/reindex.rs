@@ -1,78 +1,137 @@
 use anyhow::Result;
+use std::sync::{mpsc, Arc, Mutex};
 
 use crate::db;
 use crate::embed::Embedder;
 
-pub fn run() -> Result<()> {
+/// Upper bound on parallel embedding workers — each one loads its own model copy.
+const MAX_WORKERS: usize = 8;
+
+pub fn run(since: Option<&str>, missing_only: bool, jobs: usize) -> Result<()> {
     let conn = db::open()?;
+    if !db::vectors_available() {
+        anyhow::bail!("sqlite-vec failed to load, so there is nowhere to store vectors. Reinstall ctxovrflw for this platform.");
+    }
+    let mut since = since.map(parse_since).transpose()?;
+    let mut missing_only = missing_only;
 
-    // Get all non-deleted memories
+    // Vectors go to the table of the model this process embeds with
+    let cfg = crate::config::Config::load().unwrap_or_default();
+    db::activate_configured_model(&conn, &cfg)?;
+
+    // Switching vector_quantization rebuilds the vector table in the new
+    // format, which means every memory needs a fresh vector.
+    let wanted = cfg.vector_quantization;
+    let stored = db::vector_quantization(&conn);
+    if wanted != stored {
+        println!("Converting vector storage: {stored} → {wanted}");
+        conn.execute_batch("DROP TABLE IF EXISTS memory_vectors;")?;
+        db::create_vector_table(&conn, crate::embed::embedding_dim(), wanted)?;
+        since = None;
+        missing_only = false;
+    }
+
+    // Get all non-deleted memories, except those stored keyword-only
     let mut stmt = conn.prepare(
-        "SELECT id, content FROM memories WHERE deleted = 0"
+        "SELECT m.id, m.content, m.updated_at,
+                EXISTS (SELECT 1 FROM memory_vectors v WHERE v.id = m.id)
+         FROM memories m WHERE m.deleted = 0 AND m.skip_embedding = 0"
     )?;
 
-    let memories: Vec<(String, String)> = stmt
+    let rows: Vec<(String, String, String, bool)> = stmt
         .query_map([], |row| {
-            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
         })?
         .filter_map(|r| r.ok())
         .collect();
+    let all_count = rows.len();
+
+    // With no filters everything is rebuilt; with filters, a memory qualifies
+    // if it matches either one.
+    let incremental = since.is_some() || missing_only;
+    let memories: Vec<(String, String)> = rows
+        .into_iter()
+        .filter(|(_, _, updated_at, has_vector)| {
+            if !incremental {
+                return true;
+            }
+            let missing = missing_only && !has_vector;
+            let recent = since.is_some_and(|cutoff| {
+                chrono::DateTime::parse_from_rfc3339(updated_at)
+                    // Unparseable timestamps are re-embedded to be safe
+                    .map_or(true, |t| t.to_utc() >= cutoff)
+            });
+            missing || recent
+        })
+        .map(|(id, content, _, _)| (id, content))
+        .collect();
 
     let total = memories.len();
     if total == 0 {
-        println!("No memories to reindex.");
+        if incremental {
+            println!("Nothing to reindex — all {all_count} memories are up to date.");
+        } else {
+            println!("No memories to reindex.");
+        }
         return Ok(());
     }
 
-    println!("Reindexing {} memories...", total);
+    if incremental {
+        println!("Reindexing {} of {} memories...", total, all_count);
+    } else {
+        println!("Reindexing {} memories...", total);
+    }
+
+    // Each worker owns its own Embedder (ONNX sessions aren't shareable); the
+    // global pool is left untouched. Vectors are written back here, on the
+    // thread that owns the DB connection.
+    let jobs = jobs.clamp(1, MAX_WORKERS).min(total);
+    let mut embedders = Vec::with_capacity(jobs);
+    for i in 0..jobs {
+        let mut embedder = Embedder::new()?;
+        // A full rebuild also refreshes the CPU fallback's token statistics;
+        // later workers load what the first one stored
+        if i == 0 && !incremental {
+            embedder.recount_token_stats()?;
+        }
+        embedders.push(embedder);
+    }
+    if jobs > 1 {
+        println!("  Using {jobs} embedding workers");
+    }
+
+    let queue = Arc::new(Mutex::new(memories.into_iter()));
+    let (tx, rx) = mpsc::channel::<(String, Result<Vec<f32>>)>();
+
+    let workers: Vec<_> = embedders
+        .into_iter()
+        .map(|mut embedder| {
+            let queue = queue.clone();
+            let tx = tx.clone();
+            std::thread::spawn(move || loop {
+                let next = queue.lock().unwrap_or_else(|e| e.into_inner()).next();
+                let Some((id, content)) = next else { break };
+                let result = embed_with_fallback(&mut embedder, &content);
+                if tx.send((id, result)).is_err() {
+                    break;
+                }
+            })
+        })
+        .collect();
+    drop(tx);
 
-    let mut embedder = Embedder::new()?;
     let mut success = 0;
     let mut failed = 0;
 
-    for (i, (id, content)) in memories.iter().enumerate() {
-        match embedder.embed(content) {
+    for (i, (id, result)) in rx.iter().enumerate() {
+        match result {
             Ok(embedding) => {
-                let _ = conn.execute(
-                    "INSERT OR REPLACE INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-                    rusqlite::params![id, db::memories::bytemuck_cast_pub(&embedding)],
-                );
+                let _ = db::memories::upsert_vector(&conn, &id, &embedding);
                 success += 1;
             }
             Err(e) => {
-                // Fallback for very long memories: chunk and average embeddings.
-                let chunks = crate::chunking::split_text_with_overlap(content, 1800, 220);
-                if chunks.len() > 1 {
-                    let mut agg: Option<Vec<f32>> = None;
-                    let mut n = 0usize;
-                    for ch in &chunks {
-                        if let Ok(v) = embedder.embed(ch) {
-                            if let Some(ref mut a) = agg {
-                                for (ai, vi) in a.iter_mut().zip(v.iter()) { *ai += *vi; }
-                            } else {
-                                agg = Some(v);
-                            }
-                            n += 1;
-                        }
-                    }
-
-                    if let Some(mut vec) = agg {
-                        if n > 1 {
-                            for x in &mut vec { *x /= n as f32; }
-                        }
-                        let _ = conn.execute(
-                            "INSERT OR REPLACE INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-                            rusqlite::params![id, db::memories::bytemuck_cast_pub(&vec)],
-                        );
-                        success += 1;
-                    } else {
-                        eprintln!("  Failed to embed {}: {}", &id[..8], e);
-                        failed += 1;
-                    }
-                } else {
-                    eprintln!("  Failed to embed {}: {}", &id[..8], e);
-                    failed += 1;
-                }
+                eprintln!("  Failed to embed {}: {}", &id[..8.min(id.len())], e);
+                failed += 1;
             }
         }
 
@@ -82,8 +141,95 @@ pub fn run() -> Result<()> {
         }
     }
 
+    for w in workers {
+        let _ = w.join();
+    }
+
     println!();
     println!("✓ Reindex complete: {} embedded, {} failed out of {} total", success, failed, total);
 
     Ok(())
 }
+
+/// Report drift between memories and their vectors, then offer to delete
+/// orphaned vectors and embed the missing ones.
+pub fn verify(jobs: usize) -> Result<()> {
+    let conn = db::open()?;
+    let (missing, orphans) = db::memories::vector_drift(&conn)?;
+
+    if missing.is_empty() && orphans.is_empty() {
+        println!("✓ Vectors in sync — every memory has exactly one vector.");
+        return Ok(());
+    }
+
+    let sample = |ids: &[String]| {
+        let shown: Vec<&str> = ids.iter().take(5).map(|id| &id[..8.min(id.len())]).collect();
+        let more = if ids.len() > 5 { format!(", … {} more", ids.len() - 5) } else { String::new() };
+        format!("{}{more}", shown.join(", "))
+    };
+    if !missing.is_empty() {
+        println!("⚠ {} memories have no vector (invisible to semantic search): {}", missing.len(), sample(&missing));
+    }
+    if !orphans.is_empty() {
+        println!("⚠ {} vectors belong to no memory: {}", orphans.len(), sample(&orphans));
+    }
+
+    if !atty::is(atty::Stream::Stdin) {
+        println!("Fix with `ctxovrflw reindex --verify` in a terminal, or `ctxovrflw reindex --missing-only`.");
+        return Ok(());
+    }
+    let fix = dialoguer::Confirm::new()
+        .with_prompt("Fix now? (delete orphaned vectors, embed missing ones)")
+        .default(true)
+        .interact()?;
+    if !fix {
+        return Ok(());
+    }
+
+    if !orphans.is_empty() {
+        let deleted = db::memories::delete_vectors(&conn, &orphans)?;
+        println!("✓ Deleted {deleted} orphaned vectors");
+    }
+    drop(conn);
+    if !missing.is_empty() {
+        run(None, true, jobs)?;
+    }
+    Ok(())
+}
+
+/// Embed a memory, falling back to chunk-and-average for very long content.
+fn embed_with_fallback(embedder: &mut Embedder, content: &str) -> Result<Vec<f32>> {
+    let err = match embedder.embed(content) {
+        Ok(embedding) => return Ok(embedding),
+        Err(e) => e,
+    };
+
+    let chunks = crate::chunking::split_text_with_overlap(content, 1800, 220);
+    if chunks.len() <= 1 {
+        return Err(err);
+    }
+
+    let mut agg: Option<Vec<f32>> = None;
+    let mut n = 0usize;
+    for ch in &chunks {
+        if let Ok(v) = embedder.embed(ch) {
+            if let Some(ref mut a) = agg {
+                for (ai, vi) in a.iter_mut().zip(v.iter()) { *ai += *vi; }
+            } else {
+                agg = Some(v);
+            }
+            n += 1;
+        }
+    }
+
+    let Some(mut vec) = agg else { return Err(err) };
+    if n > 1 {
+        for x in &mut vec { *x /= n as f32; }
+    }
+    Ok(vec)
+}
+
+/// Parse a `--since` value (see [`crate::validation::parse_time`]).
+fn parse_since(s: &str) -> Result<chrono::DateTime<chrono::Utc>> {
+    crate::validation::parse_time(s).map_err(|e| anyhow::anyhow!("--since: {e}"))
+}
@@ -1,7 +1,14 @@
 use anyhow::Result;
 use crate::config::Config;
 
-pub async fn run(cfg: &Config, text: &str, memory_type: Option<&str>, tags: Vec<String>, subject: Option<&str>) -> Result<()> {
+pub async fn run(
+    cfg: &Config,
+    text: &str,
+    memory_type: Option<&str>,
+    tags: Vec<String>,
+    subject: Option<&str>,
+    skip_embedding: bool,
+) -> Result<()> {
     let conn = crate::db::open()?;
     let mtype = memory_type.unwrap_or("semantic").parse().unwrap_or_default();
 
@@ -14,7 +21,23 @@ pub async fn run(cfg: &Config, text: &str, memory_type: Option<&str>, tags: Vec<
         }
     }
 
-    let embedding = if cfg.tier.semantic_search_enabled() {
+    let (text, secrets) = match crate::secrets::apply(cfg.secret_policy, text) {
+        Ok(scanned) => scanned,
+        Err(msg) => {
+            eprintln!("{msg}");
+            std::process::exit(1);
+        }
+    };
+    let text = text.as_str();
+    if !secrets.is_empty() {
+        match cfg.secret_policy {
+            crate::secrets::SecretPolicy::Redact => eprintln!("⚠ Redacted likely secrets: {}", secrets.join(", ")),
+            _ => eprintln!("⚠ Content may contain secrets ({}) — memories are shared across agents and synced to the cloud", secrets.join(", ")),
+        }
+    }
+
+    let skip_embedding = skip_embedding || cfg.skip_embedding;
+    let embedding = if !skip_embedding && cfg.tier.semantic_search_enabled() {
         match crate::embed::Embedder::new() {
             Ok(mut e) => match e.embed(text) {
                 Ok(emb) => {
@@ -35,7 +58,18 @@ pub async fn run(cfg: &Config, text: &str, memory_type: Option<&str>, tags: Vec<
         None
     };
 
-    let memory = crate::db::memories::store(&conn, text, &mtype, &tags, subject, Some("cli"), embedding.as_deref(), None)?;
+    let memory = crate::db::memories::store_with_expiry(
+        &conn,
+        text,
+        &mtype,
+        &tags,
+        subject,
+        Some("cli"),
+        embedding.as_deref(),
+        None,
+        None,
+        skip_embedding,
+    )?;
     println!("Remembered [{}]: {}", memory.id, text);
 
     // Immediate push to cloud if logged in
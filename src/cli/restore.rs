@@ -0,0 +1,34 @@
+use anyhow::Result;
+use crate::config::Config;
+
+pub async fn run(cfg: &Config, id: &str) -> Result<()> {
+    let conn = crate::db::open()?;
+
+    let Some(memory) = crate::db::memories::restore(&conn, id)? else {
+        println!("No deleted memory {id} found (it may have been purged already).");
+        println!("List restorable memories with: ctxovrflw forget --list");
+        return Ok(());
+    };
+
+    // Re-embed so the memory is searchable again even if its vector was dropped
+    if cfg.tier.semantic_search_enabled() {
+        match crate::embed::Embedder::new().and_then(|mut e| e.embed(&memory.content)) {
+            Ok(emb) => {
+                crate::db::memories::update(&conn, &memory.id, None, None, None, None, Some(&emb))?;
+            }
+            Err(e) => eprintln!("⚠ Re-embedding failed, run `ctxovrflw reindex --missing-only` later: {e}"),
+        }
+    }
+
+    println!("Restored [{}]: {}", memory.id, memory.content);
+
+    if cfg.is_logged_in() {
+        match crate::sync::push_one(cfg, &memory.id).await {
+            Ok(true) => println!("☁ Synced to cloud"),
+            Ok(false) => {}
+            Err(e) => eprintln!("☁ Cloud sync failed (will retry): {e}"),
+        }
+    }
+
+    Ok(())
+}
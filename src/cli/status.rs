@@ -35,7 +35,7 @@ pub async fn run(cfg: &Config) -> Result<()> {
     let daemon_status = if cfg.is_remote_client() {
         format!("remote → {}", cfg.daemon_url())
     } else if service_running {
-        "running (systemd) ✓".to_string()
+        format!("running ({}) ✓", crate::daemon::service_manager())
     } else if let Some(pid) = &pid_running {
         format!("running (pid {pid})")
     } else {
@@ -47,22 +47,86 @@ pub async fn run(cfg: &Config) -> Result<()> {
     if cfg.is_remote_client() {
         println!("  REST API:      {}/v1/", cfg.daemon_url());
         println!("  MCP SSE:       {}/mcp/sse", cfg.daemon_url());
+        println!("  MCP HTTP:      {}/mcp", cfg.daemon_url());
     } else if service_running || pid_running.is_some() {
         println!("  REST API:      http://localhost:{}/v1/", cfg.port);
         println!("  MCP SSE:       http://localhost:{}/mcp/sse", cfg.port);
+        println!("  MCP HTTP:      http://localhost:{}/mcp", cfg.port);
     }
     if !cfg.is_remote_client() {
         println!("Service:         {}", if service_installed { "installed" } else { "not installed" });
     }
+    if cfg.is_remote_client() || service_running || pid_running.is_some() {
+        match fetch_clients(cfg).await {
+            Some(clients) if !clients.is_empty() => {
+                let now = chrono::Utc::now();
+                let list: Vec<String> = clients
+                    .iter()
+                    .map(|c| {
+                        if c.active {
+                            format!("{} — active", c.name)
+                        } else {
+                            format!("{} — last seen {}", c.name, ago(now - c.last_seen))
+                        }
+                    })
+                    .collect();
+                println!("MCP clients:     {}", list.join(", "));
+            }
+            Some(_) => println!("MCP clients:     none since the daemon started"),
+            None => {}
+        }
+    }
     println!();
 
     // Memory stats
     println!("Tier:            {:?}", cfg.tier);
     println!("Memories:        {}/{}", count, max);
-    println!("Semantic search: {}", if cfg.tier.semantic_search_enabled() { "enabled" } else { "keyword only" });
+    let workspaces = crate::db::list_workspaces(&conn).unwrap_or_default();
+    if workspaces.iter().any(|(name, _)| *name != cfg.current_workspace) {
+        let in_current = workspaces
+            .iter()
+            .find(|(name, _)| *name == cfg.current_workspace)
+            .map(|(_, n)| *n)
+            .unwrap_or(0);
+        println!("Workspace:       {} ({} memories, {} workspaces)", cfg.current_workspace, in_current, workspaces.len());
+    } else if cfg.current_workspace != crate::db::DEFAULT_WORKSPACE {
+        println!("Workspace:       {}", cfg.current_workspace);
+    }
+    if !cfg.tier.semantic_search_enabled() {
+        println!("Semantic search: keyword only");
+    } else if !crate::db::vectors_available() {
+        println!("Semantic search: unavailable — sqlite-vec failed to load, using keyword search");
+    } else {
+        println!("Semantic search: enabled");
+    }
+    let stored_vectors = crate::db::vector_quantization(&conn);
+    if stored_vectors != cfg.vector_quantization {
+        println!("Vector storage:  {stored_vectors} (config wants {} — run `ctxovrflw reindex`)", cfg.vector_quantization);
+    } else if stored_vectors != crate::config::VectorQuantization::None {
+        println!("Vector storage:  {stored_vectors}");
+    }
     println!("Cloud sync:      {}", if cfg.effective_cloud_sync() { "enabled" } else { "disabled" });
+    if cfg.is_logged_in() || cfg.capability_token.is_some() {
+        println!("Capability:      {}", cfg.capability_status());
+    }
+    if let Some(backlog) = crate::sync::backlog_summary(cfg) {
+        println!("Sync backlog:    {backlog}");
+    }
     println!();
     println!("Data dir:        {}", Config::data_dir()?.display());
+    let problems = cfg.validate();
+    if problems.is_empty() {
+        println!("Config:          ok");
+    } else {
+        println!("Config:          {} problem(s) in {}", problems.len(), Config::config_path()?.display());
+        for problem in &problems {
+            println!("  ⚠ {problem}");
+        }
+    }
+    if let Some((at, message)) = crate::daemon::last_crash() {
+        println!("Last crash:      {at} — {message}");
+        println!("  Details:       {}", Config::crash_log_path()?.display());
+    }
 
     if !service_installed {
         println!();
@@ -75,16 +139,46 @@ pub async fn run(cfg: &Config) -> Result<()> {
     Ok(())
 }
 
+/// MCP clients the daemon has seen, or None if it can't be asked.
+async fn fetch_clients(cfg: &Config) -> Option<Vec<crate::mcp::sse::ClientStatus>> {
+    #[derive(serde::Deserialize)]
+    struct ClientsResponse {
+        clients: Vec<crate::mcp::sse::ClientStatus>,
+    }
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(2))
+        .build()
+        .ok()?;
+    let mut request = client.get(format!("{}/v1/clients", cfg.daemon_url()));
+    if let Some(token) = &cfg.auth_token {
+        request = request.bearer_auth(token);
+    }
+    let resp = request.send().await.ok()?;
+    if !resp.status().is_success() {
+        return None;
+    }
+    resp.json::<ClientsResponse>().await.ok().map(|r| r.clients)
+}
+
+/// "42s ago", "5m ago", "3h ago", "2d ago".
+fn ago(elapsed: chrono::Duration) -> String {
+    let secs = elapsed.num_seconds().max(0);
+    match secs {
+        0..60 => format!("{secs}s ago"),
+        60..3600 => format!("{}m ago", secs / 60),
+        3600..86400 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
+
 /// Fetch the user's tier from cloud and update local config if it changed.
 /// Returns Some(updated_config) if tier changed, None if no change.
 async fn sync_tier_from_cloud(cfg: &Config) -> Result<Option<Config>> {
     let api_key = cfg.api_key.as_deref().unwrap();
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()?;
-
-    let resp = client
+    let resp = crate::config::http_client()?
         .get(format!("{}/v1/auth/profile", cfg.cloud_url))
+        .timeout(std::time::Duration::from_secs(5))
         .header("Authorization", format!("Bearer {api_key}"))
         .send()
         .await?;
@@ -119,3 +213,26 @@ async fn sync_tier_from_cloud(cfg: &Config) -> Result<Option<Config>> {
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ago_units_and_boundaries() {
+        let ago_secs = |s| ago(chrono::Duration::seconds(s));
+        assert_eq!(ago_secs(0), "0s ago");
+        assert_eq!(ago_secs(59), "59s ago");
+        assert_eq!(ago_secs(60), "1m ago");
+        assert_eq!(ago_secs(3599), "59m ago");
+        assert_eq!(ago_secs(3600), "1h ago");
+        assert_eq!(ago_secs(86399), "23h ago");
+        assert_eq!(ago_secs(86400), "1d ago");
+        assert_eq!(ago_secs(10 * 86400 + 5), "10d ago");
+    }
+
+    #[test]
+    fn test_ago_clamps_clock_skew() {
+        assert_eq!(ago(chrono::Duration::seconds(-30)), "0s ago");
+    }
+}
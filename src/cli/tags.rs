@@ -0,0 +1,47 @@
+use anyhow::Result;
+use crate::config::Config;
+
+pub fn list() -> Result<()> {
+    let conn = crate::db::open()?;
+    let tags = crate::db::search::list_tags(&conn)?;
+
+    if tags.is_empty() {
+        println!("No tags yet. Add them with `ctxovrflw remember --tags a,b`.");
+        return Ok(());
+    }
+
+    let width = tags.iter().map(|(t, _)| t.len()).max().unwrap_or(0);
+    for (tag, count) in &tags {
+        println!("{tag:<width$}  {count}");
+    }
+    println!("\n{} tags", tags.len());
+    Ok(())
+}
+
+pub async fn rename(cfg: &Config, from: &str, to: &str) -> Result<()> {
+    let to = to.trim();
+    if to.is_empty() {
+        anyhow::bail!("New tag name cannot be empty");
+    }
+    if from == to {
+        println!("Nothing to do — '{from}' and '{to}' are the same tag.");
+        return Ok(());
+    }
+    crate::validation::validate_tags(&[to.to_string()]).map_err(|e| anyhow::anyhow!(e))?;
+
+    let conn = crate::db::open()?;
+    let updated = crate::db::memories::rename_tag(&conn, from, to)?;
+    if updated == 0 {
+        println!("No memories are tagged '{from}'.");
+        return Ok(());
+    }
+    println!("✓ Renamed '{from}' → '{to}' on {updated} memories");
+
+    if cfg.is_logged_in() {
+        match crate::sync::run_silent(cfg).await {
+            Ok((pushed, _, _)) => println!("  Pushed {pushed} changes"),
+            Err(e) => println!("  ⚠ Sync failed ({e}) — changes will be pushed on the next sync"),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use chrono::SecondsFormat;
+
+/// Print episodic memories from `since` to `until`, oldest first, under a
+/// heading per day (or ISO week with `weekly`).
+pub fn run(since: &str, until: Option<&str>, subject: Option<&str>, weekly: bool, limit: usize) -> Result<()> {
+    let parse = |flag: &str, value: &str| {
+        crate::validation::parse_time(value)
+            .map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+            .map_err(|e| anyhow::anyhow!("--{flag}: {e}"))
+    };
+    let since = parse("since", since)?;
+    let until = until.map(|u| parse("until", u)).transpose()?;
+
+    let conn = crate::db::open()?;
+    let memories = crate::db::memories::list_episodic(&conn, Some(&since), until.as_deref(), subject, limit)?;
+    if memories.is_empty() {
+        println!("No episodic memories since {}.", &since[..10]);
+        return Ok(());
+    }
+
+    for (label, group) in crate::mcp::tools::timeline_buckets(memories, weekly) {
+        println!("{label}");
+        for memory in &group {
+            let at = if weekly { memory.created_at.get(5..16) } else { memory.created_at.get(11..16) };
+            let preview: String = memory.content.chars().take(80).collect();
+            let ellipsis = if memory.content.chars().count() > 80 { "…" } else { "" };
+            println!("  {}  {}  {preview}{ellipsis}", at.unwrap_or("").replace('T', " "), &memory.id[..8]);
+        }
+        println!();
+    }
+    Ok(())
+}
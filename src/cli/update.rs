@@ -12,7 +12,7 @@ struct LatestResponse {
 
 /// Check the API for the latest available version.
 pub async fn check_latest(cfg: &Config) -> Result<Option<String>> {
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let resp = client
         .get(format!("{}/v1/releases/latest", cfg.cloud_url))
         .timeout(std::time::Duration::from_secs(5))
@@ -29,7 +29,7 @@ pub async fn check_latest(cfg: &Config) -> Result<Option<String>> {
 
 /// Compare semver versions. Supports pre-release (e.g., 0.5.0-rc.1).
 /// Pre-release versions are OLDER than their release counterpart (0.5.0-rc.1 < 0.5.0).
-fn is_newer(latest: &str) -> bool {
+pub(crate) fn is_newer(latest: &str) -> bool {
     let latest_clean = latest.trim_start_matches('v');
     let current_clean = CURRENT_VERSION.trim_start_matches('v');
 
@@ -152,7 +152,7 @@ pub async fn run(check_only: bool) -> Result<()> {
     let (os, arch) = detect_platform();
     println!("Downloading {latest} for {os}-{arch}...");
 
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let resp = client
         .get(format!(
             "{}/v1/download?os={os}&arch={arch}",
@@ -313,9 +313,7 @@ pub async fn run(check_only: bool) -> Result<()> {
     // Restart daemon if running
     if crate::daemon::is_service_running() {
         println!("Restarting daemon...");
-        let _ = std::process::Command::new("systemctl")
-            .args(["--user", "restart", "ctxovrflw"])
-            .status();
+        let _ = crate::daemon::service_restart();
         println!("✓ Daemon restarted");
     }
 
@@ -0,0 +1,206 @@
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::cli::init::{self, AGENTS, CTXOVRFLW_RULES_MARKER};
+use crate::config::Config;
+
+const CONFIRM_WORD: &str = "wipe";
+
+/// Reset local state: memories (and their vectors and graph), logs, sync state
+/// and credentials. Downloaded models are kept since they hold no user data.
+pub async fn run(cfg: &Config, keep_config: bool, agents: bool, cloud: bool, yes: bool) -> Result<()> {
+    let data_dir = Config::data_dir()?;
+    let databases = database_paths(cfg)?;
+
+    println!("This permanently deletes:");
+    if cloud {
+        println!("  • all memories stored in the cloud for {}", cfg.email.as_deref().unwrap_or("this account"));
+    }
+    for db in &databases {
+        println!("  • memories, vectors and knowledge graph in {}", db.display());
+    }
+    println!("  • logs and sync state in {}", data_dir.display());
+    if keep_config {
+        println!("  • cloud credentials in config.toml (other settings are kept)");
+    } else {
+        println!("  • config.toml, including credentials and all profiles");
+    }
+    if agents {
+        println!("  • ctxovrflw MCP entries, rules sections and the agent skill");
+    }
+    println!();
+
+    if !yes {
+        if !atty::is(atty::Stream::Stdin) {
+            anyhow::bail!("Refusing to wipe without confirmation — pass --yes to run non-interactively");
+        }
+        let typed: String = dialoguer::Input::new()
+            .with_prompt(format!("Type '{CONFIRM_WORD}' to confirm"))
+            .allow_empty(true)
+            .interact_text()?;
+        if typed.trim() != CONFIRM_WORD {
+            println!("Aborted — nothing was deleted.");
+            return Ok(());
+        }
+    }
+
+    // Cloud deletion goes through the normal sync path, so it needs a working
+    // login; do it first so a failure leaves local data intact.
+    if cloud {
+        let deleted = delete_cloud_memories(cfg).await?;
+        println!("✓ Deleted {deleted} memories from the cloud.");
+    }
+
+    if crate::daemon::is_service_running() || Config::pid_path()?.exists() {
+        crate::daemon::stop(cfg).await?;
+    }
+
+    for db in &databases {
+        for suffix in ["", "-wal", "-shm"] {
+            remove_path(&PathBuf::from(format!("{}{suffix}", db.display())))?;
+        }
+    }
+
+    if data_dir.exists() {
+        for entry in std::fs::read_dir(&data_dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            if name == "models" || (keep_config && name == "config.toml") {
+                continue;
+            }
+            remove_path(&path)?;
+        }
+    }
+
+    if keep_config {
+        let mut kept = Config::load().unwrap_or_else(|_| cfg.clone());
+        kept.api_key = None;
+        kept.device_id = None;
+        kept.email = None;
+        kept.pin_verifier = None;
+        kept.key_salt = None;
+        kept.cached_key = None;
+        kept.key_cached_at = None;
+        kept.capability_token = None;
+        kept.tier = Default::default();
+        kept.profiles.clear();
+        kept.active_profile = crate::config::DEFAULT_PROFILE.to_string();
+        kept.db_path = None;
+        kept.save()?;
+    }
+    println!("✓ Local data wiped.");
+
+    if agents {
+        remove_agent_integrations()?;
+    }
+
+    if crate::daemon::is_service_installed() {
+        println!();
+        println!("The background service is still installed — remove it with `ctxovrflw service uninstall`.");
+    }
+    println!("Run `ctxovrflw init` to start fresh.");
+    Ok(())
+}
+
+/// The active database plus any profile databases, wherever they live.
+fn database_paths(cfg: &Config) -> Result<Vec<PathBuf>> {
+    let mut paths = vec![Config::db_path()?];
+    for (name, profile) in &cfg.profiles {
+        let path = match &profile.db_path {
+            Some(path) => path.clone(),
+            None if name == crate::config::DEFAULT_PROFILE => Config::data_dir()?.join("memories.db"),
+            None => continue,
+        };
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths.retain(|p| p.exists());
+    Ok(paths)
+}
+
+/// Pull first so memories only other devices have pushed are tombstoned too,
+/// then push the deletions.
+async fn delete_cloud_memories(cfg: &Config) -> Result<usize> {
+    if let Some(blocker) = crate::sync::blocker(cfg).filter(|b| *b != crate::sync::SyncBlocker::OverLimit) {
+        anyhow::bail!("Can't delete cloud memories: {blocker}");
+    }
+    crate::sync::run_silent(cfg).await?;
+    let deleted = crate::db::memories::delete_all(&crate::db::open()?)?;
+    crate::sync::run_silent(cfg).await?;
+    let pending = crate::sync::pending_count(cfg)?;
+    if pending > 0 {
+        anyhow::bail!(
+            "{pending} deletions didn't reach the cloud — local data was kept; re-run `ctxovrflw wipe --cloud`"
+        );
+    }
+    Ok(deleted)
+}
+
+/// Undo what `init` set up: MCP server entries, rules sections and the skill.
+fn remove_agent_integrations() -> Result<()> {
+    let home = dirs::home_dir().unwrap_or_default();
+
+    for def in AGENTS {
+        for loc in def.config_paths {
+            let path = init::resolve_config_path(loc);
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            let Ok(mut config) = serde_json::from_str::<serde_json::Value>(&content) else { continue };
+            let removed = config
+                .get_mut("mcpServers")
+                .and_then(|s| s.as_object_mut())
+                .is_some_and(|servers| servers.remove("ctxovrflw").is_some());
+            if removed {
+                std::fs::write(&path, serde_json::to_string_pretty(&config)?)?;
+                println!("✓ Removed MCP entry from {} ({})", def.name, path.display());
+            }
+        }
+
+        if let Some(cmd) = def.cli_install {
+            let mut parts = cmd.split_whitespace();
+            if let (Some(bin), Some(sub)) = (parts.next(), parts.next())
+                && init::which(bin)
+            {
+                let removed = std::process::Command::new(bin)
+                    .args([sub, "remove", "--scope", "user", "ctxovrflw"])
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .status()
+                    .is_ok_and(|s| s.success());
+                if removed {
+                    println!("✓ Removed MCP entry from {}", def.name);
+                }
+            }
+        }
+
+        if let Some(rel) = def.global_rules_path {
+            let path = home.join(rel);
+            let Ok(content) = std::fs::read_to_string(&path) else { continue };
+            if content.contains(CTXOVRFLW_RULES_MARKER) {
+                let stripped = init::replace_ctxovrflw_section(&content, "");
+                if stripped.trim().is_empty() {
+                    std::fs::remove_file(&path)?;
+                } else {
+                    std::fs::write(&path, stripped.trim_end().to_string() + "\n")?;
+                }
+                println!("✓ Removed ctxovrflw rules from {} ({})", def.name, path.display());
+            }
+        }
+    }
+
+    let skill_dir = home.join(".skills").join("ctxovrflw");
+    if skill_dir.exists() {
+        remove_path(&skill_dir)?;
+        println!("✓ Removed agent skill ({})", skill_dir.display());
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> Result<()> {
+    if path.is_dir() {
+        std::fs::remove_dir_all(path)?;
+    } else if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
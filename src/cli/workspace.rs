@@ -0,0 +1,46 @@
+use anyhow::Result;
+use crate::config::Config;
+
+pub fn list(cfg: &Config) -> Result<()> {
+    let conn = crate::db::open()?;
+    let mut workspaces = crate::db::list_workspaces(&conn)?;
+
+    // The active workspace is listed even before its first memory
+    if !workspaces.iter().any(|(name, _)| *name == cfg.current_workspace) {
+        workspaces.push((cfg.current_workspace.clone(), 0));
+        workspaces.sort();
+    }
+
+    for (name, count) in &workspaces {
+        let marker = if *name == cfg.current_workspace { "*" } else { " " };
+        println!("{marker} {name} ({count} memories)");
+    }
+
+    Ok(())
+}
+
+pub fn switch(name: &str) -> Result<()> {
+    validate_name(name)?;
+
+    let mut cfg = Config::load()?;
+    if cfg.current_workspace == name {
+        println!("Already in workspace '{name}'.");
+        return Ok(());
+    }
+    cfg.current_workspace = name.to_string();
+    cfg.save()?;
+
+    println!("✓ Switched to workspace '{name}'.");
+    println!("  Recall and remember now only see this workspace (pass --all-workspaces to read across all).");
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() || name.len() > 64 {
+        anyhow::bail!("Workspace name must be 1-64 characters");
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        anyhow::bail!("Workspace name may only contain letters, digits, '-', '_' and '.'");
+    }
+    Ok(())
+}
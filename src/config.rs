@@ -1,23 +1,73 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Environment variables that override config.toml on load (env wins).
+/// Overridden values are never written back by `save`.
+const ENV_PORT: &str = "CTXOVRFLW_PORT";
+const ENV_CLOUD_URL: &str = "CTXOVRFLW_CLOUD_URL";
+const ENV_EMBEDDING_MODEL: &str = "CTXOVRFLW_EMBEDDING_MODEL";
+const ENV_AUTH_TOKEN: &str = "CTXOVRFLW_AUTH_TOKEN";
+/// Data directory in place of `~/.ctxovrflw`; read on every path lookup.
+const ENV_DATA_DIR: &str = "CTXOVRFLW_DATA_DIR";
+
+/// Profile the existing top-level credentials belong to before any switch.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Schema version written to config.toml. Bump it and add a step to
+/// `migrate_table` whenever fields are renamed or defaults need rewriting.
+pub const CONFIG_VERSION: u32 = 2;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
+    /// Schema version of this file; older files are migrated on load.
+    #[serde(default = "legacy_config_version")]
+    pub config_version: u32,
+
+    /// Daemon HTTP port. Overridden by `CTXOVRFLW_PORT`.
     #[serde(default = "default_port")]
     pub port: u16,
 
+    /// Address the HTTP server binds to. Anything other than loopback exposes
+    /// the daemon to the network, protected only by `auth_token`, so the daemon
+    /// won't start that way without one.
+    #[serde(default = "default_bind_address")]
+    pub bind_address: String,
+
     #[serde(default)]
     pub tier: Tier,
 
+    /// Embedding model id (see `ctxovrflw model list`).
+    /// Overridden by `CTXOVRFLW_EMBEDDING_MODEL`.
     #[serde(default = "default_embedding_model")]
     pub embedding_model: String,
 
+    /// Base URL that replaces `https://huggingface.co` when downloading models,
+    /// for air-gapped installs or regions where Hugging Face is blocked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_mirror: Option<String>,
+
+    /// Maximum number of embedding model instances loaded at once, so concurrent
+    /// recall/remember requests embed in parallel. Each one costs the model's
+    /// memory footprint; extra instances are only loaded under contention.
+    #[serde(default = "default_embedder_pool_size")]
+    pub embedder_pool_size: usize,
+
+    /// Storage format for vectors in `memory_vectors`. Changing it requires
+    /// `ctxovrflw reindex`, which rebuilds the vector table in the new format.
+    #[serde(default)]
+    pub vector_quantization: VectorQuantization,
+
+    /// Workspace that reads and writes are scoped to (`ctxovrflw workspace switch`)
+    #[serde(default = "default_workspace")]
+    pub current_workspace: String,
+
     // This is runtime-derived, not serialized
     #[serde(skip)]
     pub embedding_dim: usize,
 
     // Cloud settings
+    /// Cloud API base URL. Overridden by `CTXOVRFLW_CLOUD_URL`.
     #[serde(default = "default_cloud_url")]
     pub cloud_url: String,
 
@@ -33,6 +83,26 @@ pub struct Config {
     #[serde(default = "default_auto_sync")]
     pub auto_sync: bool,
 
+    /// Don't push each write from the daemon as it happens; leave it to the
+    /// periodic auto-sync batch. MCP/HTTP callers can override per call.
+    #[serde(default)]
+    pub defer_sync: bool,
+
+    /// Store new memories keyword-only, without computing an embedding, to
+    /// save CPU on low-powered devices. They are found by keyword search but
+    /// not by meaning. MCP/HTTP/CLI callers can override per memory.
+    #[serde(default)]
+    pub skip_embedding: bool,
+
+    /// Seconds to wait for a connection to the cloud or a model host.
+    #[serde(default = "default_http_connect_timeout")]
+    pub http_connect_timeout_secs: u64,
+
+    /// Seconds an outbound request or download may go without receiving data
+    /// before it's abandoned, so a hung connection can't stall auto-sync.
+    #[serde(default = "default_http_read_timeout")]
+    pub http_read_timeout_secs: u64,
+
     /// Run periodic background consolidation passes (Pro tier)
     #[serde(default = "default_auto_consolidation")]
     pub auto_consolidation: bool,
@@ -41,6 +111,29 @@ pub struct Config {
     #[serde(default = "default_consolidation_interval")]
     pub consolidation_interval_secs: u64,
 
+    /// Cosine similarity at or above which two memories with the same subject
+    /// are recorded as a consolidation candidate by the background pass.
+    #[serde(default = "default_consolidation_similarity")]
+    pub consolidation_similarity: f64,
+
+    /// Multiplier applied to a relation's confidence when the memory it was
+    /// extracted from (`source_memory_id`) is forgotten.
+    #[serde(default = "default_relation_forget_penalty")]
+    pub relation_forget_penalty: f64,
+
+    /// Days an inferred relation (extracted from a memory, or confidence < 1.0)
+    /// may go without being re-confirmed before it starts decaying. 0 disables decay.
+    #[serde(default = "default_relation_decay_days")]
+    pub relation_decay_days: u64,
+
+    /// Daily confidence multiplier for stale inferred relations.
+    #[serde(default = "default_relation_decay_factor")]
+    pub relation_decay_factor: f64,
+
+    /// Relations whose confidence falls below this are deleted.
+    #[serde(default = "default_relation_min_confidence")]
+    pub relation_min_confidence: f64,
+
     // Zero-knowledge encryption
     #[serde(default)]
     pub email: Option<String>,
@@ -52,6 +145,12 @@ pub struct Config {
     #[serde(default)]
     pub key_salt: Option<String>,
 
+    /// PBKDF2 iterations for the sync key. Raising it re-keys on the next
+    /// `ctxovrflw login`; clients older than this setting can't unlock keys
+    /// derived with a non-default count.
+    #[serde(default = "default_pbkdf2_iterations")]
+    pub pbkdf2_iterations: u32,
+
     /// Cached derived key (hex-encoded), cleared after 30 days
     #[serde(default)]
     pub cached_key: Option<String>,
@@ -65,14 +164,158 @@ pub struct Config {
     #[serde(default)]
     pub remote_daemon_url: Option<String>,
 
+    /// OpenAI-compatible API base URL (e.g. "https://api.openai.com/v1", or
+    /// "http://localhost:11434/v1" for Ollama) that the `context` tool sends
+    /// its briefing to for a narrative summary. Unset keeps the structured
+    /// briefing.
+    #[serde(default)]
+    pub summarizer_url: Option<String>,
+
+    /// Model name for `summarizer_url`.
+    #[serde(default)]
+    pub summarizer_model: Option<String>,
+
+    /// Bearer token for `summarizer_url`, if it needs one.
+    #[serde(default)]
+    pub summarizer_api_key: Option<String>,
+
     /// Cloud-signed capability token for tier enforcement
     #[serde(default)]
     pub capability_token: Option<String>,
 
+    /// Number of daily daemon.log files to keep when running detached
+    #[serde(default = "default_log_retention_days")]
+    pub log_retention_days: usize,
+
+    /// Log filter when `RUST_LOG` isn't set, e.g. `debug` or
+    /// `ctxovrflw=debug,tower_http=info`. Unset logs `ctxovrflw=info`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+
+    /// Extra browser origins allowed to call the local API, on top of
+    /// ctxovrflw.dev and the localhost dev servers, e.g. `["http://localhost:8080"]`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cors_origins: Vec<String>,
+
     /// Bearer token for localhost API authentication.
     /// Generated on first `init`, required for all non-health routes.
+    /// Overridden by `CTXOVRFLW_AUTH_TOKEN`, which keeps the secret off disk.
     #[serde(default)]
     pub auth_token: Option<String>,
+
+    /// Score multiplier recall applies to memories tagged pinned/policy/workflow/critical
+    /// (1.0 disables the boost).
+    #[serde(default = "default_priority_boost")]
+    pub priority_boost: f64,
+
+    /// Weight of the usage signal in recall ranking: scores are multiplied by
+    /// `1 + access_boost × ln(1 + access_count)`, where `access_count` counts how
+    /// often a memory was returned by recall or fetched by ID. Off (0.0) by
+    /// default, since it feeds recall's own picks back into its ranking; 0.05
+    /// is a gentle starting point.
+    #[serde(default = "default_access_boost")]
+    pub access_boost: f64,
+
+    /// Most memories `pin_memory` keeps pinned at once (0 = no cap), so the
+    /// pin boost stays meaningful.
+    #[serde(default = "default_max_pinned")]
+    pub max_pinned: usize,
+
+    /// What `pin_memory` does when `max_pinned` is reached.
+    #[serde(default)]
+    pub pin_overflow: PinOverflow,
+
+    /// Default minimum similarity for semantic recall results; weaker matches are
+    /// dropped. Unset returns the top-k regardless of how weak the best match is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recall_min_score: Option<f64>,
+
+    /// Memories matching any of these patterns are never pushed to the cloud.
+    /// `tag:<glob>` matches a tag, `subject:<glob>` the subject, a bare glob either
+    /// (case-insensitive, `*` and `?` wildcards), e.g. `["tag:local-only", "subject:scratch*"]`.
+    #[serde(default)]
+    pub sync_exclude: Vec<String>,
+
+    /// Days a synced tombstone (deleted memory) is kept before being purged
+    /// locally; `restore` only works until then. 0 purges it as soon as the
+    /// deletion itself has been pushed.
+    /// Unsynced tombstones are always purged after 1 day.
+    #[serde(default = "default_tombstone_retention_days")]
+    pub tombstone_retention_days: u64,
+
+    /// Cipher for newly encrypted sync payloads: `aes-256-gcm` (default) or
+    /// `xchacha20-poly1305`, whose 192-bit nonces suit very large vaults.
+    /// Existing ciphertext decrypts either way.
+    #[serde(default)]
+    pub sync_cipher: crate::crypto::Cipher,
+
+    /// What `remember` and edits do with content that looks like a credential (AWS keys,
+    /// private keys, tokens, `password=` values): `off`, `warn` (default, store
+    /// and report), `redact` or `refuse`.
+    #[serde(default)]
+    pub secret_policy: crate::secrets::SecretPolicy,
+
+    /// How the knowledge graph finds entities in memory content, beyond the
+    /// subject and namespaced tags: `off` (default) or `rules` (URLs, code
+    /// identifiers, capitalized phrases — linked at low confidence).
+    #[serde(default)]
+    pub entity_extraction: crate::entities::EntityExtraction,
+
+    /// Replaces the built-in guidance MCP clients receive (the `initialize`
+    /// instructions and the `ctxovrflw-context` prompt), e.g. team-specific
+    /// rules or subject conventions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mcp_instructions: Option<String>,
+
+    /// Max memories each agent may store per hour via MCP `remember`, keyed by
+    /// agent_id. A `"*"` entry applies to agents without their own entry, and
+    /// to writes that carry no agent_id at all (counted together).
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub agent_write_quotas: std::collections::BTreeMap<String, usize>,
+
+    /// Record every MCP tool call (tool, agent_id, arguments with memory content
+    /// and queries hashed, outcome) in the append-only audit log (`ctxovrflw audit`).
+    #[serde(default)]
+    pub audit_log: bool,
+
+    /// Name of the cloud identity the top-level credentials belong to
+    /// (`ctxovrflw profile switch`).
+    #[serde(default = "default_profile")]
+    pub active_profile: String,
+
+    /// Database for the active profile; unset uses `memories.db` in the data dir.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<PathBuf>,
+
+    /// Inactive profiles, swapped with the top-level credentials on switch.
+    #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
+    pub profiles: std::collections::BTreeMap<String, Profile>,
+}
+
+/// A cloud identity parked while another profile is active: the credentials,
+/// key material and tier that `login`/`sync`/`account` use, plus its database.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pin_verifier: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_salt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cached_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_cached_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capability_token: Option<String>,
+    #[serde(default)]
+    pub tier: Tier,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub db_path: Option<PathBuf>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
@@ -84,6 +327,36 @@ pub enum Tier {
     Pro,
 }
 
+/// On-disk vector format. `Int8` stores each dimension as a signed byte
+/// (~4x smaller) at a small cost in recall precision.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VectorQuantization {
+    #[default]
+    None,
+    Int8,
+}
+
+/// What `pin_memory` does once `max_pinned` memories are pinned.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PinOverflow {
+    /// Refuse the new pin.
+    #[default]
+    Reject,
+    /// Unpin the least recently reinforced (recalled or edited) pin to make room.
+    Evict,
+}
+
+impl std::fmt::Display for VectorQuantization {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VectorQuantization::None => write!(f, "float32"),
+            VectorQuantization::Int8 => write!(f, "int8"),
+        }
+    }
+}
+
 impl Tier {
     pub fn max_memories(&self) -> Option<usize> {
         match self {
@@ -134,10 +407,18 @@ impl Tier {
     }
 }
 
+fn default_pbkdf2_iterations() -> u32 {
+    crate::crypto::DEFAULT_PBKDF2_ITERATIONS
+}
+
 fn default_port() -> u16 {
     7437
 }
 
+fn default_bind_address() -> String {
+    "127.0.0.1".to_string()
+}
+
 fn default_cloud_url() -> String {
     "https://api.ctxovrflw.dev".to_string()
 }
@@ -146,6 +427,14 @@ fn default_sync_interval() -> u64 {
     60
 }
 
+fn default_http_connect_timeout() -> u64 {
+    10
+}
+
+fn default_http_read_timeout() -> u64 {
+    30
+}
+
 fn default_auto_sync() -> bool {
     true
 }
@@ -154,29 +443,229 @@ fn default_auto_consolidation() -> bool {
     true
 }
 
+fn default_relation_forget_penalty() -> f64 {
+    0.5
+}
+
+fn default_relation_decay_days() -> u64 {
+    30
+}
+
+fn default_relation_decay_factor() -> f64 {
+    0.9
+}
+
+fn default_relation_min_confidence() -> f64 {
+    0.2
+}
+
 fn default_consolidation_interval() -> u64 {
     6 * 60 * 60
 }
 
+fn default_consolidation_similarity() -> f64 {
+    0.92
+}
+
+fn default_log_retention_days() -> usize {
+    7
+}
+
+fn default_tombstone_retention_days() -> u64 {
+    7
+}
+
+fn default_workspace() -> String {
+    crate::db::DEFAULT_WORKSPACE.to_string()
+}
+
+fn default_priority_boost() -> f64 {
+    1.25
+}
+
+fn default_access_boost() -> f64 {
+    0.0
+}
+
+fn default_max_pinned() -> usize {
+    50
+}
+
+/// Files written before `config_version` existed.
+fn legacy_config_version() -> u32 {
+    1
+}
+
+/// Upgrade a raw config table to `CONFIG_VERSION` in place, one version step at
+/// a time. Returns the version it started from, or None if already current.
+fn migrate_table(table: &mut toml::Table) -> Option<u32> {
+    let from = table
+        .get("config_version")
+        .and_then(|v| v.as_integer())
+        .map(|v| v as u32)
+        .unwrap_or_else(legacy_config_version);
+    if from >= CONFIG_VERSION {
+        return None;
+    }
+
+    if from < 2 {
+        // v1 persisted embedding_dim, which is now derived from the model on load;
+        // a stale value left behind after a model switch only confuses readers.
+        table.remove("embedding_dim");
+        // v1 stored URLs as typed; requests are built with `format!("{url}/v1/...")`
+        for key in ["cloud_url", "remote_daemon_url"] {
+            if let Some(toml::Value::String(url)) = table.get_mut(key) {
+                let trimmed = url.trim_end_matches('/').len();
+                url.truncate(trimmed);
+            }
+        }
+    }
+
+    table.insert("config_version".into(), toml::Value::Integer(CONFIG_VERSION as i64));
+    Some(from)
+}
+
+/// The active profile's `db_path`, resolved once at startup (and by
+/// `switch_profile`). Not touched by `Config::load`, so a long-running process
+/// keeps its database when another process switches profiles.
+static ACTIVE_DB_PATH: std::sync::RwLock<Option<PathBuf>> = std::sync::RwLock::new(None);
+
+/// Use `path` (or the default `memories.db` when `None`) for the rest of this run.
+pub fn set_active_db_path(path: Option<&Path>) {
+    *ACTIVE_DB_PATH.write().unwrap_or_else(|e| e.into_inner()) = path.map(Path::to_path_buf);
+}
+
+/// Set from the global `--cloud-url` flag before the config is loaded; wins
+/// over both config.toml and `CTXOVRFLW_CLOUD_URL`.
+static CLOUD_URL_OVERRIDE: std::sync::RwLock<Option<String>> = std::sync::RwLock::new(None);
+
+/// Use `url` as the cloud API base URL for the rest of this run.
+pub fn set_cloud_url_override(url: Option<&str>) {
+    *CLOUD_URL_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) =
+        url.map(|u| u.trim_end_matches('/').to_string());
+}
+
+fn cloud_url_override() -> Option<String> {
+    CLOUD_URL_OVERRIDE.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Set from the global `--data-dir` flag before the config is loaded; wins
+/// over `CTXOVRFLW_DATA_DIR`.
+static DATA_DIR_OVERRIDE: std::sync::RwLock<Option<PathBuf>> = std::sync::RwLock::new(None);
+
+/// Keep config, databases, models and logs under `dir` for the rest of this run.
+pub fn set_data_dir_override(dir: Option<&Path>) {
+    *DATA_DIR_OVERRIDE.write().unwrap_or_else(|e| e.into_inner()) = dir.map(Path::to_path_buf);
+}
+
+/// A fresh data directory for one test. The override is process-wide, so tests
+/// using it run one at a time while this is held.
+#[cfg(test)]
+pub(crate) struct TestDataDir {
+    pub dir: tempfile::TempDir,
+    _lock: std::sync::MutexGuard<'static, ()>,
+}
+
+#[cfg(test)]
+pub(crate) fn test_data_dir() -> TestDataDir {
+    static LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    let lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let dir = tempfile::tempdir().expect("temp data dir");
+    set_data_dir_override(Some(dir.path()));
+    set_active_db_path(None);
+    TestDataDir { dir, _lock: lock }
+}
+
+/// Shared client for outbound requests (cloud API, model and runtime
+/// downloads), built from the startup config. Every caller shares its
+/// connection pool, so keep-alive connections are reused across a sync cycle.
+static HTTP_CLIENT: std::sync::OnceLock<reqwest::Client> = std::sync::OnceLock::new();
+
+/// Build the shared client with `cfg`'s connect and read timeouts. Called once
+/// at startup; later calls keep the first client, so timeout changes take
+/// effect on restart.
+pub fn init_http_client(cfg: &Config) -> Result<&'static reqwest::Client> {
+    if let Some(client) = HTTP_CLIENT.get() {
+        return Ok(client);
+    }
+    let client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(cfg.http_connect_timeout_secs.max(1)))
+        .read_timeout(std::time::Duration::from_secs(cfg.http_read_timeout_secs.max(1)))
+        .build()
+        .context("Failed to build the HTTP client")?;
+    Ok(HTTP_CLIENT.get_or_init(|| client))
+}
+
+/// The shared outbound client; default timeouts if `init_http_client` hasn't run.
+pub fn http_client() -> Result<reqwest::Client> {
+    match HTTP_CLIENT.get() {
+        Some(client) => Ok(client.clone()),
+        None => Ok(init_http_client(&Config::default())?.clone()),
+    }
+}
+
+/// Non-empty value of an override environment variable.
+fn env_override(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.trim().is_empty())
+}
+
+fn default_profile() -> String {
+    DEFAULT_PROFILE.to_string()
+}
+
+fn default_embedder_pool_size() -> usize {
+    2
+}
+
 fn default_embedding_model() -> String {
     "all-MiniLM-L6-v2".to_string()
 }
 
 impl Config {
+    /// Root of everything ctxovrflw stores: `--data-dir`, else
+    /// `CTXOVRFLW_DATA_DIR`, else `~/.ctxovrflw`. Every other path helper
+    /// hangs off this one.
     pub fn data_dir() -> Result<PathBuf> {
-        let dir = dirs::home_dir()
-            .context("Could not determine home directory")?
-            .join(".ctxovrflw");
-        std::fs::create_dir_all(&dir)?;
+        let dir = match Self::data_dir_override() {
+            Some(dir) => dir,
+            None => dirs::home_dir()
+                .context("Could not determine home directory")?
+                .join(".ctxovrflw"),
+        };
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("Could not create data directory {}", dir.display()))?;
         Ok(dir)
     }
 
+    /// Data directory chosen by `--data-dir` or `CTXOVRFLW_DATA_DIR`, if any,
+    /// so spawned services can be pointed at the same one.
+    pub fn data_dir_override() -> Option<PathBuf> {
+        DATA_DIR_OVERRIDE
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+            .or_else(|| env_override(ENV_DATA_DIR).map(PathBuf::from))
+            .map(|dir| std::path::absolute(&dir).unwrap_or(dir))
+    }
+
     pub fn config_path() -> Result<PathBuf> {
         Ok(Self::data_dir()?.join("config.toml"))
     }
 
+    /// Database of the active profile, as of startup or the last `switch_profile`.
     pub fn db_path() -> Result<PathBuf> {
-        Ok(Self::data_dir()?.join("memories.db"))
+        let active = ACTIVE_DB_PATH.read().unwrap_or_else(|e| e.into_inner()).clone();
+        match active {
+            Some(path) => Ok(path),
+            None => Ok(Self::data_dir()?.join("memories.db")),
+        }
+    }
+
+    /// Default database location for a new profile.
+    pub fn profile_db_path(name: &str) -> Result<PathBuf> {
+        let dir = Self::data_dir()?.join("profiles").join(name);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir.join("memories.db"))
     }
 
     pub fn pid_path() -> Result<PathBuf> {
@@ -189,20 +678,46 @@ impl Config {
         Ok(dir)
     }
 
+    pub fn mcp_log_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("mcp-debug.log"))
+    }
+
+    pub fn crash_log_path() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("crash.log"))
+    }
+
     #[allow(dead_code)]
     pub fn sync_state_path() -> Result<PathBuf> {
         Ok(Self::data_dir()?.join("sync_state.json"))
     }
 
-    pub fn load() -> Result<Self> {
+    /// Config as stored in config.toml, without environment overrides.
+    /// Files from an older `config_version` are migrated and rewritten (the
+    /// original is kept next to it as `config.toml.v<N>.bak`).
+    fn load_file() -> Result<Self> {
         let path = Self::config_path()?;
-        let mut config = if path.exists() {
-            let contents = std::fs::read_to_string(&path)
-                .with_context(|| format!("Failed to read config at {}", path.display()))?;
-            toml::from_str(&contents).context("Failed to parse config.toml")?
-        } else {
-            Self::default()
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config at {}", path.display()))?;
+        let mut table: toml::Table = toml::from_str(&contents).context("Failed to parse config.toml")?;
+
+        let Some(from) = migrate_table(&mut table) else {
+            return table.try_into().context("Failed to parse config.toml");
         };
+        let config: Self = table.try_into().context("Failed to parse migrated config.toml")?;
+        let backup = path.with_extension(format!("toml.v{from}.bak"));
+        std::fs::copy(&path, &backup)
+            .with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+        config.write_to(&path)?;
+        tracing::info!("Migrated config.toml from v{from} to v{CONFIG_VERSION} (backup: {})", backup.display());
+        Ok(config)
+    }
+
+    pub fn load() -> Result<Self> {
+        let mut config = Self::load_file()?;
+        config.apply_env_overrides()?;
 
         // Resolve embedding_dim from model registry
         let dim = crate::embed::models::get_model(&config.embedding_model)
@@ -213,16 +728,69 @@ impl Config {
         Ok(config)
     }
 
+    /// Apply `CTXOVRFLW_*` environment variables on top of file values.
+    fn apply_env_overrides(&mut self) -> Result<()> {
+        if let Some(port) = env_override(ENV_PORT) {
+            self.port = port
+                .parse()
+                .with_context(|| format!("{ENV_PORT} must be a port number, got '{port}'"))?;
+        }
+        if let Some(url) = env_override(ENV_CLOUD_URL) {
+            self.cloud_url = url.trim_end_matches('/').to_string();
+        }
+        if let Some(url) = cloud_url_override() {
+            self.cloud_url = url;
+        }
+        if let Some(model) = env_override(ENV_EMBEDDING_MODEL) {
+            if crate::embed::models::get_model(&model).is_none() {
+                anyhow::bail!("{ENV_EMBEDDING_MODEL}: unknown model '{model}'");
+            }
+            self.embedding_model = model;
+        }
+        if let Some(token) = env_override(ENV_AUTH_TOKEN) {
+            self.auth_token = Some(token);
+        }
+        Ok(())
+    }
+
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path()?;
+
+        // Keep env- and flag-provided values out of the file: write back what was there
+        let mut on_disk = self.clone();
+        let cloud_url_overridden = env_override(ENV_CLOUD_URL).is_some() || cloud_url_override().is_some();
+        if cloud_url_overridden
+            || [ENV_PORT, ENV_EMBEDDING_MODEL, ENV_AUTH_TOKEN]
+                .iter()
+                .any(|name| env_override(name).is_some())
+        {
+            let file = Self::load_file().unwrap_or_default();
+            if env_override(ENV_PORT).is_some() {
+                on_disk.port = file.port;
+            }
+            if cloud_url_overridden {
+                on_disk.cloud_url = file.cloud_url;
+            }
+            if env_override(ENV_EMBEDDING_MODEL).is_some() {
+                on_disk.embedding_model = file.embedding_model;
+            }
+            if env_override(ENV_AUTH_TOKEN).is_some() {
+                on_disk.auth_token = file.auth_token;
+            }
+        }
+
+        on_disk.write_to(&path)
+    }
+
+    fn write_to(&self, path: &std::path::Path) -> Result<()> {
         let contents = toml::to_string_pretty(self)?;
-        std::fs::write(&path, &contents)?;
+        std::fs::write(path, &contents)?;
 
         // Restrict permissions to owner-only (600) — config contains API keys and encryption keys
         #[cfg(unix)]
         {
             use std::os::unix::fs::PermissionsExt;
-            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
         }
 
         Ok(())
@@ -292,11 +860,46 @@ impl Config {
         })
     }
 
+    /// The saved capability token with its signature and claims checked, but
+    /// not its expiry. `None` when no token is saved.
+    fn saved_capability(&self) -> Option<Result<crate::capability::CapabilityPayload, String>> {
+        let token = self.capability_token.as_ref()?;
+        Some(crate::capability::decode_capability_token(token).and_then(|payload| payload.validate(None).map(|()| payload)))
+    }
+
+    /// Classify the saved capability token without discarding expired ones, so
+    /// gated features can explain themselves after a long stretch offline.
+    pub fn capability_status(&self) -> CapabilityStatus {
+        match self.saved_capability() {
+            None => CapabilityStatus::Missing,
+            Some(Err(e)) => CapabilityStatus::Invalid(e),
+            Some(Ok(payload)) => CapabilityStatus::of(&payload, chrono::Utc::now().timestamp().max(0) as u64),
+        }
+    }
+
+    /// Error text for a gated tool when the capability token has expired, so the
+    /// caller is told to sync rather than to upgrade. `None` if it hasn't expired.
+    pub fn capability_expired_message(&self, what: &str) -> Option<String> {
+        match self.capability_status() {
+            CapabilityStatus::Expired { tier, expired_at } => Some(format!(
+                "{what} is unavailable: your {tier} capability token expired {}. \
+                 Run `ctxovrflw sync` (or `ctxovrflw account`) while online to refresh it.",
+                chrono::DateTime::from_timestamp(expired_at as i64, 0)
+                    .map(|t| t.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default()
+            )),
+            _ => None,
+        }
+    }
+
     /// Check if a feature is enabled, consulting capability token first.
-    /// Falls back to local tier config if no valid token.
+    /// Falls back to local tier config if no valid token. A token that expired
+    /// less than [`CAPABILITY_GRACE_SECS`] ago still grants its features; past
+    /// that they stay off, instead of silently falling back, until a sync.
     pub fn feature_enabled(&self, feature: &str) -> bool {
-        if let Some(cap) = self.capability() {
-            return cap.has_feature(feature);
+        if let Some(Ok(cap)) = self.saved_capability() {
+            let status = CapabilityStatus::of(&cap, chrono::Utc::now().timestamp().max(0) as u64);
+            return !matches!(status, CapabilityStatus::Expired { .. }) && cap.has_feature(feature);
         }
         // Fallback to local tier (for offline/free users)
         match feature {
@@ -316,6 +919,14 @@ impl Config {
         self.tier.max_memories()
     }
 
+    /// Hourly write quota for an agent, if one is configured.
+    pub fn agent_write_quota(&self, agent_id: &str) -> Option<usize> {
+        self.agent_write_quotas
+            .get(agent_id)
+            .or_else(|| self.agent_write_quotas.get("*"))
+            .copied()
+    }
+
     pub fn effective_cloud_sync(&self) -> bool {
         if let Some(cap) = self.capability() {
             return cap.cloud_sync;
@@ -350,25 +961,63 @@ fn hex_decode(s: &str) -> Option<Vec<u8>> {
 impl Default for Config {
     fn default() -> Self {
         Self {
+            config_version: CONFIG_VERSION,
             port: default_port(),
+            bind_address: default_bind_address(),
             tier: Tier::Free,
             embedding_model: default_embedding_model(),
+            embedder_pool_size: default_embedder_pool_size(),
+            model_mirror: None,
+            vector_quantization: VectorQuantization::None,
+            current_workspace: default_workspace(),
             embedding_dim: 384, // Will be updated by load()
             cloud_url: default_cloud_url(),
             api_key: None,
             device_id: None,
             sync_interval_secs: default_sync_interval(),
             auto_sync: default_auto_sync(),
+            defer_sync: false,
+            skip_embedding: false,
+            http_connect_timeout_secs: default_http_connect_timeout(),
+            http_read_timeout_secs: default_http_read_timeout(),
             auto_consolidation: default_auto_consolidation(),
             consolidation_interval_secs: default_consolidation_interval(),
+            consolidation_similarity: default_consolidation_similarity(),
+            relation_forget_penalty: default_relation_forget_penalty(),
+            relation_decay_days: default_relation_decay_days(),
+            relation_decay_factor: default_relation_decay_factor(),
+            relation_min_confidence: default_relation_min_confidence(),
             email: None,
             pin_verifier: None,
             key_salt: None,
+            pbkdf2_iterations: default_pbkdf2_iterations(),
             cached_key: None,
             key_cached_at: None,
             remote_daemon_url: None,
+            summarizer_url: None,
+            summarizer_model: None,
+            summarizer_api_key: None,
             capability_token: None,
+            log_retention_days: default_log_retention_days(),
             auth_token: None,
+            priority_boost: default_priority_boost(),
+            access_boost: default_access_boost(),
+            max_pinned: default_max_pinned(),
+            pin_overflow: PinOverflow::Reject,
+            recall_min_score: None,
+            sync_exclude: Vec::new(),
+            tombstone_retention_days: default_tombstone_retention_days(),
+            sync_cipher: Default::default(),
+            secret_policy: Default::default(),
+            entity_extraction: Default::default(),
+            mcp_instructions: None,
+            agent_write_quotas: Default::default(),
+            audit_log: false,
+            log_level: None,
+            cors_origins: Vec::new(),
+            active_profile: default_profile(),
+            db_path: None,
+            profiles: Default::default(),
         }
     }
 }
@@ -389,16 +1038,409 @@ impl Config {
         Ok(())
     }
 
+    /// Make `name` the active profile: park the current credentials under
+    /// `active_profile` and move the target's into the top-level fields. This
+    /// process uses the target's database from here on.
+    pub fn switch_profile(&mut self, name: &str) -> Result<()> {
+        let target = self
+            .profiles
+            .remove(name)
+            .with_context(|| format!("No profile named '{name}' (create it with `ctxovrflw profile add {name}`)"))?;
+        let current = Profile {
+            api_key: self.api_key.take(),
+            device_id: self.device_id.take(),
+            email: self.email.take(),
+            pin_verifier: self.pin_verifier.take(),
+            key_salt: self.key_salt.take(),
+            cached_key: self.cached_key.take(),
+            key_cached_at: self.key_cached_at.take(),
+            capability_token: self.capability_token.take(),
+            tier: std::mem::take(&mut self.tier),
+            db_path: self.db_path.take(),
+        };
+        let previous = std::mem::replace(&mut self.active_profile, name.to_string());
+        self.profiles.insert(previous, current);
+
+        self.api_key = target.api_key;
+        self.device_id = target.device_id;
+        self.email = target.email;
+        self.pin_verifier = target.pin_verifier;
+        self.key_salt = target.key_salt;
+        self.cached_key = target.cached_key;
+        self.key_cached_at = target.key_cached_at;
+        self.capability_token = target.capability_token;
+        self.tier = target.tier;
+        self.db_path = target.db_path;
+        set_active_db_path(self.db_path.as_deref());
+        Ok(())
+    }
+
     /// Returns true if this instance should connect to a remote daemon
     /// instead of running its own.
     pub fn is_remote_client(&self) -> bool {
         self.remote_daemon_url.is_some()
     }
 
+    /// How deleting a memory weakens relations extracted from it.
+    pub fn forget_penalty(&self) -> crate::db::graph::ForgetPenalty {
+        crate::db::graph::ForgetPenalty {
+            #[cfg(feature = "pro")]
+            penalty: self.relation_forget_penalty,
+            #[cfg(feature = "pro")]
+            min_confidence: self.relation_min_confidence,
+        }
+    }
+
     /// The base URL for the daemon API (local or remote).
     pub fn daemon_url(&self) -> String {
         self.remote_daemon_url
             .clone()
             .unwrap_or_else(|| format!("http://127.0.0.1:{}", self.port))
     }
+
+    /// Check settings that would otherwise fail later inside sync, embed or the
+    /// HTTP server. Returns every problem found, each with a suggested fix.
+    pub fn validate(&self) -> Vec<ConfigProblem> {
+        let mut problems = Vec::new();
+        let mut problem = |field: &'static str, problem: String, fix: String| {
+            problems.push(ConfigProblem { field, problem, fix });
+        };
+
+        if self.port == 0 {
+            problem("port", "0 is not a usable daemon port".into(), "set port to e.g. 7437".into());
+        } else if self.port < 1024 && cfg!(unix) {
+            problem(
+                "port",
+                format!("{} is privileged and needs root to bind", self.port),
+                "use a port above 1023, e.g. 7437".into(),
+            );
+        }
+        if self.bind_address.parse::<std::net::IpAddr>().is_err() {
+            problem(
+                "bind_address",
+                format!("'{}' is not an IP address", self.bind_address),
+                "use 127.0.0.1 (or 0.0.0.0 to listen on all interfaces)".into(),
+            );
+        }
+
+        let urls = [
+            ("cloud_url", Some(&self.cloud_url)),
+            ("remote_daemon_url", self.remote_daemon_url.as_ref()),
+            ("model_mirror", self.model_mirror.as_ref()),
+            ("summarizer_url", self.summarizer_url.as_ref()),
+        ];
+        for (field, url) in urls {
+            let Some(url) = url else { continue };
+            match url::Url::parse(url) {
+                Ok(u) if matches!(u.scheme(), "http" | "https") && u.host().is_some() => {}
+                Ok(_) => problem(field, format!("'{url}' is not an http(s) URL"), "use a URL like https://host[:port]".into()),
+                Err(e) => problem(field, format!("'{url}' is not a valid URL ({e})"), "use a URL like https://host[:port]".into()),
+            }
+        }
+
+        if self.summarizer_url.is_some() && self.summarizer_model.is_none() {
+            problem(
+                "summarizer_model",
+                "is required when summarizer_url is set".into(),
+                "set summarizer_model to a model the endpoint serves, e.g. \"gpt-4o-mini\"".into(),
+            );
+        }
+
+        match crate::embed::models::get_model(&self.embedding_model) {
+            None => problem(
+                "embedding_model",
+                format!("unknown model '{}'", self.embedding_model),
+                format!(
+                    "pick one from `ctxovrflw model list` (default: {})",
+                    crate::embed::models::default_model().id
+                ),
+            ),
+            Some(model) if model.dim != self.embedding_dim => problem(
+                "embedding_model",
+                format!("{} produces {}-dim vectors but {} are expected", model.id, model.dim, self.embedding_dim),
+                "run `ctxovrflw model switch` to re-select the model and rebuild vectors".into(),
+            ),
+            Some(_) => {}
+        }
+        for (field, secs) in [
+            ("http_connect_timeout_secs", self.http_connect_timeout_secs),
+            ("http_read_timeout_secs", self.http_read_timeout_secs),
+        ] {
+            if secs == 0 {
+                problem(field, "must be at least 1".into(), format!("set {field} = 1 or more"));
+            }
+        }
+        if self.embedder_pool_size == 0 {
+            problem("embedder_pool_size", "must be at least 1".into(), "set embedder_pool_size = 1 or more".into());
+        }
+
+        let fractions = [
+            ("consolidation_similarity", self.consolidation_similarity),
+            ("relation_forget_penalty", self.relation_forget_penalty),
+            ("relation_decay_factor", self.relation_decay_factor),
+            ("relation_min_confidence", self.relation_min_confidence),
+        ];
+        for (field, value) in fractions {
+            if !(0.0..=1.0).contains(&value) {
+                problem(field, format!("{value} is outside 0.0–1.0"), "use a value between 0.0 and 1.0".into());
+            }
+        }
+        if let Some(score) = self.recall_min_score
+            && !(-1.0..=1.0).contains(&score)
+        {
+            problem("recall_min_score", format!("{score} is outside -1.0–1.0"), "use a similarity between 0.0 and 1.0, or remove it".into());
+        }
+        if self.priority_boost <= 0.0 {
+            problem("priority_boost", format!("{} would hide pinned memories", self.priority_boost), "use 1.0 to disable the boost".into());
+        }
+        if self.access_boost < 0.0 {
+            problem("access_boost", format!("{} would rank frequently used memories lower", self.access_boost), "use 0.0 to disable the boost".into());
+        }
+        if self.pbkdf2_iterations < crate::crypto::DEFAULT_PBKDF2_ITERATIONS {
+            problem(
+                "pbkdf2_iterations",
+                format!("{} is weaker than the {} minimum", self.pbkdf2_iterations, crate::crypto::DEFAULT_PBKDF2_ITERATIONS),
+                format!("use {} or more", crate::crypto::DEFAULT_PBKDF2_ITERATIONS),
+            );
+        }
+        if self.auth_token.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            problem("auth_token", "is set but empty".into(), "remove it and run `ctxovrflw init` to generate one".into());
+        }
+        if self.mcp_instructions.as_deref().is_some_and(|t| t.trim().is_empty()) {
+            problem("mcp_instructions", "is set but empty, so agents get no guidance".into(), "remove it to use the built-in instructions".into());
+        }
+        if let Some(level) = &self.log_level
+            && let Err(e) = tracing_subscriber::EnvFilter::try_new(level)
+        {
+            problem("log_level", format!("'{level}' isn't a valid filter ({e})"), "use a level like `info` or `debug`, or remove it".into());
+        }
+        for origin in &self.cors_origins {
+            if !(origin.starts_with("http://") || origin.starts_with("https://")) || origin.ends_with('/') {
+                problem("cors_origins", format!("'{origin}' isn't an origin"), "use scheme and host only, e.g. `http://localhost:8080`".into());
+            }
+        }
+
+        problems
+    }
+}
+
+/// A misconfiguration found by [`Config::validate`].
+#[derive(Debug, Clone)]
+pub struct ConfigProblem {
+    pub field: &'static str,
+    pub problem: String,
+    pub fix: String,
+}
+
+impl std::fmt::Display for ConfigProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} — {}", self.field, self.problem, self.fix)
+    }
+}
+
+/// How long an expired capability token keeps granting its features, so a
+/// few days offline (or a flaky cloud) doesn't switch paid tools off.
+pub const CAPABILITY_GRACE_SECS: u64 = 7 * 86400;
+
+/// State of the cloud-issued capability token, as reported by [`Config::capability_status`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum CapabilityStatus {
+    /// No token saved — never synced, or not logged in. Features follow the local tier.
+    Missing,
+    Valid { tier: String, expires_at: u64 },
+    /// Past its expiry but within [`CAPABILITY_GRACE_SECS`]; its features stay on.
+    Grace { tier: String, expired_at: u64 },
+    /// Genuine but past the grace period. Features it gated stay off until the next sync refreshes it.
+    Expired { tier: String, expired_at: u64 },
+    /// Bad signature, malformed, or failed claim validation. Treated like `Missing`.
+    Invalid(String),
+}
+
+impl CapabilityStatus {
+    /// Classify a genuine token's payload at unix time `now`.
+    fn of(payload: &crate::capability::CapabilityPayload, now: u64) -> Self {
+        let tier = payload.tier.clone();
+        if now <= payload.exp {
+            CapabilityStatus::Valid { tier, expires_at: payload.exp }
+        } else if now <= payload.exp.saturating_add(CAPABILITY_GRACE_SECS) {
+            CapabilityStatus::Grace { tier, expired_at: payload.exp }
+        } else {
+            CapabilityStatus::Expired { tier, expired_at: payload.exp }
+        }
+    }
+}
+
+impl std::fmt::Display for CapabilityStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let date = |secs: &u64| {
+            chrono::DateTime::from_timestamp(*secs as i64, 0)
+                .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                .unwrap_or_else(|| secs.to_string())
+        };
+        match self {
+            CapabilityStatus::Missing => write!(f, "none (using local tier)"),
+            CapabilityStatus::Valid { tier, expires_at } => {
+                write!(f, "{tier}, valid until {}", date(expires_at))
+            }
+            CapabilityStatus::Grace { tier, expired_at } => write!(
+                f,
+                "{tier}, expired {} — features kept until {}; run `ctxovrflw sync` to refresh",
+                date(expired_at),
+                date(&expired_at.saturating_add(CAPABILITY_GRACE_SECS))
+            ),
+            CapabilityStatus::Expired { tier, expired_at } => {
+                write!(f, "{tier}, EXPIRED {} — run `ctxovrflw sync` to refresh", date(expired_at))
+            }
+            CapabilityStatus::Invalid(reason) => write!(f, "invalid ({reason}) — using local tier"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V1_CONFIG: &str = r#"
+port = 7437
+tier = "standard"
+embedding_model = "bge-small-en-v1.5"
+embedding_dim = 384
+cloud_url = "https://api.ctxovrflw.dev/"
+remote_daemon_url = "http://10.0.0.5:7437//"
+auto_sync = false
+api_key = "key-123"
+"#;
+
+    #[test]
+    fn test_switch_profile_swaps_credentials_and_db() {
+        let data = test_data_dir();
+        let work_db = data.dir.path().join("work.db");
+        let mut cfg = Config { api_key: Some("personal-key".into()), tier: Tier::Standard, ..Config::default() };
+        cfg.profiles.insert(
+            "work".into(),
+            Profile { api_key: Some("work-key".into()), db_path: Some(work_db.clone()), ..Profile::default() },
+        );
+
+        cfg.switch_profile("work").unwrap();
+        assert_eq!(cfg.active_profile, "work");
+        assert_eq!(cfg.api_key.as_deref(), Some("work-key"));
+        assert_eq!(cfg.tier, Tier::Free);
+        assert_eq!(Config::db_path().unwrap(), work_db);
+        let parked = &cfg.profiles[DEFAULT_PROFILE];
+        assert_eq!(parked.api_key.as_deref(), Some("personal-key"));
+        assert_eq!(parked.tier, Tier::Standard);
+
+        cfg.switch_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(cfg.api_key.as_deref(), Some("personal-key"));
+        assert_eq!(Config::db_path().unwrap(), data.dir.path().join("memories.db"));
+        assert!(cfg.profiles.contains_key("work"));
+
+        assert!(cfg.switch_profile("missing").is_err());
+        assert_eq!(cfg.active_profile, DEFAULT_PROFILE);
+    }
+
+    #[test]
+    fn test_load_keeps_the_startup_db() {
+        let data = test_data_dir();
+        let startup_db = data.dir.path().join("memories.db");
+        let cfg = Config::load().unwrap();
+        set_active_db_path(cfg.db_path.as_deref());
+
+        // Another process switches profiles and saves
+        let mut other = cfg.clone();
+        other.active_profile = "work".into();
+        other.db_path = Some(data.dir.path().join("work.db"));
+        other.save().unwrap();
+
+        // Re-reading config (as the daemon does per request) doesn't move this process
+        let reloaded = Config::load().unwrap();
+        assert_eq!(reloaded.active_profile, "work");
+        assert_eq!(Config::db_path().unwrap(), startup_db);
+    }
+
+    #[test]
+    fn test_v1_config_upgrades() {
+        let mut table: toml::Table = toml::from_str(V1_CONFIG).unwrap();
+        assert_eq!(migrate_table(&mut table), Some(1));
+        assert!(!table.contains_key("embedding_dim"));
+
+        let cfg: Config = table.try_into().unwrap();
+        assert_eq!(cfg.config_version, CONFIG_VERSION);
+        assert_eq!(cfg.cloud_url, "https://api.ctxovrflw.dev");
+        assert_eq!(cfg.remote_daemon_url.as_deref(), Some("http://10.0.0.5:7437"));
+        // Existing values survive, missing ones take current defaults
+        assert_eq!(cfg.embedding_model, "bge-small-en-v1.5");
+        assert!(!cfg.auto_sync);
+        assert_eq!(cfg.api_key.as_deref(), Some("key-123"));
+        assert_eq!(cfg.embedder_pool_size, default_embedder_pool_size());
+        assert_eq!(cfg.priority_boost, default_priority_boost());
+        assert_eq!(cfg.access_boost, default_access_boost());
+
+        // Rewritten file parses as current and doesn't migrate again
+        let mut rewritten: toml::Table = toml::from_str(&toml::to_string_pretty(&cfg).unwrap()).unwrap();
+        assert_eq!(migrate_table(&mut rewritten), None);
+    }
+
+    #[test]
+    fn test_current_config_untouched() {
+        let mut table: toml::Table =
+            toml::from_str(&toml::to_string_pretty(&Config::default()).unwrap()).unwrap();
+        let before = table.clone();
+        assert_eq!(migrate_table(&mut table), None);
+        assert_eq!(table, before);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(Config::default().validate().is_empty());
+
+        let cfg = Config {
+            port: 0,
+            cloud_url: "api.ctxovrflw.dev".into(),
+            embedding_model: "no-such-model".into(),
+            relation_decay_factor: 1.5,
+            ..Config::default()
+        };
+        let fields: Vec<_> = cfg.validate().iter().map(|p| p.field).collect();
+        assert_eq!(fields, ["port", "cloud_url", "embedding_model", "relation_decay_factor"]);
+    }
+
+    #[test]
+    fn test_capability_grace_period() {
+        let payload = crate::capability::CapabilityPayload {
+            sub: "device".into(),
+            tier: "pro".into(),
+            features: vec!["consolidation".into()],
+            max_memories: None,
+            max_devices: None,
+            cloud_sync: true,
+            iat: 1_000,
+            exp: 1_000_000,
+        };
+        let pro = "pro".to_string();
+        assert_eq!(CapabilityStatus::of(&payload, 999_999), CapabilityStatus::Valid { tier: pro.clone(), expires_at: 1_000_000 });
+        assert_eq!(CapabilityStatus::of(&payload, 1_000_001), CapabilityStatus::Grace { tier: pro.clone(), expired_at: 1_000_000 });
+        assert_eq!(
+            CapabilityStatus::of(&payload, 1_000_000 + CAPABILITY_GRACE_SECS),
+            CapabilityStatus::Grace { tier: pro.clone(), expired_at: 1_000_000 }
+        );
+        assert_eq!(
+            CapabilityStatus::of(&payload, 1_000_001 + CAPABILITY_GRACE_SECS),
+            CapabilityStatus::Expired { tier: pro, expired_at: 1_000_000 }
+        );
+    }
+
+    #[test]
+    fn test_capability_status_without_valid_token() {
+        let mut cfg = Config::default();
+        assert_eq!(cfg.capability_status(), CapabilityStatus::Missing);
+        assert!(cfg.capability_expired_message("Consolidation").is_none());
+
+        cfg.capability_token = Some("bm90LWEtdG9rZW4.c2lnbmF0dXJl".into());
+        assert!(matches!(cfg.capability_status(), CapabilityStatus::Invalid(_)));
+        assert!(cfg.capability_expired_message("Consolidation").is_none());
+        // A forged token falls back to the local tier like a missing one
+        cfg.tier = Tier::Standard;
+        assert!(cfg.feature_enabled("webhooks"));
+    }
 }
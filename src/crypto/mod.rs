@@ -2,25 +2,68 @@
 //!
 //! Memories are encrypted client-side before push and decrypted after pull.
 //! The server never sees plaintext. Key is derived from a user-chosen sync PIN
-//! via PBKDF2-HMAC-SHA256 (600,000 iterations).
+//! via PBKDF2-HMAC-SHA256 (600,000 iterations unless `pbkdf2_iterations` in
+//! config asks for more; the count travels with the PIN verifier).
 //!
-//! Each memory is encrypted with AES-256-GCM using a unique nonce.
-//! Format: [12-byte nonce][ciphertext+tag]
+//! Each memory is encrypted with AES-256-GCM (default) or XChaCha20-Poly1305
+//! using a unique random nonce.
+//! Format: [1-byte version][nonce][ciphertext+tag]
+//!
+//! The version byte selects the cipher (1 = AES-256-GCM with a 12-byte nonce,
+//! 2 = XChaCha20-Poly1305 with a 24-byte nonce). Ciphertext written before
+//! versioning (no version byte) is AES-256-GCM and still decrypts. Synced
+//! records set the high bit of the version byte and are sealed with their id
+//! as associated data, so ciphertext can't be moved between records.
 
 use anyhow::{Context, Result};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
 use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM};
 use ring::pbkdf2;
 use ring::rand::{SecureRandom, SystemRandom};
 use std::num::NonZeroU32;
 
-const PBKDF2_ITERATIONS: u32 = 600_000;
+/// PBKDF2 rounds for sync keys, and the count assumed for verifiers that don't
+/// record one (everything created before the count was configurable).
+pub const DEFAULT_PBKDF2_ITERATIONS: u32 = 600_000;
 const SALT_PREFIX: &[u8] = b"ctxovrflw-zk-v1-";
 const NONCE_LEN: usize = 12;
 const KEY_LEN: usize = 32;
 
-/// Derives a 256-bit encryption key from a sync PIN + server-provided salt.
-/// The salt is a random 32-byte hex string generated by the server on first PIN setup.
+const XNONCE_LEN: usize = 24;
+const TAG_LEN: usize = 16;
+
+/// Format version tag: AES-256-GCM with a 96-bit random nonce. The default.
+const VERSION_AES_256_GCM: u8 = 1;
+/// Format version tag: XChaCha20-Poly1305 with a 192-bit random nonce.
+const VERSION_XCHACHA20_POLY1305: u8 = 2;
+/// Set on the version byte when the ciphertext was sealed with its record id as
+/// associated data (see [`encrypt_bound`]).
+const BOUND_TO_RECORD: u8 = 0x80;
+
+/// Cipher used for new ciphertext (`sync_cipher` in config). Decryption reads
+/// the version byte, so both are always accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum Cipher {
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    /// Random 192-bit nonces make collisions negligible even across billions of
+    /// messages under one key, unlike AES-GCM's 96-bit birthday bound.
+    #[serde(rename = "xchacha20-poly1305")]
+    XChaCha20Poly1305,
+}
+
+/// [`derive_key_with_iterations`] at the default iteration count.
+#[cfg(test)]
 pub fn derive_key(pin: &str, salt_hex: &str) -> [u8; KEY_LEN] {
+    derive_key_with_iterations(pin, salt_hex, DEFAULT_PBKDF2_ITERATIONS)
+}
+
+/// Derives a 256-bit encryption key from a sync PIN + server-provided salt,
+/// with `iterations` PBKDF2 rounds (at least 1). The salt is a random 32-byte
+/// hex string generated by the server on first PIN setup.
+pub fn derive_key_with_iterations(pin: &str, salt_hex: &str, iterations: u32) -> [u8; KEY_LEN] {
     let salt_bytes = hex_decode(salt_hex).unwrap_or_default();
     let mut salt = Vec::with_capacity(SALT_PREFIX.len() + salt_bytes.len());
     salt.extend_from_slice(SALT_PREFIX);
@@ -29,7 +72,7 @@ pub fn derive_key(pin: &str, salt_hex: &str) -> [u8; KEY_LEN] {
     let mut key = [0u8; KEY_LEN];
     pbkdf2::derive(
         pbkdf2::PBKDF2_HMAC_SHA256,
-        NonZeroU32::new(PBKDF2_ITERATIONS).unwrap(),
+        NonZeroU32::new(iterations).unwrap_or(NonZeroU32::MIN),
         &salt,
         pin.as_bytes(),
         &mut key,
@@ -37,8 +80,76 @@ pub fn derive_key(pin: &str, salt_hex: &str) -> [u8; KEY_LEN] {
     key
 }
 
-/// Encrypts plaintext with AES-256-GCM. Returns [nonce || ciphertext || tag].
+/// Encrypts plaintext with AES-256-GCM. Returns [version || nonce || ciphertext || tag].
 pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    encrypt_with(Cipher::Aes256Gcm, key, plaintext)
+}
+
+/// Encrypts plaintext with the given cipher. Returns [version || nonce || ciphertext || tag].
+pub fn encrypt_with(cipher: Cipher, key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
+    seal(cipher, key, plaintext, None)
+}
+
+/// Encrypts plaintext bound to `record_id` as associated data, so the ciphertext
+/// only decrypts (via [`decrypt_bound`]) under that same id and can't be moved
+/// onto another record.
+pub fn encrypt_bound(cipher: Cipher, key: &[u8; KEY_LEN], plaintext: &[u8], record_id: &str) -> Result<Vec<u8>> {
+    seal(cipher, key, plaintext, Some(record_id.as_bytes()))
+}
+
+/// Decrypts versioned ciphertext, dispatching on the version byte. Data without a
+/// recognised version is treated as the legacy unversioned [nonce || ciphertext || tag].
+pub fn decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+    match data.split_first() {
+        Some((&version, rest)) if version == VERSION_AES_256_GCM || version == VERSION_XCHACHA20_POLY1305 => {
+            open(version, key, rest, &[]).or_else(|e| open_legacy(key, data, e))
+        }
+        _ => open_aes_256_gcm(key, data, &[]),
+    }
+}
+
+/// Decrypts ciphertext from [`encrypt_bound`], checking it was sealed for
+/// `record_id`. Unbound ciphertext (written before binding) is still accepted.
+pub fn decrypt_bound(key: &[u8; KEY_LEN], data: &[u8], record_id: &str) -> Result<Vec<u8>> {
+    match data.split_first() {
+        Some((&version, rest)) if version & BOUND_TO_RECORD != 0 => {
+            open(version & !BOUND_TO_RECORD, key, rest, record_id.as_bytes())
+                .map_err(|_| anyhow::anyhow!("Decryption failed — wrong sync PIN, or ciphertext belongs to another record"))
+                .or_else(|e| open_legacy(key, data, e))
+        }
+        _ => decrypt(key, data),
+    }
+}
+
+/// Seal with the version byte (plus [`BOUND_TO_RECORD`] when `aad` is given) prepended.
+fn seal(cipher: Cipher, key: &[u8; KEY_LEN], plaintext: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
+    let aad_bytes = aad.unwrap_or_default();
+    let (version, sealed) = match cipher {
+        Cipher::Aes256Gcm => (VERSION_AES_256_GCM, seal_aes_256_gcm(key, plaintext, aad_bytes)?),
+        Cipher::XChaCha20Poly1305 => (VERSION_XCHACHA20_POLY1305, seal_xchacha20_poly1305(key, plaintext, aad_bytes)?),
+    };
+    let mut result = Vec::with_capacity(1 + sealed.len());
+    result.push(if aad.is_some() { version | BOUND_TO_RECORD } else { version });
+    result.extend_from_slice(&sealed);
+    Ok(result)
+}
+
+fn open(version: u8, key: &[u8; KEY_LEN], data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    match version {
+        VERSION_AES_256_GCM => open_aes_256_gcm(key, data, aad),
+        VERSION_XCHACHA20_POLY1305 => open_xchacha20_poly1305(key, data, aad),
+        other => anyhow::bail!("Unknown ciphertext version {other}"),
+    }
+}
+
+/// A legacy nonce can start with any byte, including a version tag, so versioned
+/// decryption falls back to the unversioned format before reporting `err`.
+fn open_legacy(key: &[u8; KEY_LEN], data: &[u8], err: anyhow::Error) -> Result<Vec<u8>> {
+    open_aes_256_gcm(key, data, &[]).map_err(|_| err)
+}
+
+/// AES-256-GCM seal. Returns [nonce || ciphertext || tag].
+fn seal_aes_256_gcm(key: &[u8; KEY_LEN], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
     let rng = SystemRandom::new();
     let unbound = UnboundKey::new(&AES_256_GCM, key)
         .map_err(|_| anyhow::anyhow!("Failed to create encryption key"))?;
@@ -51,7 +162,7 @@ pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
 
     let mut in_out = plaintext.to_vec();
     sealing_key
-        .seal_in_place_append_tag(nonce, Aad::empty(), &mut in_out)
+        .seal_in_place_append_tag(nonce, Aad::from(aad), &mut in_out)
         .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
 
     // Prepend nonce
@@ -61,8 +172,8 @@ pub fn encrypt(key: &[u8; KEY_LEN], plaintext: &[u8]) -> Result<Vec<u8>> {
     Ok(result)
 }
 
-/// Decrypts [nonce || ciphertext || tag] with AES-256-GCM.
-pub fn decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
+/// AES-256-GCM open of [nonce || ciphertext || tag].
+fn open_aes_256_gcm(key: &[u8; KEY_LEN], data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
     if data.len() < NONCE_LEN + AES_256_GCM.tag_len() {
         anyhow::bail!("Encrypted data too short");
     }
@@ -76,12 +187,41 @@ pub fn decrypt(key: &[u8; KEY_LEN], data: &[u8]) -> Result<Vec<u8>> {
 
     let mut in_out = ciphertext.to_vec();
     let plaintext = opening_key
-        .open_in_place(nonce, Aad::empty(), &mut in_out)
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
         .map_err(|_| anyhow::anyhow!("Decryption failed — wrong sync PIN?"))?;
 
     Ok(plaintext.to_vec())
 }
 
+/// XChaCha20-Poly1305 seal. Returns [nonce || ciphertext || tag].
+fn seal_xchacha20_poly1305(key: &[u8; KEY_LEN], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let mut nonce_bytes = [0u8; XNONCE_LEN];
+    SystemRandom::new()
+        .fill(&mut nonce_bytes)
+        .map_err(|_| anyhow::anyhow!("Failed to generate nonce"))?;
+
+    let sealed = XChaCha20Poly1305::new(key.into())
+        .encrypt(XNonce::from_slice(&nonce_bytes), Payload { msg: plaintext, aad })
+        .map_err(|_| anyhow::anyhow!("Encryption failed"))?;
+
+    let mut result = Vec::with_capacity(XNONCE_LEN + sealed.len());
+    result.extend_from_slice(&nonce_bytes);
+    result.extend_from_slice(&sealed);
+    Ok(result)
+}
+
+/// XChaCha20-Poly1305 open of [nonce || ciphertext || tag].
+fn open_xchacha20_poly1305(key: &[u8; KEY_LEN], data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if data.len() < XNONCE_LEN + TAG_LEN {
+        anyhow::bail!("Encrypted data too short");
+    }
+
+    let (nonce_bytes, ciphertext) = data.split_at(XNONCE_LEN);
+    XChaCha20Poly1305::new(key.into())
+        .decrypt(XNonce::from_slice(nonce_bytes), Payload { msg: ciphertext, aad })
+        .map_err(|_| anyhow::anyhow!("Decryption failed — wrong sync PIN?"))
+}
+
 /// Encrypts a string, returns base64-encoded ciphertext.
 pub fn encrypt_string(key: &[u8; KEY_LEN], plaintext: &str) -> Result<String> {
     use base64::Engine;
@@ -89,14 +229,30 @@ pub fn encrypt_string(key: &[u8; KEY_LEN], plaintext: &str) -> Result<String> {
     Ok(base64::engine::general_purpose::STANDARD.encode(&encrypted))
 }
 
+/// Like [`encrypt_string`] with the given cipher, bound to `record_id` (see [`encrypt_bound`]).
+pub fn encrypt_string_bound(cipher: Cipher, key: &[u8; KEY_LEN], plaintext: &str, record_id: &str) -> Result<String> {
+    use base64::Engine;
+    let encrypted = encrypt_bound(cipher, key, plaintext.as_bytes(), record_id)?;
+    Ok(base64::engine::general_purpose::STANDARD.encode(&encrypted))
+}
+
 /// Decrypts a base64-encoded ciphertext, returns plaintext string.
 pub fn decrypt_string(key: &[u8; KEY_LEN], encoded: &str) -> Result<String> {
+    let plaintext = decrypt(key, &decode_base64(encoded)?)?;
+    String::from_utf8(plaintext).context("Decrypted data is not valid UTF-8")
+}
+
+/// Like [`decrypt_string`], checking the ciphertext was bound to `record_id`.
+pub fn decrypt_string_bound(key: &[u8; KEY_LEN], encoded: &str, record_id: &str) -> Result<String> {
+    let plaintext = decrypt_bound(key, &decode_base64(encoded)?, record_id)?;
+    String::from_utf8(plaintext).context("Decrypted data is not valid UTF-8")
+}
+
+fn decode_base64(encoded: &str) -> Result<Vec<u8>> {
     use base64::Engine;
-    let data = base64::engine::general_purpose::STANDARD
+    base64::engine::general_purpose::STANDARD
         .decode(encoded)
-        .context("Invalid base64 in encrypted memory")?;
-    let plaintext = decrypt(key, &data)?;
-    String::from_utf8(plaintext).context("Decrypted data is not valid UTF-8")
+        .context("Invalid base64 in encrypted memory")
 }
 
 /// Computes a SHA-256 content hash for sync verification.
@@ -118,15 +274,38 @@ fn hex_decode(hex: &str) -> Result<Vec<u8>> {
         .collect()
 }
 
+/// [`create_pin_verifier_with_iterations`] for a key at the default iteration count.
+#[cfg(test)]
+pub fn create_pin_verifier(key: &[u8; KEY_LEN]) -> Result<String> {
+    create_pin_verifier_with_iterations(key, DEFAULT_PBKDF2_ITERATIONS)
+}
+
 /// Verify a sync PIN produces the same key by checking against a stored verifier.
 /// The verifier is: encrypt("ctxovrflw-pin-verify", key) — stored in config.
-pub fn create_pin_verifier(key: &[u8; KEY_LEN]) -> Result<String> {
-    encrypt_string(key, "ctxovrflw-pin-verify")
+/// Keys derived with a non-default `iterations` count have it recorded as an
+/// `<iterations>$` prefix so other devices derive the same key; default-count
+/// verifiers stay unprefixed, readable by older clients.
+pub fn create_pin_verifier_with_iterations(key: &[u8; KEY_LEN], iterations: u32) -> Result<String> {
+    let verifier = encrypt_string(key, "ctxovrflw-pin-verify")?;
+    if iterations == DEFAULT_PBKDF2_ITERATIONS {
+        Ok(verifier)
+    } else {
+        Ok(format!("{iterations}${verifier}"))
+    }
+}
+
+/// PBKDF2 iterations the key behind `verifier` was derived with.
+pub fn verifier_iterations(verifier: &str) -> u32 {
+    verifier
+        .split_once('$')
+        .and_then(|(iterations, _)| iterations.parse().ok())
+        .unwrap_or(DEFAULT_PBKDF2_ITERATIONS)
 }
 
 /// Check if a PIN produces the correct key by decrypting the verifier.
 pub fn verify_pin(key: &[u8; KEY_LEN], verifier: &str) -> bool {
-    match decrypt_string(key, verifier) {
+    let encrypted = verifier.split_once('$').map_or(verifier, |(_, encrypted)| encrypted);
+    match decrypt_string(key, encrypted) {
         Ok(plaintext) => plaintext == "ctxovrflw-pin-verify",
         Err(_) => false,
     }
@@ -171,6 +350,54 @@ mod tests {
         assert_eq!(h1, h2);
     }
 
+    #[test]
+    fn test_versioned_format() {
+        let key = derive_key("test1234", TEST_SALT);
+        let encrypted = encrypt(&key, b"versioned").unwrap();
+        assert_eq!(encrypted[0], VERSION_AES_256_GCM);
+        assert_eq!(encrypted.len(), 1 + NONCE_LEN + 9 + AES_256_GCM.tag_len());
+        assert_eq!(decrypt(&key, &encrypted).unwrap(), b"versioned");
+    }
+
+    #[test]
+    fn test_xchacha20_poly1305_roundtrip() {
+        let key = derive_key("test1234", TEST_SALT);
+        let raw = encrypt_with(Cipher::XChaCha20Poly1305, &key, b"abc").unwrap();
+        assert_eq!(decrypt(&key, &raw).unwrap(), b"abc");
+        assert_eq!(raw[0], VERSION_XCHACHA20_POLY1305);
+        assert_eq!(raw.len(), 1 + XNONCE_LEN + 3 + TAG_LEN);
+
+        let wrong = derive_key("wrong-pin", TEST_SALT);
+        assert!(decrypt(&wrong, &raw).is_err());
+    }
+
+    #[test]
+    fn test_bound_ciphertext_cannot_be_transplanted() {
+        let key = derive_key("test1234", TEST_SALT);
+        for cipher in [Cipher::Aes256Gcm, Cipher::XChaCha20Poly1305] {
+            let sealed = encrypt_string_bound(cipher, &key, "memory A", "memory/a").unwrap();
+            assert_eq!(decrypt_string_bound(&key, &sealed, "memory/a").unwrap(), "memory A");
+            assert!(decrypt_string_bound(&key, &sealed, "memory/b").is_err());
+            assert!(decrypt_string(&key, &sealed).is_err());
+        }
+        // Ciphertext written before binding still decrypts
+        let unbound = encrypt_string(&key, "older memory").unwrap();
+        assert_eq!(decrypt_string_bound(&key, &unbound, "memory/a").unwrap(), "older memory");
+    }
+
+    #[test]
+    fn test_legacy_unversioned_decrypts() {
+        let key = derive_key("test1234", TEST_SALT);
+        let legacy = seal_aes_256_gcm(&key, b"written before versioning", &[]).unwrap();
+        assert_eq!(decrypt(&key, &legacy).unwrap(), b"written before versioning");
+        // Including a legacy nonce that happens to start with the version byte
+        let mut legacy = seal_aes_256_gcm(&key, b"x", &[]).unwrap();
+        while legacy[0] != VERSION_AES_256_GCM {
+            legacy = seal_aes_256_gcm(&key, b"x", &[]).unwrap();
+        }
+        assert_eq!(decrypt(&key, &legacy).unwrap(), b"x");
+    }
+
     #[test]
     fn test_pin_verifier() {
         let key = derive_key("mypin", TEST_SALT);
@@ -180,4 +407,33 @@ mod tests {
         let wrong_key = derive_key("wrongpin", TEST_SALT);
         assert!(!verify_pin(&wrong_key, &verifier));
     }
+
+    #[test]
+    fn test_verifier_records_iterations() {
+        let legacy = create_pin_verifier(&derive_key("mypin", TEST_SALT)).unwrap();
+        assert_eq!(verifier_iterations(&legacy), DEFAULT_PBKDF2_ITERATIONS);
+        assert!(!legacy.contains('$'));
+
+        let key = derive_key_with_iterations("mypin", TEST_SALT, 1_000);
+        let verifier = create_pin_verifier_with_iterations(&key, 1_000).unwrap();
+        assert_eq!(verifier_iterations(&verifier), 1_000);
+        assert!(verify_pin(&key, &verifier));
+
+        // Same PIN and salt, different iteration count: a different key
+        let other = derive_key_with_iterations("mypin", TEST_SALT, 2_000);
+        assert_ne!(key, other);
+        assert!(!verify_pin(&other, &verifier));
+        assert_eq!(derive_key_with_iterations("mypin", TEST_SALT, DEFAULT_PBKDF2_ITERATIONS), derive_key("mypin", TEST_SALT));
+    }
+
+    #[test]
+    fn test_rederive_from_verifier() {
+        // Another device only has the PIN, the salt and the stored verifier
+        let verifier =
+            create_pin_verifier_with_iterations(&derive_key_with_iterations("mypin", TEST_SALT, 1_500), 1_500).unwrap();
+        let iterations = verifier_iterations(&verifier);
+        assert!(verify_pin(&derive_key_with_iterations("mypin", TEST_SALT, iterations), &verifier));
+        assert!(!verify_pin(&derive_key_with_iterations("wrongpin", TEST_SALT, iterations), &verifier));
+        assert!(!verify_pin(&derive_key("mypin", TEST_SALT), &verifier));
+    }
 }
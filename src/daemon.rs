@@ -3,6 +3,65 @@ use std::path::PathBuf;
 
 use crate::config::Config;
 
+// ── Logging ──────────────────────────────────────────────────
+
+/// Daily-rotated daemon.log in the data dir, keeping `retention_days` files.
+pub fn log_file_appender(retention_days: usize) -> Result<tracing_appender::rolling::RollingFileAppender> {
+    let appender = tracing_appender::rolling::RollingFileAppender::builder()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(crate::cli::logs::DAEMON_LOG_PREFIX)
+        .max_log_files(retention_days.max(1))
+        .build(Config::data_dir()?)?;
+    Ok(appender)
+}
+
+/// Record panics (message, location, backtrace) to crash.log and the daemon log.
+/// Panics inside spawned tasks are otherwise swallowed by their JoinHandle.
+pub fn install_panic_hook() {
+    let crash_path = Config::crash_log_path().ok();
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = info
+            .payload()
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| info.payload().downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+        let location = info
+            .location()
+            .map(|l| format!("{}:{}", l.file(), l.line()))
+            .unwrap_or_else(|| "unknown location".to_string());
+        let thread = std::thread::current().name().unwrap_or("unnamed").to_string();
+        let backtrace = std::backtrace::Backtrace::force_capture();
+
+        tracing::error!("Panic in thread '{thread}' at {location}: {message}");
+
+        if let Some(ref path) = crash_path {
+            use std::io::Write;
+            if let Ok(mut f) = std::fs::OpenOptions::new().create(true).append(true).open(path) {
+                let _ = writeln!(
+                    f,
+                    "[{}] panic in thread '{thread}' at {location}: {message}\n{backtrace}\n",
+                    chrono::Utc::now().to_rfc3339()
+                );
+            }
+        }
+
+        default_hook(info);
+    }));
+}
+
+/// Timestamp and message of the most recent crash recorded in crash.log.
+pub fn last_crash() -> Option<(String, String)> {
+    let content = std::fs::read_to_string(Config::crash_log_path().ok()?).ok()?;
+    let line = content
+        .lines()
+        .rev()
+        .find(|l| l.starts_with('[') && l.contains("] panic"))?;
+    let (ts, rest) = line[1..].split_once("] ")?;
+    Some((ts.to_string(), rest.to_string()))
+}
+
 // ── Daemon lifecycle ─────────────────────────────────────────
 
 pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
@@ -16,31 +75,30 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
     }
 
     if !foreground {
-        // If systemd service is installed, use that
+        // If a managed service is installed, use that
         if is_service_installed() {
-            println!("Starting ctxovrflw via systemd...");
-            let status = std::process::Command::new("systemctl")
-                .args(["--user", "start", "ctxovrflw"])
-                .status()?;
-            if status.success() {
+            println!("Starting ctxovrflw via {}...", service_manager());
+            if launch_service()? {
                 println!("✓ ctxovrflw daemon started");
                 println!("  MCP SSE:  http://127.0.0.1:{port}/mcp/sse");
+                println!("  MCP HTTP: http://127.0.0.1:{port}/mcp");
                 println!("  REST API: http://127.0.0.1:{port}/v1/");
-                println!("  Logs:     journalctl --user -u ctxovrflw -f");
+                println!("  Logs:     {}", service_logs_hint());
             } else {
-                println!("⚠ Failed to start via systemd. Try: ctxovrflw start --foreground");
+                println!("⚠ Failed to start via {}. Try: ctxovrflw start --foreground", service_manager());
             }
             return Ok(());
         }
 
-        // No systemd — hint to install or run foreground
-        println!("No systemd service installed. Options:");
+        // No service — hint to install or run foreground
+        println!("No background service installed. Options:");
         println!("  1. Install service: ctxovrflw service install");
         println!("  2. Run in foreground: ctxovrflw start --foreground");
         return Ok(());
     }
 
     // Foreground mode
+    install_panic_hook();
     tracing::info!("Starting ctxovrflw daemon on port {port}");
 
     // Ensure auth token exists
@@ -50,44 +108,57 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
     let pid_path = Config::pid_path()?;
     std::fs::write(&pid_path, std::process::id().to_string())?;
 
-    let _conn = crate::db::open()?;
+    let conn = crate::db::open()?;
+    // Search and backfill use the vectors of the model this daemon loads
+    crate::db::activate_configured_model(&conn, &cfg)?;
+    drop(conn);
     tracing::info!("Database initialized");
 
-    let http_handle = tokio::spawn(crate::http::serve(cfg.clone(), port));
+    // Live config shared with the HTTP server and background tasks; `POST
+    // /v1/reload` publishes a re-read copy
+    let live = tokio::sync::watch::Sender::new(cfg.clone());
 
-    // Auto-sync background task
-    let sync_handle = if cfg.auto_sync && cfg.is_logged_in() {
-        let sync_cfg = cfg.clone();
-        let interval_secs = cfg.sync_interval_secs;
-        tracing::info!("Auto-sync enabled (every {interval_secs}s)");
-        Some(tokio::spawn(async move {
-            let mut interval = tokio::time::interval(
-                tokio::time::Duration::from_secs(interval_secs),
-            );
-            // Skip the first immediate tick
-            interval.tick().await;
-            loop {
-                interval.tick().await;
-                match crate::sync::run_silent(&sync_cfg).await {
-                    Ok((pushed, pulled, pull_purged)) => {
-                        if pushed > 0 || pulled > 0 || pull_purged > 0 {
-                            tracing::info!("Auto-sync: pushed {pushed}, pulled {pulled}, purged {pull_purged}");
-                        }
+    let mut http_handle = tokio::spawn(crate::http::serve(live.clone(), port));
+
+    // Auto-sync background task. Runs regardless of the startup settings so a
+    // reload can enable sync, log in, or change the interval.
+    if cfg.auto_sync && cfg.is_logged_in() {
+        tracing::info!("Auto-sync enabled (every {}s)", cfg.sync_interval_secs);
+    } else if cfg.is_logged_in() {
+        tracing::info!("Auto-sync disabled");
+    } else {
+        tracing::info!("Not logged in — auto-sync inactive. Run `ctxovrflw login` to enable.");
+    }
+    let mut sync_cfg = live.subscribe();
+    let sync_handle = tokio::spawn(async move {
+        loop {
+            let interval_secs = sync_cfg.borrow_and_update().sync_interval_secs.max(1);
+            tokio::select! {
+                _ = tokio::time::sleep(tokio::time::Duration::from_secs(interval_secs)) => {}
+                // Config reloaded: start the wait over with the new interval
+                changed = sync_cfg.changed() => {
+                    if changed.is_err() {
+                        break;
                     }
-                    Err(e) => {
-                        tracing::warn!("Auto-sync failed: {e}");
+                    continue;
+                }
+            }
+            let cfg = sync_cfg.borrow().clone();
+            if !(cfg.auto_sync && cfg.is_logged_in()) {
+                continue;
+            }
+            match crate::sync::run_silent(&cfg).await {
+                Ok((pushed, pulled, pull_purged)) => {
+                    if pushed > 0 || pulled > 0 || pull_purged > 0 {
+                        tracing::info!("Auto-sync: pushed {pushed}, pulled {pulled}, purged {pull_purged}");
                     }
                 }
+                Err(e) => {
+                    tracing::warn!("Auto-sync failed: {e}");
+                }
             }
-        }))
-    } else {
-        if cfg.is_logged_in() {
-            tracing::info!("Auto-sync disabled");
-        } else {
-            tracing::info!("Not logged in — auto-sync inactive. Run `ctxovrflw login` to enable.");
         }
-        None
-    };
+    });
 
     // Expiry cleanup background task — runs every 5 minutes
     let cleanup_handle = tokio::spawn(async {
@@ -106,8 +177,26 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
         }
     });
 
+    // Embed memories that have no vector for the active model (e.g. after
+    // `model switch`), a batch at a time, starting right away
+    let backfill_handle = tokio::spawn(async {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(300));
+        loop {
+            interval.tick().await;
+            match tokio::task::spawn_blocking(|| crate::maintenance::embed_missing_vectors(500)).await {
+                Ok(Ok(count)) if count > 0 => {
+                    tracing::info!("Embedded {count} memories that had no vector for the active model");
+                }
+                Ok(Err(e)) => tracing::debug!("Vector backfill skipped: {e}"),
+                _ => {}
+            }
+        }
+    });
+
     // Adaptive scoring maintenance task — importance scores + recall log cleanup
-    let maintenance_handle = tokio::spawn(async {
+    #[cfg(feature = "pro")]
+    let maintenance_cfg = live.subscribe();
+    let maintenance_handle = tokio::spawn(async move {
         // Update importance scores every hour
         let mut score_interval = tokio::time::interval(tokio::time::Duration::from_secs(3600));
         score_interval.tick().await; // skip first immediate tick
@@ -137,20 +226,35 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
                     Err(e) => tracing::warn!("Maintenance: recall log cleanup failed: {e}"),
                     _ => {}
                 }
+
+                #[cfg(feature = "pro")]
+                match crate::maintenance::decay_relations(&maintenance_cfg.borrow().clone()) {
+                    Ok((decayed, removed)) if decayed + removed > 0 => {
+                        tracing::info!("Maintenance: decayed {decayed} stale relations, removed {removed}");
+                    }
+                    Err(e) => tracing::warn!("Maintenance: relation decay failed: {e}"),
+                    _ => {}
+                }
             }
         }
     });
 
     // Background consolidation task (Pro feature)
+    if cfg.auto_consolidation && let Some(msg) = cfg.capability_expired_message("Auto-consolidation") {
+        tracing::warn!("{msg}");
+    }
     let consolidation_handle = if cfg.feature_enabled("consolidation") && cfg.auto_consolidation {
         let interval_secs = cfg.consolidation_interval_secs.max(300);
         tracing::info!("Auto-consolidation enabled (every {interval_secs}s)");
+        #[cfg(feature = "pro")]
+        let consolidation_cfg = live.subscribe();
+        let forget = cfg.forget_penalty();
         Some(tokio::spawn(async move {
             let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_secs));
             interval.tick().await; // skip first immediate tick
             loop {
                 interval.tick().await;
-                match crate::maintenance::run_consolidation_pass() {
+                match crate::maintenance::run_consolidation_pass(forget) {
                     Ok(report) => {
                         if report.duplicates_removed > 0 {
                             tracing::info!(
@@ -169,6 +273,16 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
                     }
                     Err(e) => tracing::warn!("Auto-consolidation failed: {e}"),
                 }
+
+                #[cfg(feature = "pro")]
+                match crate::maintenance::detect_consolidation_candidates(&consolidation_cfg.borrow().clone()) {
+                    Ok(groups) if groups > 0 => {
+                        tracing::info!("Auto-consolidation: {groups} group(s) of near-duplicate memories to review");
+                        crate::webhooks::fire("consolidation.candidates", serde_json::json!({ "groups": groups }));
+                    }
+                    Err(e) => tracing::warn!("Consolidation candidate detection failed: {e}"),
+                    _ => {}
+                }
             }
         }))
     } else {
@@ -178,6 +292,7 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
 
     println!("ctxovrflw daemon running on port {port}");
     println!("  MCP SSE:  http://127.0.0.1:{port}/mcp/sse");
+    println!("  MCP HTTP: http://127.0.0.1:{port}/mcp");
     println!("  REST API: http://127.0.0.1:{port}/v1/");
     if cfg.auto_sync && cfg.is_logged_in() {
         println!("  Sync:     every {}s", cfg.sync_interval_secs);
@@ -188,38 +303,44 @@ pub async fn start(cfg: &Config, port: u16, foreground: bool) -> Result<()> {
     }
     println!("  Press Ctrl+C to stop.");
 
-    tokio::signal::ctrl_c().await?;
+    // Stop on Ctrl+C, or if the HTTP server exits/panics (e.g. port already in use)
+    let server_error = tokio::select! {
+        res = tokio::signal::ctrl_c() => {
+            res?;
+            None
+        }
+        res = &mut http_handle => Some(match res {
+            Ok(Ok(())) => anyhow::anyhow!("HTTP server stopped unexpectedly"),
+            Ok(Err(e)) => e,
+            Err(e) => anyhow::anyhow!("HTTP server task failed: {e}"),
+        }),
+    };
+    if let Some(ref e) = server_error {
+        tracing::error!("Daemon stopping: {e}");
+    }
     tracing::info!("Shutting down...");
 
     let _ = std::fs::remove_file(&pid_path);
     http_handle.abort();
     cleanup_handle.abort();
+    backfill_handle.abort();
     maintenance_handle.abort();
-    if let Some(h) = sync_handle {
-        h.abort();
-    }
+    sync_handle.abort();
     if let Some(h) = consolidation_handle {
         h.abort();
     }
 
-    Ok(())
+    match server_error {
+        Some(e) => Err(e),
+        None => Ok(()),
+    }
 }
 
 pub async fn stop(_cfg: &Config) -> Result<()> {
-    // Try systemd first
-    if is_service_installed() {
-        let status = std::process::Command::new("systemctl")
-            .args(["--user", "is-active", "ctxovrflw"])
-            .output()?;
-        if String::from_utf8_lossy(&status.stdout).trim() == "active" {
-            let stop = std::process::Command::new("systemctl")
-                .args(["--user", "stop", "ctxovrflw"])
-                .status()?;
-            if stop.success() {
-                println!("✓ ctxovrflw daemon stopped");
-                return Ok(());
-            }
-        }
+    // Try the managed service first
+    if is_service_installed() && service_stop()? {
+        println!("✓ ctxovrflw daemon stopped");
+        return Ok(());
     }
 
     // Fall back to PID file
@@ -235,6 +356,12 @@ pub async fn stop(_cfg: &Config) -> Result<()> {
     {
         std::process::Command::new("kill").arg(pid.to_string()).output()?;
     }
+    #[cfg(windows)]
+    {
+        std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .output()?;
+    }
 
     let _ = std::fs::remove_file(&pid_path);
     println!("✓ Stopped ctxovrflw (pid {pid}).");
@@ -242,6 +369,33 @@ pub async fn stop(_cfg: &Config) -> Result<()> {
 }
 
 // ── Service management ───────────────────────────────────────
+//
+// Linux uses a systemd user unit; macOS a launchd LaunchAgent; Windows registers
+// a per-user Task Scheduler task that runs at logon (no admin rights or service
+// wrapper needed).
+
+const WINDOWS_TASK_NAME: &str = "ctxovrflw";
+const LAUNCHD_LABEL: &str = "dev.ctxovrflw";
+
+/// Name of the service manager backing `ctxovrflw service` on this platform.
+pub fn service_manager() -> &'static str {
+    if cfg!(windows) {
+        "Task Scheduler"
+    } else if cfg!(target_os = "macos") {
+        "launchd"
+    } else {
+        "systemd"
+    }
+}
+
+/// How to follow the managed daemon's logs on this platform.
+pub fn service_logs_hint() -> &'static str {
+    if cfg!(windows) || cfg!(target_os = "macos") {
+        "ctxovrflw logs daemon -f"
+    } else {
+        "journalctl --user -u ctxovrflw -f"
+    }
+}
 
 fn service_unit_path() -> PathBuf {
     let config_dir = dirs::config_dir().unwrap_or_else(|| {
@@ -250,11 +404,53 @@ fn service_unit_path() -> PathBuf {
     config_dir.join("systemd/user/ctxovrflw.service")
 }
 
+fn launchd_plist_path() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_default()
+        .join("Library/LaunchAgents")
+        .join(format!("{LAUNCHD_LABEL}.plist"))
+}
+
+fn launchctl(args: &[&str]) -> Option<std::process::Output> {
+    std::process::Command::new("launchctl")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()
+}
+
+fn schtasks(args: &[&str]) -> Option<std::process::Output> {
+    std::process::Command::new("schtasks")
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()
+}
+
 pub fn is_service_installed() -> bool {
-    service_unit_path().exists()
+    if cfg!(windows) {
+        schtasks(&["/Query", "/TN", WINDOWS_TASK_NAME])
+            .is_some_and(|o| o.status.success())
+    } else if cfg!(target_os = "macos") {
+        launchd_plist_path().exists()
+    } else {
+        service_unit_path().exists()
+    }
 }
 
 pub fn is_service_running() -> bool {
+    if cfg!(windows) {
+        // CSV row: "\ctxovrflw","<next run>","Running"
+        return schtasks(&["/Query", "/TN", WINDOWS_TASK_NAME, "/FO", "CSV", "/NH"])
+            .filter(|o| o.status.success())
+            .is_some_and(|o| String::from_utf8_lossy(&o.stdout).contains("\"Running\""));
+    }
+    if cfg!(target_os = "macos") {
+        // `launchctl list <label>` prints a dict with a "PID" key only while running
+        return launchctl(&["list", LAUNCHD_LABEL])
+            .filter(|o| o.status.success())
+            .is_some_and(|o| String::from_utf8_lossy(&o.stdout).contains("\"PID\""));
+    }
     std::process::Command::new("systemctl")
         .args(["--user", "is-active", "ctxovrflw"])
         .output()
@@ -262,10 +458,26 @@ pub fn is_service_running() -> bool {
         .unwrap_or(false)
 }
 
+/// Data directory a service must be started with when this run uses a
+/// non-default one (`--data-dir` or `CTXOVRFLW_DATA_DIR`).
+fn service_data_dir() -> Option<String> {
+    Config::data_dir_override().map(|dir| dir.to_string_lossy().to_string())
+}
+
 pub fn service_install() -> Result<()> {
+    if cfg!(windows) {
+        return task_install();
+    }
+    if cfg!(target_os = "macos") {
+        return launchd_install();
+    }
+
     let binary = std::env::current_exe()?
         .to_string_lossy()
         .to_string();
+    let data_dir = service_data_dir()
+        .map(|dir| format!(" --data-dir \"{dir}\""))
+        .unwrap_or_default();
 
     let unit = format!(
 r#"[Unit]
@@ -274,7 +486,7 @@ After=network.target
 
 [Service]
 Type=simple
-ExecStart={binary} start --foreground
+ExecStart={binary}{data_dir} start --foreground
 Restart=on-failure
 RestartSec=5
 Environment=RUST_LOG=ctxovrflw=info
@@ -308,13 +520,34 @@ WantedBy=default.target
 
     println!("✓ Service enabled (starts on login)");
     println!("  Start now:  ctxovrflw start");
-    println!("  View logs:  journalctl --user -u ctxovrflw -f");
+    println!("  View logs:  ctxovrflw logs daemon -f");
     println!("  Uninstall:  ctxovrflw service uninstall");
 
     Ok(())
 }
 
 pub fn service_uninstall() -> Result<()> {
+    if cfg!(windows) {
+        let _ = schtasks(&["/End", "/TN", WINDOWS_TASK_NAME]);
+        if let Some(o) = schtasks(&["/Delete", "/TN", WINDOWS_TASK_NAME, "/F"])
+            && !o.status.success()
+            && is_service_installed()
+        {
+            anyhow::bail!("schtasks /Delete failed: {}", String::from_utf8_lossy(&o.stderr).trim());
+        }
+        println!("✓ ctxovrflw service removed");
+        return Ok(());
+    }
+    if cfg!(target_os = "macos") {
+        let path = launchd_plist_path();
+        if path.exists() {
+            let _ = launchctl(&["unload", "-w", &path.to_string_lossy()]);
+            std::fs::remove_file(&path)?;
+        }
+        println!("✓ ctxovrflw service removed");
+        return Ok(());
+    }
+
     // Stop and disable
     let _ = std::process::Command::new("systemctl")
         .args(["--user", "stop", "ctxovrflw"])
@@ -341,14 +574,163 @@ pub fn service_start() -> Result<()> {
         anyhow::bail!("Service not installed. Run: ctxovrflw service install");
     }
 
-    let status = std::process::Command::new("systemctl")
+    if launch_service()? {
+        println!("✓ ctxovrflw daemon started");
+    } else {
+        println!("⚠ Failed to start. Check: {}", service_logs_hint());
+    }
+    Ok(())
+}
+
+/// Ask the service manager to start the daemon. Returns whether it accepted.
+fn launch_service() -> Result<bool> {
+    if cfg!(windows) {
+        return Ok(schtasks(&["/Run", "/TN", WINDOWS_TASK_NAME]).is_some_and(|o| o.status.success()));
+    }
+    if cfg!(target_os = "macos") {
+        // Load first in case the agent was unloaded; a no-op error if already loaded
+        let _ = launchctl(&["load", "-w", &launchd_plist_path().to_string_lossy()]);
+        return Ok(launchctl(&["start", LAUNCHD_LABEL]).is_some_and(|o| o.status.success()));
+    }
+    Ok(std::process::Command::new("systemctl")
         .args(["--user", "start", "ctxovrflw"])
-        .status()?;
+        .status()?
+        .success())
+}
 
-    if status.success() {
-        println!("✓ ctxovrflw daemon started");
+/// Stop the managed daemon. Returns false if the service wasn't running.
+pub fn service_stop() -> Result<bool> {
+    if !is_service_running() {
+        return Ok(false);
+    }
+    let ok = if cfg!(windows) {
+        schtasks(&["/End", "/TN", WINDOWS_TASK_NAME]).is_some_and(|o| o.status.success())
+    } else if cfg!(target_os = "macos") {
+        launchctl(&["stop", LAUNCHD_LABEL]).is_some_and(|o| o.status.success())
+    } else {
+        std::process::Command::new("systemctl")
+            .args(["--user", "stop", "ctxovrflw"])
+            .status()?
+            .success()
+    };
+    Ok(ok)
+}
+
+/// Restart the managed daemon (used after `ctxovrflw update`).
+pub fn service_restart() -> Result<()> {
+    if cfg!(windows) {
+        let _ = schtasks(&["/End", "/TN", WINDOWS_TASK_NAME]);
+        launch_service()?;
+    } else if cfg!(target_os = "macos") {
+        let _ = launchctl(&["stop", LAUNCHD_LABEL]);
+        launch_service()?;
     } else {
-        println!("⚠ Failed to start. Check: journalctl --user -u ctxovrflw -f");
+        std::process::Command::new("systemctl")
+            .args(["--user", "restart", "ctxovrflw"])
+            .status()?;
     }
     Ok(())
 }
+
+/// Register a Task Scheduler task that starts the daemon at logon.
+fn task_install() -> Result<()> {
+    let binary = std::env::current_exe()?
+        .to_string_lossy()
+        .to_string();
+    let data_dir = service_data_dir()
+        .map(|dir| format!(" --data-dir \"{dir}\""))
+        .unwrap_or_default();
+    let command = format!("\"{binary}\"{data_dir} start --foreground");
+
+    let output = schtasks(&[
+        "/Create", "/TN", WINDOWS_TASK_NAME, "/TR", &command, "/SC", "ONLOGON", "/RL", "LIMITED", "/F",
+    ])
+    .ok_or_else(|| anyhow::anyhow!("schtasks not found — is this Windows?"))?;
+    if !output.status.success() {
+        anyhow::bail!("schtasks /Create failed: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    println!("✓ Scheduled task '{WINDOWS_TASK_NAME}' registered");
+    println!("✓ Service enabled (starts on login)");
+    println!("  Start now:  ctxovrflw start");
+    println!("  View logs:  ctxovrflw logs daemon -f");
+    println!("  Uninstall:  ctxovrflw service uninstall");
+
+    Ok(())
+}
+
+/// Write a LaunchAgent plist that runs the daemon at login and load it.
+fn launchd_install() -> Result<()> {
+    let binary = std::env::current_exe()?
+        .to_string_lossy()
+        .to_string();
+    let log = Config::data_dir()?.join("launchd.log");
+
+    let plist = format!(
+r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>Label</key>
+    <string>{LAUNCHD_LABEL}</string>
+    <key>ProgramArguments</key>
+    <array>
+        <string>{binary}</string>{data_dir}
+        <string>start</string>
+        <string>--foreground</string>
+    </array>
+    <key>EnvironmentVariables</key>
+    <dict>
+        <key>RUST_LOG</key>
+        <string>ctxovrflw=info</string>
+    </dict>
+    <key>RunAtLoad</key>
+    <true/>
+    <key>KeepAlive</key>
+    <dict>
+        <key>SuccessfulExit</key>
+        <false/>
+    </dict>
+    <key>ThrottleInterval</key>
+    <integer>5</integer>
+    <key>StandardOutPath</key>
+    <string>{log}</string>
+    <key>StandardErrorPath</key>
+    <string>{log}</string>
+</dict>
+</plist>
+"#,
+        binary = xml_escape(&binary),
+        data_dir = service_data_dir()
+            .map(|dir| format!("\n        <string>--data-dir</string>\n        <string>{}</string>", xml_escape(&dir)))
+            .unwrap_or_default(),
+        log = xml_escape(&log.to_string_lossy()),
+    );
+
+    let path = launchd_plist_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Reinstalling over a loaded agent: unload the old definition first
+    if path.exists() {
+        let _ = launchctl(&["unload", &path.to_string_lossy()]);
+    }
+    std::fs::write(&path, plist)?;
+    println!("✓ LaunchAgent written to {}", path.display());
+
+    let loaded = launchctl(&["load", "-w", &path.to_string_lossy()]).is_some_and(|o| o.status.success());
+    if !loaded {
+        anyhow::bail!("launchctl load failed for {}", path.display());
+    }
+
+    println!("✓ Service enabled (starts on login)");
+    println!("  Start now:  ctxovrflw start");
+    println!("  View logs:  ctxovrflw logs daemon -f");
+    println!("  Uninstall:  ctxovrflw service uninstall");
+
+    Ok(())
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,178 @@
+//! Audit log of MCP tool calls (`audit_log` in config): who called which tool,
+//! when, with what arguments and whether it succeeded. Memory content and
+//! recall queries are stored as hashes, and triggers keep the table append-only.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    pub id: i64,
+    pub at: String,
+    pub workspace: String,
+    pub agent_id: Option<String>,
+    pub tool: String,
+    /// Call arguments with memory content and query text replaced by hashes.
+    pub arguments: Value,
+    /// "ok" or "error"
+    pub outcome: String,
+}
+
+pub fn migrate(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS audit_log (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            at          TEXT NOT NULL DEFAULT (datetime('now')),
+            workspace   TEXT NOT NULL DEFAULT 'default',
+            agent_id    TEXT,
+            tool        TEXT NOT NULL,
+            arguments   TEXT NOT NULL DEFAULT '{}',
+            outcome     TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_audit_log_at ON audit_log(at);
+        CREATE TRIGGER IF NOT EXISTS audit_log_no_update BEFORE UPDATE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+        CREATE TRIGGER IF NOT EXISTS audit_log_no_delete BEFORE DELETE ON audit_log
+        BEGIN SELECT RAISE(ABORT, 'audit_log is append-only'); END;
+        ",
+    )?;
+    Ok(())
+}
+
+/// Record one tool call in the active workspace. `arguments` is stored with
+/// its free text hashed (see [`redact`]).
+pub fn record(conn: &Connection, tool: &str, arguments: &Value, ok: bool) -> Result<()> {
+    let agent_id = arguments["agent_id"].as_str();
+    conn.execute(
+        "INSERT INTO audit_log (workspace, agent_id, tool, arguments, outcome)
+         VALUES (current_workspace(), ?1, ?2, ?3, ?4)",
+        params![
+            agent_id,
+            tool,
+            redact(arguments).to_string(),
+            if ok { "ok" } else { "error" }
+        ],
+    )?;
+    Ok(())
+}
+
+/// Arguments holding what a memory says or what an agent went looking for.
+const FREE_TEXT_KEYS: &[&str] = &["content", "query", "queries", "topic"];
+
+/// `arguments` with every string under a [`FREE_TEXT_KEYS`] key, at any
+/// depth, replaced by `sha256:<hex>`, so the log shows what changed (and
+/// whether the same search recurs) without holding the text.
+pub fn redact(arguments: &Value) -> Value {
+    match arguments {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| {
+                    let value = if FREE_TEXT_KEYS.contains(&key.as_str()) {
+                        hash_strings(value)
+                    } else {
+                        redact(value)
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact).collect()),
+        other => other.clone(),
+    }
+}
+
+fn hash_strings(value: &Value) -> Value {
+    match value {
+        Value::String(text) => Value::String(format!("sha256:{}", crate::crypto::content_hash(text))),
+        Value::Array(items) => Value::Array(items.iter().map(hash_strings).collect()),
+        other => redact(other),
+    }
+}
+
+/// Entries for the workspace (or all with `--all-workspaces`), newest first.
+pub fn list(
+    conn: &Connection,
+    agent_id: Option<&str>,
+    tool: Option<&str>,
+    since: Option<&str>,
+    limit: Option<usize>,
+) -> Result<Vec<AuditEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, at, workspace, agent_id, tool, arguments, outcome FROM audit_log
+         WHERE in_workspace(workspace)
+           AND (?1 IS NULL OR agent_id = ?1)
+           AND (?2 IS NULL OR tool = ?2)
+           AND (?3 IS NULL OR datetime(at) >= datetime(?3))
+         ORDER BY id DESC
+         LIMIT ?4",
+    )?;
+    let limit = limit.map_or(-1, |l| l as i64);
+    let entries = stmt
+        .query_map(params![agent_id, tool, since, limit], |row| {
+            Ok(AuditEntry {
+                id: row.get(0)?,
+                at: row.get(1)?,
+                workspace: row.get(2)?,
+                agent_id: row.get(3)?,
+                tool: row.get(4)?,
+                arguments: serde_json::from_str(&row.get::<_, String>(5)?).unwrap_or_default(),
+                outcome: row.get(6)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn hashed(text: &str) -> Value {
+        json!(format!("sha256:{}", crate::crypto::content_hash(text)))
+    }
+
+    #[test]
+    fn test_redact_hashes_content_and_queries() {
+        let redacted = redact(&json!({
+            "content": "the staging password is hunter2",
+            "query": "staging password",
+            "queries": ["deploy key", "ssh key"],
+            "topic": "credentials",
+            "tags": ["ops"],
+            "limit": 5,
+            "agent_id": "agent-a",
+        }));
+        assert_eq!(redacted["content"], hashed("the staging password is hunter2"));
+        assert_eq!(redacted["query"], hashed("staging password"));
+        assert_eq!(redacted["queries"], json!([hashed("deploy key"), hashed("ssh key")]));
+        assert_eq!(redacted["topic"], hashed("credentials"));
+        assert_eq!(redacted["tags"], json!(["ops"]));
+        assert_eq!(redacted["limit"], 5);
+        assert_eq!(redacted["agent_id"], "agent-a");
+    }
+
+    #[test]
+    fn test_redact_reaches_nested_items() {
+        let redacted = redact(&json!({ "memories": [{ "content": "first note", "subject": "atlas" }] }));
+        assert_eq!(redacted["memories"][0]["content"], hashed("first note"));
+        assert_eq!(redacted["memories"][0]["subject"], "atlas");
+    }
+
+    #[test]
+    fn test_recorded_query_is_not_stored_in_plaintext() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        record(&conn, "recall", &json!({ "query": "who has the vault token", "agent_id": "agent-a" }), true).unwrap();
+
+        let stored: String = conn.query_row("SELECT arguments FROM audit_log", [], |r| r.get(0)).unwrap();
+        assert!(!stored.contains("vault token"), "{stored}");
+        let entries = list(&conn, Some("agent-a"), Some("recall"), None, None).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].arguments["query"], hashed("who has the vault token"));
+        assert_eq!(entries[0].outcome, "ok");
+    }
+}
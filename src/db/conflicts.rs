@@ -0,0 +1,104 @@
+//! Sync conflict log: edits made on two devices since the last sync.
+//! The merge still picks a winner (last writer wins); the loser is kept here for review.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+#[derive(Debug, Clone)]
+pub struct SyncConflict {
+    pub id: i64,
+    pub memory_id: String,
+    pub local_content: String,
+    pub local_updated_at: String,
+    pub remote_content: String,
+    pub remote_updated_at: String,
+    /// "local" or "remote"
+    pub winner: String,
+    pub detected_at: String,
+}
+
+pub fn migrate(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS sync_conflicts (
+            id                INTEGER PRIMARY KEY AUTOINCREMENT,
+            memory_id         TEXT NOT NULL,
+            local_content     TEXT NOT NULL,
+            local_tags        TEXT NOT NULL DEFAULT '[]',
+            local_updated_at  TEXT NOT NULL,
+            remote_content    TEXT NOT NULL,
+            remote_tags       TEXT NOT NULL DEFAULT '[]',
+            remote_updated_at TEXT NOT NULL,
+            winner            TEXT NOT NULL,
+            detected_at       TEXT NOT NULL DEFAULT (datetime('now')),
+            resolved          INTEGER NOT NULL DEFAULT 0
+        );
+        CREATE INDEX IF NOT EXISTS idx_sync_conflicts_memory_id ON sync_conflicts(memory_id);
+        CREATE INDEX IF NOT EXISTS idx_sync_conflicts_resolved ON sync_conflicts(resolved);
+        ",
+    )?;
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn record(
+    conn: &Connection,
+    memory_id: &str,
+    local_content: &str,
+    local_tags: &str,
+    local_updated_at: &str,
+    remote_content: &str,
+    remote_tags: &str,
+    remote_updated_at: &str,
+    winner: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT INTO sync_conflicts (memory_id, local_content, local_tags, local_updated_at,
+                                     remote_content, remote_tags, remote_updated_at, winner)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![memory_id, local_content, local_tags, local_updated_at, remote_content, remote_tags, remote_updated_at, winner],
+    )?;
+    Ok(())
+}
+
+/// Unresolved conflicts, newest first.
+pub fn list_unresolved(conn: &Connection) -> Result<Vec<SyncConflict>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, memory_id, local_content, local_updated_at, remote_content, remote_updated_at, winner, detected_at
+         FROM sync_conflicts WHERE resolved = 0
+         ORDER BY detected_at DESC, id DESC",
+    )?;
+    let conflicts = stmt
+        .query_map([], |row| {
+            Ok(SyncConflict {
+                id: row.get(0)?,
+                memory_id: row.get(1)?,
+                local_content: row.get(2)?,
+                local_updated_at: row.get(3)?,
+                remote_content: row.get(4)?,
+                remote_updated_at: row.get(5)?,
+                winner: row.get(6)?,
+                detected_at: row.get(7)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(conflicts)
+}
+
+pub fn count_unresolved(conn: &Connection) -> Result<usize> {
+    let count: usize = conn.query_row(
+        "SELECT COUNT(*) FROM sync_conflicts WHERE resolved = 0",
+        [],
+        |r| r.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Mark a conflict as reviewed. Returns false if no unresolved conflict has that id.
+pub fn resolve(conn: &Connection, id: i64) -> Result<bool> {
+    let changed = conn.execute(
+        "UPDATE sync_conflicts SET resolved = 1 WHERE id = ?1 AND resolved = 0",
+        params![id],
+    )?;
+    Ok(changed > 0)
+}
@@ -0,0 +1,77 @@
+//! Consolidation candidates: groups of near-identical memories (same subject,
+//! embeddings above the similarity threshold) found by the scheduled
+//! maintenance pass, so `consolidate` can return them without recomputing.
+
+use anyhow::Result;
+use rusqlite::{params, Connection};
+
+#[derive(Debug, Clone)]
+pub struct CandidateGroup {
+    pub subject: String,
+    pub memory_ids: Vec<String>,
+    /// Lowest pairwise similarity that linked the group together.
+    pub similarity: f64,
+    pub detected_at: String,
+}
+
+pub fn migrate(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS consolidation_candidates (
+            id          INTEGER PRIMARY KEY AUTOINCREMENT,
+            workspace   TEXT NOT NULL DEFAULT 'default',
+            subject     TEXT NOT NULL,
+            memory_ids  TEXT NOT NULL,
+            similarity  REAL NOT NULL,
+            detected_at TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        CREATE INDEX IF NOT EXISTS idx_consolidation_candidates_subject ON consolidation_candidates(subject);
+        ",
+    )?;
+    Ok(())
+}
+
+/// Replace the active workspace's candidate groups with `groups`.
+pub fn replace_all(conn: &Connection, groups: &[CandidateGroup]) -> Result<()> {
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM consolidation_candidates WHERE workspace = current_workspace()", [])?;
+    for group in groups {
+        tx.execute(
+            "INSERT INTO consolidation_candidates (workspace, subject, memory_ids, similarity)
+             VALUES (current_workspace(), ?1, ?2, ?3)",
+            params![group.subject, serde_json::to_string(&group.memory_ids)?, group.similarity],
+        )?;
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// Candidate groups for the active workspace, most similar first. Members that
+/// have since been forgotten are dropped, along with groups left with fewer than two.
+pub fn list(conn: &Connection, subject: Option<&str>) -> Result<Vec<CandidateGroup>> {
+    let mut stmt = conn.prepare(
+        "SELECT subject, memory_ids, similarity, detected_at FROM consolidation_candidates
+         WHERE workspace = current_workspace() AND (?1 IS NULL OR subject = ?1)
+         ORDER BY similarity DESC, id",
+    )?;
+    let groups = stmt
+        .query_map(params![subject], |row| {
+            Ok(CandidateGroup {
+                subject: row.get(0)?,
+                memory_ids: serde_json::from_str(&row.get::<_, String>(1)?).unwrap_or_default(),
+                similarity: row.get(2)?,
+                detected_at: row.get(3)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let mut live = conn.prepare("SELECT 1 FROM memories WHERE id = ?1 AND deleted = 0")?;
+    let mut result = Vec::with_capacity(groups.len());
+    for mut group in groups {
+        group.memory_ids.retain(|id| live.exists(params![id]).unwrap_or(false));
+        if group.memory_ids.len() >= 2 {
+            result.push(group);
+        }
+    }
+    Ok(result)
+}
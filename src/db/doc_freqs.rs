@@ -0,0 +1,145 @@
+//! Token document frequencies for the CPU-only embedding fallback (builds
+//! without `onnx`), which weights tokens by TF-IDF. Token ids depend on the
+//! tokenizer, so counts are kept per embedding model.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+
+/// How many memories contain each token, out of `docs`.
+#[derive(Debug, Clone, Default)]
+pub struct DocFreqs {
+    pub docs: u32,
+    pub freqs: HashMap<u32, u32>,
+}
+
+impl DocFreqs {
+    /// Smoothed inverse document frequency: 1.0 for a token in every memory
+    /// (or with no corpus yet), higher the rarer it is.
+    pub fn idf(&self, token: u32) -> f32 {
+        let df = self.freqs.get(&token).copied().unwrap_or(0);
+        ((1.0 + self.docs as f32) / (1.0 + df as f32)).ln() + 1.0
+    }
+
+    /// Whether the number of memories has drifted from `docs` by more than a
+    /// fifth (and more than 10) since these were counted.
+    pub fn is_stale(&self, live: u32) -> bool {
+        self.docs.abs_diff(live) > (self.docs / 5).max(10)
+    }
+}
+
+pub fn migrate(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "
+        CREATE TABLE IF NOT EXISTS doc_freqs (
+            model   TEXT NOT NULL,
+            token   INTEGER NOT NULL,
+            df      INTEGER NOT NULL,
+            PRIMARY KEY (model, token)
+        );
+        CREATE TABLE IF NOT EXISTS doc_freqs_meta (
+            model       TEXT PRIMARY KEY,
+            docs        INTEGER NOT NULL,
+            built_at    TEXT NOT NULL DEFAULT (datetime('now'))
+        );
+        ",
+    )?;
+    Ok(())
+}
+
+/// The stored frequencies for `model`, if they were ever built.
+pub fn load(conn: &Connection, model: &str) -> Result<Option<DocFreqs>> {
+    let docs: Option<u32> = conn
+        .query_row("SELECT docs FROM doc_freqs_meta WHERE model = ?1", params![model], |r| r.get(0))
+        .optional()?;
+    let Some(docs) = docs else { return Ok(None) };
+    let mut stmt = conn.prepare("SELECT token, df FROM doc_freqs WHERE model = ?1")?;
+    let freqs = stmt
+        .query_map(params![model], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<HashMap<u32, u32>, _>>()?;
+    Ok(Some(DocFreqs { docs, freqs }))
+}
+
+/// Live memories across every workspace — they share one vector space.
+pub fn live_count(conn: &Connection) -> Result<u32> {
+    Ok(conn.query_row("SELECT COUNT(*) FROM memories WHERE deleted = 0", [], |r| r.get(0))?)
+}
+
+/// Recount `model`'s frequencies from every live memory, tokenized by `tokenize`.
+pub fn rebuild(conn: &Connection, model: &str, tokenize: impl Fn(&str) -> Vec<u32>) -> Result<DocFreqs> {
+    let mut counted = DocFreqs::default();
+    {
+        let mut stmt = conn.prepare("SELECT content FROM memories WHERE deleted = 0")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let content: String = row.get(0)?;
+            let tokens: HashSet<u32> = tokenize(&content).into_iter().collect();
+            for token in tokens {
+                *counted.freqs.entry(token).or_insert(0) += 1;
+            }
+            counted.docs += 1;
+        }
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    tx.execute("DELETE FROM doc_freqs WHERE model = ?1", params![model])?;
+    {
+        let mut insert = tx.prepare("INSERT INTO doc_freqs (model, token, df) VALUES (?1, ?2, ?3)")?;
+        for (token, df) in &counted.freqs {
+            insert.execute(params![model, token, df])?;
+        }
+    }
+    tx.execute(
+        "INSERT INTO doc_freqs_meta (model, docs, built_at) VALUES (?1, ?2, datetime('now'))
+         ON CONFLICT(model) DO UPDATE SET docs = excluded.docs, built_at = excluded.built_at",
+        params![model, counted.docs],
+    )?;
+    tx.commit()?;
+    Ok(counted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memories::{self, MemoryType};
+
+    fn words(text: &str) -> Vec<u32> {
+        text.split_whitespace().map(|w| w.len() as u32).collect()
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let small = DocFreqs { docs: 20, ..Default::default() };
+        assert!(!small.is_stale(30));
+        assert!(small.is_stale(31));
+        assert!(small.is_stale(9));
+
+        let large = DocFreqs { docs: 1000, ..Default::default() };
+        assert!(!large.is_stale(1200));
+        assert!(large.is_stale(1201));
+        assert!(large.is_stale(799));
+    }
+
+    #[test]
+    fn test_rebuild_and_load() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        assert!(load(&conn, "m").unwrap().is_none());
+
+        for content in ["a bb ccc", "a bb", "a"] {
+            memories::store(&conn, content, &MemoryType::Semantic, &[], None, None, None, None).unwrap();
+        }
+        let built = rebuild(&conn, "m", words).unwrap();
+        assert_eq!(built.docs, 3);
+        assert_eq!(built.freqs[&1], 3);
+        assert_eq!(built.freqs[&3], 1);
+        assert!(built.idf(3) > built.idf(1));
+
+        let loaded = load(&conn, "m").unwrap().unwrap();
+        assert_eq!(loaded.docs, 3);
+        assert_eq!(loaded.freqs, built.freqs);
+        assert!(!loaded.is_stale(live_count(&conn).unwrap()));
+        // Counts are per model
+        assert!(load(&conn, "other").unwrap().is_none());
+    }
+}
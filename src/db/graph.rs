@@ -28,12 +28,19 @@ pub struct Relation {
     pub confidence: f64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub source_memory_id: Option<String>,
+    /// Start of the source memory's content (filled by `get_relations`), so the
+    /// evidence for an edge is visible without a separate lookup.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_memory_snippet: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
     pub created_at: String,
     pub updated_at: String,
 }
 
+/// Characters of the source memory included in `source_memory_snippet`.
+const SOURCE_SNIPPET_CHARS: usize = 120;
+
 fn default_confidence() -> f64 {
     1.0
 }
@@ -330,6 +337,7 @@ pub fn upsert_relation(
         relation_type,
         confidence,
         source_memory_id: source_memory_id.map(String::from),
+        source_memory_snippet: None,
         metadata: metadata.cloned(),
         created_at: now.clone(),
         updated_at: now,
@@ -358,10 +366,12 @@ pub fn get_relations(
     let base_query = "SELECT r.id, r.source_id, r.target_id, r.relation_type, r.confidence,
             r.source_memory_id, r.metadata, r.created_at, r.updated_at,
             s.id, s.name, s.type, s.metadata, s.created_at, s.updated_at,
-            t.id, t.name, t.type, t.metadata, t.created_at, t.updated_at
+            t.id, t.name, t.type, t.metadata, t.created_at, t.updated_at,
+            m.content
          FROM relations r
          JOIN entities s ON r.source_id = s.id
-         JOIN entities t ON r.target_id = t.id";
+         JOIN entities t ON r.target_id = t.id
+         LEFT JOIN memories m ON m.id = r.source_memory_id AND m.deleted = 0";
 
     let (where_clause, type_filter) = match (direction, relation_type) {
         (Some("outgoing"), Some(rt)) => (
@@ -397,7 +407,7 @@ pub fn get_relations(
     let results = if let Some(rt) = type_filter {
         stmt.query_map(params![entity_id, rt], |row| {
             Ok((
-                row_to_relation(row)?,
+                row_to_relation_with_snippet(row, 21)?,
                 row_to_entity_at(row, 9)?,
                 row_to_entity_at(row, 15)?,
             ))
@@ -405,7 +415,7 @@ pub fn get_relations(
     } else {
         stmt.query_map(params![entity_id], |row| {
             Ok((
-                row_to_relation(row)?,
+                row_to_relation_with_snippet(row, 21)?,
                 row_to_entity_at(row, 9)?,
                 row_to_entity_at(row, 15)?,
             ))
@@ -415,6 +425,61 @@ pub fn get_relations(
     Ok(results)
 }
 
+/// How forgetting a memory weakens the relations extracted from it: their
+/// confidence is scaled by `penalty`, and any left below `min_confidence` are
+/// removed. Without pro there is no graph, so it carries nothing.
+#[derive(Debug, Clone, Copy)]
+pub struct ForgetPenalty {
+    #[cfg(feature = "pro")]
+    pub penalty: f64,
+    #[cfg(feature = "pro")]
+    pub min_confidence: f64,
+}
+
+/// Scale down relations whose evidence memory was forgotten, deleting any that
+/// fall below `min_confidence`. Doesn't touch `updated_at`, so the edge still
+/// counts as unconfirmed for decay. Returns (weakened, removed).
+#[cfg(feature = "pro")]
+pub fn weaken_relations_from_memory(
+    conn: &Connection,
+    memory_id: &str,
+    penalty: f64,
+    min_confidence: f64,
+) -> Result<(usize, usize)> {
+    let weakened = conn.execute(
+        "UPDATE relations SET confidence = confidence * ?1 WHERE source_memory_id = ?2",
+        params![penalty.clamp(0.0, 1.0), memory_id],
+    )?;
+    let removed = conn.execute(
+        "DELETE FROM relations WHERE source_memory_id = ?1 AND confidence < ?2",
+        params![memory_id, min_confidence],
+    )?;
+    Ok((weakened - removed, removed))
+}
+
+/// Decay inferred relations (from a memory, or below full confidence) that
+/// haven't been re-confirmed via `upsert_relation` for `stale_days`, deleting
+/// those that drop below `min_confidence`. Returns (decayed, removed).
+#[cfg(feature = "pro")]
+pub fn decay_stale_relations(
+    conn: &Connection,
+    stale_days: u64,
+    factor: f64,
+    min_confidence: f64,
+) -> Result<(usize, usize)> {
+    let cutoff = (Utc::now() - chrono::Duration::days(stale_days as i64)).to_rfc3339();
+    let stale = "updated_at < ?1 AND (source_memory_id IS NOT NULL OR confidence < 1.0)";
+    let decayed = conn.execute(
+        &format!("UPDATE relations SET confidence = confidence * ?2 WHERE {stale}"),
+        params![cutoff, factor.clamp(0.0, 1.0)],
+    )?;
+    let removed = conn.execute(
+        &format!("DELETE FROM relations WHERE {stale} AND confidence < ?2"),
+        params![cutoff, min_confidence],
+    )?;
+    Ok((decayed - removed, removed))
+}
+
 pub fn delete_relation(conn: &Connection, id: &str) -> Result<bool> {
     let changed = conn.execute("DELETE FROM relations WHERE id = ?1", params![id])?;
     Ok(changed > 0)
@@ -425,6 +490,127 @@ pub fn count_relations(conn: &Connection) -> Result<usize> {
     Ok(count)
 }
 
+// ── Bulk import ─────────────────────────────────────────────
+
+/// A JSON document of entities and relations to seed the graph with.
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportDocument {
+    #[serde(default)]
+    pub entities: Vec<ImportEntity>,
+    #[serde(default)]
+    pub relations: Vec<ImportRelation>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ImportEntity {
+    pub name: String,
+    #[serde(rename = "type", default = "default_entity_type")]
+    pub entity_type: String,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// A relation whose endpoints are given by name (+ optional type) rather
+/// than entity id. Without a type the name must match exactly one entity.
+#[derive(Debug, Deserialize)]
+pub struct ImportRelation {
+    pub source: String,
+    #[serde(default)]
+    pub source_type: Option<String>,
+    pub target: String,
+    #[serde(default)]
+    pub target_type: Option<String>,
+    #[serde(rename = "type", alias = "relation", alias = "relation_type")]
+    pub relation_type: String,
+    #[serde(default = "default_confidence")]
+    pub confidence: f64,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+fn default_entity_type() -> String {
+    "generic".to_string()
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ImportReport {
+    pub entities_created: usize,
+    pub entities_updated: usize,
+    pub relations_created: usize,
+    pub relations_updated: usize,
+    /// Endpoints that matched no entity (or several, when untyped),
+    /// formatted as `name` or `type:name`.
+    pub unresolved: Vec<String>,
+    /// Entries rejected by validation, e.g. an empty name or bad confidence.
+    pub errors: Vec<String>,
+}
+
+/// Upsert every entity, then every relation, in a single transaction.
+/// Relations with an unresolved endpoint are skipped and reported.
+pub fn import(conn: &Connection, doc: &ImportDocument) -> Result<ImportReport> {
+    let tx = conn.unchecked_transaction()?;
+    let mut report = ImportReport::default();
+
+    for e in &doc.entities {
+        let existed = !find_entity(&tx, e.name.trim(), Some(&e.entity_type.trim().to_lowercase()))?.is_empty();
+        match upsert_entity(&tx, &e.name, &e.entity_type, e.metadata.as_ref()) {
+            Ok(_) if existed => report.entities_updated += 1,
+            Ok(_) => report.entities_created += 1,
+            Err(err) => report.errors.push(format!("entity \"{}\": {err}", e.name)),
+        }
+    }
+
+    for r in &doc.relations {
+        let source = resolve_endpoint(&tx, &r.source, r.source_type.as_deref())?;
+        let target = resolve_endpoint(&tx, &r.target, r.target_type.as_deref())?;
+        let (source, target) = match (source, target) {
+            (Ok(s), Ok(t)) => (s, t),
+            (s, t) => {
+                report.unresolved.extend(s.err());
+                report.unresolved.extend(t.err());
+                continue;
+            }
+        };
+
+        let existed: bool = tx.query_row(
+            "SELECT COUNT(*) FROM relations WHERE source_id = ?1 AND target_id = ?2 AND relation_type = ?3",
+            params![source, target, r.relation_type.trim().to_lowercase()],
+            |row| row.get::<_, i32>(0),
+        )? > 0;
+        match upsert_relation(&tx, &source, &target, &r.relation_type, r.confidence, None, r.metadata.as_ref()) {
+            Ok(_) if existed => report.relations_updated += 1,
+            Ok(_) => report.relations_created += 1,
+            Err(err) => report.errors.push(format!(
+                "relation {} -[{}]-> {}: {err}",
+                r.source, r.relation_type, r.target
+            )),
+        }
+    }
+
+    report.unresolved.sort();
+    report.unresolved.dedup();
+    tx.commit()?;
+    Ok(report)
+}
+
+/// Resolve an import endpoint to an entity id; the inner `Err` is the
+/// endpoint label for the unresolved list.
+fn resolve_endpoint(
+    conn: &Connection,
+    name: &str,
+    entity_type: Option<&str>,
+) -> Result<std::result::Result<String, String>> {
+    let name = name.trim();
+    let entity_type = entity_type.map(|t| t.trim().to_lowercase());
+    let matches = find_entity(conn, name, entity_type.as_deref())?;
+    Ok(match (matches.as_slice(), entity_type) {
+        ([only], _) => Ok(only.id.clone()),
+        ([], Some(t)) => Err(format!("{t}:{name}")),
+        ([], None) => Err(name.to_string()),
+        (_, _) => Err(format!("{name} (ambiguous — {} entities, give a type)", matches.len())),
+    })
+}
+
 // ── Graph traversal ─────────────────────────────────────────
 
 /// BFS traversal from an entity up to `max_depth` hops.
@@ -564,6 +750,7 @@ fn row_to_relation(row: &rusqlite::Row) -> rusqlite::Result<Relation> {
         relation_type: row.get(3)?,
         confidence: row.get(4)?,
         source_memory_id: row.get(5)?,
+        source_memory_snippet: None,
         metadata: row
             .get::<_, Option<String>>(6)?
             .and_then(|s| serde_json::from_str(&s).ok()),
@@ -572,6 +759,19 @@ fn row_to_relation(row: &rusqlite::Row) -> rusqlite::Result<Relation> {
     })
 }
 
+/// `row_to_relation` plus the source memory content at `offset`, cut to a snippet.
+fn row_to_relation_with_snippet(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Relation> {
+    let mut relation = row_to_relation(row)?;
+    relation.source_memory_snippet = row.get::<_, Option<String>>(offset)?.map(|content| {
+        let mut snippet: String = content.chars().take(SOURCE_SNIPPET_CHARS).collect();
+        if content.chars().count() > SOURCE_SNIPPET_CHARS {
+            snippet.push('…');
+        }
+        snippet
+    });
+    Ok(relation)
+}
+
 fn row_to_entity_at(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Entity> {
     Ok(Entity {
         id: row.get(offset)?,
@@ -584,3 +784,56 @@ fn row_to_entity_at(row: &rusqlite::Row, offset: usize) -> rusqlite::Result<Enti
         updated_at: row.get(offset + 5)?,
     })
 }
+
+#[cfg(all(test, feature = "pro"))]
+mod tests {
+    use super::*;
+    use crate::db::memories::{self, MemoryType};
+
+    const FORGET: ForgetPenalty = ForgetPenalty { penalty: 0.5, min_confidence: 0.3 };
+
+    #[test]
+    fn test_forget_weakens_extracted_relations() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let mem = memories::store(&conn, "Alice works on Atlas", &MemoryType::Semantic, &[], None, None, None, None).unwrap();
+        let alice = upsert_entity(&conn, "Alice", "person", None).unwrap();
+        let atlas = upsert_entity(&conn, "Atlas", "project", None).unwrap();
+        let bob = upsert_entity(&conn, "Bob", "person", None).unwrap();
+        let strong = upsert_relation(&conn, &alice.id, &atlas.id, "works_on", 0.9, Some(&mem.id), None).unwrap();
+        let weak = upsert_relation(&conn, &bob.id, &atlas.id, "works_on", 0.5, Some(&mem.id), None).unwrap();
+        let manual = upsert_relation(&conn, &alice.id, &bob.id, "knows", 1.0, None, None).unwrap();
+
+        assert!(memories::delete(&conn, &mem.id, FORGET).unwrap());
+
+        let strong = get_relation(&conn, &strong.id).unwrap().expect("weakened, not removed");
+        assert!((strong.confidence - 0.45).abs() < 1e-9);
+        assert!(get_relation(&conn, &weak.id).unwrap().is_none(), "0.25 < min_confidence");
+        assert_eq!(get_relation(&conn, &manual.id).unwrap().unwrap().confidence, 1.0);
+    }
+
+    #[test]
+    fn test_decay_stale_inferred_relations() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let a = upsert_entity(&conn, "A", "thing", None).unwrap();
+        let b = upsert_entity(&conn, "B", "thing", None).unwrap();
+        let inferred = upsert_relation(&conn, &a.id, &b.id, "uses", 0.8, None, None).unwrap();
+        let doomed = upsert_relation(&conn, &b.id, &a.id, "uses", 0.4, None, None).unwrap();
+        let confirmed = upsert_relation(&conn, &a.id, &b.id, "owns", 1.0, None, None).unwrap();
+        let fresh = upsert_relation(&conn, &a.id, &a.id, "mentions", 0.8, None, None).unwrap();
+        let old = (Utc::now() - chrono::Duration::days(40)).to_rfc3339();
+        conn.execute(
+            "UPDATE relations SET updated_at = ?1 WHERE id IN (?2, ?3, ?4)",
+            params![old, inferred.id, doomed.id, confirmed.id],
+        )
+        .unwrap();
+
+        assert_eq!(decay_stale_relations(&conn, 30, 0.5, 0.3).unwrap(), (1, 1));
+        assert!((get_relation(&conn, &inferred.id).unwrap().unwrap().confidence - 0.4).abs() < 1e-9);
+        assert!(get_relation(&conn, &doomed.id).unwrap().is_none());
+        // Full-confidence manual edges and recently confirmed ones are left alone
+        assert_eq!(get_relation(&conn, &confirmed.id).unwrap().unwrap().confidence, 1.0);
+        assert_eq!(get_relation(&conn, &fresh.id).unwrap().unwrap().confidence, 0.8);
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::Result;
 use chrono::Utc;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::config::PinOverflow;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Memory {
     pub id: String,
@@ -22,7 +24,7 @@ pub struct Memory {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum MemoryType {
     #[default]
@@ -63,11 +65,19 @@ impl std::str::FromStr for MemoryType {
             "agent_personality" | "agentpersonality" => Ok(MemoryType::AgentPersonality),
             "agent_rules" | "agentrules" => Ok(MemoryType::AgentRules),
             "channel_private" | "channelprivate" | "private" => Ok(MemoryType::ChannelPrivate),
-            _ => anyhow::bail!("Unknown memory type: {s}"),
+            _ => anyhow::bail!(
+                "Unknown memory type: {s} (expected one of semantic, episodic, procedural, \
+                 preference, agent_personality, agent_rules, channel_private)"
+            ),
         }
     }
 }
 
+/// Parse memory type names (e.g. from a recall filter), failing on the first unknown one.
+pub fn parse_types<S: AsRef<str>>(names: &[S]) -> Result<Vec<MemoryType>> {
+    names.iter().map(|n| n.as_ref().trim().parse()).collect()
+}
+
 pub fn store(
     conn: &Connection,
     content: &str,
@@ -78,9 +88,12 @@ pub fn store(
     embedding: Option<&[f32]>,
     agent_id: Option<&str>,
 ) -> Result<Memory> {
-    store_with_expiry(conn, content, memory_type, tags, subject, source, embedding, None, agent_id)
+    store_with_expiry(conn, content, memory_type, tags, subject, source, embedding, None, agent_id, false)
 }
 
+/// Store a memory with an optional expiry. `skip_embedding` stores it
+/// keyword-only: it gets no vector on store, edit or restore, and backfill
+/// and reindex pass it over.
 pub fn store_with_expiry(
     conn: &Connection,
     content: &str,
@@ -91,14 +104,15 @@ pub fn store_with_expiry(
     embedding: Option<&[f32]>,
     expires_at: Option<&str>,
     agent_id: Option<&str>,
+    skip_embedding: bool,
 ) -> Result<Memory> {
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
     let tags_json = serde_json::to_string(tags)?;
 
     conn.execute(
-        "INSERT INTO memories (id, content, type, tags, subject, source, embedding, expires_at, agent_id, created_at, updated_at)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+        "INSERT INTO memories (id, content, type, tags, subject, source, embedding, expires_at, agent_id, created_at, updated_at, skip_embedding, workspace)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, current_workspace())",
         params![
             id,
             content,
@@ -106,20 +120,18 @@ pub fn store_with_expiry(
             tags_json,
             subject,
             source,
-            embedding.map(|e| bytemuck_cast(e)),
+            embedding.filter(|_| !skip_embedding).map(|e| bytemuck_cast(e)),
             expires_at,
             agent_id,
             now,
             now,
+            skip_embedding,
         ],
     )?;
 
     // If we have an embedding, also store in vec table
-    if let Some(emb) = embedding {
-        let _ = conn.execute(
-            "INSERT INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-            params![id, bytemuck_cast(emb)],
-        );
+    if let Some(emb) = embedding.filter(|_| !skip_embedding) {
+        let _ = upsert_vector(conn, &id, emb);
     }
 
     Ok(Memory {
@@ -139,7 +151,7 @@ pub fn store_with_expiry(
 pub fn get(conn: &Connection, id: &str) -> Result<Option<Memory>> {
     let mut stmt = conn.prepare(
         "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
-         FROM memories WHERE id = ?1 AND deleted = 0",
+         FROM memories WHERE id = ?1 AND deleted = 0 AND in_workspace(workspace)",
     )?;
 
     let result = stmt
@@ -165,14 +177,81 @@ pub fn get(conn: &Connection, id: &str) -> Result<Option<Memory>> {
     Ok(result)
 }
 
-pub fn delete(conn: &Connection, id: &str) -> Result<bool> {
+/// Tombstone a memory. With pro, relations extracted from it are weakened by `forget`.
+pub fn delete(conn: &Connection, id: &str, forget: super::graph::ForgetPenalty) -> Result<bool> {
     let changed = conn.execute(
-        "UPDATE memories SET deleted = 1, updated_at = ?1 WHERE id = ?2 AND deleted = 0",
+        "UPDATE memories SET deleted = 1, updated_at = ?1 WHERE id = ?2 AND deleted = 0 AND in_workspace(workspace)",
         params![Utc::now().to_rfc3339(), id],
     )?;
+    // Relations extracted from this memory lose their evidence
+    #[cfg(feature = "pro")]
+    if changed > 0 {
+        super::graph::weaken_relations_from_memory(conn, id, forget.penalty, forget.min_confidence)?;
+    }
+    #[cfg(not(feature = "pro"))]
+    let _ = forget;
     Ok(changed > 0)
 }
 
+/// Tombstone every live memory in every workspace, so the next push deletes
+/// them from the cloud too. Returns how many were deleted.
+pub fn delete_all(conn: &Connection) -> Result<usize> {
+    Ok(conn.execute(
+        "UPDATE memories SET deleted = 1, updated_at = ?1 WHERE deleted = 0",
+        params![Utc::now().to_rfc3339()],
+    )?)
+}
+
+/// Un-delete a tombstoned memory that hasn't been purged yet.
+/// An expiry that has already passed is cleared so the memory is visible again.
+/// `expires_at` may be RFC 3339 or SQLite's `YYYY-MM-DD HH:MM:SS`, so both
+/// sides are compared as julian days rather than as text.
+pub fn restore(conn: &Connection, id: &str) -> Result<Option<Memory>> {
+    let now = Utc::now().to_rfc3339();
+    let changed = conn.execute(
+        "UPDATE memories SET deleted = 0, updated_at = ?1,
+             expires_at = CASE WHEN julianday(expires_at) <= julianday('now') THEN NULL ELSE expires_at END
+         WHERE id = ?2 AND deleted = 1 AND in_workspace(workspace)",
+        params![now, id],
+    )?;
+    if changed == 0 {
+        return Ok(None);
+    }
+    get(conn, id)
+}
+
+/// Tombstoned memories still inside the purge window, most recently deleted first.
+pub fn list_deleted(conn: &Connection, limit: usize) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
+         FROM memories WHERE deleted = 1 AND in_workspace(workspace)
+         ORDER BY updated_at DESC LIMIT ?1",
+    )?;
+
+    let memories = stmt
+        .query_map(params![limit], |row| {
+            Ok(Memory {
+                id: row.get(0)?,
+                content: row.get(1)?,
+                memory_type: row
+                    .get::<_, String>(2)?
+                    .parse()
+                    .unwrap_or_default(),
+                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                subject: row.get(4)?,
+                source: row.get(5)?,
+                agent_id: row.get(6)?,
+                expires_at: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(memories)
+}
+
+/// Live memories across all workspaces — tier limits apply to the whole vault.
 pub fn count(conn: &Connection) -> Result<usize> {
     let count: usize =
         conn.query_row("SELECT COUNT(*) FROM memories WHERE deleted = 0", [], |r| {
@@ -181,10 +260,58 @@ pub fn count(conn: &Connection) -> Result<usize> {
     Ok(count)
 }
 
+/// IDs of live memories in the current workspace, oldest first.
+pub fn list_ids(conn: &Connection) -> Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT id FROM memories WHERE deleted = 0 AND in_workspace(workspace)
+         AND (expires_at IS NULL OR expires_at > datetime('now'))
+         ORDER BY created_at",
+    )?;
+    let ids = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(ids)
+}
+
+/// Replace tag `from` with `to` on every memory in the current workspace that
+/// carries it. Touches `updated_at`, so the next sync pushes the change.
+/// Returns the number of memories updated.
+pub fn rename_tag(conn: &Connection, from: &str, to: &str) -> Result<usize> {
+    let rows: Vec<(String, String)> = {
+        let mut stmt = conn.prepare(
+            "SELECT id, tags FROM memories
+             WHERE deleted = 0 AND in_workspace(workspace)
+             AND EXISTS (SELECT 1 FROM json_each(memories.tags) t WHERE t.value = ?1)",
+        )?;
+        stmt.query_map(params![from], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<Vec<_>, _>>()?
+    };
+
+    let tx = conn.unchecked_transaction()?;
+    for (id, tags_json) in &rows {
+        let tags: Vec<String> = serde_json::from_str::<Vec<String>>(tags_json)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|t| if t == from { to.to_string() } else { t })
+            .collect();
+        let tags = crate::validation::validate_tags(&tags).map_err(|e| anyhow::anyhow!("{id}: {e}"))?;
+        update(&tx, id, None, Some(&tags), None, None, None)?;
+    }
+    tx.commit()?;
+    Ok(rows.len())
+}
+
+/// Most recent time any memory was pushed to or pulled from the cloud.
+pub fn last_synced_at(conn: &Connection) -> Result<Option<String>> {
+    let last: Option<String> =
+        conn.query_row("SELECT MAX(synced_at) FROM memories", [], |r| r.get(0))?;
+    Ok(last)
+}
+
 pub fn list(conn: &Connection, limit: usize, offset: usize) -> Result<Vec<Memory>> {
     let mut stmt = conn.prepare(
         "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
-         FROM memories WHERE deleted = 0
+         FROM memories WHERE deleted = 0 AND in_workspace(workspace)
          AND (expires_at IS NULL OR expires_at > datetime('now'))
          ORDER BY created_at DESC LIMIT ?1 OFFSET ?2",
     )?;
@@ -212,6 +339,160 @@ pub fn list(conn: &Connection, limit: usize, offset: usize) -> Result<Vec<Memory
     Ok(memories)
 }
 
+/// Live memories of `types` that were neither updated nor accessed (recalled
+/// or fetched) at or after `before`, oldest first. Pinned/policy memories are
+/// left out.
+pub fn list_stale(conn: &Connection, before: &str, types: &[MemoryType]) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
+         FROM memories WHERE deleted = 0 AND in_workspace(workspace)
+         AND datetime(updated_at) < datetime(?1)
+         AND (last_accessed_at IS NULL OR datetime(last_accessed_at) < datetime(?1))
+         ORDER BY datetime(updated_at) ASC",
+    )?;
+
+    let memories = stmt
+        .query_map(params![before], |row| {
+            Ok(Memory {
+                id: row.get(0)?,
+                content: row.get(1)?,
+                memory_type: row
+                    .get::<_, String>(2)?
+                    .parse()
+                    .unwrap_or_default(),
+                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                subject: row.get(4)?,
+                source: row.get(5)?,
+                agent_id: row.get(6)?,
+                expires_at: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(memories
+        .into_iter()
+        .filter(|m| types.contains(&m.memory_type) && !super::search::is_priority(m))
+        .collect())
+}
+
+/// The newest `limit` episodic memories created in `[since, until)` (RFC 3339
+/// bounds, either optional), optionally for one subject, oldest first.
+pub fn list_episodic(
+    conn: &Connection,
+    since: Option<&str>,
+    until: Option<&str>,
+    subject: Option<&str>,
+    limit: usize,
+) -> Result<Vec<Memory>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
+         FROM memories WHERE deleted = 0 AND in_workspace(workspace)
+         AND (expires_at IS NULL OR expires_at > datetime('now'))
+         AND type = 'episodic'
+         AND (?1 IS NULL OR datetime(created_at) >= datetime(?1))
+         AND (?2 IS NULL OR datetime(created_at) < datetime(?2))
+         AND (?3 IS NULL OR subject = ?3)
+         ORDER BY datetime(created_at) DESC LIMIT ?4",
+    )?;
+
+    let mut memories = stmt
+        .query_map(params![since, until, subject, limit], |row| {
+            Ok(Memory {
+                id: row.get(0)?,
+                content: row.get(1)?,
+                memory_type: row
+                    .get::<_, String>(2)?
+                    .parse()
+                    .unwrap_or_default(),
+                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                subject: row.get(4)?,
+                source: row.get(5)?,
+                agent_id: row.get(6)?,
+                expires_at: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    memories.reverse();
+    Ok(memories)
+}
+
+/// Memories tagged `pinned`, least recently reinforced first, each with the
+/// time it was last reinforced — recalled or edited, whichever is later.
+pub fn list_pinned(conn: &Connection) -> Result<Vec<(Memory, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at,
+                MAX(datetime(updated_at), COALESCE(
+                    (SELECT MAX(recalled_at) FROM recall_logs r WHERE r.memory_id = memories.id), ''
+                )) AS reinforced_at
+         FROM memories WHERE deleted = 0 AND in_workspace(workspace)
+         AND EXISTS (SELECT 1 FROM json_each(memories.tags) t WHERE t.value = 'pinned')
+         ORDER BY reinforced_at ASC",
+    )?;
+
+    let pinned = stmt
+        .query_map([], |row| {
+            Ok((
+                Memory {
+                    id: row.get(0)?,
+                    content: row.get(1)?,
+                    memory_type: row
+                        .get::<_, String>(2)?
+                        .parse()
+                        .unwrap_or_default(),
+                    tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                    subject: row.get(4)?,
+                    source: row.get(5)?,
+                    agent_id: row.get(6)?,
+                    expires_at: row.get(7)?,
+                    created_at: row.get(8)?,
+                    updated_at: row.get(9)?,
+                },
+                row.get(10)?,
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    Ok(pinned)
+}
+
+/// Whether another memory can be pinned under the `max_pinned` cap.
+#[derive(Debug, PartialEq, Eq)]
+pub enum PinRoom {
+    /// There's room; holds the ids unpinned to make it, if any.
+    Made(Vec<String>),
+    /// The cap is reached and `pin_overflow` is `reject`; holds how many are pinned.
+    Full(usize),
+}
+
+/// Make room for one more pin under `max_pinned` (0 = no cap), evicting the
+/// least recently reinforced pins when `overflow` is `evict`. Call before
+/// pinning a memory that isn't pinned yet.
+pub fn make_room_for_pin(conn: &Connection, max_pinned: usize, overflow: PinOverflow) -> Result<PinRoom> {
+    if max_pinned == 0 {
+        return Ok(PinRoom::Made(Vec::new()));
+    }
+    let pinned = list_pinned(conn)?;
+    if pinned.len() < max_pinned {
+        return Ok(PinRoom::Made(Vec::new()));
+    }
+    if overflow == PinOverflow::Reject {
+        return Ok(PinRoom::Full(pinned.len()));
+    }
+    let mut evicted = Vec::new();
+    for (old, _) in &pinned[..=pinned.len() - max_pinned] {
+        let tags: Vec<String> = old.tags.iter().filter(|t| *t != "pinned").cloned().collect();
+        if update(conn, &old.id, None, Some(&tags), None, None, None)?.is_some() {
+            evicted.push(old.id.clone());
+        }
+    }
+    Ok(PinRoom::Made(evicted))
+}
+
 /// Update a memory's mutable fields. Only non-None fields are updated.
 pub fn update(
     conn: &Connection,
@@ -223,6 +504,8 @@ pub fn update(
     embedding: Option<&[f32]>,
 ) -> Result<Option<Memory>> {
     let now = Utc::now().to_rfc3339();
+    // Keyword-only memories stay that way through edits
+    let embedding = embedding.filter(|_| !skips_embedding(conn, id));
 
     // Build dynamic UPDATE
     let mut sets = vec!["updated_at = ?1".to_string()];
@@ -260,7 +543,7 @@ pub fn update(
     params_vec.push(Box::new(id.to_string()));
 
     let sql = format!(
-        "UPDATE memories SET {} WHERE id = ?{} AND deleted = 0",
+        "UPDATE memories SET {} WHERE id = ?{} AND deleted = 0 AND in_workspace(workspace)",
         sets.join(", "),
         id_param_idx
     );
@@ -274,19 +557,31 @@ pub fn update(
 
     // Update vec table if embedding provided
     if let Some(emb) = embedding {
-        let _ = conn.execute(
-            "DELETE FROM memory_vectors WHERE id = ?1",
-            params![id],
-        );
-        let _ = conn.execute(
-            "INSERT INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-            params![id, bytemuck_cast(emb)],
-        );
+        let _ = upsert_vector(conn, id, emb);
     }
 
     get(conn, id)
 }
 
+/// Whether a memory was stored keyword-only (`skip_embedding`).
+pub fn skips_embedding(conn: &Connection, id: &str) -> bool {
+    conn.query_row("SELECT skip_embedding FROM memories WHERE id = ?1", params![id], |r| r.get::<_, bool>(0))
+        .unwrap_or(false)
+}
+
+/// Memories an agent (or, for `None`, callers without an agent_id) created in
+/// the last `window_secs` seconds, across all workspaces and including ones
+/// deleted since.
+pub fn count_recent_by_agent(conn: &Connection, agent_id: Option<&str>, window_secs: i64) -> Result<usize> {
+    let count: usize = conn.query_row(
+        "SELECT COUNT(*) FROM memories
+         WHERE agent_id IS ?1 AND datetime(created_at) > datetime('now', ?2)",
+        params![agent_id, format!("-{window_secs} seconds")],
+        |r| r.get(0),
+    )?;
+    Ok(count)
+}
+
 /// Delete memories that have expired. Returns count of cleaned up memories.
 pub fn cleanup_expired(conn: &Connection) -> Result<usize> {
     let count = conn.execute(
@@ -297,12 +592,247 @@ pub fn cleanup_expired(conn: &Connection) -> Result<usize> {
     Ok(count)
 }
 
+/// Write a memory's vector into `memory_vectors`, in whatever format the table
+/// was created with (float32 or int8).
+pub fn upsert_vector(conn: &Connection, id: &str, embedding: &[f32]) -> Result<()> {
+    if !super::vectors_available() {
+        return Ok(());
+    }
+    // vec0 doesn't support REPLACE conflict resolution — delete first
+    conn.execute("DELETE FROM memory_vectors WHERE id = ?1", params![id])?;
+    match super::vector_quantization(conn) {
+        crate::config::VectorQuantization::None => conn.execute(
+            "INSERT INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
+            params![id, bytemuck_cast(embedding)],
+        )?,
+        crate::config::VectorQuantization::Int8 => conn.execute(
+            "INSERT INTO memory_vectors (id, embedding) VALUES (?1, vec_int8(?2))",
+            params![id, quantize_int8(embedding)],
+        )?,
+    };
+    Ok(())
+}
+
+/// Drift between `memories` and `memory_vectors`, across all workspaces:
+/// live memories with no vector (other than keyword-only ones), and vectors
+/// whose memory no longer exists.
+/// Vectors of tombstoned memories are kept for `restore` and not counted.
+pub fn vector_drift(conn: &Connection) -> Result<(Vec<String>, Vec<String>)> {
+    if !super::vectors_available() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+    let ids = |sql: &str| -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(sql)?;
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<String>, _>>()?;
+        Ok(ids)
+    };
+    let missing = ids(
+        "SELECT m.id FROM memories m LEFT JOIN memory_vectors v ON v.id = m.id
+         WHERE m.deleted = 0 AND m.skip_embedding = 0 AND v.id IS NULL",
+    )?;
+    let orphans = ids(
+        "SELECT v.id FROM memory_vectors v LEFT JOIN memories m ON m.id = v.id
+         WHERE m.id IS NULL",
+    )?;
+    Ok((missing, orphans))
+}
+
+/// Delete the vectors with these ids.
+pub fn delete_vectors(conn: &Connection, ids: &[String]) -> Result<usize> {
+    if !super::vectors_available() {
+        return Ok(0);
+    }
+    let mut deleted = 0;
+    for id in ids {
+        deleted += conn.execute("DELETE FROM memory_vectors WHERE id = ?1", params![id])?;
+    }
+    Ok(deleted)
+}
+
+/// Read a memory's vector back from `memory_vectors` as f32, undoing int8
+/// quantization if the table stores it that way.
+pub fn get_vector(conn: &Connection, id: &str) -> Result<Option<Vec<f32>>> {
+    if !super::vectors_available() {
+        return Ok(None);
+    }
+    let bytes: Option<Vec<u8>> = conn
+        .query_row("SELECT embedding FROM memory_vectors WHERE id = ?1", params![id], |r| r.get(0))
+        .optional()?;
+    let Some(bytes) = bytes else {
+        return Ok(None);
+    };
+    let vector = match super::vector_quantization(conn) {
+        crate::config::VectorQuantization::None => bytes
+            .chunks_exact(4)
+            .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect(),
+        crate::config::VectorQuantization::Int8 => bytes.iter().map(|&b| b as i8 as f32 / INT8_SCALE).collect(),
+    };
+    Ok(Some(vector))
+}
+
+/// Scale factor between unit-range f32 components and stored int8 values.
+pub const INT8_SCALE: f32 = 127.0;
+
+/// Quantize a (normalized) embedding to int8 bytes for `int8[N]` vec0 columns.
+pub fn quantize_int8(floats: &[f32]) -> Vec<u8> {
+    floats
+        .iter()
+        .map(|f| (f * INT8_SCALE).round().clamp(-INT8_SCALE, INT8_SCALE) as i8 as u8)
+        .collect()
+}
+
 /// Cast f32 slice to bytes for SQLite BLOB storage
 fn bytemuck_cast(floats: &[f32]) -> Vec<u8> {
     floats.iter().flat_map(|f| f.to_le_bytes()).collect()
 }
 
-/// Public version for use by sync module
-pub fn bytemuck_cast_pub(floats: &[f32]) -> Vec<u8> {
-    bytemuck_cast(floats)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn age(conn: &Connection, id: &str, days: i64) {
+        let then = (Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+        conn.execute("UPDATE memories SET updated_at = ?1 WHERE id = ?2", params![then, id]).unwrap();
+    }
+
+    #[test]
+    fn test_restore_clears_a_passed_expiry() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let ttl = |minutes: i64, rfc3339: bool| {
+            let at = Utc::now() + chrono::Duration::minutes(minutes);
+            let at = if rfc3339 { at.to_rfc3339() } else { at.format("%Y-%m-%d %H:%M:%S").to_string() };
+            let id = store(&conn, "short-lived note", &MemoryType::Semantic, &[], None, None, None, None).unwrap().id;
+            conn.execute("UPDATE memories SET expires_at = ?1, deleted = 1 WHERE id = ?2", params![at, id]).unwrap();
+            (id, at)
+        };
+        // RFC 3339 text sorts after `datetime('now')` on the same day, expired or not
+        let (expired, _) = ttl(-1, true);
+        let (expired_sqlite, _) = ttl(-1, false);
+        let (pending, pending_at) = ttl(60, true);
+
+        assert_eq!(restore(&conn, &expired).unwrap().unwrap().expires_at, None);
+        assert_eq!(restore(&conn, &expired_sqlite).unwrap().unwrap().expires_at, None);
+        assert_eq!(restore(&conn, &pending).unwrap().unwrap().expires_at, Some(pending_at));
+    }
+
+    #[test]
+    fn test_list_episodic_range_subject_and_limit() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let add = |content: &str, kind: MemoryType, subject: Option<&str>, created_at: &str| {
+            let id = store(&conn, content, &kind, &[], subject, None, None, None).unwrap().id;
+            conn.execute("UPDATE memories SET created_at = ?1 WHERE id = ?2", params![created_at, id]).unwrap();
+            id
+        };
+        let before = add("before range", MemoryType::Episodic, None, "2025-03-02T23:59:59+00:00");
+        let start = add("at since", MemoryType::Episodic, Some("api"), "2025-03-03T00:00:00+00:00");
+        // Offsets are compared as instants: this is 2025-03-04T01:00:00Z
+        let offset = add("offset timestamp", MemoryType::Episodic, None, "2025-03-03T20:00:00-05:00");
+        let end = add("at until", MemoryType::Episodic, Some("api"), "2025-03-10T00:00:00+00:00");
+        add("a fact", MemoryType::Semantic, None, "2025-03-05T00:00:00+00:00");
+        let deleted = add("deleted", MemoryType::Episodic, None, "2025-03-05T00:00:00+00:00");
+        conn.execute("UPDATE memories SET deleted = 1 WHERE id = ?1", params![deleted]).unwrap();
+
+        let ids = |memories: Vec<Memory>| memories.into_iter().map(|m| m.id).collect::<Vec<_>>();
+        let since = Some("2025-03-03T00:00:00Z");
+        let until = Some("2025-03-10T00:00:00Z");
+        assert_eq!(ids(list_episodic(&conn, since, until, None, 10).unwrap()), [start.clone(), offset.clone()]);
+        assert_eq!(ids(list_episodic(&conn, since, None, Some("api"), 10).unwrap()), [start.clone(), end.clone()]);
+        assert_eq!(ids(list_episodic(&conn, None, None, None, 10).unwrap()), [before, start, offset.clone(), end.clone()]);
+        // The limit keeps the newest, still returned oldest first
+        assert_eq!(ids(list_episodic(&conn, None, None, None, 2).unwrap()), [offset, end]);
+    }
+
+    #[test]
+    fn test_list_stale() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let add = |content: &str, kind: MemoryType, tags: &[&str]| {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            store(&conn, content, &kind, &tags, None, None, None, None).unwrap().id
+        };
+        let stale = add("old episode", MemoryType::Episodic, &[]);
+        add("new episode", MemoryType::Episodic, &[]);
+        let pinned = add("old pinned episode", MemoryType::Episodic, &["pinned"]);
+        let other_type = add("old fact", MemoryType::Semantic, &[]);
+        let accessed = add("old but recalled", MemoryType::Episodic, &[]);
+        let deleted = add("old and deleted", MemoryType::Episodic, &[]);
+        for id in [&stale, &pinned, &other_type, &accessed, &deleted] {
+            age(&conn, id, 90);
+        }
+        conn.execute("UPDATE memories SET last_accessed_at = ?1 WHERE id = ?2", params![Utc::now().to_rfc3339(), accessed])
+            .unwrap();
+        conn.execute("UPDATE memories SET deleted = 1 WHERE id = ?1", params![deleted]).unwrap();
+
+        let before = (Utc::now() - chrono::Duration::days(30)).to_rfc3339();
+        let found: Vec<String> = list_stale(&conn, &before, &[MemoryType::Episodic])
+            .unwrap()
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(found, [stale]);
+
+        let both = list_stale(&conn, &before, &[MemoryType::Episodic, MemoryType::Semantic]).unwrap();
+        assert_eq!(both.len(), 2);
+    }
+
+    #[test]
+    fn test_make_room_for_pin() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let pinned = ["pinned".to_string()];
+        let tagged = ["pinned".to_string(), "infra".to_string()];
+        let oldest = store(&conn, "oldest pin", &MemoryType::Semantic, &tagged, None, None, None, None).unwrap().id;
+        let newer = store(&conn, "newer pin", &MemoryType::Semantic, &pinned, None, None, None, None).unwrap().id;
+        age(&conn, &oldest, 10);
+        age(&conn, &newer, 5);
+
+        assert_eq!(make_room_for_pin(&conn, 0, PinOverflow::Reject).unwrap(), PinRoom::Made(vec![]));
+        assert_eq!(make_room_for_pin(&conn, 3, PinOverflow::Reject).unwrap(), PinRoom::Made(vec![]));
+        assert_eq!(make_room_for_pin(&conn, 2, PinOverflow::Reject).unwrap(), PinRoom::Full(2));
+        assert_eq!(list_pinned(&conn).unwrap().len(), 2);
+
+        // Evicting unpins the least recently reinforced, keeping its other tags
+        assert_eq!(make_room_for_pin(&conn, 2, PinOverflow::Evict).unwrap(), PinRoom::Made(vec![oldest.clone()]));
+        assert_eq!(get(&conn, &oldest).unwrap().unwrap().tags, ["infra"]);
+        let still: Vec<String> = list_pinned(&conn).unwrap().into_iter().map(|(m, _)| m.id).collect();
+        assert_eq!(still, [newer]);
+    }
+
+    #[test]
+    fn test_keyword_only_store() {
+        let data = crate::config::test_data_dir();
+        let conn = crate::db::open_in_memory(&data).unwrap();
+        let emb = vec![0.1f32; crate::embed::embedding_dim()];
+        let embedded = store(&conn, "Atlas deploys on Fridays", &MemoryType::Semantic, &[], None, None, Some(&emb), None).unwrap();
+        let keyword_only = store_with_expiry(
+            &conn, "Zanzibar gateway timeout is 45 seconds", &MemoryType::Semantic, &[], None, None, Some(&emb), None, None, true,
+        )
+        .unwrap();
+        let has_embedding = |id: &str| -> bool {
+            conn.query_row("SELECT embedding IS NOT NULL FROM memories WHERE id = ?1", params![id], |r| r.get(0)).unwrap()
+        };
+
+        assert!(skips_embedding(&conn, &keyword_only.id));
+        assert!(!skips_embedding(&conn, &embedded.id));
+        assert!(has_embedding(&embedded.id));
+        assert!(!has_embedding(&keyword_only.id));
+
+        // Edits don't give it a vector either, and it isn't reported as missing one
+        update(&conn, &keyword_only.id, Some("Zanzibar gateway timeout is 60 seconds"), None, None, None, Some(&emb)).unwrap();
+        assert!(!has_embedding(&keyword_only.id));
+        assert!(vector_drift(&conn).unwrap().0.is_empty());
+
+        // Semantic recall still finds it by keyword
+        let found: Vec<String> = crate::db::search::semantic_search_all(&conn, "zanzibar timeout", &emb, 5)
+            .unwrap()
+            .into_iter()
+            .map(|(m, _)| m.id)
+            .collect();
+        assert!(found.contains(&keyword_only.id), "{found:?}");
+    }
 }
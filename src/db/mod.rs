@@ -1,3 +1,8 @@
+pub mod audit;
+#[cfg(feature = "pro")]
+pub mod consolidation;
+pub mod conflicts;
+pub mod doc_freqs;
 pub mod graph;
 pub mod memories;
 pub mod recall;
@@ -6,10 +11,12 @@ pub mod search;
 pub mod webhooks;
 
 use anyhow::Result;
-use rusqlite::Connection;
-use std::sync::Once;
+use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::functions::FunctionFlags;
+use std::sync::{Once, OnceLock};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use crate::config::Config;
+use crate::config::{Config, VectorQuantization};
 
 /// Register sqlite-vec extension exactly once.
 static SQLITE_VEC_INIT: Once = Once::new();
@@ -24,6 +31,81 @@ fn init_sqlite_vec() {
     });
 }
 
+/// Whether sqlite-vec actually loaded, probed once by the first `open`.
+static SQLITE_VEC_AVAILABLE: OnceLock<bool> = OnceLock::new();
+
+/// Check that the vec0 extension is usable on `conn`. A broken install (wrong
+/// architecture, bad build) otherwise only shows up as cryptic "no such
+/// module" errors deep inside vector queries.
+fn probe_sqlite_vec(conn: &Connection) -> bool {
+    *SQLITE_VEC_AVAILABLE.get_or_init(|| {
+        match conn.query_row("SELECT vec_version()", [], |r| r.get::<_, String>(0)) {
+            Ok(version) => {
+                tracing::debug!("sqlite-vec {version} loaded");
+                true
+            }
+            Err(e) => {
+                tracing::warn!(
+                    "sqlite-vec failed to load ({e}). Vector search is disabled and recall falls back to \
+                     keyword search; reinstall ctxovrflw for this platform to restore semantic search."
+                );
+                false
+            }
+        }
+    })
+}
+
+/// Whether vectors can be stored and searched. Vector reads and writes in
+/// this module are no-ops when sqlite-vec failed to load, so search degrades
+/// to keyword instead of erroring. Assumed true until the first `open`.
+pub fn vectors_available() -> bool {
+    SQLITE_VEC_AVAILABLE.get().copied().unwrap_or(true)
+}
+
+/// Workspace for memories created before workspaces existed, and the initial active one.
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// Set by `--all-workspaces`: reads on connections opened afterwards span every workspace.
+static ALL_WORKSPACES: AtomicBool = AtomicBool::new(false);
+
+pub fn set_all_workspaces(all: bool) {
+    ALL_WORKSPACES.store(all, Ordering::Relaxed);
+}
+
+/// Register the SQL functions that scope queries to the active workspace:
+/// `current_workspace()` names it (used for writes) and `in_workspace(ws)` is
+/// the read filter, true for every row when `--all-workspaces` is set.
+fn register_workspace_functions(conn: &Connection) -> Result<()> {
+    let current = Config::load()
+        .map(|c| c.current_workspace)
+        .unwrap_or_else(|_| DEFAULT_WORKSPACE.to_string());
+    let all = ALL_WORKSPACES.load(Ordering::Relaxed);
+    let flags = FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC;
+
+    let name = current.clone();
+    conn.create_scalar_function("current_workspace", 0, flags, move |_| Ok(name.clone()))?;
+    conn.create_scalar_function("in_workspace", 1, flags, move |ctx| {
+        if all {
+            return Ok(true);
+        }
+        let ws: Option<String> = ctx.get(0)?;
+        Ok(ws.as_deref().unwrap_or(DEFAULT_WORKSPACE) == current)
+    })?;
+    Ok(())
+}
+
+/// Workspaces with at least one live memory, and their memory counts.
+pub fn list_workspaces(conn: &Connection) -> Result<Vec<(String, usize)>> {
+    let mut stmt = conn.prepare(
+        "SELECT workspace, COUNT(*) FROM memories WHERE deleted = 0
+         GROUP BY workspace ORDER BY workspace",
+    )?;
+    let workspaces = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(workspaces)
+}
+
 pub fn open() -> Result<Connection> {
     let path = Config::db_path()?;
 
@@ -31,7 +113,22 @@ pub fn open() -> Result<Connection> {
     init_sqlite_vec();
 
     let conn = Connection::open(&path)?;
+    prepare(&conn)?;
+    Ok(conn)
+}
 
+/// A private in-memory database with the full schema, for tests. Config is
+/// read from the test's data directory, never the user's.
+#[cfg(test)]
+pub(crate) fn open_in_memory(_data: &crate::config::TestDataDir) -> Result<Connection> {
+    init_sqlite_vec();
+    let conn = Connection::open_in_memory()?;
+    prepare(&conn)?;
+    Ok(conn)
+}
+
+/// Pragmas, SQL functions and migrations every connection needs.
+fn prepare(conn: &Connection) -> Result<()> {
     // Performance pragmas
     conn.execute_batch(
         "
@@ -42,12 +139,256 @@ pub fn open() -> Result<Connection> {
         ",
     )?;
 
-    migrate(&conn)?;
+    register_workspace_functions(conn)?;
+    migrate(conn)?;
+    conflicts::migrate(conn)?;
+    audit::migrate(conn)?;
+    doc_freqs::migrate(conn)?;
     #[cfg(feature = "pro")]
-    graph::migrate(&conn)?;
+    graph::migrate(conn)?;
     #[cfg(feature = "pro")]
-    webhooks::migrate(&conn)?;
-    Ok(conn)
+    webhooks::migrate(conn)?;
+    #[cfg(feature = "pro")]
+    consolidation::migrate(conn)?;
+    Ok(())
+}
+
+/// Create the `memory_vectors` vec0 table in the given storage format.
+pub fn create_vector_table(conn: &Connection, dim: usize, quantization: VectorQuantization) -> Result<()> {
+    create_vec0(conn, "memory_vectors", dim, quantization)
+}
+
+fn create_vec0(conn: &Connection, table: &str, dim: usize, quantization: VectorQuantization) -> Result<()> {
+    let column = match quantization {
+        VectorQuantization::None => format!("float[{dim}]"),
+        VectorQuantization::Int8 => format!("int8[{dim}]"),
+    };
+    conn.execute_batch(&format!(
+        "CREATE VIRTUAL TABLE IF NOT EXISTS {table} USING vec0(
+            id TEXT PRIMARY KEY,
+            embedding {column}
+        );"
+    ))?;
+    Ok(())
+}
+
+fn table_sql(conn: &Connection, table: &str) -> Option<String> {
+    conn.query_row("SELECT sql FROM sqlite_master WHERE name = ?1", [table], |r| r.get(0))
+        .ok()
+}
+
+fn quantization_of(sql: Option<&str>) -> VectorQuantization {
+    match sql {
+        Some(sql) if sql.contains("int8[") => VectorQuantization::Int8,
+        _ => VectorQuantization::None,
+    }
+}
+
+/// Vector dimension declared in a vec0 table's schema.
+fn dim_of(sql: &str) -> Option<usize> {
+    let start = sql.find('[')? + 1;
+    let end = start + sql[start..].find(']')?;
+    sql[start..end].trim().parse().ok()
+}
+
+/// Storage format of the existing `memory_vectors` table, read from its schema.
+pub fn vector_quantization(conn: &Connection) -> VectorQuantization {
+    quantization_of(table_sql(conn, "memory_vectors").as_deref())
+}
+
+/// Table holding a model's vectors while another model is active.
+fn parked_vector_table(model_id: &str) -> String {
+    let slug: String = model_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' })
+        .collect();
+    format!("vectors_{slug}")
+}
+
+/// Copy every vector between two vec0 tables of the same dimension and format.
+fn copy_vectors(conn: &Connection, from: &str, to: &str, quantization: VectorQuantization) -> Result<()> {
+    let value = match quantization {
+        VectorQuantization::None => "embedding",
+        VectorQuantization::Int8 => "vec_int8(embedding)",
+    };
+    conn.execute_batch(&format!("INSERT INTO {to} (id, embedding) SELECT id, {value} FROM {from};"))?;
+    Ok(())
+}
+
+/// Create the per-model vector bookkeeping and, on a fresh database, an empty
+/// `memory_vectors` for the configured model. Never swaps models: opening a
+/// connection mustn't depend on which model this process happens to have
+/// loaded; that's [`activate_vector_model`]'s job.
+fn ensure_vector_table(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS vector_models (
+            model_id    TEXT PRIMARY KEY,
+            dim         INTEGER NOT NULL,
+            active      INTEGER NOT NULL DEFAULT 0,
+            parked_at   TEXT
+        );",
+    )?;
+    let current_sql = table_sql(conn, "memory_vectors");
+    let active: Option<(String, usize)> = conn
+        .query_row("SELECT model_id, dim FROM vector_models WHERE active = 1", [], |r| {
+            Ok((r.get(0)?, r.get(1)?))
+        })
+        .optional()?;
+
+    match (current_sql, active) {
+        (Some(_), Some(_)) => {}
+        // The table was dropped behind our back; start it over for the same model
+        (None, Some((_, dim))) => {
+            create_vector_table(conn, dim, VectorQuantization::default())?;
+        }
+        (current_sql, None) => {
+            let cfg = Config::load().unwrap_or_default();
+            let dim = match &current_sql {
+                // Built before vectors were tracked per model, by whichever one was configured
+                Some(sql) => dim_of(sql).unwrap_or(cfg.embedding_dim),
+                None => {
+                    create_vector_table(conn, cfg.embedding_dim, cfg.vector_quantization)?;
+                    cfg.embedding_dim
+                }
+            };
+            conn.execute(
+                "INSERT OR REPLACE INTO vector_models (model_id, dim, active) VALUES (?1, ?2, 1)",
+                params![cfg.embedding_model, dim],
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// The model whose vectors are in `memory_vectors`.
+pub fn active_vector_model(conn: &Connection) -> Result<Option<String>> {
+    if !vectors_available() {
+        return Ok(None);
+    }
+    Ok(conn
+        .query_row("SELECT model_id FROM vector_models WHERE active = 1", [], |r| r.get(0))
+        .optional()?)
+}
+
+/// Make `memory_vectors` hold `model_id`'s vectors. Called by `model switch`
+/// and by the daemon for the model it has loaded, never on a plain `open`.
+///
+/// Vectors are only comparable within one model, so when the model changes the
+/// outgoing model's vectors are parked in a table of their own and the incoming
+/// model's parked vectors, if any, are moved back — switching models (and back)
+/// never throws embeddings away. Memories left without a vector are embedded on
+/// demand by `maintenance::embed_missing_vectors`.
+pub fn activate_vector_model(
+    conn: &Connection,
+    model_id: &str,
+    dim: usize,
+    quantization: VectorQuantization,
+) -> Result<()> {
+    if !vectors_available() {
+        return Ok(());
+    }
+    let current_sql = table_sql(conn, "memory_vectors");
+    let active = active_vector_model(conn)?;
+    if current_sql.is_some() && active.as_deref() == Some(model_id) {
+        return Ok(());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    if let Some(outgoing) = &active {
+        match &current_sql {
+            Some(sql) => {
+                let parked = parked_vector_table(outgoing);
+                let outgoing_quantization = quantization_of(Some(sql));
+                tx.execute_batch(&format!("DROP TABLE IF EXISTS {parked};"))?;
+                create_vec0(&tx, &parked, dim_of(sql).unwrap_or(dim), outgoing_quantization)?;
+                copy_vectors(&tx, "memory_vectors", &parked, outgoing_quantization)?;
+                tx.execute_batch("DROP TABLE memory_vectors;")?;
+                tx.execute(
+                    "UPDATE vector_models SET active = 0, parked_at = ?2 WHERE model_id = ?1",
+                    params![outgoing, chrono::Utc::now().to_rfc3339()],
+                )?;
+            }
+            // The table was dropped behind our back; nothing left to park
+            None => {
+                tx.execute("DELETE FROM vector_models WHERE model_id = ?1", params![outgoing])?;
+            }
+        }
+    }
+
+    let parked = parked_vector_table(model_id);
+    let parked_at: Option<String> = tx
+        .query_row(
+            "SELECT parked_at FROM vector_models WHERE model_id = ?1 AND active = 0",
+            params![model_id],
+            |r| r.get(0),
+        )
+        .optional()?
+        .flatten();
+    let parked_sql = table_sql(&tx, &parked);
+    let dim = match (&parked_sql, parked_at) {
+        (Some(sql), Some(parked_at)) => {
+            let parked_quantization = quantization_of(Some(sql));
+            let dim = dim_of(sql).unwrap_or(dim);
+            create_vec0(&tx, "memory_vectors", dim, parked_quantization)?;
+            copy_vectors(&tx, &parked, "memory_vectors", parked_quantization)?;
+            tx.execute_batch(&format!("DROP TABLE {parked};"))?;
+
+            // Vectors of memories purged or edited while parked no longer match
+            // them (timestamps are compared to the second, so ties count as edited)
+            let stale: Vec<String> = tx
+                .prepare(
+                    "SELECT v.id FROM memory_vectors v LEFT JOIN memories m ON m.id = v.id
+                     WHERE m.id IS NULL OR datetime(m.updated_at) >= datetime(?1)",
+                )?
+                .query_map(params![parked_at], |r| r.get(0))?
+                .collect::<std::result::Result<_, _>>()?;
+            memories::delete_vectors(&tx, &stale)?;
+            dim
+        }
+        _ => {
+            create_vector_table(&tx, dim, quantization)?;
+            dim
+        }
+    };
+    tx.execute(
+        "INSERT INTO vector_models (model_id, dim, active) VALUES (?1, ?2, 1)
+         ON CONFLICT(model_id) DO UPDATE SET dim = excluded.dim, active = 1, parked_at = NULL",
+        params![model_id, dim],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// [`activate_vector_model`] for `cfg`'s embedding model.
+pub fn activate_configured_model(conn: &Connection, cfg: &Config) -> Result<()> {
+    activate_vector_model(conn, &cfg.embedding_model, cfg.embedding_dim, cfg.vector_quantization)
+}
+
+/// An embedding model with stored vectors.
+#[derive(Debug, Clone)]
+pub struct VectorModel {
+    pub model_id: String,
+    pub dim: usize,
+    pub vectors: usize,
+    /// Its vectors are the ones in `memory_vectors`, used for search.
+    pub active: bool,
+}
+
+/// Every model with stored vectors, the active one first.
+pub fn vector_models(conn: &Connection) -> Result<Vec<VectorModel>> {
+    let mut stmt = conn.prepare("SELECT model_id, dim, active FROM vector_models ORDER BY active DESC, model_id")?;
+    let rows: Vec<(String, usize, bool)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<std::result::Result<_, _>>()?;
+    let mut models = Vec::with_capacity(rows.len());
+    for (model_id, dim, active) in rows {
+        let table = if active { "memory_vectors".to_string() } else { parked_vector_table(&model_id) };
+        let vectors: usize = conn
+            .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get(0))
+            .unwrap_or(0);
+        models.push(VectorModel { model_id, dim, vectors, active });
+    }
+    Ok(models)
 }
 
 fn migrate(conn: &Connection) -> Result<()> {
@@ -91,7 +432,7 @@ fn migrate(conn: &Connection) -> Result<()> {
             VALUES ('delete', old.rowid, old.content, old.tags);
         END;
 
-        CREATE TRIGGER IF NOT EXISTS memories_au AFTER UPDATE ON memories BEGIN
+        CREATE TRIGGER IF NOT EXISTS memories_au AFTER UPDATE OF content, tags ON memories BEGIN
             INSERT INTO memories_fts(memories_fts, rowid, content, tags)
             VALUES ('delete', old.rowid, old.content, old.tags);
             INSERT INTO memories_fts(rowid, content, tags)
@@ -128,14 +469,51 @@ fn migrate(conn: &Connection) -> Result<()> {
     }
     conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_memories_agent_id ON memories(agent_id);")?;
 
-    // sqlite-vec virtual table for vector search
-    let dim = crate::embed::embedding_dim();
-    conn.execute_batch(&format!(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS memory_vectors USING vec0(
-            id TEXT PRIMARY KEY,
-            embedding float[{dim}]
-        );"
-    ))?;
+    // Add workspace column if missing — existing memories land in the default workspace
+    let has_workspace: bool = conn
+        .prepare("SELECT workspace FROM memories LIMIT 0")
+        .is_ok();
+    if !has_workspace {
+        conn.execute_batch(&format!(
+            "ALTER TABLE memories ADD COLUMN workspace TEXT NOT NULL DEFAULT '{DEFAULT_WORKSPACE}';"
+        ))?;
+    }
+    conn.execute_batch("CREATE INDEX IF NOT EXISTS idx_memories_workspace ON memories(workspace);")?;
+
+    // Add access tracking columns if missing. Recall bumps them constantly, so
+    // the FTS trigger is narrowed to the columns it indexes at the same time.
+    let has_access_count: bool = conn
+        .prepare("SELECT access_count FROM memories LIMIT 0")
+        .is_ok();
+    if !has_access_count {
+        conn.execute_batch(
+            "
+            ALTER TABLE memories ADD COLUMN access_count INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE memories ADD COLUMN last_accessed_at TEXT;
+            DROP TRIGGER IF EXISTS memories_au;
+            CREATE TRIGGER memories_au AFTER UPDATE OF content, tags ON memories BEGIN
+                INSERT INTO memories_fts(memories_fts, rowid, content, tags)
+                VALUES ('delete', old.rowid, old.content, old.tags);
+                INSERT INTO memories_fts(rowid, content, tags)
+                VALUES (new.rowid, new.content, new.tags);
+            END;
+            ",
+        )?;
+    }
+
+    // Add skip_embedding column if missing — set on memories stored keyword-only
+    let has_skip_embedding: bool = conn
+        .prepare("SELECT skip_embedding FROM memories LIMIT 0")
+        .is_ok();
+    if !has_skip_embedding {
+        conn.execute_batch("ALTER TABLE memories ADD COLUMN skip_embedding INTEGER NOT NULL DEFAULT 0;")?;
+    }
+
+    // sqlite-vec virtual table for vector search. The storage format is only
+    // chosen when the table is created; switching later goes through reindex.
+    if probe_sqlite_vec(conn) {
+        ensure_vector_table(conn)?;
+    }
 
     // Recall logs for importance scoring (Phase 2: Adaptive Scoring)
     conn.execute_batch(
@@ -167,3 +545,50 @@ fn migrate(conn: &Connection) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memories::MemoryType;
+
+    fn count(conn: &Connection, table: &str) -> usize {
+        conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get(0)).unwrap()
+    }
+
+    #[test]
+    fn test_switching_models_parks_and_restores_vectors() {
+        let data = crate::config::test_data_dir();
+        let conn = open_in_memory(&data).unwrap();
+        let cfg = Config::load().unwrap();
+        let emb = vec![0.5f32; cfg.embedding_dim];
+        let memory = memories::store(&conn, "Atlas deploys on Fridays", &MemoryType::Semantic, &[], None, None, Some(&emb), None).unwrap();
+        // Parked vectors of memories edited in the same second are dropped as stale
+        conn.execute("UPDATE memories SET updated_at = '2020-01-01T00:00:00Z'", []).unwrap();
+        assert_eq!(active_vector_model(&conn).unwrap().as_deref(), Some(cfg.embedding_model.as_str()));
+
+        // Re-running migrations under another configured model leaves the vectors alone
+        let mut other = cfg.clone();
+        other.embedding_model = "bge-base-en-v1.5".into();
+        other.save().unwrap();
+        prepare(&conn).unwrap();
+        assert_eq!(active_vector_model(&conn).unwrap().as_deref(), Some(cfg.embedding_model.as_str()));
+        assert_eq!(count(&conn, "memory_vectors"), 1);
+
+        // Activating drops memory_vectors and recreates it for the new model
+        activate_vector_model(&conn, "bge-base-en-v1.5", 768, VectorQuantization::None).unwrap();
+        let parked = parked_vector_table(&cfg.embedding_model);
+        assert_eq!(dim_of(&table_sql(&conn, "memory_vectors").unwrap()), Some(768));
+        assert_eq!(count(&conn, "memory_vectors"), 0);
+        assert_eq!(count(&conn, &parked), 1);
+        assert!(search::load_embedding(&conn, &memory.id).is_none());
+
+        // Switching back restores the parked vectors
+        activate_configured_model(&conn, &cfg).unwrap();
+        assert_eq!(count(&conn, "memory_vectors"), 1);
+        assert!(table_sql(&conn, &parked).is_none());
+        assert_eq!(search::load_embedding(&conn, &memory.id), Some(emb));
+        let models = vector_models(&conn).unwrap();
+        assert!(models[0].active && models[0].model_id == cfg.embedding_model);
+        assert!(models.iter().any(|m| !m.active && m.model_id == "bge-base-en-v1.5" && m.dim == 768));
+    }
+}
@@ -2,8 +2,10 @@
 //! Phase 2: Tracks every recall to build importance scores
 //! Phase 3: Decay calculation and rehydration
 
+use std::collections::HashMap;
+
 use anyhow::Result;
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 
 /// Log a recall event for importance tracking
 pub fn log_recall(
@@ -21,6 +23,43 @@ pub fn log_recall(
     Ok(())
 }
 
+/// Bump `access_count` and `last_accessed_at` for memories that were just
+/// returned by recall or fetched by ID
+pub fn record_access(conn: &Connection, memory_ids: &[&str]) -> Result<()> {
+    let mut stmt = conn.prepare_cached(
+        "UPDATE memories SET access_count = access_count + 1, last_accessed_at = datetime('now')
+         WHERE id = ?1",
+    )?;
+    for id in memory_ids {
+        stmt.execute([id])?;
+    }
+    Ok(())
+}
+
+/// `access_count` for each of `memory_ids`; unknown IDs are left out
+pub fn access_counts(conn: &Connection, memory_ids: &[&str]) -> Result<HashMap<String, i64>> {
+    let mut stmt = conn.prepare_cached("SELECT access_count FROM memories WHERE id = ?1")?;
+    let mut counts = HashMap::new();
+    for id in memory_ids {
+        if let Some(count) = stmt.query_row([id], |row| row.get::<_, i64>(0)).optional()? {
+            counts.insert(id.to_string(), count);
+        }
+    }
+    Ok(counts)
+}
+
+/// Access count and last access time for one memory
+pub fn access_stats(conn: &Connection, memory_id: &str) -> Result<Option<(i64, Option<String>)>> {
+    let stats = conn
+        .query_row(
+            "SELECT access_count, last_accessed_at FROM memories WHERE id = ?1",
+            [memory_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()?;
+    Ok(stats)
+}
+
 /// Update importance scores for all memories
 /// Call this periodically (e.g., hourly) via background task
 pub fn update_importance_scores(conn: &Connection) -> Result<usize> {
@@ -100,7 +139,7 @@ pub fn get_rehydration_candidates(
         "SELECT ms.memory_id, ms.decay_factor, ms.importance
          FROM memory_scores ms
          JOIN memories m ON ms.memory_id = m.id
-         WHERE m.deleted = 0
+         WHERE m.deleted = 0 AND in_workspace(m.workspace)
          AND (m.expires_at IS NULL OR m.expires_at > datetime('now'))
          ORDER BY ms.importance DESC
          LIMIT ?1"
@@ -190,3 +229,37 @@ pub fn get_important_memories(
             .map_err(Into::into)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::memories::{self, MemoryType};
+
+    #[test]
+    fn test_record_access_counts_each_fetch() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        let a = memories::store(&conn, "first", &MemoryType::Semantic, &[], None, None, None, None).unwrap();
+        let b = memories::store(&conn, "second", &MemoryType::Semantic, &[], None, None, None, None).unwrap();
+
+        record_access(&conn, &[&a.id, &b.id]).unwrap();
+        record_access(&conn, &[&a.id, "no-such-memory"]).unwrap();
+
+        let counts = access_counts(&conn, &[&a.id, &b.id, "no-such-memory"]).unwrap();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts[&a.id], 2);
+        assert_eq!(counts[&b.id], 1);
+        let (count, last) = access_stats(&conn, &a.id).unwrap().unwrap();
+        assert_eq!(count, 2);
+        assert!(last.is_some());
+    }
+
+    #[test]
+    fn test_access_counts_start_at_zero() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        let m = memories::store(&conn, "untouched", &MemoryType::Semantic, &[], None, None, None, None).unwrap();
+        assert_eq!(access_counts(&conn, &[&m.id]).unwrap()[&m.id], 0);
+        assert_eq!(access_stats(&conn, &m.id).unwrap(), Some((0, None)));
+    }
+}
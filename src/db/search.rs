@@ -1,4 +1,3 @@
-#[cfg(feature = "pro")]
 use std::collections::HashMap;
 
 use anyhow::Result;
@@ -10,11 +9,17 @@ use super::memories::Memory;
 /// Below this threshold, results are considered noise and filtered out.
 pub const MIN_SEMANTIC_SCORE: f64 = 0.15;
 
+/// Cosine similarity above which two recall results are treated as near-duplicates.
+pub const NEAR_DUPLICATE_THRESHOLD: f64 = 0.95;
+
+/// Score assigned to results pulled in through knowledge-graph relations rather than
+/// by the search itself. Kept low so they rank below every direct match.
+pub const GRAPH_BOOST_SCORE: f64 = 0.01;
+
 /// Hard lower bound for adaptive thresholding (model-aware threshold never drops below this).
 const MIN_ADAPTIVE_THRESHOLD: f64 = 0.05;
 
 /// RRF constant (k=60 is standard). Higher k reduces the impact of rank position.
-#[cfg(feature = "pro")]
 const RRF_K: f64 = 60.0;
 
 /// Indicates which search method produced the results
@@ -35,6 +40,118 @@ impl std::fmt::Display for SearchMethod {
     }
 }
 
+/// Why a single recall result matched (reported by `recall --explain`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchSource {
+    Keyword,
+    Semantic,
+    Hybrid,
+    /// Only the memory's subject or a tag matched the query (hybrid search)
+    #[cfg(feature = "pro")]
+    Subject,
+    GraphBoost,
+}
+
+impl From<SearchMethod> for MatchSource {
+    fn from(method: SearchMethod) -> Self {
+        match method {
+            SearchMethod::Keyword => MatchSource::Keyword,
+            SearchMethod::Semantic => MatchSource::Semantic,
+            SearchMethod::Hybrid => MatchSource::Hybrid,
+        }
+    }
+}
+
+impl std::fmt::Display for MatchSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchSource::Keyword => write!(f, "keyword"),
+            MatchSource::Semantic => write!(f, "semantic"),
+            MatchSource::Hybrid => write!(f, "hybrid"),
+            #[cfg(feature = "pro")]
+            MatchSource::Subject => write!(f, "subject"),
+            MatchSource::GraphBoost => write!(f, "graph-boost"),
+        }
+    }
+}
+
+/// Match source, raw score and matched query terms for one recall result.
+#[derive(Debug, Clone)]
+pub struct MatchExplanation {
+    pub source: MatchSource,
+    pub score: f64,
+    pub matched_terms: Vec<String>,
+}
+
+impl MatchExplanation {
+    pub fn summary(&self) -> String {
+        let mut s = format!("match: {}, raw score: {:.4}", self.source, self.score);
+        if !self.matched_terms.is_empty() {
+            s.push_str(&format!(", terms: {}", self.matched_terms.join(", ")));
+        }
+        s
+    }
+}
+
+/// Explain a result. Matched terms are only reported for sources that involve
+/// keyword search — the query terms (stopwords removed) found in the content.
+pub fn explain_match(source: MatchSource, score: f64, query: &str, content: &str) -> MatchExplanation {
+    let matched_terms = if matches!(source, MatchSource::Keyword | MatchSource::Hybrid) {
+        let content_lower = content.to_lowercase();
+        let mut seen = std::collections::HashSet::new();
+        let mut terms = query_terms(query);
+        terms.retain(|t| seen.insert(t.clone()) && content_lower.contains(t.as_str()));
+        terms
+    } else {
+        Vec::new()
+    };
+    MatchExplanation { source, score, matched_terms }
+}
+
+/// Byte ranges of the words in `content` that match a query term, in order,
+/// for highlighting. A word matches a term exactly or by stem ("deploy" marks
+/// "deploying" and "deployed"); nothing is marked when a result matched on
+/// meaning alone.
+pub fn highlight_ranges(query: &str, content: &str) -> Vec<std::ops::Range<usize>> {
+    let stems: std::collections::HashSet<String> = query_terms(query).iter().map(|t| stem(t).to_string()).collect();
+    if stems.is_empty() {
+        return Vec::new();
+    }
+    word_ranges(content)
+        .into_iter()
+        .filter(|range| stems.contains(stem(&content[range.clone()].to_lowercase())))
+        .collect()
+}
+
+/// Byte ranges of the words in `text`: runs of letters, digits and `_`. Queries
+/// and content are split the same way, so `deploy?` and `(deploy)` match `deploy`.
+fn word_ranges(text: &str) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        match (c.is_alphanumeric() || c == '_', start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                ranges.push(from..i);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    ranges
+}
+
+/// Crude English stem: a common inflection suffix, then a trailing "e",
+/// stripped while at least three characters remain. Enough to line up
+/// "cache"/"caches"/"cached"/"caching".
+fn stem(word: &str) -> &str {
+    fn strip<'a>(word: &'a str, suffix: &str) -> Option<&'a str> {
+        word.strip_suffix(suffix).filter(|rest| rest.chars().count() >= 3)
+    }
+    let word = ["ing", "ed", "s", "ly"].iter().find_map(|suffix| strip(word, suffix)).unwrap_or(word);
+    strip(word, "e").unwrap_or(word)
+}
+
 /// Common English stopwords to exclude from FTS queries
 const STOPWORDS: &[&str] = &[
     "a", "an", "the", "is", "are", "was", "were", "be", "been", "being",
@@ -52,10 +169,8 @@ const STOPWORDS: &[&str] = &[
 /// Sanitize a query string for FTS5.
 /// Removes stopwords, wraps tokens in quotes, uses OR for broader matching.
 fn sanitize_fts_query(query: &str) -> String {
-    let tokens: Vec<String> = query
-        .split_whitespace()
-        .map(|t| t.to_lowercase().replace('"', "").replace('?', "").replace('.', "").replace(',', ""))
-        .filter(|t| t.len() > 1 && !STOPWORDS.contains(&t.as_str()))
+    let tokens: Vec<String> = query_terms(query)
+        .into_iter()
         .map(|t| format!("\"{}\"", t))
         .collect();
 
@@ -68,6 +183,14 @@ fn sanitize_fts_query(query: &str) -> String {
     tokens.join(" OR ")
 }
 
+/// Lowercased query words (see [`word_ranges`]) without stopwords or single characters.
+fn query_terms(query: &str) -> Vec<String> {
+    word_ranges(query)
+        .into_iter()
+        .map(|range| query[range].to_lowercase())
+        .filter(|t| t.chars().count() > 1 && !STOPWORDS.contains(&t.as_str()))
+        .collect()
+}
 
 fn pinned_policy_boost(tags: &[String], subject: &Option<String>) -> f64 {
     let mut boost: f64 = 0.0;
@@ -88,20 +211,26 @@ fn pinned_policy_boost(tags: &[String], subject: &Option<String>) -> f64 {
 
 /// Keyword search via FTS5 (free tier)
 pub fn keyword_search(conn: &Connection, query: &str, limit: usize) -> Result<Vec<(Memory, f64)>> {
+    fts_search(conn, query, limit, false)
+}
+
+/// FTS5 matches for `query`, optionally only among keyword-only memories.
+fn fts_search(conn: &Connection, query: &str, limit: usize, unembedded_only: bool) -> Result<Vec<(Memory, f64)>> {
     let sanitized = sanitize_fts_query(query);
     let mut stmt = conn.prepare(
         "SELECT m.id, m.content, m.type, m.tags, m.subject, m.source, m.agent_id, m.expires_at, m.created_at, m.updated_at,
                 rank
          FROM memories_fts fts
          JOIN memories m ON m.rowid = fts.rowid
-         WHERE memories_fts MATCH ?1 AND m.deleted = 0
+         WHERE memories_fts MATCH ?1 AND m.deleted = 0 AND in_workspace(m.workspace)
          AND (m.expires_at IS NULL OR m.expires_at > datetime('now'))
+         AND (?3 = 0 OR m.skip_embedding = 1)
          ORDER BY rank
          LIMIT ?2",
     )?;
 
     let mut results = stmt
-        .query_map(params![sanitized, limit], |row| {
+        .query_map(params![sanitized, limit, unembedded_only], |row| {
             let rank: f64 = row.get(10)?;
             Ok((
                 Memory {
@@ -132,6 +261,39 @@ pub fn keyword_search(conn: &Connection, query: &str, limit: usize) -> Result<Ve
     Ok(results)
 }
 
+/// Best score a keyword-only match can reach in [`semantic_search_all`].
+const KEYWORD_ONLY_CEILING: f64 = 0.8;
+
+/// [`semantic_search`], plus keyword matches among memories stored without
+/// an embedding (`skip_embedding`), which have no vector to be found by. The
+/// share of query terms such a memory contains is mapped onto the range from
+/// the model's semantic baseline up to the best semantic hit (at most
+/// [`KEYWORD_ONLY_CEILING`]), so matching every term ties with that hit
+/// instead of outranking every semantic result.
+pub fn semantic_search_all(
+    conn: &Connection,
+    query: &str,
+    query_embedding: &[f32],
+    limit: usize,
+) -> Result<Vec<(Memory, f64)>> {
+    let mut results = semantic_search(conn, query_embedding, limit)?;
+    let terms = query_terms(query);
+    if terms.is_empty() {
+        return Ok(results);
+    }
+    let ceiling = results.first().map_or(KEYWORD_ONLY_CEILING, |(_, top)| top.min(KEYWORD_ONLY_CEILING));
+    let floor = model_semantic_baseline().min(ceiling);
+    for (mem, _) in fts_search(conn, query, limit, true)? {
+        let content = mem.content.to_lowercase();
+        let share = terms.iter().filter(|t| content.contains(t.as_str())).count() as f64 / terms.len() as f64;
+        let score = (floor + share * (ceiling - floor) + pinned_policy_boost(&mem.tags, &mem.subject)).min(ceiling);
+        results.push((mem, score));
+    }
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    results.truncate(limit);
+    Ok(results)
+}
+
 fn model_semantic_baseline() -> f64 {
     // Light model-aware baseline tuning (can evolve into persisted calibration stats).
     let model = crate::config::Config::load()
@@ -211,17 +373,34 @@ pub fn semantic_search(
     query_embedding: &[f32],
     limit: usize,
 ) -> Result<Vec<(Memory, f64)>> {
-    let embedding_bytes: Vec<u8> = query_embedding.iter().flat_map(|f| f.to_le_bytes()).collect();
+    if !super::vectors_available() {
+        return Ok(Vec::new());
+    }
+    // Query vectors must match the stored format; int8 distances are in
+    // quantized units and get scaled back before scoring.
+    let quantization = super::vector_quantization(conn);
+    let (embedding_bytes, match_expr, distance_scale) = match quantization {
+        crate::config::VectorQuantization::None => (
+            query_embedding.iter().flat_map(|f| f.to_le_bytes()).collect::<Vec<u8>>(),
+            "?1",
+            1.0,
+        ),
+        crate::config::VectorQuantization::Int8 => (
+            super::memories::quantize_int8(query_embedding),
+            "vec_int8(?1)",
+            super::memories::INT8_SCALE as f64,
+        ),
+    };
 
     // sqlite-vec uses a KNN query via the virtual table's match syntax
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&format!(
         "SELECT v.id, v.distance, m.content, m.type, m.tags, m.subject, m.source, m.agent_id, m.expires_at, m.created_at, m.updated_at
          FROM memory_vectors v
          JOIN memories m ON m.id = v.id
-         WHERE v.embedding MATCH ?1 AND k = ?2
-         AND m.deleted = 0
+         WHERE v.embedding MATCH {match_expr} AND k = ?2
+         AND m.deleted = 0 AND in_workspace(m.workspace)
          AND (m.expires_at IS NULL OR m.expires_at > datetime('now'))",
-    )?;
+    ))?;
 
     // Fetch more candidates than requested to allow for score filtering.
     // sqlite-vec's k parameter limits the KNN search, so we need headroom.
@@ -229,7 +408,7 @@ pub fn semantic_search(
 
     let adjusted: Vec<(Memory, f64)> = stmt
         .query_map(params![embedding_bytes, k], |row| {
-            let distance: f64 = row.get(1)?;
+            let distance: f64 = row.get::<_, f64>(1)? / distance_scale;
             let score = 1.0 - (distance * distance / 2.0);
             Ok((
                 Memory {
@@ -281,6 +460,65 @@ pub fn semantic_search(
     Ok(filtered)
 }
 
+/// Memories most similar to `id`, using its stored vector as the query (no
+/// re-embedding). Returns `None` when the memory has no vector yet.
+pub fn related(conn: &Connection, id: &str, limit: usize) -> Result<Option<Vec<(Memory, f64)>>> {
+    if !super::vectors_available() {
+        return Ok(None);
+    }
+    let embedding: Option<Vec<u8>> = conn
+        .query_row("SELECT embedding FROM memory_vectors WHERE id = ?1", params![id], |r| r.get(0))
+        .ok();
+    let Some(embedding) = embedding else {
+        return Ok(None);
+    };
+
+    // The stored blob is already in the table's format, so it can be matched as-is.
+    let (match_expr, distance_scale) = match super::vector_quantization(conn) {
+        crate::config::VectorQuantization::None => ("?1", 1.0),
+        crate::config::VectorQuantization::Int8 => ("vec_int8(?1)", super::memories::INT8_SCALE as f64),
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT v.id, v.distance, m.content, m.type, m.tags, m.subject, m.source, m.agent_id, m.expires_at, m.created_at, m.updated_at
+         FROM memory_vectors v
+         JOIN memories m ON m.id = v.id
+         WHERE v.embedding MATCH {match_expr} AND k = ?2
+         AND v.id != ?3
+         AND m.deleted = 0 AND in_workspace(m.workspace)
+         AND (m.expires_at IS NULL OR m.expires_at > datetime('now'))
+         ORDER BY v.distance",
+    ))?;
+
+    // Headroom for the memory itself and rows dropped by the filters above.
+    let k = (limit * 2 + 1).min(200);
+    let mut results: Vec<(Memory, f64)> = stmt
+        .query_map(params![embedding, k, id], |row| {
+            let distance: f64 = row.get::<_, f64>(1)? / distance_scale;
+            Ok((
+                Memory {
+                    id: row.get(0)?,
+                    content: row.get(2)?,
+                    memory_type: row
+                        .get::<_, String>(3)?
+                        .parse()
+                        .unwrap_or_default(),
+                    tags: serde_json::from_str(&row.get::<_, String>(4)?).unwrap_or_default(),
+                    subject: row.get(5)?,
+                    source: row.get(6)?,
+                    agent_id: row.get(7)?,
+                    expires_at: row.get(8)?,
+                    created_at: row.get(9)?,
+                    updated_at: row.get(10)?,
+                },
+                1.0 - (distance * distance / 2.0),
+            ))
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    results.truncate(limit);
+    Ok(Some(results))
+}
+
 #[cfg(feature = "pro")]
 /// Hybrid search: combines semantic (vector) and keyword (FTS5) results using
 /// Reciprocal Rank Fusion (RRF). This dramatically improves recall quality by
@@ -294,6 +532,19 @@ pub fn hybrid_search(
     query_embedding: &[f32],
     limit: usize,
 ) -> Result<Vec<(Memory, f64)>> {
+    let results = hybrid_search_sourced(conn, query, query_embedding, limit)?;
+    Ok(results.into_iter().map(|(mem, score, _)| (mem, score)).collect())
+}
+
+#[cfg(feature = "pro")]
+/// [`hybrid_search`], also reporting which lists each result came from: both
+/// (hybrid), semantic or keyword only, or only a subject/tag match.
+pub fn hybrid_search_sourced(
+    conn: &Connection,
+    query: &str,
+    query_embedding: &[f32],
+    limit: usize,
+) -> Result<Vec<(Memory, f64, MatchSource)>> {
     // Fetch more candidates from each source for better fusion
     let fetch_limit = (limit * 3).max(15);
 
@@ -311,15 +562,17 @@ pub fn hybrid_search(
         return Ok(vec![]);
     }
     if semantic_results.is_empty() {
-        return Ok(keyword_results.into_iter().take(limit).collect());
+        return Ok(keyword_results.into_iter().take(limit).map(|(m, s)| (m, s, MatchSource::Keyword)).collect());
     }
     if keyword_results.is_empty() {
-        return Ok(semantic_results.into_iter().take(limit).collect());
+        return Ok(semantic_results.into_iter().take(limit).map(|(m, s)| (m, s, MatchSource::Semantic)).collect());
     }
 
     // Build weighted RRF scores (rank fusion + normalized source score blending)
     let mut scores: HashMap<String, f64> = HashMap::new();
     let mut memories: HashMap<String, Memory> = HashMap::new();
+    // (found semantically, found by keyword) per memory
+    let mut found_by: HashMap<String, (bool, bool)> = HashMap::new();
 
     const W_SEMANTIC: f64 = 0.65;
     const W_KEYWORD: f64 = 0.45;
@@ -343,6 +596,7 @@ pub fn hybrid_search(
         let rrf = 1.0 / (RRF_K + rank as f64 + 1.0);
         let sem_norm = normalize(score, sem_min, sem_max);
         *scores.entry(mem.id.clone()).or_default() += (rrf * W_SEMANTIC) + (sem_norm * 0.20);
+        found_by.entry(mem.id.clone()).or_default().0 = true;
         memories.entry(mem.id.clone()).or_insert(mem);
     }
 
@@ -351,6 +605,7 @@ pub fn hybrid_search(
         let rrf = 1.0 / (RRF_K + rank as f64 + 1.0);
         let kw_norm = normalize(score, kw_min, kw_max);
         *scores.entry(mem.id.clone()).or_default() += (rrf * W_KEYWORD) + (kw_norm * 0.15);
+        found_by.entry(mem.id.clone()).or_default().1 = true;
         memories.entry(mem.id.clone()).or_insert(mem);
     }
 
@@ -362,10 +617,16 @@ pub fn hybrid_search(
     }
 
     // Sort by combined RRF score (highest first)
-    let mut fused: Vec<(Memory, f64)> = scores
+    let mut fused: Vec<(Memory, f64, MatchSource)> = scores
         .into_iter()
         .filter_map(|(id, score)| {
-            memories.remove(&id).map(|mem| (mem, score))
+            let source = match found_by.get(&id).copied().unwrap_or_default() {
+                (true, true) => MatchSource::Hybrid,
+                (true, false) => MatchSource::Semantic,
+                (false, true) => MatchSource::Keyword,
+                (false, false) => MatchSource::Subject,
+            };
+            memories.remove(&id).map(|mem| (mem, score, source))
         })
         .collect();
 
@@ -390,7 +651,7 @@ fn extract_subject_matches(conn: &Connection, query: &str, limit: usize) -> Vec<
     // Check if any known subjects appear in the query.
     // Skip subjects that are too generic (appear in too many memories)
     // — they'd flood results without adding signal.
-    if let Ok(subjects) = list_subjects(conn) {
+    if let Ok(subjects) = list_subjects(conn, SubjectSort::Count, None, 0) {
         for (subject, count) in subjects {
             // Skip subjects with too many memories (generic catch-alls)
             if count > 15 { continue; }
@@ -429,7 +690,7 @@ fn search_by_tags(conn: &Connection, query_words: &[&str], limit: usize) -> Resu
         let pattern = format!("%\"{}\"%", word);
         let mut stmt = conn.prepare(
             "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
-             FROM memories WHERE tags LIKE ?1 AND deleted = 0
+             FROM memories WHERE tags LIKE ?1 AND deleted = 0 AND in_workspace(workspace)
              AND (expires_at IS NULL OR expires_at > datetime('now'))
              ORDER BY updated_at DESC LIMIT ?2",
         )?;
@@ -462,7 +723,7 @@ pub fn by_subject_fuzzy(conn: &Connection, subject: &str, limit: usize) -> Resul
     let pattern = format!("%{}%", subject.replace('%', "\\%").replace('_', "\\_"));
     let mut stmt = conn.prepare(
         "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
-         FROM memories WHERE subject LIKE ?1 ESCAPE '\\' AND deleted = 0
+         FROM memories WHERE subject LIKE ?1 ESCAPE '\\' AND deleted = 0 AND in_workspace(workspace)
          AND (expires_at IS NULL OR expires_at > datetime('now'))
          ORDER BY updated_at DESC LIMIT ?2",
     )?;
@@ -486,11 +747,72 @@ pub fn by_subject_fuzzy(conn: &Connection, subject: &str, limit: usize) -> Resul
     Ok(results)
 }
 
+/// Lowest similarity at which a subject counts as a fuzzy match.
+const FUZZY_SUBJECT_MIN: f64 = 0.7;
+
+/// Shortest name that can match another by containment.
+const FUZZY_SUBJECT_MIN_CONTAINED: usize = 3;
+
+/// Subjects similar to `subject`, most similar first, with a 0-1 confidence.
+/// Containment on token boundaries (`myapp` → `project:myapp`, but not `app`
+/// → `project:myapp`) qualifies for names of at least
+/// [`FUZZY_SUBJECT_MIN_CONTAINED`] characters, scaled by how much of the longer
+/// name it covers; other names qualify on edit distance, so a typo like
+/// `projct:myapp` still finds `project:myapp`. Case is ignored.
+pub fn match_subjects(conn: &Connection, subject: &str) -> Result<Vec<(String, f64)>> {
+    let wanted = subject.to_lowercase();
+    let mut matches: Vec<(String, f64)> = list_subjects(conn, SubjectSort::Name, None, 0)?
+        .into_iter()
+        .filter_map(|(candidate, _)| {
+            let score = subject_similarity(&wanted, &candidate.to_lowercase());
+            (score >= FUZZY_SUBJECT_MIN).then_some((candidate, score))
+        })
+        .collect();
+    matches.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Ok(matches)
+}
+
+fn subject_similarity(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
+    }
+    let (short, long) = if a.chars().count() <= b.chars().count() { (a, b) } else { (b, a) };
+    let long_len = long.chars().count() as f64;
+    if short.chars().count() >= FUZZY_SUBJECT_MIN_CONTAINED && contains_token(long, short) {
+        return FUZZY_SUBJECT_MIN + 0.25 * short.chars().count() as f64 / long_len;
+    }
+    1.0 - levenshtein(a, b) as f64 / long_len
+}
+
+/// Whether `needle` occurs in `haystack` with no letter or digit right before or after it.
+fn contains_token(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let before = haystack[..start].chars().next_back();
+        let after = haystack[start + needle.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != cb);
+            cur[j + 1] = substitution.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
 /// List all memories about a specific subject
 pub fn by_subject(conn: &Connection, subject: &str, limit: usize) -> Result<Vec<Memory>> {
     let mut stmt = conn.prepare(
         "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
-         FROM memories WHERE subject = ?1 AND deleted = 0
+         FROM memories WHERE subject = ?1 AND deleted = 0 AND in_workspace(workspace)
          AND (expires_at IS NULL OR expires_at > datetime('now'))
          ORDER BY updated_at DESC LIMIT ?2",
     )?;
@@ -518,7 +840,7 @@ pub fn by_subject(conn: &Connection, subject: &str, limit: usize) -> Result<Vec<
 pub fn by_agent(conn: &Connection, agent_id: &str, limit: usize) -> Result<Vec<Memory>> {
     let mut stmt = conn.prepare(
         "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
-         FROM memories WHERE agent_id = ?1 AND deleted = 0
+         FROM memories WHERE agent_id = ?1 AND deleted = 0 AND in_workspace(workspace)
          AND (expires_at IS NULL OR expires_at > datetime('now'))
          ORDER BY updated_at DESC LIMIT ?2",
     )?;
@@ -542,34 +864,494 @@ pub fn by_agent(conn: &Connection, agent_id: &str, limit: usize) -> Result<Vec<M
     Ok(results)
 }
 
+/// List memories carrying any of `tags`, most recently updated first
+pub fn by_any_tag(conn: &Connection, tags: &[&str], limit: usize) -> Result<Vec<Memory>> {
+    let tags_json = serde_json::to_string(tags)?;
+    let mut stmt = conn.prepare(
+        "SELECT id, content, type, tags, subject, source, agent_id, expires_at, created_at, updated_at
+         FROM memories WHERE deleted = 0 AND in_workspace(workspace)
+         AND EXISTS (SELECT 1 FROM json_each(memories.tags) t
+                     WHERE t.value IN (SELECT value FROM json_each(?1)))
+         AND (expires_at IS NULL OR expires_at > datetime('now'))
+         ORDER BY updated_at DESC LIMIT ?2",
+    )?;
+
+    let results = stmt
+        .query_map(params![tags_json, limit], |row| {
+            Ok(Memory {
+                id: row.get(0)?,
+                content: row.get(1)?,
+                memory_type: row.get::<_, String>(2)?.parse().unwrap_or_default(),
+                tags: serde_json::from_str(&row.get::<_, String>(3)?).unwrap_or_default(),
+                subject: row.get(4)?,
+                source: row.get(5)?,
+                agent_id: row.get(6)?,
+                expires_at: row.get(7)?,
+                created_at: row.get(8)?,
+                updated_at: row.get(9)?,
+            })
+        })?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(results)
+}
+
+/// Tags (set by `pin_memory`, or by hand for `critical`) that lift a memory in recall.
+pub const PRIORITY_TAGS: &[&str] = &["pinned", "policy", "workflow", "critical"];
+
+pub fn is_priority(memory: &Memory) -> bool {
+    memory.tags.iter().any(|t| PRIORITY_TAGS.contains(&t.as_str()))
+}
+
+/// Multiply the score of priority-tagged results by `multiplier` and re-sort, so
+/// pinned memories rank above otherwise-equivalent unpinned ones. Non-positive
+/// scores are left alone — scaling them would push the result down, not up.
+pub fn boost_priority(mut results: Vec<(Memory, f64)>, multiplier: f64) -> Vec<(Memory, f64)> {
+    if multiplier == 1.0 {
+        return results;
+    }
+    for (memory, score) in results.iter_mut() {
+        if *score > 0.0 && is_priority(memory) {
+            *score *= multiplier;
+        }
+    }
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    results
+}
+
+/// Multiply each result's positive score by `1 + weight × ln(1 + access_count)`
+/// and re-sort, so memories that keep getting recalled or fetched edge out
+/// otherwise-equivalent ones. A weight of 0.0 leaves the ranking untouched.
+pub fn boost_access(conn: &Connection, mut results: Vec<(Memory, f64)>, weight: f64) -> Result<Vec<(Memory, f64)>> {
+    if weight == 0.0 || results.is_empty() {
+        return Ok(results);
+    }
+    let ids: Vec<&str> = results.iter().map(|(m, _)| m.id.as_str()).collect();
+    let counts = super::recall::access_counts(conn, &ids)?;
+    for (memory, score) in results.iter_mut() {
+        let count = counts.get(&memory.id).copied().unwrap_or(0);
+        if *score > 0.0 && count > 0 {
+            *score *= 1.0 + weight * (count as f64).ln_1p();
+        }
+    }
+    results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+    Ok(results)
+}
+
+/// Union of several queries' results, fused by Reciprocal Rank Fusion: each
+/// memory once, ranked by the sum of `1 / (k + rank)` over the queries that
+/// returned it, so a memory several phrasings agree on outranks one a single
+/// query scored highly — raw scores from different queries (and methods)
+/// aren't comparable. Each memory keeps its best raw score for display and
+/// `min_score`. Also maps each memory id to the index of the query that ranked
+/// it highest.
+pub fn merge_query_results(per_query: Vec<Vec<(Memory, f64)>>) -> (Vec<(Memory, f64)>, HashMap<String, usize>) {
+    // (memory, best score, fused rank score, best rank, query of best rank)
+    let mut merged: Vec<(Memory, f64, f64, usize, usize)> = Vec::new();
+    let mut position: HashMap<String, usize> = HashMap::new();
+    for (query_idx, results) in per_query.into_iter().enumerate() {
+        for (rank, (memory, score)) in results.into_iter().enumerate() {
+            let rrf = 1.0 / (RRF_K + rank as f64 + 1.0);
+            match position.get(&memory.id) {
+                Some(&i) => {
+                    let entry = &mut merged[i];
+                    entry.1 = entry.1.max(score);
+                    entry.2 += rrf;
+                    if rank < entry.3 {
+                        entry.3 = rank;
+                        entry.4 = query_idx;
+                    }
+                }
+                None => {
+                    position.insert(memory.id.clone(), merged.len());
+                    merged.push((memory, score, rrf, rank, query_idx));
+                }
+            }
+        }
+    }
+    // Stable, so a single query keeps its own ranking
+    merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    let matched = merged.iter().map(|(m, _, _, _, q)| (m.id.clone(), *q)).collect();
+    (merged.into_iter().map(|(m, s, _, _, _)| (m, s)).collect(), matched)
+}
+
+/// Whether `requesting_agent` may see `mem`. ChannelPrivate memories are only
+/// visible to the agent_id that stored them; everything else is visible to all.
+pub fn visible_to(mem: &Memory, requesting_agent: Option<&str>) -> bool {
+    if matches!(mem.memory_type, super::memories::MemoryType::ChannelPrivate) {
+        // Only include if the requesting agent matches the stored agent_id
+        match (requesting_agent, &mem.agent_id) {
+            (Some(req), Some(stored)) => req == stored,
+            _ => false, // No agent_id → hide private memories
+        }
+    } else {
+        true // Non-private memories pass through
+    }
+}
+
 /// Filter out ChannelPrivate memories not belonging to the requesting agent.
-/// ChannelPrivate memories are only visible to the agent_id that stored them.
 pub fn filter_channel_private(results: Vec<(Memory, f64)>, requesting_agent: Option<&str>) -> Vec<(Memory, f64)> {
-    results.into_iter().filter(|(mem, _)| {
-        if matches!(mem.memory_type, super::memories::MemoryType::ChannelPrivate) {
-            // Only include if the requesting agent matches the stored agent_id
-            match (requesting_agent, &mem.agent_id) {
-                (Some(req), Some(stored)) => req == stored,
-                _ => false, // No agent_id → hide private memories
+    results.into_iter().filter(|(mem, _)| visible_to(mem, requesting_agent)).collect()
+}
+
+/// Collapse results that would spend the recall budget on the same information.
+///
+/// Chunks sharing a `chunkset:` tag keep only their highest-scoring chunk. When
+/// `near_duplicate_threshold` is set, results whose stored embedding is at least that
+/// similar to an already-kept (higher-scoring) result are dropped as well.
+/// Result order is preserved.
+pub fn dedup_results(
+    conn: &Connection,
+    results: Vec<(Memory, f64)>,
+    near_duplicate_threshold: Option<f64>,
+) -> Vec<(Memory, f64)> {
+    // Best chunk per chunkset
+    let mut best_chunk: std::collections::HashMap<&str, usize> = std::collections::HashMap::new();
+    for (i, (mem, score)) in results.iter().enumerate() {
+        if let Some(set) = mem.tags.iter().find_map(|t| t.strip_prefix("chunkset:")) {
+            match best_chunk.get(set) {
+                Some(&j) if results[j].1 >= *score => {}
+                _ => {
+                    best_chunk.insert(set, i);
+                }
             }
-        } else {
-            true // Non-private memories pass through
         }
-    }).collect()
+    }
+    let keep_chunk: Vec<bool> = results
+        .iter()
+        .enumerate()
+        .map(|(i, (mem, _))| match mem.tags.iter().find_map(|t| t.strip_prefix("chunkset:")) {
+            Some(set) => best_chunk.get(set) == Some(&i),
+            None => true,
+        })
+        .collect();
+
+    let Some(threshold) = near_duplicate_threshold else {
+        return results
+            .into_iter()
+            .zip(keep_chunk)
+            .filter_map(|(r, keep)| keep.then_some(r))
+            .collect();
+    };
+
+    // Compare in score order so the better of two near-duplicates survives
+    let mut order: Vec<usize> = (0..results.len()).filter(|&i| keep_chunk[i]).collect();
+    order.sort_by(|&a, &b| results[b].1.partial_cmp(&results[a].1).unwrap_or(std::cmp::Ordering::Equal));
+
+    let mut kept = vec![false; results.len()];
+    let mut kept_embeddings: Vec<Vec<f32>> = Vec::new();
+    for i in order {
+        let embedding = load_embedding(conn, &results[i].0.id);
+        if let Some(ref emb) = embedding
+            && kept_embeddings.iter().any(|k| cosine_similarity(k, emb) >= threshold)
+        {
+            continue;
+        }
+        kept[i] = true;
+        if let Some(emb) = embedding {
+            kept_embeddings.push(emb);
+        }
+    }
+
+    results
+        .into_iter()
+        .zip(kept)
+        .filter_map(|(r, keep)| keep.then_some(r))
+        .collect()
 }
 
-/// List all distinct subjects
-pub fn list_subjects(conn: &Connection) -> Result<Vec<(String, usize)>> {
-    let mut stmt = conn.prepare(
+/// A memory's vector under the active model, from `memory_vectors` (the
+/// `memories.embedding` blob may be from another model). Int8 vectors come back
+/// at their stored scale, which cosine similarity ignores.
+pub(crate) fn load_embedding(conn: &Connection, id: &str) -> Option<Vec<f32>> {
+    if !super::vectors_available() {
+        return None;
+    }
+    let bytes: Vec<u8> = conn
+        .query_row("SELECT embedding FROM memory_vectors WHERE id = ?1", params![id], |row| row.get(0))
+        .ok()?;
+    if bytes.is_empty() {
+        return None;
+    }
+    match super::vector_quantization(conn) {
+        crate::config::VectorQuantization::Int8 => Some(bytes.iter().map(|&b| b as i8 as f32).collect()),
+        crate::config::VectorQuantization::None if bytes.len().is_multiple_of(4) => Some(
+            bytes
+                .chunks_exact(4)
+                .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+                .collect(),
+        ),
+        crate::config::VectorQuantization::None => None,
+    }
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let (mut dot, mut na, mut nb) = (0.0f64, 0.0f64, 0.0f64);
+    for (x, y) in a.iter().zip(b) {
+        dot += (*x as f64) * (*y as f64);
+        na += (*x as f64) * (*x as f64);
+        nb += (*y as f64) * (*y as f64);
+    }
+    if na == 0.0 || nb == 0.0 {
+        return 0.0;
+    }
+    dot / (na.sqrt() * nb.sqrt())
+}
+
+/// Ordering for [`list_subjects`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SubjectSort {
+    /// Most memories first
+    #[default]
+    Count,
+    /// Alphabetical
+    Name,
+}
+
+/// List distinct subjects with memory counts, optionally paged
+pub fn list_subjects(
+    conn: &Connection,
+    sort: SubjectSort,
+    limit: Option<usize>,
+    offset: usize,
+) -> Result<Vec<(String, usize)>> {
+    let order = match sort {
+        SubjectSort::Count => "cnt DESC, subject ASC",
+        SubjectSort::Name => "subject COLLATE NOCASE ASC",
+    };
+    let mut stmt = conn.prepare(&format!(
         "SELECT subject, COUNT(*) as cnt FROM memories
-         WHERE subject IS NOT NULL AND deleted = 0
-         GROUP BY subject ORDER BY cnt DESC",
-    )?;
+         WHERE subject IS NOT NULL AND deleted = 0 AND in_workspace(workspace)
+         GROUP BY subject ORDER BY {order}
+         LIMIT ?1 OFFSET ?2",
+    ))?;
 
+    // SQLite treats a negative LIMIT as "no limit"
+    let limit = limit.map(|l| l as i64).unwrap_or(-1);
     let results = stmt
-        .query_map([], |row| {
+        .query_map(params![limit, offset as i64], |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, usize>(1)?))
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
     Ok(results)
 }
+
+/// Number of distinct subjects
+pub fn count_subjects(conn: &Connection) -> Result<usize> {
+    let count: usize = conn.query_row(
+        "SELECT COUNT(DISTINCT subject) FROM memories
+         WHERE subject IS NOT NULL AND deleted = 0 AND in_workspace(workspace)",
+        [],
+        |r| r.get(0),
+    )?;
+    Ok(count)
+}
+
+/// List distinct tags with the number of memories carrying each, most used first
+pub fn list_tags(conn: &Connection) -> Result<Vec<(String, usize)>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.value, COUNT(*) FROM memories m, json_each(m.tags) t
+         WHERE m.deleted = 0 AND in_workspace(m.workspace)
+         AND (m.expires_at IS NULL OR m.expires_at > datetime('now'))
+         GROUP BY t.value
+         ORDER BY COUNT(*) DESC, t.value",
+    )?;
+    let tags = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    Ok(tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn memory(id: &str) -> Memory {
+        Memory {
+            id: id.to_string(),
+            content: String::new(),
+            memory_type: crate::db::memories::MemoryType::Semantic,
+            tags: Vec::new(),
+            subject: None,
+            source: None,
+            agent_id: None,
+            expires_at: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_boost_access_reorders_by_use() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        let store = |content: &str| {
+            crate::db::memories::store(&conn, content, &crate::db::memories::MemoryType::Semantic, &[], None, None, None, None)
+                .unwrap()
+        };
+        let (fresh, used) = (store("fresh note"), store("well-used note"));
+        for _ in 0..5 {
+            super::super::recall::record_access(&conn, &[&used.id]).unwrap();
+        }
+        let results = vec![(fresh.clone(), 0.80), (used.clone(), 0.78), (memory("gone"), 0.5)];
+
+        // Off: the ranking is untouched
+        let unchanged = boost_access(&conn, results.clone(), 0.0).unwrap();
+        assert_eq!(ids(&unchanged), [fresh.id.as_str(), used.id.as_str(), "gone"]);
+
+        let boosted = boost_access(&conn, results, 0.05).unwrap();
+        assert_eq!(ids(&boosted), [used.id.as_str(), fresh.id.as_str(), "gone"]);
+        assert!((boosted[0].1 - 0.78 * (1.0 + 0.05 * 6f64.ln())).abs() < 1e-9);
+        assert_eq!(boosted[1].1, 0.80);
+    }
+
+    fn ids(results: &[(Memory, f64)]) -> Vec<&str> {
+        results.iter().map(|(m, _)| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_subject_containment_needs_a_whole_token() {
+        assert!(subject_similarity("myapp", "project:myapp") >= FUZZY_SUBJECT_MIN);
+        assert!(subject_similarity("project:myapp", "myapp") >= FUZZY_SUBJECT_MIN);
+        assert!(subject_similarity("auth", "service/auth-api") >= FUZZY_SUBJECT_MIN);
+        // Inside a word, or too short to mean anything
+        assert!(subject_similarity("app", "project:myapp") < FUZZY_SUBJECT_MIN);
+        assert!(subject_similarity("a", "project:a") < FUZZY_SUBJECT_MIN);
+        assert!(subject_similarity("io", "io") == 1.0);
+    }
+
+    #[test]
+    fn test_subject_typos_still_match() {
+        assert!(subject_similarity("projct:myapp", "project:myapp") >= FUZZY_SUBJECT_MIN);
+        assert!(subject_similarity("project:other", "project:myapp") < FUZZY_SUBJECT_MIN);
+    }
+
+    #[test]
+    fn test_match_subjects_ranks_and_filters() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        for subject in ["project:myapp", "project:mapper", "user"] {
+            crate::db::memories::store(
+                &conn, "note", &crate::db::memories::MemoryType::Semantic, &[], Some(subject), None, None, None,
+            )
+            .unwrap();
+        }
+        let names = |wanted: &str| -> Vec<String> {
+            match_subjects(&conn, wanted).unwrap().into_iter().map(|(name, _)| name).collect()
+        };
+        assert_eq!(names("MyApp"), ["project:myapp"]);
+        assert!(names("app").is_empty());
+        assert!(names("us").is_empty());
+        assert_eq!(names("user"), ["user"]);
+    }
+
+    #[test]
+    fn test_keyword_only_scores_stay_below_semantic_scale() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        let store = |content: &str, tags: &[&str]| {
+            let tags: Vec<String> = tags.iter().map(|t| t.to_string()).collect();
+            crate::db::memories::store_with_expiry(
+                &conn, content, &crate::db::memories::MemoryType::Semantic, &tags, None, None, None, None, None, true,
+            )
+            .unwrap()
+        };
+        let full = store("rotate the deploy keys every quarter", &["pinned", "policy"]);
+        let partial = store("deploy on fridays", &[]);
+
+        let query_embedding = vec![0.0; crate::embed::embedding_dim()];
+        let results = semantic_search_all(&conn, "rotate deploy keys", &query_embedding, 10).unwrap();
+        assert_eq!(ids(&results), [full.id.as_str(), partial.id.as_str()]);
+        assert!(results.iter().all(|(_, score)| *score <= KEYWORD_ONLY_CEILING), "{results:?}");
+        assert!(results[1].1 < results[0].1);
+    }
+
+    #[cfg(feature = "pro")]
+    #[test]
+    fn test_hybrid_search_reports_source_per_result() {
+        let _data = crate::config::test_data_dir();
+        let conn = crate::db::open().unwrap();
+        let dim = crate::embed::embedding_dim();
+        let toward = |x: f32, y: f32| {
+            let mut v = vec![0.0f32; dim];
+            v[0] = x;
+            v[1] = y;
+            v
+        };
+        let store = |content: &str, embedding: Option<&[f32]>| {
+            crate::db::memories::store(&conn, content, &crate::db::memories::MemoryType::Semantic, &[], None, None, embedding, None)
+                .unwrap()
+                .id
+        };
+        let both = store("rotate the deploy keys quarterly", Some(&toward(1.0, 0.0)));
+        let meaning = store("credentials get replaced every few months", Some(&toward(0.8, 0.6)));
+        let words = store("deploy keys live in the vault", None);
+
+        let results = hybrid_search_sourced(&conn, "deploy keys", &toward(1.0, 0.0), 10).unwrap();
+        let source = |id: &str| results.iter().find(|(m, _, _)| m.id == id).map(|(_, _, s)| *s);
+        assert_eq!(source(&both), Some(MatchSource::Hybrid));
+        assert_eq!(source(&meaning), Some(MatchSource::Semantic));
+        assert_eq!(source(&words), Some(MatchSource::Keyword));
+    }
+
+    fn highlighted<'a>(query: &str, content: &'a str) -> Vec<&'a str> {
+        highlight_ranges(query, content).into_iter().map(|r| &content[r]).collect()
+    }
+
+    #[test]
+    fn test_query_terms_drop_punctuation() {
+        assert_eq!(query_terms("How do we deploy? (staging!) \"cache-layer\""), ["deploy", "staging", "cache", "layer"]);
+        assert_eq!(query_terms("project:atlas, v2"), ["project", "atlas", "v2"]);
+    }
+
+    #[test]
+    fn test_highlight_matches_stems() {
+        let content = "Deploying caches; we deployed the cache and deploys run nightly.";
+        assert_eq!(highlighted("deploy cache?", content), ["Deploying", "caches", "deployed", "cache", "deploys"]);
+    }
+
+    #[test]
+    fn test_highlight_non_ascii_byte_ranges() {
+        let content = "Le déploiement du café — naïve Straße tests";
+        let ranges = highlight_ranges("DÉPLOIEMENT, straße naïve", content);
+        let words: Vec<&str> = ranges.iter().map(|r| &content[r.clone()]).collect();
+        assert_eq!(words, ["déploiement", "naïve", "Straße"]);
+        assert!(ranges.iter().all(|r| content.is_char_boundary(r.start) && content.is_char_boundary(r.end)));
+    }
+
+    #[test]
+    fn test_highlight_nothing_without_term_matches() {
+        assert!(highlighted("the of and", "the state of the art").is_empty());
+        assert!(highlighted("kubernetes", "deploy with docker compose").is_empty());
+        assert!(highlighted("", "anything").is_empty());
+    }
+
+    #[test]
+    fn test_merge_single_query_keeps_order() {
+        let (results, matched) = merge_query_results(vec![vec![(memory("a"), 0.2), (memory("b"), 0.9)]]);
+        assert_eq!(ids(&results), ["a", "b"]);
+        assert_eq!(matched["b"], 0);
+    }
+
+    #[test]
+    fn test_merge_ranks_agreement_over_one_high_score() {
+        // "solo" tops one query with a score the other query's scale never reaches
+        let (results, matched) = merge_query_results(vec![
+            vec![(memory("solo"), 9.5), (memory("shared"), 0.6)],
+            vec![(memory("shared"), 0.8), (memory("other"), 0.7)],
+        ]);
+        assert_eq!(ids(&results), ["shared", "solo", "other"]);
+        // Best raw score is kept; attributed to the query that ranked it highest
+        assert_eq!(results[0].1, 0.8);
+        assert_eq!(matched["shared"], 1);
+        assert_eq!(matched["solo"], 0);
+    }
+
+    #[test]
+    fn test_merge_ties_break_by_query_order() {
+        let (results, _) = merge_query_results(vec![vec![(memory("a"), 0.1)], vec![(memory("b"), 0.9)]]);
+        assert_eq!(ids(&results), ["a", "b"]);
+    }
+}
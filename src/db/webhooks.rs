@@ -20,12 +20,14 @@ pub const VALID_EVENTS: &[&str] = &[
     "memory.created",
     "memory.updated",
     "memory.deleted",
+    "memory.restored",
     "entity.created",
     "entity.updated",
     "entity.deleted",
     "relation.created",
     "relation.updated",
     "relation.deleted",
+    "consolidation.candidates",
 ];
 
 pub fn migrate(conn: &Connection) -> Result<()> {
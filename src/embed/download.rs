@@ -0,0 +1,325 @@
+//! Model file downloads shared by `init` and `ctxovrflw model`.
+//!
+//! Small files go through [`fetch`]; model weights go through [`fetch_to_file`],
+//! which streams into a `.part` file and resumes with a `Range` request after a
+//! dropped connection. Both rewrite URLs onto the configured `model_mirror` and
+//! check the bytes against the expected SHA-256 (when the model entry pins one)
+//! before anything lands at the final path.
+
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::models::mirrored_url;
+use crate::config::Config;
+
+/// Resolve a model URL against the configured mirror.
+pub fn resolve_url(url: &str) -> String {
+    let mirror = Config::load().ok().and_then(|c| c.model_mirror);
+    mirrored_url(url, mirror.as_deref())
+}
+
+/// Download `url` (mirror-aware) and verify it against `expected_sha256`.
+pub async fn fetch(
+    url: &str,
+    expected_sha256: Option<&str>,
+) -> Result<Vec<u8>> {
+    let url = resolve_url(url);
+    let resp = crate::config::http_client()?
+        .get(&url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch {url}"))?;
+    if !resp.status().is_success() {
+        anyhow::bail!("HTTP {} when downloading {}", resp.status(), url);
+    }
+    let bytes = resp
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to download {url}"))?
+        .to_vec();
+    verify_sha256(&url, &bytes, expected_sha256)?;
+    Ok(bytes)
+}
+
+/// How many times a dropped transfer is resumed before giving up.
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Stream `url` (mirror-aware) to `dest`, resuming from `<dest>.part` if a
+/// previous attempt was interrupted. The file is renamed into place only once
+/// it is complete, at least `min_bytes` long, and matches `expected_sha256`;
+/// a resumed `.part` that fails those checks (e.g. the file changed upstream)
+/// is discarded and downloaded once more from scratch. `on_progress` is called
+/// with `(bytes_so_far, total_if_known)` as data arrives. Returns the final
+/// size in bytes.
+pub async fn fetch_to_file(
+    url: &str,
+    expected_sha256: Option<&str>,
+    dest: &Path,
+    min_bytes: u64,
+    mut on_progress: impl FnMut(u64, Option<u64>),
+) -> Result<u64> {
+    let url = resolve_url(url);
+    let part = part_path(dest);
+
+    let mut restarted = false;
+    loop {
+        let mut resumed = false;
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match stream_to_part(&url, &part, &mut on_progress).await {
+                Ok(appended) => {
+                    resumed |= appended;
+                    break;
+                }
+                Err(e) if attempt < MAX_ATTEMPTS => {
+                    tracing::warn!("Download of {url} interrupted ({e:#}), resuming (attempt {})", attempt + 1);
+                    tokio::time::sleep(std::time::Duration::from_secs(u64::from(attempt))).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let size = std::fs::metadata(&part)?.len();
+        match check_part(&url, &part, size, min_bytes, expected_sha256) {
+            Ok(()) => {
+                std::fs::rename(&part, dest)
+                    .with_context(|| format!("Failed to move download into place at {}", dest.display()))?;
+                return Ok(size);
+            }
+            Err(e) => {
+                // A bad .part would otherwise be "resumed" forever
+                let _ = std::fs::remove_file(&part);
+                if resumed && !restarted {
+                    tracing::warn!("Resumed download of {url} failed verification ({e:#}), starting over");
+                    restarted = true;
+                    continue;
+                }
+                return Err(e);
+            }
+        }
+    }
+}
+
+/// One transfer attempt: continue `part` from its current length. Returns
+/// whether the result builds on bytes from an earlier attempt.
+async fn stream_to_part(
+    url: &str,
+    part: &Path,
+    on_progress: &mut impl FnMut(u64, Option<u64>),
+) -> Result<bool> {
+    let offset = std::fs::metadata(part).map(|m| m.len()).unwrap_or(0);
+
+    // The shared client's default redirect policy follows Hugging Face's CDN redirects
+    let mut req = crate::config::http_client()?.get(url);
+    if offset > 0 {
+        req = req.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+    }
+    let mut resp = req
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch {url}"))?;
+
+    let status = resp.status();
+    if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // Only a .part exactly as long as the file (`Content-Range: bytes */<len>`)
+        // can be complete; anything else is stale, so start over
+        let total = resp
+            .headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes */"))
+            .and_then(|len| len.trim().parse::<u64>().ok());
+        if total == Some(offset) {
+            on_progress(offset, total);
+            return Ok(true);
+        }
+        std::fs::remove_file(part)?;
+        anyhow::bail!("server rejected resuming at byte {offset} (file is {total:?} bytes); restarting");
+    }
+    if !status.is_success() {
+        anyhow::bail!("HTTP {} when downloading {}", status, url);
+    }
+
+    // Servers that ignore Range answer 200 with the full body
+    let resuming = offset > 0 && status == reqwest::StatusCode::PARTIAL_CONTENT;
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resuming)
+        .truncate(!resuming)
+        .open(part)
+        .with_context(|| format!("Failed to open {}", part.display()))?;
+
+    let mut received = if resuming { offset } else { 0 };
+    let total = resp.content_length().map(|len| received + len);
+    on_progress(received, total);
+
+    while let Some(chunk) = resp
+        .chunk()
+        .await
+        .with_context(|| format!("Connection dropped while downloading {url}"))?
+    {
+        file.write_all(&chunk)?;
+        received += chunk.len() as u64;
+        on_progress(received, total);
+    }
+    file.flush()?;
+    Ok(resuming)
+}
+
+fn check_part(
+    url: &str,
+    part: &Path,
+    size: u64,
+    min_bytes: u64,
+    expected_sha256: Option<&str>,
+) -> Result<()> {
+    if size < min_bytes {
+        anyhow::bail!("Downloaded file too small ({size} bytes) — likely a redirect/error page.");
+    }
+    let mut file = std::fs::File::open(part)?;
+    let mut head = [0u8; 5];
+    let n = file.read(&mut head)?;
+    if head[..n].starts_with(b"<!") || head[..n].starts_with(b"<html") {
+        anyhow::bail!("Downloaded HTML instead of a model file from {url}");
+    }
+
+    let Some(expected) = expected_sha256 else {
+        return Ok(());
+    };
+    let mut file = std::fs::File::open(part)?;
+    let mut ctx = ring::digest::Context::new(&ring::digest::SHA256);
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        ctx.update(&buf[..n]);
+    }
+    compare_digest(url, ctx.finish(), expected)
+}
+
+fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Fail if `bytes` doesn't hash to `expected` (hex, case-insensitive).
+pub fn verify_sha256(url: &str, bytes: &[u8], expected: Option<&str>) -> Result<()> {
+    let Some(expected) = expected else {
+        return Ok(());
+    };
+    compare_digest(url, ring::digest::digest(&ring::digest::SHA256, bytes), expected)
+}
+
+fn compare_digest(url: &str, digest: ring::digest::Digest, expected: &str) -> Result<()> {
+    let actual: String = digest
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!(
+            "Checksum mismatch for {url}\n  expected sha256: {expected}\n  actual sha256:   {actual}\n\
+             The file is corrupt or was altered in transit. Nothing was written; \
+             check `model_mirror` in your config and retry."
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &[u8] = b"not really an onnx model";
+    const WRONG_SHA256: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+    fn body_digest() -> String {
+        ring::digest::digest(&ring::digest::SHA256, BODY)
+            .as_ref()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_sha256() {
+        let digest = body_digest();
+        assert!(verify_sha256("u", BODY, Some(&digest)).is_ok());
+        assert!(verify_sha256("u", BODY, Some(&digest.to_uppercase())).is_ok());
+        assert!(verify_sha256("u", BODY, None).is_ok());
+
+        let err = verify_sha256("u", BODY, Some(WRONG_SHA256)).unwrap_err().to_string();
+        assert!(err.contains("Checksum mismatch"), "{err}");
+    }
+
+    #[test]
+    fn test_check_part_rejects_checksum_mismatch() {
+        let dir = tempfile::tempdir().unwrap();
+        let part = dir.path().join("model.onnx.part");
+        std::fs::write(&part, BODY).unwrap();
+        let size = BODY.len() as u64;
+
+        assert!(check_part("u", &part, size, 1, Some(&body_digest())).is_ok());
+        assert!(check_part("u", &part, size, 1, Some(WRONG_SHA256)).is_err());
+        // Too small fails before hashing
+        assert!(check_part("u", &part, size, size + 1, Some(&body_digest())).is_err());
+    }
+
+    /// Serve `BODY`, answering every Range request with 416 the way a server
+    /// does when the range starts at or past the end of the file.
+    async fn serve_body() -> String {
+        use axum::http::{header, HeaderMap, StatusCode};
+        use axum::response::IntoResponse;
+        let app = axum::Router::new().route(
+            "/model.onnx",
+            axum::routing::get(|headers: HeaderMap| async move {
+                if headers.contains_key(header::RANGE) {
+                    (StatusCode::RANGE_NOT_SATISFIABLE, [(header::CONTENT_RANGE, format!("bytes */{}", BODY.len()))])
+                        .into_response()
+                } else {
+                    BODY.into_response()
+                }
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{addr}/model.onnx")
+    }
+
+    async fn fetch_with_part(part_contents: &[u8]) -> (Result<u64>, Vec<u8>) {
+        let data = crate::config::test_data_dir();
+        let url = serve_body().await;
+        let dest = data.dir.path().join("model.onnx");
+        std::fs::write(part_path(&dest), part_contents).unwrap();
+        let result = fetch_to_file(&url, Some(&body_digest()), &dest, 1, |_, _| {}).await;
+        (result, std::fs::read(&dest).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_416_keeps_a_complete_part() {
+        let (result, written) = fetch_with_part(BODY).await;
+        assert_eq!(result.unwrap(), BODY.len() as u64);
+        assert_eq!(written, BODY);
+    }
+
+    #[tokio::test]
+    async fn test_416_restarts_a_stale_part() {
+        let (result, written) = fetch_with_part(b"an older, longer build of the model file").await;
+        assert_eq!(result.unwrap(), BODY.len() as u64);
+        assert_eq!(written, BODY);
+    }
+
+    #[tokio::test]
+    async fn test_416_restarts_a_corrupt_part_of_the_right_length() {
+        let (result, written) = fetch_with_part(&vec![b'x'; BODY.len()]).await;
+        assert_eq!(result.unwrap(), BODY.len() as u64);
+        assert_eq!(written, BODY);
+    }
+}
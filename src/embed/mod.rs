@@ -1,8 +1,9 @@
+pub mod download;
 pub mod models;
 
 use anyhow::Result;
 use std::path::PathBuf;
-use std::sync::{atomic::{AtomicUsize, Ordering}, Arc, Mutex, OnceLock};
+use std::sync::{atomic::{AtomicBool, AtomicUsize, Ordering}, Arc, Condvar, Mutex, RwLock};
 
 use crate::config::Config;
 
@@ -17,18 +18,242 @@ pub fn set_embedding_dim(dim: usize) {
     EMBEDDING_DIM_RUNTIME.store(dim, Ordering::Relaxed);
 }
 
-/// Global singleton embedder — loaded once, shared across HTTP, sync, MCP, CLI.
-static GLOBAL_EMBEDDER: OnceLock<Arc<Mutex<Embedder>>> = OnceLock::new();
+/// Global embedder pool — loaded once, shared across HTTP, sync, MCP, CLI.
+/// Replaced by [`reload`] when the daemon picks up a new embedding model.
+static GLOBAL_EMBEDDER: RwLock<Option<Arc<EmbedderPool>>> = RwLock::new(None);
 
-/// Get or initialize the global embedder singleton.
-/// First call loads the model; subsequent calls return the cached instance.
-pub fn get_or_init() -> Result<Arc<Mutex<Embedder>>> {
-    if let Some(emb) = GLOBAL_EMBEDDER.get() {
-        return Ok(emb.clone());
+/// Held while the first model instance loads, so concurrent callers wait for
+/// that load instead of starting their own.
+static INIT: Mutex<()> = Mutex::new(());
+
+/// Set while [`warm_in_background`] is loading the model.
+static WARMING: AtomicBool = AtomicBool::new(false);
+
+/// Get or initialize the global embedder pool.
+/// First call loads one model instance; more are loaded on demand up to
+/// `embedder_pool_size` when concurrent requests would otherwise wait.
+pub fn get_or_init() -> Result<Arc<EmbedderPool>> {
+    if let Some(pool) = loaded() {
+        return Ok(pool);
+    }
+    let _init = INIT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(pool) = loaded() {
+        return Ok(pool);
+    }
+    let pool = Arc::new(new_pool()?);
+    let mut slot = GLOBAL_EMBEDDER.write().unwrap_or_else(|e| e.into_inner());
+    Ok(slot.get_or_insert(pool).clone())
+}
+
+/// Load the global embedder on a background thread, so the daemon can serve
+/// requests while the model loads. [`warming`] is true until it finishes.
+pub fn warm_in_background() {
+    if loaded().is_some() || WARMING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    std::thread::spawn(|| {
+        let started = std::time::Instant::now();
+        match get_or_init() {
+            Ok(_) => tracing::info!("ONNX embedder loaded (global pool) in {:.1}s", started.elapsed().as_secs_f64()),
+            Err(e) => tracing::warn!("Failed to load embedder: {e}. Semantic search unavailable."),
+        }
+        WARMING.store(false, Ordering::SeqCst);
+    });
+}
+
+/// Whether the startup warm-up is still loading the model.
+pub fn warming() -> bool {
+    WARMING.load(Ordering::SeqCst)
+}
+
+/// The global embedder pool if one is loaded (never forces a model load).
+pub fn loaded() -> Option<Arc<EmbedderPool>> {
+    GLOBAL_EMBEDDER.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Load a fresh pool for the configured model and make it the global one.
+/// Callers holding the old pool finish with it. If loading fails the old pool
+/// is dropped anyway, since its vectors no longer match the configured model.
+pub fn reload() -> Result<Arc<EmbedderPool>> {
+    let pool = new_pool().map(Arc::new);
+    let mut slot = GLOBAL_EMBEDDER.write().unwrap_or_else(|e| e.into_inner());
+    *slot = pool.as_ref().ok().cloned();
+    pool
+}
+
+fn new_pool() -> Result<EmbedderPool> {
+    let size = Config::load().unwrap_or_default().embedder_pool_size;
+    Ok(EmbedderPool::new(Embedder::new()?, size))
+}
+
+/// Approximate token count for context budgeting.
+/// Uses the loaded pool's tokenizer when the global embedder is already
+/// initialized (never forces a model load, and never waits for a busy
+/// instance); otherwise falls back to ~4 bytes per token.
+pub fn count_tokens(text: &str) -> usize {
+    if let Some(pool) = loaded()
+        && let Some(n) = pool.count_tokens(text)
+    {
+        return n;
+    }
+    text.len() / 4
+}
+
+/// A bounded set of `Embedder` instances so independent requests can embed in
+/// parallel instead of serializing on one model. `acquire` blocks (call it from
+/// blocking contexts) until an instance is free or a new one can be loaded.
+pub struct EmbedderPool {
+    state: Mutex<PoolState>,
+    available: Condvar,
+    max: usize,
+    model_id: String,
+    query_prefix: Option<String>,
+    /// Shared with the instances, so counting tokens needn't check one out
+    tokenizer: tokenizers::Tokenizer,
+    query_cache: Mutex<QueryCache>,
+}
+
+/// Recall queries cached per pool (and so per model).
+const QUERY_CACHE_CAPACITY: usize = 256;
+
+/// Small LRU of query embeddings keyed by the prefixed query text.
+#[derive(Default)]
+struct QueryCache {
+    entries: std::collections::HashMap<String, (Vec<f32>, u64)>,
+    tick: u64,
+}
+
+impl QueryCache {
+    fn get(&mut self, key: &str) -> Option<Vec<f32>> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(key).map(|(vec, used)| {
+            *used = tick;
+            vec.clone()
+        })
+    }
+
+    fn insert(&mut self, key: String, vec: Vec<f32>) {
+        if self.entries.len() >= QUERY_CACHE_CAPACITY
+            && !self.entries.contains_key(&key)
+            && let Some(oldest) = self.entries.iter().min_by_key(|(_, (_, used))| *used).map(|(k, _)| k.clone())
+        {
+            self.entries.remove(&oldest);
+        }
+        self.tick += 1;
+        self.entries.insert(key, (vec, self.tick));
+    }
+}
+
+struct PoolState {
+    idle: Vec<Embedder>,
+    loaded: usize,
+    /// Set when loading an extra instance failed; the pool stops growing.
+    capped: bool,
+}
+
+impl EmbedderPool {
+    pub fn new(first: Embedder, max: usize) -> Self {
+        Self {
+            model_id: first.model_id.clone(),
+            query_prefix: first.query_prefix.clone(),
+            tokenizer: first.tokenizer.clone(),
+            state: Mutex::new(PoolState { idle: vec![first], loaded: 1, capped: false }),
+            available: Condvar::new(),
+            max: max.max(1),
+            query_cache: Mutex::new(QueryCache::default()),
+        }
+    }
+
+    /// Embed a recall query, reusing the vector when the same (prefixed) query
+    /// was embedded recently. Agents re-run near-identical session-start recalls,
+    /// so this skips the model entirely on repeats.
+    pub fn embed_query(&self, query: &str) -> Result<Vec<f32>> {
+        let key = format!("{}{query}", self.query_prefix.as_deref().unwrap_or(""));
+        if let Some(hit) = self.query_cache.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            return Ok(hit);
+        }
+        let embedding = self.acquire().embed(query)?;
+        self.query_cache
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key, embedding.clone());
+        Ok(embedding)
+    }
+
+    /// Check out an embedder; it returns to the pool when the guard drops.
+    pub fn acquire(&self) -> PooledEmbedder<'_> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            if let Some(embedder) = state.idle.pop() {
+                return PooledEmbedder { pool: self, embedder: Some(embedder) };
+            }
+            if !state.capped && state.loaded < self.max {
+                state.loaded += 1;
+                drop(state);
+                match Embedder::new() {
+                    Ok(embedder) => {
+                        tracing::debug!("Embedder pool grew to {} instance(s)", self.size());
+                        return PooledEmbedder { pool: self, embedder: Some(embedder) };
+                    }
+                    Err(e) => {
+                        tracing::warn!("Failed to load an extra embedder instance: {e}");
+                        state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                        state.loaded -= 1;
+                        state.capped = true;
+                        continue;
+                    }
+                }
+            }
+            state = self.available.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+    }
+
+    /// Count tokens with this model's tokenizer (special tokens excluded).
+    /// Counts past the tokenizer's truncation limit and ignores padding.
+    pub fn count_tokens(&self, text: &str) -> Option<usize> {
+        let encoding = self.tokenizer.encode(text, false).ok()?;
+        let real = |e: &tokenizers::Encoding| e.get_attention_mask().iter().filter(|&&m| m != 0).count();
+        Some(real(&encoding) + encoding.get_overflowing().iter().map(real).sum::<usize>())
+    }
+
+    /// The embedding model this pool's instances run.
+    pub fn model_id(&self) -> &str {
+        &self.model_id
+    }
+
+    /// Number of model instances currently loaded.
+    pub fn size(&self) -> usize {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).loaded
+    }
+}
+
+/// An embedder checked out of an `EmbedderPool`.
+pub struct PooledEmbedder<'a> {
+    pool: &'a EmbedderPool,
+    embedder: Option<Embedder>,
+}
+
+impl std::ops::Deref for PooledEmbedder<'_> {
+    type Target = Embedder;
+    fn deref(&self) -> &Embedder {
+        self.embedder.as_ref().expect("embedder present until drop")
+    }
+}
+
+impl std::ops::DerefMut for PooledEmbedder<'_> {
+    fn deref_mut(&mut self) -> &mut Embedder {
+        self.embedder.as_mut().expect("embedder present until drop")
+    }
+}
+
+impl Drop for PooledEmbedder<'_> {
+    fn drop(&mut self) {
+        if let Some(embedder) = self.embedder.take() {
+            self.pool.state.lock().unwrap_or_else(|e| e.into_inner()).idle.push(embedder);
+            self.pool.available.notify_one();
+        }
     }
-    let emb = Embedder::new()?;
-    let arc = Arc::new(Mutex::new(emb));
-    Ok(GLOBAL_EMBEDDER.get_or_init(|| arc.clone()).clone())
 }
 
 /// Embedding model using ONNX Runtime + configurable models
@@ -37,7 +262,10 @@ pub struct Embedder {
     #[cfg(feature = "onnx")]
     session: ort::session::Session,
     tokenizer: tokenizers::Tokenizer,
+    model_id: String,
     query_prefix: Option<String>,
+    #[cfg(not(feature = "onnx"))]
+    doc_freqs: crate::db::doc_freqs::DocFreqs,
 }
 
 impl Embedder {
@@ -77,6 +305,7 @@ impl Embedder {
             .map_err(|e| anyhow::anyhow!("Failed to load tokenizer: {e}"))?;
 
         let query_prefix = model_info.query_prefix.map(|s| s.to_string());
+        let model_id = cfg.embedding_model.clone();
 
         #[cfg(feature = "onnx")]
         {
@@ -108,34 +337,51 @@ impl Embedder {
                 ),
             };
 
-            Ok(Self { session, tokenizer, query_prefix })
+            Ok(Self { session, tokenizer, model_id, query_prefix })
         }
 
         #[cfg(not(feature = "onnx"))]
         {
-            Ok(Self { tokenizer, query_prefix })
+            let doc_freqs = load_doc_freqs(&cfg.embedding_model);
+            Ok(Self { tokenizer, model_id, query_prefix, doc_freqs })
         }
     }
 
     /// Generate embedding for a text string. Returns dynamic-dim f32 vector.
     pub fn embed(&mut self, text: &str) -> Result<Vec<f32>> {
-        let text_to_embed = if let Some(ref prefix) = self.query_prefix {
-            format!("{}{}", prefix, text)
-        } else {
-            text.to_string()
-        };
-        
         #[cfg(feature = "onnx")]
         {
+            let text_to_embed = if let Some(ref prefix) = self.query_prefix {
+                format!("{}{}", prefix, text)
+            } else {
+                text.to_string()
+            };
             self.embed_onnx(&text_to_embed)
         }
 
+        // The query prefix is an instruction for the model; it would only add
+        // noise tokens to the TF-IDF projection
         #[cfg(not(feature = "onnx"))]
         {
-            Ok(tokenizer_hash_embed(&self.tokenizer, &text_to_embed))
+            Ok(tfidf_embed(&self.tokenizer, &self.doc_freqs, text))
         }
     }
 
+    /// Recount the TF-IDF fallback's document frequencies from the current
+    /// memories, so a full reindex embeds against up-to-date statistics.
+    /// A no-op in ONNX builds.
+    pub fn recount_token_stats(&mut self) -> Result<()> {
+        #[cfg(not(feature = "onnx"))]
+        {
+            let cfg = Config::load().unwrap_or_default();
+            let conn = crate::db::open()?;
+            self.doc_freqs = crate::db::doc_freqs::rebuild(&conn, &cfg.embedding_model, |text| {
+                token_ids(&self.tokenizer, text)
+            })?;
+        }
+        Ok(())
+    }
+
     /// Check if ONNX embedding is available (vs hash fallback)
     #[allow(dead_code)]
     pub fn is_onnx(&self) -> bool {
@@ -264,10 +510,12 @@ impl Embedder {
             }
         }
 
-        // 2. ~/.ctxovrflw/ locations
+        // 2. Data directory (~/.ctxovrflw/ unless overridden)
+        if let Ok(data_dir) = crate::config::Config::data_dir() {
+            search_paths.push(data_dir.join("bin").join(lib_name));
+            search_paths.push(data_dir.join("lib").join(lib_name));
+        }
         if let Some(home) = dirs::home_dir() {
-            search_paths.push(home.join(".ctxovrflw").join("bin").join(lib_name));
-            search_paths.push(home.join(".ctxovrflw").join("lib").join(lib_name));
             // 3. ~/.local/
             search_paths.push(home.join(".local").join("lib").join(lib_name));
             search_paths.push(home.join(".local").join("bin").join(lib_name));
@@ -295,22 +543,73 @@ impl Embedder {
     }
 }
 
-/// Tokenizer-aware hash embedding. Uses actual token IDs for better
-/// semantic distribution than raw byte hashing. Used in non-ONNX builds.
-#[allow(dead_code)]
-fn tokenizer_hash_embed(tokenizer: &tokenizers::Tokenizer, text: &str) -> Vec<f32> {
+/// Token ids for `text`, including any past the tokenizer's truncation limit.
+#[cfg(not(feature = "onnx"))]
+fn token_ids(tokenizer: &tokenizers::Tokenizer, text: &str) -> Vec<u32> {
+    let Ok(encoding) = tokenizer.encode(text, false) else { return Vec::new() };
+    let mut ids = encoding.get_ids().to_vec();
+    for overflow in encoding.get_overflowing() {
+        ids.extend_from_slice(overflow.get_ids());
+    }
+    ids
+}
+
+/// Document frequencies for the TF-IDF fallback, as last counted by
+/// `ctxovrflw reindex`. Only read here — recounting means re-embedding every
+/// stored vector, so missing or drifted stats get a warning instead. Without
+/// stats every token weighs the same.
+#[cfg(not(feature = "onnx"))]
+fn load_doc_freqs(model: &str) -> crate::db::doc_freqs::DocFreqs {
+    use crate::db::doc_freqs;
+
+    let load = || -> Result<Option<doc_freqs::DocFreqs>> {
+        let conn = crate::db::open()?;
+        let live = doc_freqs::live_count(&conn)?;
+        let stored = doc_freqs::load(&conn, model)?;
+        match &stored {
+            None if live > 0 => tracing::warn!(
+                "No token statistics for the CPU embedding fallback yet, so all tokens weigh the same. \
+                 Run `ctxovrflw reindex` to count them from your {live} memories."
+            ),
+            Some(stored) if stored.is_stale(live) => tracing::warn!(
+                "Token statistics for the CPU embedding fallback were counted from {} memories, now {live}. \
+                 Run `ctxovrflw reindex` to refresh them.",
+                stored.docs
+            ),
+            _ => {}
+        }
+        Ok(stored)
+    };
+    load().unwrap_or_else(|e| {
+        tracing::debug!("Token statistics unavailable, weighting all tokens equally: {e}");
+        None
+    })
+    .unwrap_or_default()
+}
+
+/// TF-IDF embedding used in non-ONNX builds. Each token is weighted by
+/// `(1 + ln tf) * idf` and scattered, with a hashed sign, into a few fixed
+/// dimensions derived from its id — a sparse random projection of the TF-IDF
+/// vector, so texts sharing rare tokens land close together. IDF comes from
+/// the stored memories and drifts as they change; `ctxovrflw reindex`
+/// re-embeds stored vectors against the current statistics.
+#[cfg(not(feature = "onnx"))]
+fn tfidf_embed(tokenizer: &tokenizers::Tokenizer, doc_freqs: &crate::db::doc_freqs::DocFreqs, text: &str) -> Vec<f32> {
+    const SLOTS_PER_TOKEN: u64 = 4;
+
     let dim = embedding_dim();
     let mut embedding = vec![0.0f32; dim];
 
-    if let Ok(encoding) = tokenizer.encode(text, true) {
-        for (i, &id) in encoding.get_ids().iter().enumerate() {
-            let base = (id as usize * 7) % dim;
-            for k in 0..3 {
-                let idx = (base + k * 131) % dim;
-                let sign = if (id as usize + k) % 2 == 0 { 1.0 } else { -1.0 };
-                let decay = 1.0 / (1.0 + i as f32 * 0.1);
-                embedding[idx] += sign * decay;
-            }
+    let mut term_freqs: std::collections::HashMap<u32, u32> = std::collections::HashMap::new();
+    for id in token_ids(tokenizer, text) {
+        *term_freqs.entry(id).or_insert(0) += 1;
+    }
+    for (id, tf) in term_freqs {
+        let weight = (1.0 + (tf as f32).ln()) * doc_freqs.idf(id);
+        for k in 0..SLOTS_PER_TOKEN {
+            let h = splitmix64(((id as u64) << 8) | k);
+            let sign = if h >> 63 == 0 { 1.0 } else { -1.0 };
+            embedding[(h % dim as u64) as usize] += sign * weight;
         }
     }
 
@@ -321,6 +620,14 @@ fn tokenizer_hash_embed(tokenizer: &tokenizers::Tokenizer, text: &str) -> Vec<f3
     embedding
 }
 
+#[cfg(not(feature = "onnx"))]
+fn splitmix64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
 /// Simple hash embedding for when no tokenizer is available (testing/fallback).
 #[allow(dead_code)]
 pub fn hash_embed(text: &str) -> Vec<f32> {
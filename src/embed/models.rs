@@ -9,6 +9,22 @@ pub struct EmbeddingModel {
     pub requires_prefix: bool,      // Some models need "query: " or "passage: " prefix
     pub query_prefix: Option<&'static str>,  // e.g. Some("query: ")
     pub num_inputs: usize,          // 2 or 3 — whether model accepts token_type_ids
+    pub onnx_sha256: Option<&'static str>,      // expected hex SHA-256 of the ONNX file
+    pub tokenizer_sha256: Option<&'static str>, // expected hex SHA-256 of tokenizer.json
+}
+
+/// Default host for model downloads. `model_mirror` in config replaces this prefix.
+pub const MODEL_HOST: &str = "https://huggingface.co";
+
+/// Rewrite a model URL to point at a mirror, if one is configured.
+pub fn mirrored_url(url: &str, mirror: Option<&str>) -> String {
+    match mirror.map(|m| m.trim_end_matches('/')).filter(|m| !m.is_empty()) {
+        Some(m) => match url.strip_prefix(MODEL_HOST) {
+            Some(rest) => format!("{m}{rest}"),
+            None => url.to_string(),
+        },
+        None => url.to_string(),
+    }
 }
 
 pub const MODELS: &[EmbeddingModel] = &[
@@ -24,6 +40,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "bge-small-en-v1.5",
@@ -36,6 +54,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "gte-small",
@@ -48,6 +68,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "e5-small-v2",
@@ -60,6 +82,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: true,
         query_prefix: Some("query: "),
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
 
     // ── Medium (512d) ────────────────────────────────────────────────
@@ -74,6 +98,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
 
     // ── Base (768d) ──────────────────────────────────────────────────
@@ -88,6 +114,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "gte-base",
@@ -100,6 +128,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "jina-embeddings-v2-base-en",
@@ -112,6 +142,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "snowflake-arctic-embed-m-v2.0",
@@ -124,6 +156,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: Some("query: "),
         num_inputs: 2, // GTE-based, no token_type_ids
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
 
     // ── Large / Multilingual (768-1024d) ─────────────────────────────
@@ -138,6 +172,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: true,
         query_prefix: Some("query: "),
         num_inputs: 3,
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "multilingual-e5-base",
@@ -150,6 +186,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: true,
         query_prefix: Some("query: "),
         num_inputs: 2, // XLM-RoBERTa based
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
     EmbeddingModel {
         id: "bge-m3",
@@ -162,6 +200,8 @@ pub const MODELS: &[EmbeddingModel] = &[
         requires_prefix: false,
         query_prefix: None,
         num_inputs: 2, // XLM-RoBERTa based
+        onnx_sha256: None,
+        tokenizer_sha256: None,
     },
 ];
 
@@ -172,3 +212,30 @@ pub fn get_model(id: &str) -> Option<&'static EmbeddingModel> {
 pub fn default_model() -> &'static EmbeddingModel {
     &MODELS[0]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_sha256(hex: &str) -> bool {
+        hex.len() == 64 && hex.bytes().all(|b| b.is_ascii_hexdigit())
+    }
+
+    #[test]
+    fn test_registry_digests_are_hex() {
+        for m in MODELS {
+            for digest in [m.onnx_sha256, m.tokenizer_sha256].into_iter().flatten() {
+                assert!(is_sha256(digest), "{}: bad sha256 '{digest}'", m.id);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "registry digests not yet filled in; run scripts/model-digests.sh and paste its output"]
+    fn test_registry_has_digests() {
+        for m in MODELS {
+            assert!(m.onnx_sha256.is_some(), "{}: no onnx_sha256", m.id);
+            assert!(m.tokenizer_sha256.is_some(), "{}: no tokenizer_sha256", m.id);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Entity extraction from memory content for the knowledge graph.
+//!
+//! A memory's subject and namespaced tags always become entities. With
+//! `entity_extraction` enabled the content body is scanned as well, and each
+//! entity found is linked to the memory via `mentioned_in` at
+//! [`EXTRACTED_CONFIDENCE`] — low enough that an extraction nothing else
+//! confirms decays out of the graph like any other stale inference.
+
+use anyhow::Result;
+use regex::Regex;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// How entities are found in memory content (`entity_extraction` in config).
+/// A model-based NER would be another variant, dispatched in [`extract`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityExtraction {
+    /// Only the subject and namespaced tags become entities.
+    #[default]
+    Off,
+    /// Pattern rules: URLs, code identifiers and capitalized multi-word phrases.
+    Rules,
+}
+
+/// Confidence of a `mentioned_in` relation found in content rather than
+/// stated through the subject (which links at 1.0).
+pub const EXTRACTED_CONFIDENCE: f64 = 0.4;
+
+/// Most entities taken from one memory, so a pasted log doesn't flood the graph.
+const MAX_ENTITIES: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtractedEntity {
+    pub name: String,
+    /// `url`, `code` or `generic`
+    pub entity_type: &'static str,
+}
+
+static URL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"https?://[^\s<>()\[\]{}'"`]+"#).expect("valid url pattern"));
+
+static CODE_SPAN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`([^`\n]{2,60})`").expect("valid code span pattern"));
+
+/// Paths (`std::sync`), calls (`parse()`), snake_case, camelCase and PascalCase.
+static IDENTIFIER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"\b[A-Za-z_][A-Za-z0-9_]*(?:::[A-Za-z_][A-Za-z0-9_]*)+\b",
+        r"|\b[A-Za-z_][A-Za-z0-9_]*\(\)",
+        r"|\b[a-z][a-z0-9]*(?:_[a-z0-9]+)+\b",
+        r"|\b[a-z]+(?:[A-Z][a-z0-9]+)+\b",
+        r"|\b[A-Z][a-z0-9]+(?:[A-Z][a-z0-9]+)+\b",
+    ))
+    .expect("valid identifier pattern")
+});
+
+/// Two or more capitalized words in a row ("Sarah Connor", "Google Cloud Run").
+static PHRASE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[A-Z][A-Za-z0-9]*(?: [A-Z][A-Za-z0-9]*)+\b").expect("valid phrase pattern"));
+
+/// Capitalized only because they start a sentence; dropped from the front of a phrase.
+const LEADING_STOPWORDS: &[&str] = &[
+    "A", "An", "And", "As", "At", "But", "By", "For", "From", "He", "I", "If", "In", "It", "My", "On", "Our",
+    "She", "So", "The", "Then", "These", "They", "This", "Those", "To", "We", "When", "With", "You",
+];
+
+/// Entities mentioned in `text`, first mention first, without duplicates.
+pub fn extract(mode: EntityExtraction, text: &str) -> Vec<ExtractedEntity> {
+    match mode {
+        EntityExtraction::Off => Vec::new(),
+        EntityExtraction::Rules => extract_rules(text),
+    }
+}
+
+fn extract_rules(text: &str) -> Vec<ExtractedEntity> {
+    let mut found = Vec::new();
+    let mut seen = HashSet::new();
+    let mut push = |name: &str, entity_type: &'static str| {
+        let name = name.trim();
+        if name.chars().count() >= 3 && seen.insert(name.to_lowercase()) {
+            found.push(ExtractedEntity { name: name.to_string(), entity_type });
+        }
+    };
+
+    for url in URL.find_iter(text) {
+        push(url.as_str().trim_end_matches(['.', ',', ';', ':', '!', '?']), "url");
+    }
+    // URLs and code spans are taken whole, so blank them before the word rules
+    let rest = URL.replace_all(text, " ");
+    for span in CODE_SPAN.captures_iter(&rest) {
+        push(&span[1], "code");
+    }
+    let rest = CODE_SPAN.replace_all(&rest, " ");
+    for ident in IDENTIFIER.find_iter(&rest) {
+        push(ident.as_str(), "code");
+    }
+    for phrase in PHRASE.find_iter(&rest) {
+        let words: Vec<&str> = phrase
+            .as_str()
+            .split(' ')
+            .skip_while(|w| LEADING_STOPWORDS.contains(w))
+            .collect();
+        if words.len() >= 2 {
+            push(&words.join(" "), "generic");
+        }
+    }
+
+    found.truncate(MAX_ENTITIES);
+    found
+}
+
+/// Link every entity extracted from a memory's `content` to the memory's
+/// graph node via `mentioned_in`. A name that already has an entity (e.g.
+/// `person:Maria Lopez` from a subject) links to that one, and existing links
+/// are left at their confidence. Returns how many were linked.
+pub fn link_mentions(conn: &Connection, memory_id: &str, content: &str, mode: EntityExtraction) -> Result<usize> {
+    use crate::db::graph;
+
+    let entities = extract(mode, content);
+    if entities.is_empty() {
+        return Ok(0);
+    }
+    let mem_entity = graph::upsert_entity(conn, memory_id, "memory", None)?;
+    let already_linked: HashSet<String> = graph::get_relations(conn, &mem_entity.id, Some("mentioned_in"), Some("incoming"))?
+        .into_iter()
+        .map(|(relation, _, _)| relation.source_id)
+        .collect();
+    let metadata = serde_json::json!({ "extracted_by": mode });
+    let mut linked = 0;
+    for extracted in entities {
+        let existing = graph::find_entity(conn, &extracted.name, None)?
+            .into_iter()
+            .find(|e| e.entity_type != "memory");
+        let entity = match existing {
+            Some(entity) => entity,
+            None => graph::upsert_entity(conn, &extracted.name, extracted.entity_type, None)?,
+        };
+        if already_linked.contains(&entity.id) {
+            continue;
+        }
+        graph::upsert_relation(
+            conn,
+            &entity.id,
+            &mem_entity.id,
+            "mentioned_in",
+            EXTRACTED_CONFIDENCE,
+            Some(memory_id),
+            Some(&metadata),
+        )?;
+        linked += 1;
+    }
+    Ok(linked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(text: &str) -> Vec<(String, &'static str)> {
+        extract(EntityExtraction::Rules, text).into_iter().map(|e| (e.name, e.entity_type)).collect()
+    }
+
+    #[test]
+    fn test_extracts_urls_code_and_phrases() {
+        let found = rules("Sarah Connor deploys `make release` to https://fly.io/apps/my_app. The tokio::sync channel uses sendMessage() via HashMap.");
+        assert_eq!(
+            found,
+            [
+                ("https://fly.io/apps/my_app".to_string(), "url"),
+                ("make release".to_string(), "code"),
+                ("tokio::sync".to_string(), "code"),
+                ("sendMessage()".to_string(), "code"),
+                ("HashMap".to_string(), "code"),
+                ("Sarah Connor".to_string(), "generic"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ignores_plain_prose() {
+        assert!(rules("User prefers tabs over spaces. We deploy on Fridays.").is_empty());
+        // A sentence-initial word doesn't make a phrase on its own
+        assert_eq!(rules("The Rust Foundation funds it"), [("Rust Foundation".to_string(), "generic")]);
+        assert!(extract(EntityExtraction::Off, "Sarah Connor at https://example.com").is_empty());
+    }
+}
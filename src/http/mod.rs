@@ -1,44 +1,247 @@
 pub mod routes;
 
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
 use anyhow::Result;
 use axum::Router;
 use axum::http::{header, Method};
 use axum::middleware::{self, Next};
-use axum::extract::Request;
+use axum::extract::{Request, State};
 use axum::response::{Response, IntoResponse};
-use std::sync::Mutex;
-use tower_http::cors::CorsLayer;
+use tokio::sync::watch;
+use tower_http::cors::{AllowOrigin, CorsLayer};
 use tower_http::limit::RequestBodyLimitLayer;
+use tracing_subscriber::{reload, EnvFilter, Registry};
 
 use crate::config::Config;
-use crate::embed::Embedder;
+use crate::embed::EmbedderPool;
 
-/// Shared application state — loaded once at daemon startup.
+/// Shared application state. The config is live: `POST /v1/reload` publishes a
+/// re-read copy to the routes, the MCP transport and the daemon's background tasks.
 #[derive(Clone)]
 pub struct AppState {
-    pub embedder: Option<Arc<Mutex<Embedder>>>,
-    pub config: Config,
+    pub live: watch::Sender<Config>,
+    /// MCP clients seen over SSE, for `GET /v1/clients`.
+    pub clients: crate::mcp::sse::ClientTracker,
 }
 
-/// Auth middleware: checks Bearer token on all routes except /health and /.
+impl AppState {
+    /// The config currently in effect.
+    pub fn config(&self) -> Config {
+        self.live.borrow().clone()
+    }
+
+    /// The loaded embedder pool, if any. A failed load isn't retried per request.
+    pub fn embedder(&self) -> Option<Arc<EmbedderPool>> {
+        crate::embed::loaded()
+    }
+}
+
+/// Origins always allowed by CORS; `cors_origins` in config adds more.
+const BUILTIN_ORIGINS: &[&str] = &[
+    "https://ctxovrflw.dev",
+    "http://localhost:5173",
+    "http://127.0.0.1:5173",
+    "http://localhost:3000",
+    "http://127.0.0.1:3000",
+];
+
+/// Fields only read when the daemon starts (the listener, the database, the log
+/// file and the consolidation schedule); everything else takes effect on reload.
+const RESTART_FIELDS: &[&str] = &[
+    "port",
+    "bind_address",
+    "active_profile",
+    "db_path",
+    "log_retention_days",
+    "auto_consolidation",
+    "consolidation_interval_secs",
+];
+
+/// Outcome of `POST /v1/reload`: which changed fields are now in effect.
+#[derive(Debug, Default)]
+pub struct ReloadReport {
+    pub applied: Vec<String>,
+    /// Changed, but only picked up by `ctxovrflw stop && ctxovrflw start`.
+    pub requires_restart: Vec<String>,
+    pub embedder_reloaded: bool,
+    pub warnings: Vec<String>,
+}
+
+/// Re-read config.toml and publish it to the running daemon. The embedder is
+/// reloaded only when the model (or pool size) changed.
+pub async fn reload(state: &AppState) -> Result<ReloadReport> {
+    let new = Config::load()?;
+    let old = state.config();
+
+    let mut report = ReloadReport {
+        warnings: new.validate().iter().map(ToString::to_string).collect(),
+        ..Default::default()
+    };
+    for field in changed_fields(&old, &new)? {
+        if RESTART_FIELDS.contains(&field.as_str()) {
+            report.requires_restart.push(field);
+        } else {
+            report.applied.push(field);
+        }
+    }
+    let changed = |field: &str| report.applied.iter().any(|f| f == field);
+    let (model_changed, log_level_changed) =
+        (changed("embedding_model") || changed("embedder_pool_size"), changed("log_level"));
+
+    if model_changed {
+        match tokio::task::spawn_blocking(crate::embed::reload).await? {
+            Ok(_) => {
+                tracing::info!("Embedder reloaded for model '{}'", new.embedding_model);
+                report.embedder_reloaded = true;
+                let conn = crate::db::open()?;
+                crate::db::activate_configured_model(&conn, &new)?;
+            }
+            Err(e) => {
+                tracing::warn!("Failed to load embedder: {e}. Semantic search unavailable.");
+                report.warnings.push(format!(
+                    "Embedding model '{}' failed to load, so semantic search is unavailable: {e}",
+                    new.embedding_model
+                ));
+            }
+        }
+    }
+    if log_level_changed {
+        if LOG_OVERRIDE.get().is_some() {
+            report.warnings.push("The daemon was started with -q/-v, which overrides log_level".into());
+        } else if std::env::var_os("RUST_LOG").is_some() {
+            report.warnings.push("RUST_LOG is set and overrides log_level".into());
+        }
+        set_log_level(&new)?;
+    }
+
+    state.live.send_replace(new);
+    tracing::info!(
+        "Config reloaded (applied: {}; requires restart: {})",
+        report.applied.join(", "),
+        report.requires_restart.join(", ")
+    );
+    Ok(report)
+}
+
+/// Handle to the installed log filter, so a config reload can change `log_level`.
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Filter forced by the `-q`/`-v` flags for the life of the process.
+static LOG_OVERRIDE: OnceLock<&'static str> = OnceLock::new();
+
+/// The `-q`/`-v` filter if given, else `RUST_LOG` if set, else the configured
+/// `log_level`, else `ctxovrflw=info`.
+fn log_filter(cfg: &Config) -> EnvFilter {
+    if let Some(forced) = LOG_OVERRIDE.get() {
+        return EnvFilter::new(forced);
+    }
+    EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(cfg.log_level.as_deref().unwrap_or("ctxovrflw=info")))
+        .unwrap_or_else(|_| "ctxovrflw=info".into())
+}
+
+/// Make the filter swappable by [`set_log_level`]. Call once, when installing the
+/// subscriber; `forced` is the filter from `-q`/`-v`, which reloads leave alone.
+pub fn reloadable_log_filter(cfg: &Config, forced: Option<&'static str>) -> reload::Layer<EnvFilter, Registry> {
+    if let Some(forced) = forced {
+        let _ = LOG_OVERRIDE.set(forced);
+    }
+    let (layer, handle) = reload::Layer::new(log_filter(cfg));
+    let _ = LOG_FILTER.set(handle);
+    layer
+}
+
+/// Apply `cfg.log_level` to the running process. `-q`/`-v` and `RUST_LOG` still win.
+pub fn set_log_level(cfg: &Config) -> Result<()> {
+    if let Some(handle) = LOG_FILTER.get() {
+        handle.reload(log_filter(cfg))?;
+    }
+    Ok(())
+}
+
+/// Top-level config keys whose values differ.
+fn changed_fields(old: &Config, new: &Config) -> Result<Vec<String>> {
+    let old = serde_json::to_value(old)?;
+    let new = serde_json::to_value(new)?;
+    let (Some(old), Some(new)) = (old.as_object(), new.as_object()) else {
+        return Ok(Vec::new());
+    };
+    let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    Ok(keys
+        .into_iter()
+        .filter(|k| old.get(*k) != new.get(*k))
+        .cloned()
+        .collect())
+}
+
+/// Largest request body accepted by the HTTP server and MCP transport.
+const MAX_REQUEST_BODY: usize = 512 * 1024;
+
+/// Replace the bare 413 from the body limit (or an extractor that hit it)
+/// with a JSON error explaining the limits, like the content-size check in
+/// `remember` does.
+async fn payload_too_large_json(
+    request: Request,
+    next: Next,
+) -> Response {
+    let response = next.run(request).await;
+    if response.status() != axum::http::StatusCode::PAYLOAD_TOO_LARGE {
+        return response;
+    }
+
+    (
+        axum::http::StatusCode::PAYLOAD_TOO_LARGE,
+        axum::Json(serde_json::json!({
+            "ok": false,
+            "error": format!(
+                "Request body too large. Maximum is {} bytes per request and {} bytes per memory; \
+                 split large content into several smaller memories.",
+                MAX_REQUEST_BODY,
+                crate::validation::MAX_CONTENT_SIZE,
+            ),
+            "max_body_bytes": MAX_REQUEST_BODY,
+            "max_content_bytes": crate::validation::MAX_CONTENT_SIZE,
+        })),
+    ).into_response()
+}
+
+/// Whether a browser page at `origin` may call the daemon: the built-in
+/// origins plus `cors_origins` from config.
+fn origin_allowed(origin: &str, cfg: &Config) -> bool {
+    BUILTIN_ORIGINS.contains(&origin) || cfg.cors_origins.iter().any(|o| o == origin)
+}
+
+/// Auth middleware: checks the Bearer token (or `?token=`) on every route
+/// except / and /health. `/mcp` also refuses browser origins outside the CORS
+/// allow-list; `/mcp/messages` is addressed by a session id handed out on an
+/// already authenticated SSE stream, so it needs no token of its own.
 async fn auth_middleware(
+    State(live): State<watch::Receiver<Config>>,
     request: Request,
     next: Next,
 ) -> Response {
     let path = request.uri().path().to_string();
-
-    // Skip auth for health and MCP endpoints (MCP is how external agents connect)
-    if path == "/" || path == "/health" || path.starts_with("/mcp") {
+    if path == "/" || path == "/health" {
         return next.run(request).await;
     }
 
-    // Get expected token from config
-    let expected_token = match Config::load() {
-        Ok(cfg) => cfg.auth_token,
-        Err(_) => None,
+    let expected_token = {
+        let cfg = live.borrow();
+        if path.starts_with("/mcp")
+            && let Some(origin) = request.headers().get(header::ORIGIN)
+            && !origin.to_str().is_ok_and(|o| origin_allowed(o, &cfg))
+        {
+            return (
+                axum::http::StatusCode::FORBIDDEN,
+                axum::Json(serde_json::json!({ "error": "Origin not allowed" })),
+            ).into_response();
+        }
+        cfg.auth_token.clone()
     };
+    if path == "/mcp/messages" {
+        return next.run(request).await;
+    }
 
     // If no token configured, allow all (backwards compat during migration)
     let Some(expected) = expected_token else {
@@ -82,20 +285,14 @@ async fn auth_middleware(
     next.run(request).await
 }
 
-pub async fn serve(cfg: Config, port: u16) -> Result<()> {
-    let origins: Vec<axum::http::HeaderValue> = [
-            "https://ctxovrflw.dev",
-            "http://localhost:5173",
-            "http://127.0.0.1:5173",
-            "http://localhost:3000",
-            "http://127.0.0.1:3000",
-        ]
-        .iter()
-        .filter_map(|o| o.parse().ok())
-        .collect();
-
+/// The daemon's HTTP app: REST routes, both MCP transports, auth, CORS and
+/// the body limit.
+fn app(live: watch::Sender<Config>) -> Router {
+    let origins = live.subscribe();
     let cors = CorsLayer::new()
-        .allow_origin(origins)
+        .allow_origin(AllowOrigin::predicate(move |origin, _| {
+            origin.to_str().is_ok_and(|origin| origin_allowed(origin, &origins.borrow()))
+        }))
         .allow_methods([
             Method::GET,
             Method::POST,
@@ -103,37 +300,158 @@ pub async fn serve(cfg: Config, port: u16) -> Result<()> {
             Method::DELETE,
             Method::OPTIONS,
         ])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION])
+        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::HeaderName::from_static("mcp-session-id")])
+        .expose_headers([
+            header::HeaderName::from_static("x-search-method"),
+            header::HeaderName::from_static("mcp-session-id"),
+        ])
         .max_age(std::time::Duration::from_secs(86400));
 
-    // Use the global singleton embedder — shared with sync, MCP, CLI
-    let embedder = match crate::embed::get_or_init() {
-        Ok(e) => {
-            tracing::info!("ONNX embedder loaded (global singleton)");
-            Some(e)
-        }
-        Err(e) => {
-            tracing::warn!("Failed to load embedder: {e}. Semantic search unavailable.");
-            None
-        }
-    };
+    let clients = crate::mcp::sse::ClientTracker::default();
+    let mcp = crate::mcp::sse::router(live.subscribe(), clients.clone())
+        .merge(crate::mcp::streamable::router(live.subscribe(), clients.clone()));
+    let auth = live.subscribe();
+    let state = AppState { live, clients };
 
-    let state = AppState {
-        embedder,
-        config: cfg.clone(),
-    };
-
-    let app = Router::new()
+    Router::new()
         .merge(routes::router(state))
-        .nest("/mcp", crate::mcp::sse::router(cfg))
-        .layer(middleware::from_fn(auth_middleware))
+        .nest("/mcp", mcp)
+        .layer(middleware::from_fn_with_state(auth, auth_middleware))
         .layer(cors)
-        .layer(RequestBodyLimitLayer::new(512 * 1024)); // 512 KB max request body
+        .layer(RequestBodyLimitLayer::new(MAX_REQUEST_BODY))
+        .layer(middleware::from_fn(payload_too_large_json))
+}
 
-    let listener = tokio::net::TcpListener::bind(format!("127.0.0.1:{port}")).await?;
-    tracing::info!("HTTP API listening on http://localhost:{port}");
-    tracing::info!("MCP SSE endpoint at http://localhost:{port}/mcp/sse");
+pub async fn serve(live: watch::Sender<Config>, port: u16) -> Result<()> {
+    let cfg = live.borrow().clone();
+
+    let bind_ip: std::net::IpAddr = cfg.bind_address.parse().map_err(|_| {
+        anyhow::anyhow!("Invalid bind_address in config.toml: {}", cfg.bind_address)
+    })?;
+    if !bind_ip.is_loopback() {
+        if cfg.auth_token.as_deref().is_none_or(str::is_empty) {
+            anyhow::bail!(
+                "Refusing to bind to {bind_ip} without an auth_token: the API would be open to anyone \
+                 who can reach it. Set auth_token in config.toml (or CTXOVRFLW_AUTH_TOKEN), or bind to 127.0.0.1."
+            );
+        }
+        tracing::warn!("══════════════════════════════════════════════════════════════");
+        tracing::warn!("HTTP server bound to {bind_ip} — reachable from the network.");
+        tracing::warn!("The auth token is now the only protection for /v1 and /mcp.");
+        tracing::warn!("Use a firewall or VPN as well.");
+        tracing::warn!("══════════════════════════════════════════════════════════════");
+    }
+
+    // Load the global embedder pool (shared with sync, MCP, CLI) without
+    // holding up the listener; recall uses keyword search until it's ready
+    crate::embed::warm_in_background();
+
+    let app = app(live);
+    let listener = tokio::net::TcpListener::bind(std::net::SocketAddr::new(bind_ip, port)).await?;
+    tracing::info!("HTTP API listening on http://{}", listener.local_addr()?);
+    tracing::info!("MCP SSE endpoint at http://{}/mcp/sse", listener.local_addr()?);
+    tracing::info!("MCP Streamable HTTP endpoint at http://{}/mcp", listener.local_addr()?);
 
     axum::serve(listener, app).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::StatusCode;
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "test-token";
+    const INITIALIZE: &str = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","clientInfo":{"name":"test"}}}"#;
+    const TOOLS_LIST: &str = r#"{"jsonrpc":"2.0","id":2,"method":"tools/list"}"#;
+
+    fn test_app() -> Router {
+        let cfg = Config { auth_token: Some(TOKEN.into()), ..Config::default() };
+        app(watch::channel(cfg).0)
+    }
+
+    async fn post(app: &Router, path: &str, headers: &[(&str, &str)], body: &str) -> Response {
+        let mut request = Request::post(path);
+        for (name, value) in headers {
+            request = request.header(*name, *value);
+        }
+        app.clone().oneshot(request.body(Body::from(body.to_string())).unwrap()).await.unwrap()
+    }
+
+    const AUTHED_JSON: &[(&str, &str)] = &[("authorization", "Bearer test-token"), ("content-type", "application/json")];
+
+    #[tokio::test]
+    async fn test_network_bind_requires_token() {
+        let cfg = Config { bind_address: "0.0.0.0".into(), auth_token: None, ..Config::default() };
+        let err = serve(watch::channel(cfg).0, 0).await.unwrap_err();
+        assert!(err.to_string().contains("without an auth_token"));
+    }
+
+    #[tokio::test]
+    async fn test_mcp_requires_token() {
+        let app = test_app();
+        let json = [("content-type", "application/json")];
+        assert_eq!(post(&app, "/mcp", &json, INITIALIZE).await.status(), StatusCode::UNAUTHORIZED);
+
+        let wrong = [("authorization", "Bearer nope"), ("content-type", "application/json")];
+        assert_eq!(post(&app, "/mcp", &wrong, INITIALIZE).await.status(), StatusCode::UNAUTHORIZED);
+
+        let response = post(&app, "/mcp?token=test-token", &json, INITIALIZE).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.headers().contains_key("mcp-session-id"));
+        assert_eq!(post(&app, "/mcp", AUTHED_JSON, INITIALIZE).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_mcp_checks_origin() {
+        let app = test_app();
+        let foreign = [AUTHED_JSON[0], AUTHED_JSON[1], ("origin", "https://evil.example")];
+        assert_eq!(post(&app, "/mcp", &foreign, INITIALIZE).await.status(), StatusCode::FORBIDDEN);
+        // The origin check comes before the token, so a page can't probe for it
+        let foreign_no_token = [("content-type", "application/json"), ("origin", "https://evil.example")];
+        assert_eq!(post(&app, "/mcp", &foreign_no_token, INITIALIZE).await.status(), StatusCode::FORBIDDEN);
+
+        let allowed = [AUTHED_JSON[0], AUTHED_JSON[1], ("origin", "http://localhost:3000")];
+        assert_eq!(post(&app, "/mcp", &allowed, INITIALIZE).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_mcp_requires_json() {
+        let app = test_app();
+        let plain = [AUTHED_JSON[0], ("content-type", "text/plain")];
+        assert_eq!(post(&app, "/mcp", &plain, INITIALIZE).await.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(post(&app, "/mcp", &AUTHED_JSON[..1], INITIALIZE).await.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let charset = [AUTHED_JSON[0], ("content-type", "application/json; charset=utf-8")];
+        assert_eq!(post(&app, "/mcp", &charset, INITIALIZE).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_mcp_requires_session() {
+        let app = test_app();
+        assert_eq!(post(&app, "/mcp", AUTHED_JSON, TOOLS_LIST).await.status(), StatusCode::BAD_REQUEST);
+
+        let unknown = [AUTHED_JSON[0], AUTHED_JSON[1], ("mcp-session-id", "not-a-session")];
+        assert_eq!(post(&app, "/mcp", &unknown, TOOLS_LIST).await.status(), StatusCode::NOT_FOUND);
+
+        let response = post(&app, "/mcp", AUTHED_JSON, INITIALIZE).await;
+        let session = response.headers()["mcp-session-id"].to_str().unwrap().to_string();
+        let with_session = [AUTHED_JSON[0], AUTHED_JSON[1], ("mcp-session-id", session.as_str())];
+        assert_eq!(post(&app, "/mcp", &with_session, TOOLS_LIST).await.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_sse_messages_use_session_not_token() {
+        let app = test_app();
+        // No token needed (the session id came from an authenticated stream),
+        // but an unknown session is still refused
+        let response = post(&app, "/mcp/messages?sessionId=nope", &[("content-type", "application/json")], TOOLS_LIST).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let foreign = [("content-type", "application/json"), ("origin", "https://evil.example")];
+        let response = post(&app, "/mcp/messages?sessionId=nope", &foreign, TOOLS_LIST).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -1,9 +1,12 @@
 use axum::{
+    body::Body,
     extract::{Json, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use crate::config::Config;
@@ -22,11 +25,15 @@ pub fn router(state: AppState) -> Router {
         .route("/v1/memories", post(store_memory))
         .route("/v1/memories", get(list_memories))
         .route("/v1/memories/recall", post(recall))
+        .route("/v1/remember", post(remember))
         .route("/v1/memories/{id}", get(get_memory))
         .route("/v1/memories/{id}", put(update_memory))
         .route("/v1/memories/{id}", delete(delete_memory))
+        .route("/v1/embeddings", get(embeddings))
         .route("/v1/subjects", get(subjects))
-        .route("/v1/status", get(status));
+        .route("/v1/status", get(status))
+        .route("/v1/clients", get(clients))
+        .route("/v1/reload", post(reload));
 
     // Knowledge graph routes (Standard+ tier, always compiled)
     let r = r
@@ -37,7 +44,10 @@ pub fn router(state: AppState) -> Router {
         .route("/v1/relations", post(create_relation))
         .route("/v1/relations/{entity_id}", get(get_relations_http))
         .route("/v1/relations/{id}/delete", delete(delete_relation_http))
-        .route("/v1/graph/traverse/{entity_id}", get(traverse_http));
+        .route("/v1/graph/traverse/{entity_id}", get(traverse_http))
+        .route("/v1/graph/entities", get(graph_entities))
+        .route("/v1/graph/relations", get(graph_relations))
+        .route("/v1/graph/traverse", get(graph_traverse));
 
     // Webhook routes (Standard + Pro tier)
     #[cfg(feature = "pro")]
@@ -53,7 +63,12 @@ async fn health() -> Json<Value> {
     Json(json!({
         "service": "ctxovrflw",
         "status": "ok",
-        "version": env!("CARGO_PKG_VERSION")
+        "version": env!("CARGO_PKG_VERSION"),
+        "mcp": {
+            "sse": "/mcp/sse",
+            "streamable_http": "/mcp",
+            "protocol_versions": crate::mcp::PROTOCOL_VERSIONS
+        }
     }))
 }
 
@@ -74,6 +89,11 @@ struct StoreRequest {
     ttl: Option<String>,
     #[serde(default)]
     expires_at: Option<String>,
+    #[serde(default)]
+    defer_sync: Option<bool>,
+    /// Store keyword-only, without an embedding (defaults to `skip_embedding` in config)
+    #[serde(default)]
+    skip_embedding: Option<bool>,
 }
 
 async fn store_memory(State(state): State<AppState>, Json(body): Json<StoreRequest>) -> Json<Value> {
@@ -94,7 +114,7 @@ async fn store_memory(State(state): State<AppState>, Json(body): Json<StoreReque
         return Json(json!({ "ok": false, "error": e }));
     }
 
-    let cfg = &state.config;
+    let cfg = &state.config();
 
     let conn = match db::open() {
         Ok(c) => c,
@@ -137,6 +157,7 @@ async fn store_memory(State(state): State<AppState>, Json(body): Json<StoreReque
         None
     };
 
+    let skip_embedding = body.skip_embedding.unwrap_or(cfg.skip_embedding);
     let mut created: Vec<db::memories::Memory> = Vec::new();
 
     for (idx, chunk) in chunks.iter().enumerate() {
@@ -150,27 +171,22 @@ async fn store_memory(State(state): State<AppState>, Json(body): Json<StoreReque
         let chunk_tags = validate_tags(&chunk_tags).unwrap_or(chunk_tags);
 
         // Generate embedding using shared embedder (spawn_blocking to avoid blocking tokio)
-        let embedding = if let Some(ref emb) = state.embedder {
-            let emb = emb.clone();
+        let embedding = if skip_embedding {
+            None
+        } else if let Some(emb) = state.embedder() {
             let content = chunk.clone();
             tokio::task::spawn_blocking(move || {
-                let mut e = emb.lock().unwrap_or_else(|e| e.into_inner());
+                let mut e = emb.acquire();
                 e.embed(&content).ok()
             }).await.ok().flatten()
         } else {
             None
         };
 
-        match db::memories::store_with_expiry(&conn, chunk, &mtype, &chunk_tags, body.subject.as_deref(), Some(source), embedding.as_deref(), expires_at.as_deref(), body.agent_id.as_deref()) {
+        match db::memories::store_with_expiry(&conn, chunk, &mtype, &chunk_tags, body.subject.as_deref(), Some(source), embedding.as_deref(), expires_at.as_deref(), body.agent_id.as_deref(), skip_embedding) {
             Ok(memory) => {
                 { #[cfg(feature = "pro")] crate::webhooks::fire("memory.created", json!({ "memory": memory })); }
-                if cfg.is_logged_in() {
-                    let id = memory.id.clone();
-                    let cfg2 = cfg.clone();
-                    tokio::spawn(async move {
-                        let _ = crate::sync::push_one(&cfg2, &id).await;
-                    });
-                }
+                crate::sync::push_soon(cfg, &memory.id, body.defer_sync);
                 created.push(memory);
             }
             Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })),
@@ -193,6 +209,30 @@ async fn store_memory(State(state): State<AppState>, Json(body): Json<StoreReque
     }
 }
 
+/// `POST /v1/remember` — the MCP `remember` tool over plain HTTP: same validation,
+/// chunking, write quotas, embedding and immediate push.
+async fn remember(State(state): State<AppState>, Json(body): Json<Value>) -> Json<Value> {
+    match crate::mcp::tools::remember(&state.config(), &body, "api").await {
+        Ok(Ok(remembered)) => {
+            let ids: Vec<&str> = remembered.memories.iter().map(|m| m.id.as_str()).collect();
+            let mut response = json!({
+                "ok": true,
+                "ids": ids,
+                "chunked": remembered.memories.len() > 1,
+                "chunk_parent": remembered.chunk_parent,
+                "secrets": remembered.secrets,
+                "memories": remembered.memories
+            });
+            if body["debug"].as_bool().unwrap_or(false) {
+                response["debug"] = remembered.embedding_debug(&state.config());
+            }
+            Json(response)
+        }
+        Ok(Err(message)) => Json(json!({ "ok": false, "error": message })),
+        Err(e) => Json(json!({ "ok": false, "error": sanitize_error(&e) })),
+    }
+}
+
 #[derive(Deserialize)]
 struct ListQuery {
     #[serde(default = "default_limit")]
@@ -221,6 +261,60 @@ async fn list_memories(Query(q): Query<ListQuery>) -> Json<Value> {
     }
 }
 
+#[derive(Deserialize)]
+struct EmbeddingsQuery {
+    /// Comma-separated memory IDs; all memories when omitted.
+    #[serde(default)]
+    ids: Option<String>,
+}
+
+/// Serialized directly (not via `json!`) so components print at f32 precision.
+#[derive(Serialize)]
+struct EmbeddingLine<'a> {
+    id: &'a str,
+    vector: Option<Vec<f32>>,
+}
+
+/// GET /v1/embeddings — stored vectors as NDJSON, one `{"id", "vector"}` object
+/// per line, read a memory at a time so large vaults stream. Requested IDs
+/// without a vector (unknown, deleted or not yet embedded) get `"vector": null`.
+async fn embeddings(Query(q): Query<EmbeddingsQuery>) -> Response {
+    let conn = match db::open() {
+        Ok(c) => c,
+        Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+    };
+
+    let requested: Option<Vec<String>> = q.ids.map(|ids| {
+        ids.split(',')
+            .map(|id| id.trim().to_string())
+            .filter(|id| !id.is_empty())
+            .collect()
+    });
+    let ids = match &requested {
+        Some(ids) => ids.clone(),
+        None => match db::memories::list_ids(&conn) {
+            Ok(ids) => ids,
+            Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+        },
+    };
+
+    let stream = async_stream::stream! {
+        for id in ids {
+            let vector = match db::memories::get(&conn, &id) {
+                Ok(Some(_)) => db::memories::get_vector(&conn, &id).ok().flatten(),
+                _ => None,
+            };
+            if vector.is_none() && requested.is_none() {
+                continue;
+            }
+            let mut line = serde_json::to_string(&EmbeddingLine { id: &id, vector }).unwrap_or_default();
+            line.push('\n');
+            yield Ok::<_, std::convert::Infallible>(line);
+        }
+    };
+    ([(header::CONTENT_TYPE, NDJSON)], Body::from_stream(stream)).into_response()
+}
+
 #[derive(Deserialize)]
 struct RecallRequest {
     query: String,
@@ -240,106 +334,136 @@ fn default_recall_limit() -> usize {
     10
 }
 
-async fn recall(State(state): State<AppState>, Json(body): Json<RecallRequest>) -> Json<Value> {
+/// POST /v1/memories/recall. With `Accept: application/x-ndjson` the results
+/// are streamed one JSON object per line instead of returned as one document.
+async fn recall(State(state): State<AppState>, headers: HeaderMap, Json(body): Json<RecallRequest>) -> Response {
     let conn = match db::open() {
         Ok(c) => c,
-        Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })),
+        Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
     };
 
+    let needs_embedding = body.subject.is_none()
+        && body.agent_id.is_none()
+        && body.search_method.as_deref() != Some("keyword");
+    let embedder = state.embedder();
+    // The daemon loads the model in the background at startup; until then
+    // recall answers from keyword search rather than waiting
+    let warming_up = needs_embedding && embedder.is_none() && crate::embed::warming();
+    let embedding = match embedder {
+        Some(emb) if needs_embedding => {
+            let query = body.query.clone();
+            tokio::task::spawn_blocking(move || emb.embed_query(&query).ok())
+                .await
+                .ok()
+                .flatten()
+        }
+        _ => None,
+    };
+
+    let (results, method) = recall_results(&conn, &body, embedding);
+    let agent_id = body.agent_id.unwrap_or_default();
+
+    let wants_ndjson = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains(NDJSON));
+    if wants_ndjson {
+        // Build each line lazily so the client sees the first result before
+        // the rest are annotated and serialized.
+        let stream = async_stream::stream! {
+            for (rank, (memory, score)) in results.iter().enumerate() {
+                let mut entry = recall_entry(&conn, memory, *score, &agent_id);
+                entry["rank"] = json!(rank + 1);
+                let mut line = entry.to_string();
+                line.push('\n');
+                yield Ok::<_, std::convert::Infallible>(line);
+            }
+        };
+        return (
+            [(header::CONTENT_TYPE, NDJSON.to_string()), (header::HeaderName::from_static("x-search-method"), method)],
+            Body::from_stream(stream),
+        )
+            .into_response();
+    }
+
+    let results_json: Vec<Value> = results
+        .iter()
+        .map(|(memory, score)| recall_entry(&conn, memory, *score, &agent_id))
+        .collect();
+    Json(json!({ "ok": true, "results": results_json, "search_method": method, "warming_up": warming_up })).into_response()
+}
+
+const NDJSON: &str = "application/x-ndjson";
+
+/// One recall result as returned over HTTP, with the agent's importance
+/// weighting when it's above neutral.
+fn recall_entry(conn: &rusqlite::Connection, memory: &db::memories::Memory, score: f64, agent_id: &str) -> Value {
+    let importance = db::recall::get_agent_importance(conn, &memory.id, agent_id).unwrap_or(1.0);
+    let mut entry = json!({ "memory": memory, "score": score });
+    if importance > 1.0 {
+        entry["importance"] = json!(importance);
+    }
+    entry
+}
+
+/// Run the recall (by subject, agent or query), apply the token budget and log
+/// the recalls. `embedding` is the query's, when semantic search is possible.
+/// Returns the results and the search method used.
+fn recall_results(
+    conn: &rusqlite::Connection,
+    body: &RecallRequest,
+    embedding: Option<Vec<f32>>,
+) -> (Vec<(db::memories::Memory, f64)>, String) {
     use crate::db::search::SearchMethod;
 
     // Subject-scoped search
     if let Some(ref subj) = body.subject {
-        let memories = db::search::by_subject(&conn, subj, body.limit).unwrap_or_default();
+        let memories = db::search::by_subject(conn, subj, body.limit).unwrap_or_default();
         // Log recalls
         for memory in &memories {
-            let _ = db::recall::log_recall(&conn, &memory.id, body.agent_id.as_deref(), Some(subj), Some(1.0));
+            let _ = db::recall::log_recall(conn, &memory.id, body.agent_id.as_deref(), Some(subj), Some(1.0));
         }
-        let results_json: Vec<Value> = memories
-            .iter()
-            .map(|memory| json!({ "memory": memory, "score": 1.0 }))
-            .collect();
-        return Json(json!({ "ok": true, "results": results_json, "search_method": "subject" }));
+        return (memories.into_iter().map(|m| (m, 1.0)).collect(), "subject".to_string());
     }
 
     // Agent-scoped search
     if let Some(ref agent) = body.agent_id {
-        let memories = db::search::by_agent(&conn, agent, body.limit).unwrap_or_default();
+        let memories = db::search::by_agent(conn, agent, body.limit).unwrap_or_default();
         // Log recalls
         for memory in &memories {
-            let _ = db::recall::log_recall(&conn, &memory.id, Some(agent), None, Some(1.0));
+            let _ = db::recall::log_recall(conn, &memory.id, Some(agent), None, Some(1.0));
         }
-        let results_json: Vec<Value> = memories
-            .iter()
-            .map(|memory| json!({ "memory": memory, "score": 1.0 }))
-            .collect();
-        return Json(json!({ "ok": true, "results": results_json, "search_method": "agent" }));
+        return (memories.into_iter().map(|m| (m, 1.0)).collect(), "agent".to_string());
     }
 
     let fetch_limit = if body.max_tokens.is_some() { body.limit.max(20) } else { body.limit };
 
-    let forced_method = body.search_method.as_deref();
-
-    let (results, method) = match forced_method {
-        Some("keyword") => {
-            (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
-        }
-        Some("semantic") => {
-            if let Some(ref emb) = state.embedder {
-                let emb = emb.clone();
-                let query = body.query.clone();
-                let embedding = tokio::task::spawn_blocking(move || {
-                    let mut embedder = emb.lock().unwrap_or_else(|e| e.into_inner());
-                    embedder.embed(&query).ok()
-                }).await.ok().flatten();
-                match embedding {
-                    Some(emb_vec) => {
-                        let sem = db::search::semantic_search(&conn, &emb_vec, fetch_limit).unwrap_or_default();
-                        (sem, SearchMethod::Semantic)
-                    }
-                    None => {
-                        (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
-                    }
+    let keyword = || (db::search::keyword_search(conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword);
+
+    let (results, method) = match (body.search_method.as_deref(), embedding) {
+        (Some("keyword"), _) | (_, None) => keyword(),
+        (Some("semantic"), Some(emb_vec)) => {
+            let sem = db::search::semantic_search_all(conn, &body.query, &emb_vec, fetch_limit).unwrap_or_default();
+            (sem, SearchMethod::Semantic)
+        }
+        (_, Some(emb_vec)) => {
+            #[cfg(feature = "pro")]
+            {
+                let hybrid = db::search::hybrid_search(conn, &body.query, &emb_vec, fetch_limit).unwrap_or_default();
+                if !hybrid.is_empty() {
+                    (hybrid, SearchMethod::Hybrid)
+                } else {
+                    keyword()
                 }
-            } else {
-                (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
             }
-        }
-        _ => {
-            if let Some(ref emb) = state.embedder {
-                let emb = emb.clone();
-                let query = body.query.clone();
-                let embedding = tokio::task::spawn_blocking(move || {
-                    let mut embedder = emb.lock().unwrap_or_else(|e| e.into_inner());
-                    embedder.embed(&query).ok()
-                }).await.ok().flatten();
-                match embedding {
-                    Some(emb_vec) => {
-                        #[cfg(feature = "pro")]
-                        {
-                            let hybrid = db::search::hybrid_search(&conn, &body.query, &emb_vec, fetch_limit).unwrap_or_default();
-                            if !hybrid.is_empty() {
-                                (hybrid, SearchMethod::Hybrid)
-                            } else {
-                                (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
-                            }
-                        }
-                        #[cfg(not(feature = "pro"))]
-                        {
-                            let sem = db::search::semantic_search(&conn, &emb_vec, fetch_limit).unwrap_or_default();
-                            if !sem.is_empty() {
-                                (sem, SearchMethod::Semantic)
-                            } else {
-                                (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
-                            }
-                        }
-                    }
-                    None => {
-                        (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
-                    }
+            #[cfg(not(feature = "pro"))]
+            {
+                let sem = db::search::semantic_search_all(conn, &body.query, &emb_vec, fetch_limit).unwrap_or_default();
+                if !sem.is_empty() {
+                    (sem, SearchMethod::Semantic)
+                } else {
+                    keyword()
                 }
-            } else {
-                (db::search::keyword_search(&conn, &body.query, fetch_limit).unwrap_or_default(), SearchMethod::Keyword)
             }
         }
     };
@@ -351,7 +475,7 @@ async fn recall(State(state): State<AppState>, Json(body): Json<RecallRequest>)
     let filtered: Vec<&(db::memories::Memory, f64)> = if let Some(budget) = body.max_tokens {
         let mut token_count = 0usize;
         results.iter().take_while(|(mem, _)| {
-            let tokens = mem.content.len() / 4;
+            let tokens = crate::embed::count_tokens(&mem.content);
             if token_count + tokens > budget { return false; }
             token_count += tokens;
             true
@@ -363,26 +487,12 @@ async fn recall(State(state): State<AppState>, Json(body): Json<RecallRequest>)
     // Log recalls for importance tracking (Phase 2: Adaptive Scoring)
     let agent_id = body.agent_id.as_deref();
     for (memory, score) in &filtered {
-        let _ = db::recall::log_recall(&conn, &memory.id, agent_id, Some(&body.query), Some(*score));
+        let _ = db::recall::log_recall(conn, &memory.id, agent_id, Some(&body.query), Some(*score));
     }
+    let accessed: Vec<&str> = filtered.iter().map(|(memory, _)| memory.id.as_str()).collect();
+    let _ = db::recall::record_access(conn, &accessed);
 
-    let results_json: Vec<Value> = filtered
-        .iter()
-        .map(|(memory, score)| {
-            let importance = db::recall::get_agent_importance(
-                &conn,
-                &memory.id,
-                body.agent_id.as_deref().unwrap_or(""),
-            ).unwrap_or(1.0);
-            let mut entry = json!({ "memory": memory, "score": score });
-            if importance > 1.0 {
-                entry["importance"] = json!(importance);
-            }
-            entry
-        })
-        .collect();
-
-    Json(json!({ "ok": true, "results": results_json, "search_method": method.to_string() }))
+    (filtered.into_iter().cloned().collect(), method.to_string())
 }
 
 async fn get_memory(Path(id): Path<String>) -> Json<Value> {
@@ -392,19 +502,22 @@ async fn get_memory(Path(id): Path<String>) -> Json<Value> {
     };
 
     match db::memories::get(&conn, &id) {
-        Ok(Some(memory)) => Json(json!({ "ok": true, "memory": memory })),
+        Ok(Some(memory)) => {
+            let _ = db::recall::record_access(&conn, &[memory.id.as_str()]);
+            Json(json!({ "ok": true, "memory": memory }))
+        }
         Ok(None) => Json(json!({ "ok": false, "error": "Not found" })),
         Err(e) => Json(json!({ "ok": false, "error": sanitize_error(&e) })),
     }
 }
 
-async fn delete_memory(Path(id): Path<String>) -> Json<Value> {
+async fn delete_memory(State(state): State<AppState>, Path(id): Path<String>) -> Json<Value> {
     let conn = match db::open() {
         Ok(c) => c,
         Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })),
     };
 
-    match db::memories::delete(&conn, &id) {
+    match db::memories::delete(&conn, &id, state.config().forget_penalty()) {
         Ok(true) => {
             { #[cfg(feature = "pro")] crate::webhooks::fire("memory.deleted", json!({ "memory_id": id })); }
             Json(json!({ "ok": true }))
@@ -428,10 +541,12 @@ struct UpdateRequest {
     expires_at: Option<String>,
     #[serde(default)]
     remove_expiry: Option<bool>,
+    #[serde(default)]
+    defer_sync: Option<bool>,
 }
 
 async fn update_memory(State(state): State<AppState>, Path(id): Path<String>, Json(body): Json<UpdateRequest>) -> Json<Value> {
-    let cfg = &state.config;
+    let cfg = &state.config();
 
     let conn = match db::open() {
         Ok(c) => c,
@@ -462,13 +577,18 @@ async fn update_memory(State(state): State<AppState>, Path(id): Path<String>, Js
         None
     };
 
+    let (content, secrets) = match body.content.as_deref().map(|c| crate::secrets::apply(cfg.secret_policy, c)).transpose() {
+        Ok(Some((content, secrets))) => (Some(content), secrets),
+        Ok(None) => (None, Vec::new()),
+        Err(e) => return Json(json!({ "ok": false, "error": e })),
+    };
+
     // Re-embed if content changed (spawn_blocking)
-    let embedding = if let Some(ref c) = body.content {
-        if let Some(ref emb) = state.embedder {
-            let emb = emb.clone();
+    let embedding = if let Some(ref c) = content {
+        if let Some(emb) = state.embedder() {
             let content = c.clone();
             tokio::task::spawn_blocking(move || {
-                let mut e = emb.lock().unwrap_or_else(|e| e.into_inner());
+                let mut e = emb.acquire();
                 e.embed(&content).ok()
             }).await.ok().flatten()
         } else { None }
@@ -480,17 +600,11 @@ async fn update_memory(State(state): State<AppState>, Path(id): Path<String>, Js
 
     let expires_ref = expires_at.as_ref().map(|e| e.as_deref());
 
-    match db::memories::update(&conn, &id, body.content.as_deref(), validated_tags.as_deref(), subject, expires_ref, embedding.as_deref()) {
+    match db::memories::update(&conn, &id, content.as_deref(), validated_tags.as_deref(), subject, expires_ref, embedding.as_deref()) {
         Ok(Some(memory)) => {
             { #[cfg(feature = "pro")] crate::webhooks::fire("memory.updated", json!({ "memory": memory })); }
-            if cfg.is_logged_in() {
-                let mid = memory.id.clone();
-                let cfg2 = cfg.clone();
-                tokio::spawn(async move {
-                    let _ = crate::sync::push_one(&cfg2, &mid).await;
-                });
-            }
-            Json(json!({ "ok": true, "memory": memory }))
+            crate::sync::push_soon(cfg, &memory.id, body.defer_sync);
+            Json(json!({ "ok": true, "memory": memory, "secrets": secrets }))
         }
         Ok(None) => Json(json!({ "ok": false, "error": "Not found" })),
         Err(e) => Json(json!({ "ok": false, "error": sanitize_error(&e) })),
@@ -503,7 +617,7 @@ async fn subjects() -> Json<Value> {
         Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })),
     };
 
-    match db::search::list_subjects(&conn) {
+    match db::search::list_subjects(&conn, db::search::SubjectSort::Count, None, 0) {
         Ok(subjects) => {
             let list: Vec<Value> = subjects
                 .iter()
@@ -539,6 +653,26 @@ async fn status() -> Json<Value> {
     }))
 }
 
+/// MCP clients seen over SSE since the daemon started, connected ones first.
+async fn clients(State(state): State<AppState>) -> Json<Value> {
+    Json(json!({ "ok": true, "clients": state.clients.snapshot() }))
+}
+
+/// Re-read config.toml without restarting the daemon (open SSE sessions and
+/// the loaded embedder survive unless the model changed).
+async fn reload(State(state): State<AppState>) -> Json<Value> {
+    match super::reload(&state).await {
+        Ok(report) => Json(json!({
+            "ok": true,
+            "applied": report.applied,
+            "requires_restart": report.requires_restart,
+            "embedder_reloaded": report.embedder_reloaded,
+            "warnings": report.warnings,
+        })),
+        Err(e) => Json(json!({ "ok": false, "error": sanitize_error(&e) })),
+    }
+}
+
 // ── Knowledge Graph routes (Standard+ tier) ─────────────
 
 mod graph_routes {
@@ -742,6 +876,104 @@ mod graph_routes {
             Err(e) => Json(json!({ "ok": false, "error": sanitize_error(&e) })),
         }
     }
+
+    // ── Read-only graph API (/v1/graph/*) for visualizers ──
+
+    /// Deepest traversal `/v1/graph/traverse` will run.
+    const MAX_GRAPH_DEPTH: usize = 5;
+
+    /// 403 unless the tier (or capability token) includes the knowledge graph.
+    fn graph_forbidden(state: &AppState) -> Option<Response> {
+        let cfg = state.config();
+        if cfg.feature_enabled("knowledge_graph") {
+            return None;
+        }
+        let message = cfg.capability_expired_message("The knowledge graph").unwrap_or_else(|| {
+            "The knowledge graph requires Standard tier or higher. Upgrade at https://ctxovrflw.dev/pricing".into()
+        });
+        Some((StatusCode::FORBIDDEN, Json(json!({ "ok": false, "error": message }))).into_response())
+    }
+
+    /// The entity with this id, else the first named `key` (optionally of `entity_type`).
+    fn resolve_entity(conn: &rusqlite::Connection, key: &str, entity_type: Option<&str>) -> anyhow::Result<Option<db::graph::Entity>> {
+        if let Some(entity) = db::graph::get_entity(conn, key)? {
+            return Ok(Some(entity));
+        }
+        Ok(db::graph::find_entity(conn, key, entity_type)?.into_iter().next())
+    }
+
+    pub async fn graph_entities(State(state): State<AppState>, query: Query<ListEntitiesQuery>) -> Response {
+        if let Some(forbidden) = graph_forbidden(&state) {
+            return forbidden;
+        }
+        list_entities_http(query).await.into_response()
+    }
+
+    #[derive(Deserialize)]
+    pub struct GraphRelationsQuery {
+        pub entity: String,
+        #[serde(default)]
+        pub entity_type: Option<String>,
+        #[serde(default)]
+        pub relation_type: Option<String>,
+        #[serde(default)]
+        pub direction: Option<String>,
+    }
+
+    pub async fn graph_relations(State(state): State<AppState>, Query(q): Query<GraphRelationsQuery>) -> Response {
+        if let Some(forbidden) = graph_forbidden(&state) {
+            return forbidden;
+        }
+        let conn = match db::open() {
+            Ok(c) => c,
+            Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+        };
+        let entity = match resolve_entity(&conn, &q.entity, q.entity_type.as_deref()) {
+            Ok(Some(entity)) => entity,
+            Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({ "ok": false, "error": "Entity not found" }))).into_response(),
+            Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+        };
+        let mut response = get_relations_http(
+            Path(entity.id.clone()),
+            Query(GetRelationsQuery { relation_type: q.relation_type, direction: q.direction }),
+        )
+        .await;
+        response.0["entity"] = json!(entity);
+        response.into_response()
+    }
+
+    #[derive(Deserialize)]
+    pub struct GraphTraverseQuery {
+        pub entity: String,
+        #[serde(default)]
+        pub entity_type: Option<String>,
+        #[serde(default = "default_max_depth")]
+        pub depth: usize,
+        #[serde(default)]
+        pub relation_type: Option<String>,
+        #[serde(default)]
+        pub min_confidence: f64,
+    }
+
+    pub async fn graph_traverse(State(state): State<AppState>, Query(q): Query<GraphTraverseQuery>) -> Response {
+        if let Some(forbidden) = graph_forbidden(&state) {
+            return forbidden;
+        }
+        let conn = match db::open() {
+            Ok(c) => c,
+            Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+        };
+        let entity = match resolve_entity(&conn, &q.entity, q.entity_type.as_deref()) {
+            Ok(Some(entity)) => entity,
+            Ok(None) => return (StatusCode::NOT_FOUND, Json(json!({ "ok": false, "error": "Entity not found" }))).into_response(),
+            Err(e) => return Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+        };
+        let depth = q.depth.min(MAX_GRAPH_DEPTH);
+        match db::graph::traverse(&conn, &entity.id, depth, q.relation_type.as_deref(), q.min_confidence) {
+            Ok(nodes) => Json(json!({ "ok": true, "entity": entity, "depth": depth, "nodes": nodes, "total": nodes.len() })).into_response(),
+            Err(e) => Json(json!({ "ok": false, "error": sanitize_error(&e) })).into_response(),
+        }
+    }
 }
 
 use graph_routes::*;
@@ -4,9 +4,13 @@ pub mod config;
 pub mod crypto;
 pub mod db;
 pub mod embed;
+pub mod entities;
 pub mod http;
 pub mod mcp;
 pub mod maintenance;
+pub mod secrets;
+#[cfg(feature = "pro")]
+pub mod summarizer;
 pub mod sync;
 pub mod validation;
 #[cfg(feature = "pro")]
@@ -9,6 +9,10 @@ mod embed;
 mod http;
 mod mcp;
 mod maintenance;
+mod entities;
+mod secrets;
+#[cfg(feature = "pro")]
+mod summarizer;
 mod sync;
 mod validation;
 #[cfg(feature = "pro")]
@@ -16,48 +20,110 @@ mod webhooks;
 
 use clap::Parser;
 use cli::{Cli, Command};
+use tracing_subscriber::prelude::*;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     let cli = Cli::parse();
 
+    // Needs no config, and its stdout must stay a clean script
+    if let Command::Completions { shell } = cli.command {
+        return cli::completions::run(shell);
+    }
+
+    config::set_data_dir_override(cli.data_dir.as_deref());
+    config::set_cloud_url_override(cli.cloud_url.as_deref());
+    let cfg = config::Config::load()?;
+    config::set_active_db_path(cfg.db_path.as_deref());
+    config::init_http_client(&cfg)?;
+    db::set_all_workspaces(cli.all_workspaces);
+
+    // Surface misconfiguration up front (status lists it itself; MCP stdio must stay quiet)
+    if !matches!(cli.command, Command::Mcp | Command::Status) {
+        for problem in cfg.validate() {
+            eprintln!("⚠ config.toml {problem}");
+        }
+    }
+
     // In MCP mode, stdout is the JSON-RPC transport — no logging to stdout/stderr
     // to avoid corrupting the protocol stream
+    let mut _log_guard = None;
     if !matches!(cli.command, Command::Mcp) {
-        tracing_subscriber::fmt()
-            .with_env_filter(
-                tracing_subscriber::EnvFilter::try_from_default_env()
-                    .unwrap_or_else(|_| "ctxovrflw=info".into()),
-            )
+        // A foreground daemon without a terminal (systemd, nohup, ...) also logs to
+        // a rotating daemon.log so output survives after detaching
+        let detached = matches!(cli.command, Command::Start { foreground: true, .. })
+            && !atty::is(atty::Stream::Stderr);
+        let file_layer = if detached {
+            match daemon::log_file_appender(cfg.log_retention_days) {
+                Ok(appender) => {
+                    let (writer, guard) = tracing_appender::non_blocking(appender);
+                    _log_guard = Some(guard);
+                    Some(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(writer))
+                }
+                Err(e) => {
+                    eprintln!("Failed to open daemon log file: {e}");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        tracing_subscriber::registry()
+            .with(http::reloadable_log_filter(&cfg, cli.log_filter()))
+            .with(tracing_subscriber::fmt::layer())
+            .with(file_layer)
             .init();
     }
 
-    let cfg = config::Config::load()?;
-
     match cli.command {
-        Command::Init { non_interactive } => {
+        Command::Init { non_interactive, scope, model } => {
             if non_interactive {
-                cli::init_auto::run(&cfg).await
+                cli::init_auto::run(&cfg, scope, model.as_deref()).await
             } else if atty::is(atty::Stream::Stdout) {
-                cli::init_tui::run(&cfg).await
+                cli::init_tui::run(&cfg, scope).await
             } else {
-                cli::init::run(&cfg).await
+                cli::init::run(&cfg, scope).await
             }
         }
-        Command::Start { port, foreground } => daemon::start(&cfg, port, foreground).await,
+        Command::Start { port, foreground } => daemon::start(&cfg, port.unwrap_or(cfg.port), foreground).await,
         Command::Stop => daemon::stop(&cfg).await,
         Command::Status => cli::status::run(&cfg).await,
-        Command::Remember { text, r#type, tags, subject } => {
-            cli::remember::run(&cfg, &text, r#type.as_deref(), tags, subject.as_deref()).await
+        Command::Remember { text, r#type, tags, subject, skip_embedding } => {
+            cli::remember::run(&cfg, &text, r#type.as_deref(), tags, subject.as_deref(), skip_embedding).await
+        }
+        Command::Recall { query, queries, related, limit, explain, interactive, raw, types, tags } => {
+            match related {
+                Some(id) => cli::recall::related(&id, limit, &types, raw),
+                None if query.is_none() && queries.is_empty() => cli::recall::tagged(&tags, limit, &types, raw),
+                None => {
+                    let queries: Vec<String> = query.into_iter().chain(queries).collect();
+                    cli::recall::run(&cfg, &queries, limit, &types, &tags, explain, interactive, raw).await
+                }
+            }
+        }
+        Command::Forget { id, dry_run, list } => {
+            if list {
+                cli::forget::list(&cfg).await
+            } else {
+                cli::forget::run(&cfg, id.as_deref().unwrap_or_default(), dry_run).await
+            }
+        }
+        Command::Cleanup { older_than, types, dry_run } => cli::cleanup::run(&cfg, &older_than, &types, dry_run),
+        Command::Restore { id } => cli::restore::run(&cfg, &id).await,
+        Command::Pinned => cli::pinned::run(&cfg),
+        Command::Timeline { since, until, subject, weekly, limit } => {
+            cli::timeline::run(&since, until.as_deref(), subject.as_deref(), weekly, limit)
+        }
+        Command::Memories { format, subject, tags } => {
+            cli::memories::run(&cfg, format, subject.as_deref(), &tags).await
         }
-        Command::Recall { query, limit } => cli::recall::run(&cfg, &query, limit).await,
-        Command::Forget { id, dry_run } => cli::forget::run(&cfg, &id, dry_run).await,
-        Command::Memories => cli::memories::run(&cfg).await,
         #[cfg(feature = "pro")]
         Command::Graph { action } => {
             match action {
-                cli::GraphAction::Build => cli::graph::build()?,
+                cli::GraphAction::Build => cli::graph::build(&cfg)?,
                 cli::GraphAction::Stats => cli::graph::stats()?,
+                cli::GraphAction::Import { file } => cli::graph::import(&file)?,
             }
             Ok(())
         },
@@ -70,19 +136,66 @@ async fn main() -> anyhow::Result<()> {
             }
             Ok(())
         },
-        Command::Reindex => {
-            cli::reindex::run()?;
+        Command::Reindex { since, missing_only, verify, jobs } => {
+            if verify {
+                cli::reindex::verify(jobs)?;
+            } else {
+                cli::reindex::run(since.as_deref(), missing_only, jobs)?;
+            }
+            Ok(())
+        }
+        Command::Tags { action } => match action.unwrap_or(cli::TagsAction::List) {
+            cli::TagsAction::List => cli::tags::list(),
+            cli::TagsAction::Rename { from, to } => cli::tags::rename(&cfg, &from, &to).await,
+        },
+        Command::Workspace { action } => {
+            match action {
+                cli::WorkspaceAction::List => cli::workspace::list(&cfg)?,
+                cli::WorkspaceAction::Switch { name } => cli::workspace::switch(&name)?,
+            }
             Ok(())
         }
-        Command::Sync => sync::run(&cfg).await,
-        Command::Account => cli::account::run(&cfg).await,
-        Command::Login { key } => {
+        Command::Profile { action } => {
+            match action {
+                cli::ProfileAction::List => cli::profile::list(&cfg)?,
+                cli::ProfileAction::Add { name, db } => cli::profile::add(&name, db)?,
+                cli::ProfileAction::Switch { name } => cli::profile::switch(&name)?,
+                cli::ProfileAction::Remove { name } => cli::profile::remove(&name)?,
+            }
+            Ok(())
+        }
+        Command::Sync { conflicts, resolve, dry_run, check, self_test } => {
+            if check {
+                cli::cloud::check(&cfg).await
+            } else if self_test {
+                sync::self_test(&cfg).await
+            } else if dry_run {
+                sync::dry_run(&cfg)
+            } else if conflicts {
+                sync::show_conflicts()
+            } else if let Some(id) = resolve {
+                sync::resolve_conflict(id)
+            } else {
+                sync::run(&cfg).await
+            }
+        }
+        Command::Account { json } => cli::account::run(&cfg, json).await,
+        Command::Login { key, device } => {
             match key {
                 Some(k) => cli::login::run_with_key(&cfg, &k).await,
+                None if device => cli::login::run_device(&cfg).await,
                 None => cli::login::run(&cfg).await,
             }
         }
         Command::Logout => cli::logout::run(&cfg).await,
+        Command::Completions { .. } => unreachable!("handled before config is loaded"),
+        Command::Wipe { keep_config, agents, cloud, yes } => {
+            cli::wipe::run(&cfg, keep_config, agents, cloud, yes).await
+        }
+        Command::Audit { agent, tool, since, limit, json } => {
+            cli::audit::run(&cfg, agent.as_deref(), tool.as_deref(), since.as_deref(), limit, json)
+        }
+        Command::Logs { source, follow, lines } => cli::logs::run(source, follow, lines).await,
         Command::Service { action } => {
             match action {
                 cli::ServiceAction::Install => daemon::service_install(),
@@ -90,10 +203,10 @@ async fn main() -> anyhow::Result<()> {
                 cli::ServiceAction::Status => {
                     if daemon::is_service_installed() {
                         let running = daemon::is_service_running();
-                        println!("Service: installed");
+                        println!("Service: installed ({})", daemon::service_manager());
                         println!("Status:  {}", if running { "running ✓" } else { "stopped" });
                         if running {
-                            println!("Logs:    journalctl --user -u ctxovrflw -f");
+                            println!("Logs:    {}", daemon::service_logs_hint());
                         }
                     } else {
                         println!("Service: not installed");
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use rusqlite::OptionalExtension;
 use std::collections::HashMap;
 use tracing;
 
@@ -15,9 +16,9 @@ pub struct ConsolidationReport {
 ///
 /// Strategy: exact dedupe only (same subject + type + normalized content).
 /// Keeps the most recently updated memory and tombstones older duplicates.
-pub fn run_consolidation_pass() -> Result<ConsolidationReport> {
+pub fn run_consolidation_pass(forget: db::graph::ForgetPenalty) -> Result<ConsolidationReport> {
     let conn = db::open()?;
-    let subjects = db::search::list_subjects(&conn)?;
+    let subjects = db::search::list_subjects(&conn, db::search::SubjectSort::Count, None, 0)?;
 
     let mut report = ConsolidationReport::default();
 
@@ -45,7 +46,7 @@ pub fn run_consolidation_pass() -> Result<ConsolidationReport> {
             );
 
             if seen.contains_key(&key) {
-                if db::memories::delete(&conn, &mem.id)? {
+                if db::memories::delete(&conn, &mem.id, forget)? {
                     report.duplicates_removed += 1;
                 }
             } else {
@@ -57,6 +58,75 @@ pub fn run_consolidation_pass() -> Result<ConsolidationReport> {
     Ok(report)
 }
 
+/// Cluster near-identical memories within each subject by their stored
+/// embeddings (single-linkage at `consolidation_similarity`) and record the
+/// groups for `consolidate`. Chunks of the same memory are never grouped.
+/// Returns the number of groups found.
+#[cfg(feature = "pro")]
+pub fn detect_consolidation_candidates(cfg: &crate::config::Config) -> Result<usize> {
+    let conn = db::open()?;
+    let subjects = db::search::list_subjects(&conn, db::search::SubjectSort::Count, None, 0)?;
+    let mut groups = Vec::new();
+
+    for (subject, count) in subjects {
+        if count < 2 {
+            continue;
+        }
+        let memories: Vec<_> = db::search::by_subject(&conn, &subject, 300)?
+            .into_iter()
+            .filter_map(|m| db::search::load_embedding(&conn, &m.id).map(|e| (m, e)))
+            .collect();
+
+        // Union-find over pairs above the threshold, tracking the weakest link per root.
+        let mut parent: Vec<usize> = (0..memories.len()).collect();
+        let mut weakest: Vec<f64> = vec![1.0; memories.len()];
+        fn root(parent: &mut [usize], mut i: usize) -> usize {
+            while parent[i] != i {
+                parent[i] = parent[parent[i]];
+                i = parent[i];
+            }
+            i
+        }
+        let chunkset = |m: &db::memories::Memory| m.tags.iter().find(|t| t.starts_with("chunkset:")).cloned();
+        for i in 0..memories.len() {
+            for j in i + 1..memories.len() {
+                if chunkset(&memories[i].0).is_some() && chunkset(&memories[i].0) == chunkset(&memories[j].0) {
+                    continue;
+                }
+                let similarity = db::search::cosine_similarity(&memories[i].1, &memories[j].1);
+                if similarity < cfg.consolidation_similarity {
+                    continue;
+                }
+                let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+                let link = weakest[a].min(weakest[b]).min(similarity);
+                if a != b {
+                    parent[b] = a;
+                }
+                weakest[a] = link;
+            }
+        }
+
+        let mut clusters: HashMap<usize, Vec<String>> = HashMap::new();
+        for (i, (mem, _)) in memories.iter().enumerate() {
+            let r = root(&mut parent, i);
+            clusters.entry(r).or_default().push(mem.id.clone());
+        }
+        for (r, memory_ids) in clusters {
+            if memory_ids.len() >= 2 {
+                groups.push(db::consolidation::CandidateGroup {
+                    subject: subject.clone(),
+                    memory_ids,
+                    similarity: weakest[r],
+                    detected_at: String::new(),
+                });
+            }
+        }
+    }
+
+    db::consolidation::replace_all(&conn, &groups)?;
+    Ok(groups.len())
+}
+
 /// Update importance scores for all memories based on recall patterns
 /// Call this periodically (e.g., hourly) to keep scores fresh
 pub fn update_importance_scores() -> Result<usize> {
@@ -66,6 +136,60 @@ pub fn update_importance_scores() -> Result<usize> {
     Ok(updated)
 }
 
+/// Decay knowledge-graph relations that haven't been re-confirmed within
+/// `relation_decay_days`. Returns (decayed, removed). Call daily.
+#[cfg(feature = "pro")]
+pub fn decay_relations(cfg: &crate::config::Config) -> Result<(usize, usize)> {
+    if cfg.relation_decay_days == 0 {
+        return Ok((0, 0));
+    }
+    let conn = db::open()?;
+    db::graph::decay_stale_relations(
+        &conn,
+        cfg.relation_decay_days,
+        cfg.relation_decay_factor,
+        cfg.relation_min_confidence,
+    )
+}
+
+/// Embed up to `limit` live memories that have no vector under the active
+/// model — left behind by `model switch`, or by an embed that failed. Returns
+/// how many were embedded. Does nothing while the loaded embedder runs a
+/// different model than the one `memory_vectors` belongs to.
+pub fn embed_missing_vectors(limit: usize) -> Result<usize> {
+    let conn = db::open()?;
+    let (missing, _) = db::memories::vector_drift(&conn)?;
+    if missing.is_empty() {
+        return Ok(0);
+    }
+    let pool = crate::embed::get_or_init()?;
+    let active = db::active_vector_model(&conn)?;
+    if active.as_deref() != Some(pool.model_id()) {
+        tracing::warn!(
+            "Not embedding missing vectors: the loaded model is {} but stored vectors are for {}; restart the daemon",
+            pool.model_id(),
+            active.as_deref().unwrap_or("no model")
+        );
+        return Ok(0);
+    }
+    let mut embedder = pool.acquire();
+    let mut embedded = 0;
+    for id in missing.iter().take(limit) {
+        let content: Option<String> = conn
+            .query_row("SELECT content FROM memories WHERE id = ?1", [id], |r| r.get(0))
+            .optional()?;
+        let Some(content) = content else { continue };
+        match embedder.embed(&content) {
+            Ok(embedding) => {
+                db::memories::upsert_vector(&conn, id, &embedding)?;
+                embedded += 1;
+            }
+            Err(e) => tracing::warn!("Failed to embed memory {id}: {e}"),
+        }
+    }
+    Ok(embedded)
+}
+
 /// Clean old recall logs (keep 90 days)
 pub fn cleanup_recall_logs() -> Result<usize> {
     let conn = db::open()?;
@@ -1,6 +1,8 @@
+pub mod resources;
 pub mod tools;
 pub mod transport;
 pub mod sse;
+pub mod streamable;
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
@@ -35,15 +37,122 @@ pub struct JsonRpcError {
     pub message: String,
 }
 
-// ── Shared message handler (used by both stdio and SSE) ──────
+// ── Shared message handler (stdio, SSE and Streamable HTTP) ──
 
+/// Handle one incoming message: a single request or a batch (JSON array).
+/// Returns the serialized response, or `None` when nothing should be sent back
+/// (a notification, or a batch made up only of notifications).
 pub async fn handle_message(cfg: &Config, raw: &str) -> Result<Option<String>> {
-    let request: JsonRpcRequest = serde_json::from_str(raw)?;
+    let message: Value = match serde_json::from_str(raw) {
+        Ok(v) => v,
+        Err(e) => {
+            let resp = make_response(None, None, Some(JsonRpcError {
+                code: -32700,
+                message: format!("Parse error: {e}"),
+            }));
+            return Ok(Some(serde_json::to_string(&resp)?));
+        }
+    };
+
+    match message {
+        Value::Array(items) => {
+            if items.is_empty() {
+                let resp = make_response(None, None, Some(JsonRpcError {
+                    code: -32600,
+                    message: "Invalid Request: empty batch".to_string(),
+                }));
+                return Ok(Some(serde_json::to_string(&resp)?));
+            }
+            let mut responses = Vec::with_capacity(items.len());
+            for item in items {
+                if let Some(resp) = handle_request(cfg, item).await? {
+                    responses.push(resp);
+                }
+            }
+            if responses.is_empty() {
+                Ok(None)
+            } else {
+                Ok(Some(serde_json::to_string(&responses)?))
+            }
+        }
+        single => match handle_request(cfg, single).await? {
+            Some(resp) => Ok(Some(serde_json::to_string(&resp)?)),
+            None => Ok(None),
+        },
+    }
+}
+
+async fn handle_request(cfg: &Config, message: Value) -> Result<Option<JsonRpcResponse>> {
+    let request: JsonRpcRequest = match serde_json::from_value(message.clone()) {
+        Ok(r) => r,
+        Err(e) => {
+            return Ok(Some(make_response(
+                message.get("id").cloned(),
+                None,
+                Some(JsonRpcError { code: -32600, message: format!("Invalid Request: {e}") }),
+            )));
+        }
+    };
+    // Notifications (no id) never get a response
+    let is_notification = request.id.is_none();
+    let id = request.id.clone();
 
+    // A failing method becomes an error response rather than tearing down the session
+    let response = match dispatch(cfg, request).await {
+        Ok(response) => response,
+        Err(e) => Some(make_response(id, None, Some(JsonRpcError {
+            code: -32603,
+            message: crate::validation::sanitize_error(&e),
+        }))),
+    };
+
+    if is_notification {
+        return Ok(None);
+    }
+    Ok(response)
+}
+
+/// Protocol versions this server speaks, newest first. `initialize` echoes the
+/// client's version when it's listed and offers the newest otherwise.
+/// 2025-03-26 introduced the Streamable HTTP transport (`POST /mcp`).
+pub const PROTOCOL_VERSIONS: &[&str] = &["2025-03-26", "2024-11-05"];
+
+/// `instructions` sent on `initialize` unless `mcp_instructions` overrides it.
+const DEFAULT_INSTRUCTIONS: &str = "ctxovrflw is a shared memory layer. Use 'remember' to store important context (preferences, decisions, facts, project details) and 'recall' before answering questions that might benefit from prior context. Memories persist across sessions and are shared with other AI tools the user has connected.";
+
+/// Text of the `ctxovrflw-context` prompt unless `mcp_instructions` overrides it.
+const CONTEXT_PROMPT: &str = concat!(
+    "You have access to ctxovrflw — a shared memory layer that persists across sessions and is shared between ALL connected AI tools (Cursor, Claude Code, Cline, VS Code, etc.).\n\n",
+    "## When to use RECALL:\n",
+    "- At the START of every conversation, recall general context about the user and project\n",
+    "- Before answering questions about preferences, past decisions, or project setup\n",
+    "- When the user says \"do you remember\" or \"what did I say about\"\n",
+    "- When you need context that might have been shared in another tool\n\n",
+    "## When to use REMEMBER:\n",
+    "- When the user shares a preference (\"I prefer X over Y\")\n",
+    "- When a decision is made (\"We're going with Rust\")\n",
+    "- When important project context comes up (API endpoints, deploy targets, tech stack)\n",
+    "- When the user explicitly asks you to remember something\n",
+    "- When you learn something important about the user or project\n\n",
+    "## Best practices:\n",
+    "- Store ATOMIC facts — one concept per memory, not paragraphs\n",
+    "- Use descriptive tags with namespace:value format (e.g., project:myapp, lang:rust)\n",
+    "- Choose the right type: preference, semantic (facts), episodic (events), procedural (how-to)\n",
+    "- Use natural language for recall queries — semantic search understands meaning, not just keywords\n",
+    "- Don't store sensitive data (passwords, tokens, keys)\n\n",
+    "## The magic:\n",
+    "Memories are shared across tools. If the user tells Cursor their deploy target is Fly.io, you can recall that here. This is the key value — cross-tool context continuity."
+);
+
+async fn dispatch(cfg: &Config, request: JsonRpcRequest) -> Result<Option<JsonRpcResponse>> {
     let response = match request.method.as_str() {
         "initialize" => {
+            let requested = request.params.as_ref().and_then(|p| p["protocolVersion"].as_str());
+            let version = requested
+                .filter(|v| PROTOCOL_VERSIONS.contains(v))
+                .unwrap_or(PROTOCOL_VERSIONS[0]);
             let result = serde_json::json!({
-                "protocolVersion": "2024-11-05",
+                "protocolVersion": version,
                 "capabilities": {
                     "tools": { "listChanged": false },
                     "resources": { "listChanged": false },
@@ -54,7 +163,7 @@ pub async fn handle_message(cfg: &Config, raw: &str) -> Result<Option<String>> {
                     "version": env!("CARGO_PKG_VERSION"),
                     "description": "Universal AI context layer — shared memory across all your AI tools. What you tell one tool, every tool knows."
                 },
-                "instructions": "ctxovrflw is a shared memory layer. Use 'remember' to store important context (preferences, decisions, facts, project details) and 'recall' before answering questions that might benefit from prior context. Memories persist across sessions and are shared with other AI tools the user has connected."
+                "instructions": cfg.mcp_instructions.as_deref().unwrap_or(DEFAULT_INSTRUCTIONS)
             });
             Some(make_response(request.id, Some(result), None))
         }
@@ -73,10 +182,23 @@ pub async fn handle_message(cfg: &Config, raw: &str) -> Result<Option<String>> {
             Some(make_response(request.id, Some(result), None))
         }
         "resources/list" => {
-            Some(make_response(request.id, Some(serde_json::json!({ "resources": [] })), None))
+            Some(make_response(request.id, Some(resources::list()?), None))
         }
         "resources/templates/list" => {
-            Some(make_response(request.id, Some(serde_json::json!({ "resourceTemplates": [] })), None))
+            Some(make_response(request.id, Some(resources::templates()), None))
+        }
+        "resources/read" => {
+            let uri = request.params
+                .as_ref()
+                .and_then(|p| p["uri"].as_str())
+                .unwrap_or("");
+            match resources::read(cfg, uri).await? {
+                Some(result) => Some(make_response(request.id, Some(result), None)),
+                None => Some(make_response(request.id, None, Some(JsonRpcError {
+                    code: -32002,
+                    message: format!("Resource not found: {uri}"),
+                }))),
+            }
         }
         "prompts/list" => {
             Some(make_response(request.id, Some(serde_json::json!({
@@ -100,28 +222,7 @@ pub async fn handle_message(cfg: &Config, raw: &str) -> Result<Option<String>> {
                             "role": "user",
                             "content": {
                                 "type": "text",
-                                "text": concat!(
-                                    "You have access to ctxovrflw — a shared memory layer that persists across sessions and is shared between ALL connected AI tools (Cursor, Claude Code, Cline, VS Code, etc.).\n\n",
-                                    "## When to use RECALL:\n",
-                                    "- At the START of every conversation, recall general context about the user and project\n",
-                                    "- Before answering questions about preferences, past decisions, or project setup\n",
-                                    "- When the user says \"do you remember\" or \"what did I say about\"\n",
-                                    "- When you need context that might have been shared in another tool\n\n",
-                                    "## When to use REMEMBER:\n",
-                                    "- When the user shares a preference (\"I prefer X over Y\")\n",
-                                    "- When a decision is made (\"We're going with Rust\")\n",
-                                    "- When important project context comes up (API endpoints, deploy targets, tech stack)\n",
-                                    "- When the user explicitly asks you to remember something\n",
-                                    "- When you learn something important about the user or project\n\n",
-                                    "## Best practices:\n",
-                                    "- Store ATOMIC facts — one concept per memory, not paragraphs\n",
-                                    "- Use descriptive tags with namespace:value format (e.g., project:myapp, lang:rust)\n",
-                                    "- Choose the right type: preference, semantic (facts), episodic (events), procedural (how-to)\n",
-                                    "- Use natural language for recall queries — semantic search understands meaning, not just keywords\n",
-                                    "- Don't store sensitive data (passwords, tokens, keys)\n\n",
-                                    "## The magic:\n",
-                                    "Memories are shared across tools. If the user tells Cursor their deploy target is Fly.io, you can recall that here. This is the key value — cross-tool context continuity."
-                                )
+                                "text": cfg.mcp_instructions.as_deref().unwrap_or(CONTEXT_PROMPT)
                             }
                         }]
                     })), None))
@@ -144,10 +245,7 @@ pub async fn handle_message(cfg: &Config, raw: &str) -> Result<Option<String>> {
         )),
     };
 
-    match response {
-        Some(resp) => Ok(Some(serde_json::to_string(&resp)?)),
-        None => Ok(None),
-    }
+    Ok(response)
 }
 
 pub fn make_response(
@@ -170,7 +268,7 @@ pub async fn serve_stdio(cfg: &Config) -> Result<()> {
     let mut writer = tokio::io::stdout();
 
     // Debug log to file (won't interfere with stdio protocol)
-    let log_path = Config::data_dir().ok().map(|d| d.join("mcp-debug.log"));
+    let log_path = Config::mcp_log_path().ok();
     let log = |msg: &str| {
         if let Some(ref path) = log_path {
             let _ = std::fs::OpenOptions::new()
@@ -190,7 +288,14 @@ pub async fn serve_stdio(cfg: &Config) -> Result<()> {
         match transport::read_message(&mut reader).await {
             Ok(Some(msg)) => {
                 log(&format!("← {}", &msg[..msg.len().min(200)]));
-                let response = handle_message(cfg, &msg).await?;
+                let response = match handle_message(cfg, &msg).await {
+                    Ok(r) => r,
+                    Err(e) => {
+                        // Keep the session alive — one bad frame shouldn't end it
+                        log(&format!("ERROR handling message: {e}"));
+                        continue;
+                    }
+                };
                 if let Some(resp) = response {
                     log(&format!("→ {}", &resp[..resp.len().min(200)]));
                     transport::write_message(&mut writer, &resp).await?;
@@ -0,0 +1,106 @@
+//! MCP resources: subjects as browsable resources (`ctxovrflw://subject/<name>`)
+//! plus a `ctxovrflw://recall/{query}` template backed by the recall tool.
+
+use anyhow::Result;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::db;
+
+const SUBJECT_PREFIX: &str = "ctxovrflw://subject/";
+const RECALL_PREFIX: &str = "ctxovrflw://recall/";
+
+/// Max memories returned when reading a subject resource.
+const SUBJECT_READ_LIMIT: usize = 100;
+
+pub fn list() -> Result<Value> {
+    let conn = db::open()?;
+    let resources: Vec<Value> = db::search::list_subjects(&conn, db::search::SubjectSort::Count, None, 0)?
+        .into_iter()
+        .map(|(subject, count)| {
+            json!({
+                "uri": format!("{SUBJECT_PREFIX}{}", encode(&subject)),
+                "name": subject,
+                "description": format!("{count} memories about {subject}"),
+                "mimeType": "text/plain"
+            })
+        })
+        .collect();
+    Ok(json!({ "resources": resources }))
+}
+
+pub fn templates() -> Value {
+    json!({
+        "resourceTemplates": [{
+            "uriTemplate": format!("{RECALL_PREFIX}{{query}}"),
+            "name": "recall",
+            "description": "Memories relevant to a natural-language query (same search as the recall tool)",
+            "mimeType": "text/plain"
+        }]
+    })
+}
+
+/// Read a resource. Returns `Ok(None)` when the URI doesn't name a known resource.
+pub async fn read(cfg: &Config, uri: &str) -> Result<Option<Value>> {
+    let text = if let Some(name) = uri.strip_prefix(SUBJECT_PREFIX) {
+        let subject = decode(name);
+        let conn = db::open()?;
+        let memories = db::search::by_subject(&conn, &subject, SUBJECT_READ_LIMIT)?;
+        if memories.is_empty() {
+            return Ok(None);
+        }
+        let mut text = format!("Memories about '{subject}':\n\n");
+        for memory in &memories {
+            text.push_str(&format!("- [{}] ({}) {}\n", memory.id, memory.memory_type, memory.content));
+        }
+        text
+    } else if let Some(query) = uri.strip_prefix(RECALL_PREFIX) {
+        let query = decode(query);
+        if query.trim().is_empty() {
+            return Ok(None);
+        }
+        let result = super::tools::call_tool(cfg, &json!({
+            "name": "recall",
+            "arguments": { "query": query }
+        }))
+        .await?;
+        result["content"][0]["text"].as_str().unwrap_or_default().to_string()
+    } else {
+        return Ok(None);
+    };
+
+    Ok(Some(json!({
+        "contents": [{ "uri": uri, "mimeType": "text/plain", "text": text }]
+    })))
+}
+
+/// Percent-encode everything outside the URI unreserved set (':' kept for `project:x` subjects).
+fn encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b':' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+fn decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 3 <= bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("00");
+            out.push(u8::from_str_radix(hex, 16).unwrap_or(0));
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
@@ -1,5 +1,6 @@
 use axum::{
     extract::Query,
+    http::HeaderMap,
     response::{
         sse::{Event, KeepAlive, Sse},
         IntoResponse,
@@ -8,75 +9,228 @@ use axum::{
     Router,
 };
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{mpsc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch, Mutex};
 use uuid::Uuid;
 
 use crate::config::Config;
 use crate::validation::sanitize_error;
 
-type SessionMap = Arc<Mutex<HashMap<String, mpsc::Sender<String>>>>;
+/// How long a dropped stream's session is kept so the client can resume it
+/// with `Last-Event-ID` instead of re-initializing.
+const SESSION_RESUME_SECS: u64 = 300;
 
-/// Create the MCP SSE router (mount under /mcp)
-pub fn router(cfg: Config) -> Router {
+/// Responses retained per session for replay on reconnect.
+const REPLAY_BUFFER: usize = 64;
+
+/// Per-session state. Event ids are `<session_id>:<seq>`, so a reconnecting
+/// client's `Last-Event-ID` names both the session and the last response it saw.
+struct Session {
+    /// Live stream, or `None` while the client is disconnected.
+    tx: Option<mpsc::Sender<(u64, String)>>,
+    next_seq: u64,
+    /// Recent (seq, response) pairs, replayed after `Last-Event-ID` on reconnect.
+    backlog: VecDeque<(u64, String)>,
+    /// Bumped on every (re)attach so a stale stream's cleanup leaves the new one alone.
+    generation: u64,
+    /// `clientInfo.name` from the session's `initialize`.
+    client: Option<String>,
+}
+
+type SessionMap = Arc<Mutex<HashMap<String, Session>>>;
+
+/// Which MCP clients are connected over SSE, and when each client (by
+/// `clientInfo.name`) or agent (by a tool call's `agent_id`) was last seen.
+/// Streamable HTTP clients hold no stream, so they only update `last_seen`.
+#[derive(Clone, Default)]
+pub struct ClientTracker(Arc<std::sync::Mutex<HashMap<String, ClientSeen>>>);
+
+struct ClientSeen {
+    /// Attached SSE streams for this client.
+    streams: usize,
+    last_seen: chrono::DateTime<chrono::Utc>,
+}
+
+/// One client as reported by `GET /v1/clients`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ClientStatus {
+    pub name: String,
+    pub active: bool,
+    pub last_seen: chrono::DateTime<chrono::Utc>,
+}
+
+impl ClientTracker {
+    fn update(&self, name: &str, streams: impl FnOnce(usize) -> usize) {
+        let mut clients = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let seen = clients
+            .entry(name.to_string())
+            .or_insert(ClientSeen { streams: 0, last_seen: chrono::Utc::now() });
+        seen.streams = streams(seen.streams);
+        seen.last_seen = chrono::Utc::now();
+    }
+
+    pub(crate) fn seen(&self, name: &str) {
+        self.update(name, |n| n);
+    }
+
+    fn attached(&self, name: &str) {
+        self.update(name, |n| n + 1);
+    }
+
+    fn detached(&self, name: &str) {
+        self.update(name, |n| n.saturating_sub(1));
+    }
+
+    /// Every client seen since the daemon started, most recent first.
+    pub fn snapshot(&self) -> Vec<ClientStatus> {
+        let clients = self.0.lock().unwrap_or_else(|e| e.into_inner());
+        let mut list: Vec<ClientStatus> = clients
+            .iter()
+            .map(|(name, seen)| ClientStatus { name: name.clone(), active: seen.streams > 0, last_seen: seen.last_seen })
+            .collect();
+        list.sort_by(|a, b| b.active.cmp(&a.active).then(b.last_seen.cmp(&a.last_seen)));
+        list
+    }
+}
+
+/// Create the MCP SSE router (mount under /mcp). Each message is handled with
+/// the config current at the time, so a daemon reload applies to open sessions.
+pub fn router(cfg: watch::Receiver<Config>, clients: ClientTracker) -> Router {
     let sessions: SessionMap = Arc::new(Mutex::new(HashMap::new()));
 
     Router::new()
         .route("/sse", get({
             let sessions = sessions.clone();
-            let cfg = cfg.clone();
-            move || handle_sse(sessions, cfg)
+            let clients = clients.clone();
+            move |headers| handle_sse(sessions, clients, headers)
         }))
         .route("/messages", post({
             let sessions = sessions.clone();
             let cfg = cfg.clone();
-            move |query, body| handle_message(sessions, cfg, query, body)
+            move |query, body| handle_message(sessions, clients, cfg, query, body)
         }))
 }
 
-/// Drop guard that removes the session from the map when the SSE stream disconnects.
+fn event_id(session_id: &str, seq: u64) -> String {
+    format!("{session_id}:{seq}")
+}
+
+/// Parse a `Last-Event-ID` value into (session id, last seen seq).
+fn parse_event_id(id: &str) -> Option<(&str, u64)> {
+    let (session_id, seq) = id.trim().rsplit_once(':')?;
+    Some((session_id, seq.parse().ok()?))
+}
+
+/// Drop guard that detaches the session when the SSE stream disconnects, and
+/// removes it if the client hasn't resumed within `SESSION_RESUME_SECS`.
 struct SessionDropGuard {
     session_id: String,
+    generation: u64,
     sessions: SessionMap,
+    clients: ClientTracker,
 }
 
 impl Drop for SessionDropGuard {
     fn drop(&mut self) {
         let session_id = self.session_id.clone();
+        let generation = self.generation;
         let sessions = self.sessions.clone();
+        let clients = self.clients.clone();
         // Spawn a task to clean up since we can't await in Drop
         tokio::spawn(async move {
-            sessions.lock().await.remove(&session_id);
-            tracing::debug!("SSE session {} cleaned up", session_id);
+            match sessions.lock().await.get_mut(&session_id) {
+                Some(s) if s.generation == generation => {
+                    s.tx = None;
+                    if let Some(client) = &s.client {
+                        clients.detached(client);
+                    }
+                }
+                _ => return,
+            }
+            tracing::debug!("SSE session {} detached", session_id);
+
+            tokio::time::sleep(Duration::from_secs(SESSION_RESUME_SECS)).await;
+            let mut map = sessions.lock().await;
+            if map.get(&session_id).is_some_and(|s| s.generation == generation) {
+                map.remove(&session_id);
+                tracing::debug!("SSE session {} cleaned up", session_id);
+            }
         });
     }
 }
 
-/// GET /mcp/sse — establish SSE stream
+/// GET /mcp/sse — establish SSE stream, resuming the session named by
+/// `Last-Event-ID` if it's still known (otherwise a fresh session starts and
+/// the client re-initializes as usual).
 async fn handle_sse(
     sessions: SessionMap,
-    _cfg: Config,
+    clients: ClientTracker,
+    headers: HeaderMap,
 ) -> Sse<impl futures_core::Stream<Item = Result<Event, std::convert::Infallible>>> {
-    let session_id = Uuid::new_v4().to_string();
-    let (tx, mut rx) = mpsc::channel::<String>(32);
+    let (tx, mut rx) = mpsc::channel::<(u64, String)>(32);
+    let last_event = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_event_id);
 
-    sessions.lock().await.insert(session_id.clone(), tx);
+    let (session_id, generation, resume_seq, replay) = {
+        let mut map = sessions.lock().await;
+        match last_event.and_then(|(id, seq)| map.get_mut(id).map(|s| (id, seq, s))) {
+            Some((id, last_seq, session)) => {
+                // A stale stream still attached keeps its count; its guard won't detach
+                if session.tx.replace(tx).is_none()
+                    && let Some(client) = &session.client
+                {
+                    clients.attached(client);
+                }
+                session.generation += 1;
+                let replay: Vec<_> = session
+                    .backlog
+                    .iter()
+                    .filter(|(seq, _)| *seq > last_seq)
+                    .cloned()
+                    .collect();
+                tracing::debug!("SSE session {} resumed, replaying {} event(s)", id, replay.len());
+                (id.to_string(), session.generation, last_seq, replay)
+            }
+            None => {
+                let id = Uuid::new_v4().to_string();
+                map.insert(id.clone(), Session {
+                    tx: Some(tx),
+                    next_seq: 1,
+                    backlog: VecDeque::new(),
+                    generation: 0,
+                    client: None,
+                });
+                (id, 0, 0, Vec::new())
+            }
+        }
+    };
 
     // Create drop guard for cleanup
     let _guard = SessionDropGuard {
         session_id: session_id.clone(),
+        generation,
         sessions: sessions.clone(),
+        clients,
     };
 
     let stream = async_stream::stream! {
-        // First event: tell the client where to POST messages
+        // First event: tell the client where to POST messages. On resume it
+        // carries the client's own position so a second drop doesn't rewind.
         let endpoint = format!("/mcp/messages?sessionId={}", session_id);
-        yield Ok(Event::default().event("endpoint").data(endpoint));
+        yield Ok(Event::default().event("endpoint").id(event_id(&session_id, resume_seq)).data(endpoint));
+
+        // Responses produced while the client was away
+        for (seq, msg) in replay {
+            yield Ok(Event::default().event("message").id(event_id(&session_id, seq)).data(msg));
+        }
 
         // Stream responses back to client
-        while let Some(msg) = rx.recv().await {
-            yield Ok(Event::default().event("message").data(msg));
+        while let Some((seq, msg)) = rx.recv().await {
+            yield Ok(Event::default().event("message").id(event_id(&session_id, seq)).data(msg));
         }
 
         // Guard will be dropped here, cleaning up the session
@@ -86,6 +240,29 @@ async fn handle_sse(
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
+/// Note who's behind a session: `initialize` names the client, and tool calls
+/// may carry an `agent_id`.
+fn track_client(clients: &ClientTracker, session: &mut Session, body: &str) {
+    let Ok(message) = serde_json::from_str::<serde_json::Value>(body) else { return };
+    let params = &message["params"];
+    if message["method"] == "initialize"
+        && session.client.is_none()
+        && let Some(name) = params["clientInfo"]["name"].as_str()
+    {
+        session.client = Some(name.to_string());
+        if session.tx.is_some() {
+            clients.attached(name);
+        }
+    } else if let Some(client) = &session.client {
+        clients.seen(client);
+    }
+    if let Some(agent_id) = params["arguments"]["agent_id"].as_str()
+        && session.client.as_deref() != Some(agent_id)
+    {
+        clients.seen(agent_id);
+    }
+}
+
 #[derive(Deserialize)]
 struct MessageQuery {
     #[serde(rename = "sessionId")]
@@ -95,31 +272,46 @@ struct MessageQuery {
 /// POST /mcp/messages?sessionId=xxx — receive JSON-RPC from client
 async fn handle_message(
     sessions: SessionMap,
-    cfg: Config,
+    clients: ClientTracker,
+    cfg: watch::Receiver<Config>,
     Query(query): Query<MessageQuery>,
     body: String,
 ) -> impl IntoResponse {
-    let tx = {
-        let map = sessions.lock().await;
-        map.get(&query.session_id).cloned()
-    };
-
-    let Some(tx) = tx else {
-        return (
-            axum::http::StatusCode::NOT_FOUND,
-            "Session not found".to_string(),
-        );
-    };
+    {
+        let mut map = sessions.lock().await;
+        let Some(session) = map.get_mut(&query.session_id) else {
+            return (
+                axum::http::StatusCode::NOT_FOUND,
+                "Session not found".to_string(),
+            );
+        };
+        track_client(&clients, session, &body);
+    }
 
     // Process through the shared handler
+    let cfg = cfg.borrow().clone();
     match super::handle_message(&cfg, &body).await {
         Ok(Some(response)) => {
-            // Send response via SSE
-            if tx.send(response).await.is_err() {
-                return (
-                    axum::http::StatusCode::GONE,
-                    "SSE connection closed".to_string(),
-                );
+            // Record the response for replay, then send it via SSE. A
+            // detached session keeps it buffered until the client resumes.
+            let (seq, tx) = {
+                let mut map = sessions.lock().await;
+                let Some(session) = map.get_mut(&query.session_id) else {
+                    return (
+                        axum::http::StatusCode::GONE,
+                        "SSE connection closed".to_string(),
+                    );
+                };
+                let seq = session.next_seq;
+                session.next_seq += 1;
+                session.backlog.push_back((seq, response.clone()));
+                if session.backlog.len() > REPLAY_BUFFER {
+                    session.backlog.pop_front();
+                }
+                (seq, session.tx.clone())
+            };
+            if let Some(tx) = tx {
+                let _ = tx.send((seq, response)).await;
             }
             (axum::http::StatusCode::ACCEPTED, "ok".to_string())
         }
@@ -133,3 +325,75 @@ async fn handle_message(
         ),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(live: bool) -> Session {
+        let tx = live.then(|| mpsc::channel(1).0);
+        Session { tx, next_seq: 0, backlog: VecDeque::new(), generation: 0, client: None }
+    }
+
+    fn backdate(clients: &ClientTracker, name: &str, secs: i64) {
+        let mut map = clients.0.lock().unwrap();
+        map.get_mut(name).unwrap().last_seen = chrono::Utc::now() - chrono::Duration::seconds(secs);
+    }
+
+    #[test]
+    fn test_tracker_counts_streams() {
+        let clients = ClientTracker::default();
+        clients.attached("cursor");
+        clients.attached("cursor");
+        clients.detached("cursor");
+        assert!(clients.snapshot()[0].active);
+
+        clients.detached("cursor");
+        clients.detached("cursor");
+        let list = clients.snapshot();
+        assert_eq!(list.len(), 1);
+        assert!(!list[0].active);
+    }
+
+    #[test]
+    fn test_snapshot_lists_active_then_recent() {
+        let clients = ClientTracker::default();
+        clients.seen("old-agent");
+        clients.seen("new-agent");
+        clients.attached("cursor");
+        backdate(&clients, "old-agent", 600);
+        backdate(&clients, "new-agent", 60);
+        backdate(&clients, "cursor", 3600);
+
+        let names: Vec<String> = clients.snapshot().into_iter().map(|c| c.name).collect();
+        assert_eq!(names, ["cursor", "new-agent", "old-agent"]);
+    }
+
+    #[test]
+    fn test_track_client_names_session_and_agents() {
+        let clients = ClientTracker::default();
+        let mut live = session(true);
+        track_client(&clients, &mut live, r#"{"method":"initialize","params":{"clientInfo":{"name":"cursor"}}}"#);
+        assert_eq!(live.client.as_deref(), Some("cursor"));
+
+        // A second initialize doesn't attach again
+        track_client(&clients, &mut live, r#"{"method":"initialize","params":{"clientInfo":{"name":"other"}}}"#);
+        track_client(
+            &clients,
+            &mut live,
+            r#"{"method":"tools/call","params":{"name":"recall","arguments":{"agent_id":"planner"}}}"#,
+        );
+        track_client(&clients, &mut live, "not json");
+
+        let list = clients.snapshot();
+        let cursor = list.iter().find(|c| c.name == "cursor").unwrap();
+        assert!(cursor.active);
+        assert!(list.iter().any(|c| c.name == "planner" && !c.active));
+        assert!(!list.iter().any(|c| c.name == "other"));
+
+        // Without a live stream the client is counted once one (re)attaches
+        let mut detached = session(false);
+        track_client(&clients, &mut detached, r#"{"method":"initialize","params":{"clientInfo":{"name":"zed"}}}"#);
+        assert!(clients.snapshot().iter().all(|c| c.name != "zed"));
+    }
+}
@@ -0,0 +1,184 @@
+//! MCP "Streamable HTTP" transport (protocol 2025-03-26): the client POSTs
+//! JSON-RPC to a single endpoint and gets the response in the HTTP reply.
+//! Runs alongside the older SSE transport in [`super::sse`] and shares the
+//! same `handle_message` dispatch.
+
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+    Router,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{watch, Mutex};
+use uuid::Uuid;
+
+use super::sse::ClientTracker;
+use crate::config::Config;
+use crate::validation::sanitize_error;
+
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Sessions unused for this long are dropped; the client's next request gets
+/// 404 and it re-initializes.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(24 * 60 * 60);
+
+struct Session {
+    /// `clientInfo.name` from the `initialize` that opened the session.
+    client: Option<String>,
+    last_seen: Instant,
+}
+
+/// Sessions handed out on `initialize`, until the client DELETEs them or
+/// they sit idle past [`SESSION_IDLE_TIMEOUT`].
+type SessionMap = Arc<Mutex<HashMap<String, Session>>>;
+
+/// Create the Streamable HTTP router (mount under /mcp, so the endpoint is
+/// `/mcp` itself). Responses are always plain JSON; server-initiated streams
+/// (GET) aren't offered, which the spec allows.
+pub fn router(cfg: watch::Receiver<Config>, clients: ClientTracker) -> Router {
+    let sessions: SessionMap = Arc::new(Mutex::new(HashMap::new()));
+
+    Router::new().route(
+        "/",
+        post({
+            let sessions = sessions.clone();
+            move |headers, body| handle_post(sessions, clients, cfg, headers, body)
+        })
+        .get(|| async { StatusCode::METHOD_NOT_ALLOWED })
+        .delete(move |headers| handle_delete(sessions, headers)),
+    )
+}
+
+fn session_id(headers: &HeaderMap) -> Option<String> {
+    headers.get(SESSION_HEADER).and_then(|v| v.to_str().ok()).map(String::from)
+}
+
+/// The `initialize` request in `body`, if any, and the client name it carries.
+fn initialize_client(body: &str) -> Option<Option<String>> {
+    let message: serde_json::Value = serde_json::from_str(body).ok()?;
+    (message["method"] == "initialize")
+        .then(|| message["params"]["clientInfo"]["name"].as_str().map(String::from))
+}
+
+/// Whether the request declares a JSON body (`application/json`, with or
+/// without parameters).
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(';').next())
+        .is_some_and(|mime| mime.trim().eq_ignore_ascii_case("application/json"))
+}
+
+/// POST /mcp — one JSON-RPC message or batch in, its response out. The body
+/// must be `application/json`. An `initialize` opens a session (returned in
+/// `Mcp-Session-Id`); every other request must name one, and an unknown or
+/// expired session gets 404 so the client re-initializes.
+async fn handle_post(
+    sessions: SessionMap,
+    clients: ClientTracker,
+    cfg: watch::Receiver<Config>,
+    headers: HeaderMap,
+    body: String,
+) -> Response {
+    if !is_json(&headers) {
+        return (StatusCode::UNSUPPORTED_MEDIA_TYPE, "Content-Type must be application/json").into_response();
+    }
+
+    let initialize = initialize_client(&body);
+    let new_session = match (&initialize, session_id(&headers)) {
+        (Some(client), _) => {
+            let id = Uuid::new_v4().to_string();
+            if let Some(name) = client {
+                clients.seen(name);
+            }
+            let mut map = sessions.lock().await;
+            map.retain(|_, session| session.last_seen.elapsed() < SESSION_IDLE_TIMEOUT);
+            map.insert(id.clone(), Session { client: client.clone(), last_seen: Instant::now() });
+            Some(id)
+        }
+        (None, Some(id)) => {
+            let client = {
+                let mut map = sessions.lock().await;
+                match map.get_mut(&id) {
+                    Some(session) if session.last_seen.elapsed() < SESSION_IDLE_TIMEOUT => {
+                        session.last_seen = Instant::now();
+                        session.client.clone()
+                    }
+                    Some(_) => {
+                        map.remove(&id);
+                        return (StatusCode::NOT_FOUND, "Session expired").into_response();
+                    }
+                    None => return (StatusCode::NOT_FOUND, "Session not found").into_response(),
+                }
+            };
+            if let Some(name) = client {
+                clients.seen(&name);
+            }
+            None
+        }
+        (None, None) => {
+            return (StatusCode::BAD_REQUEST, "Missing Mcp-Session-Id header; send initialize first")
+                .into_response();
+        }
+    };
+    if let Ok(message) = serde_json::from_str::<serde_json::Value>(&body)
+        && let Some(agent_id) = message["params"]["arguments"]["agent_id"].as_str()
+    {
+        clients.seen(agent_id);
+    }
+
+    let cfg = cfg.borrow().clone();
+    let mut response = match super::handle_message(&cfg, &body).await {
+        Ok(Some(reply)) => ([(header::CONTENT_TYPE, "application/json")], reply).into_response(),
+        // Notifications and client responses only: accepted, nothing to send
+        Ok(None) => StatusCode::ACCEPTED.into_response(),
+        Err(e) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Error: {}", sanitize_error(&e)),
+        )
+            .into_response(),
+    };
+    if let Some(id) = new_session
+        && let Ok(value) = HeaderValue::from_str(&id)
+    {
+        response.headers_mut().insert(SESSION_HEADER, value);
+    }
+    response
+}
+
+/// DELETE /mcp — the client is done with its session.
+async fn handle_delete(sessions: SessionMap, headers: HeaderMap) -> StatusCode {
+    let Some(id) = session_id(&headers) else {
+        return StatusCode::BAD_REQUEST;
+    };
+    match sessions.lock().await.remove(&id) {
+        Some(_) => StatusCode::NO_CONTENT,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_idle_session_expires() {
+        let sessions: SessionMap = Arc::default();
+        let idle = Instant::now().checked_sub(SESSION_IDLE_TIMEOUT + Duration::from_secs(1)).unwrap();
+        sessions.lock().await.insert("old".into(), Session { client: None, last_seen: idle });
+
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        headers.insert(SESSION_HEADER, HeaderValue::from_static("old"));
+        let cfg = watch::channel(Config::default()).1;
+        let body = r#"{"jsonrpc":"2.0","id":1,"method":"tools/list"}"#.to_string();
+
+        let response = handle_post(sessions.clone(), ClientTracker::default(), cfg, headers, body).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert!(sessions.lock().await.is_empty());
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 use crate::config::Config;
 use crate::crypto;
@@ -37,6 +38,15 @@ struct RemoteMemory {
     agent_id: Option<String>,
     #[serde(default)]
     expires_at: Option<String>,
+    /// Absent for memories pushed before workspaces existed
+    #[serde(default)]
+    workspace: Option<String>,
+    /// Stored keyword-only; absent for memories pushed before that existed
+    #[serde(default)]
+    skip_embedding: bool,
+    /// SHA-256 of the plaintext content, as computed by the pushing device
+    #[serde(default)]
+    content_hash: Option<String>,
     deleted: bool,
     created_at: String,
     updated_at: String,
@@ -63,16 +73,30 @@ fn get_encryption_key(cfg: &Config) -> Result<[u8; 32]> {
 pub async fn run(cfg: &Config) -> Result<()> {
     if !cfg.is_logged_in() {
         println!("Not logged in. Run `ctxovrflw login` first.");
+        if let Ok(n) = pending_count(cfg)
+            && n > 0
+        {
+            println!("  ⏳ {n} memories waiting to sync");
+        }
         return Ok(());
     }
 
     let api_key = cfg.api_key.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in — no API key"))?;
     let device_id = cfg.device_id.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in — no device ID"))?;
-    let enc_key = get_encryption_key(cfg)?;
+    let enc_key = match get_encryption_key(cfg) {
+        Ok(key) => key,
+        Err(e) => {
+            if let Some(backlog) = backlog_summary(cfg) {
+                eprintln!("⏳ {backlog}");
+            }
+            return Err(e);
+        }
+    };
 
+    let local_edits = pending_local_edits()?;
     let pushed = push(cfg, api_key, device_id, &enc_key).await?;
-    let (pulled, pull_purged) = pull(cfg, api_key, device_id, &enc_key).await?;
-    let purged = purge_tombstones()?;
+    let (pulled, pull_purged) = pull(cfg, api_key, device_id, &enc_key, &local_edits).await?;
+    let purged = purge_tombstones(cfg)?;
 
     println!("✓ Sync complete — pushed {pushed}, pulled {pulled}");
     if purged > 0 {
@@ -82,6 +106,103 @@ pub async fn run(cfg: &Config) -> Result<()> {
         println!("  🧹 Purged {pull_purged} server-acknowledged tombstones");
     }
     println!("  🔐 End-to-end encrypted");
+    if let Some(backlog) = backlog_summary(cfg) {
+        println!("  ⏳ {backlog}");
+    }
+    if let Ok(n) = db::open().and_then(|c| db::conflicts::count_unresolved(&c))
+        && n > 0
+    {
+        println!("  ⚠ {n} sync conflict(s) — review with `ctxovrflw sync --conflicts`");
+    }
+    Ok(())
+}
+
+/// Report what a sync would push without contacting the cloud or marking
+/// anything synced. Payloads are encrypted with a throwaway key when the real
+/// one isn't available — ciphertext size doesn't depend on the key.
+pub fn dry_run(cfg: &Config) -> Result<()> {
+    let conn = db::open()?;
+    register_sync_exclude(&conn, &cfg.sync_exclude)?;
+    let enc_key = get_encryption_key(cfg).unwrap_or([0u8; 32]);
+
+    let pending = get_unsynced_memories(&conn, &enc_key, cfg.sync_cipher, i64::MAX as usize)?;
+    let deletions = pending.iter().filter(|m| m["deleted"].as_bool() == Some(true)).count();
+    let bytes: usize = pending.iter().map(estimate_size).sum();
+    let requests = bytes.div_ceil(MAX_PAYLOAD_BYTES).max(pending.len().div_ceil(FETCH_BATCH_SIZE));
+
+    println!("Sync dry run — nothing will be sent");
+    println!();
+    println!(
+        "  Memories to push: {} ({} updates, {} deletions)",
+        pending.len(),
+        pending.len() - deletions,
+        deletions
+    );
+    println!(
+        "  Payload:          ~{:.1} KB in ~{requests} request(s)",
+        bytes as f64 / 1024.0
+    );
+    #[cfg(feature = "pro")]
+    {
+        // Graph rows carry no sync state yet (`push_graph` isn't wired up), so
+        // report totals: all of it stays local for now.
+        let count = |table: &str| -> Result<usize> {
+            Ok(conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |r| r.get(0))?)
+        };
+        println!(
+            "  Graph:            {} entities, {} relations (not synced — stays local)",
+            count("entities")?,
+            count("relations")?
+        );
+    }
+    println!("  Pull:             not checked (requires contacting the cloud)");
+
+    if let Some(max) = cfg.effective_max_memories() {
+        let total = db::memories::count(&conn)?;
+        if total > max {
+            println!();
+            println!("  ⚠ {total} memories exceeds your tier limit of {max} — the cloud will reject the excess");
+        }
+    }
+    if let Some(reason) = blocker(cfg) {
+        println!();
+        println!("  ⏳ Sync is currently blocked: {reason}");
+    }
+    Ok(())
+}
+
+/// List unresolved sync conflicts with both versions side by side.
+pub fn show_conflicts() -> Result<()> {
+    let conn = db::open()?;
+    let conflicts = db::conflicts::list_unresolved(&conn)?;
+
+    if conflicts.is_empty() {
+        println!("No unresolved sync conflicts.");
+        return Ok(());
+    }
+
+    println!("{} unresolved sync conflict(s):", conflicts.len());
+    for c in &conflicts {
+        println!();
+        println!("#{} memory {} (detected {}, {} version kept)", c.id, c.memory_id, c.detected_at, c.winner);
+        println!("  local  ({}): {}", c.local_updated_at, c.local_content);
+        println!("  remote ({}): {}", c.remote_updated_at, c.remote_content);
+    }
+    println!();
+    println!("Edit the memory if the kept version is wrong, then mark it reviewed:");
+    println!("  ctxovrflw sync --resolve <#>");
+
+    Ok(())
+}
+
+/// Mark a sync conflict as reviewed.
+pub fn resolve_conflict(id: i64) -> Result<()> {
+    let conn = db::open()?;
+    if db::conflicts::resolve(&conn, id)? {
+        println!("✓ Conflict #{id} marked resolved.");
+    } else {
+        println!("No unresolved conflict #{id}.");
+    }
     Ok(())
 }
 
@@ -101,79 +222,291 @@ pub async fn run_silent(cfg: &Config) -> Result<(usize, usize, usize)> {
         }
     };
 
+    let local_edits = pending_local_edits()?;
     let pushed = push(cfg, api_key, device_id, &enc_key).await?;
-    let (pulled, pull_purged) = pull(cfg, api_key, device_id, &enc_key).await?;
-    let _ = purge_tombstones(); // Best-effort cleanup
+    let (pulled, pull_purged) = pull(cfg, api_key, device_id, &enc_key, &local_edits).await?;
+    let _ = purge_tombstones(cfg); // Best-effort cleanup
 
     Ok((pushed, pulled, pull_purged))
 }
 
-/// Purge tombstones (soft-deleted memories) that have been synced and are older than 7 days.
+/// Why sync can't currently push the local backlog.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncBlocker {
+    NotLoggedIn,
+    TierWithoutSync,
+    NoEncryption,
+    PinExpired,
+    OverLimit,
+}
+
+impl std::fmt::Display for SyncBlocker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::NotLoggedIn => "not logged in — run `ctxovrflw login`",
+            Self::TierWithoutSync => "cloud sync not included in your tier",
+            Self::NoEncryption => "sync PIN not set up — run `ctxovrflw login`",
+            Self::PinExpired => "sync PIN expired — run `ctxovrflw login`",
+            Self::OverLimit => "memory limit reached — the cloud rejects new memories",
+        })
+    }
+}
+
+/// The reason background sync is skipping this vault, if any. `run_silent`
+/// returns quietly in these cases, so callers use this to explain the backlog.
+pub fn blocker(cfg: &Config) -> Option<SyncBlocker> {
+    if !cfg.is_logged_in() {
+        return Some(SyncBlocker::NotLoggedIn);
+    }
+    if !cfg.effective_cloud_sync() {
+        return Some(SyncBlocker::TierWithoutSync);
+    }
+    if !cfg.is_encrypted() {
+        return Some(SyncBlocker::NoEncryption);
+    }
+    if cfg.get_cached_key().is_none() {
+        return Some(SyncBlocker::PinExpired);
+    }
+    if let Some(max) = cfg.effective_max_memories()
+        && db::open().and_then(|c| db::memories::count(&c)).is_ok_and(|n| n >= max)
+    {
+        return Some(SyncBlocker::OverLimit);
+    }
+    None
+}
+
+/// Number of memories (including deletions) waiting to be pushed, ignoring
+/// ones excluded by `sync_exclude`.
+pub fn pending_count(cfg: &Config) -> Result<usize> {
+    let conn = db::open()?;
+    register_sync_exclude(&conn, &cfg.sync_exclude)?;
+    let count: usize = conn.query_row(
+        "SELECT COUNT(*) FROM memories
+         WHERE (synced_at IS NULL OR updated_at > synced_at)
+         AND NOT sync_excluded(tags, subject)",
+        [],
+        |r| r.get(0),
+    )?;
+    Ok(count)
+}
+
+/// Queue every live memory — and, once the graph tables carry sync state,
+/// every entity and relation — for another push, so the cloud copies get
+/// re-encrypted after the sync key changes. Returns how many rows were queued.
+pub fn queue_full_repush() -> Result<usize> {
+    let conn = db::open()?;
+    let mut queued = conn.execute("UPDATE memories SET synced_at = NULL WHERE deleted = 0", [])?;
+    for table in ["entities", "relations"] {
+        if has_column(&conn, table, "synced_at")? {
+            queued += conn.execute(&format!("UPDATE {table} SET synced_at = NULL"), [])?;
+        }
+    }
+    Ok(queued)
+}
+
+fn has_column(conn: &rusqlite::Connection, table: &str, column: &str) -> Result<bool> {
+    let mut stmt = conn.prepare(&format!("SELECT 1 FROM pragma_table_info('{table}') WHERE name = ?1"))?;
+    Ok(stmt.exists([column])?)
+}
+
+/// Fail when `enc_key` no longer opens the account's current PIN verifier —
+/// e.g. a daemon still holding the key from before a re-key. Pushing under it
+/// would leave records no other device can decrypt.
+fn ensure_current_key(enc_key: &[u8; 32], verifier: Option<&str>) -> Result<()> {
+    match verifier {
+        Some(verifier) if !crypto::verify_pin(enc_key, verifier) => anyhow::bail!(
+            "Cached sync key doesn't match the account's current PIN verifier (the key was changed). \
+             Run `ctxovrflw login` to re-enter your PIN."
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// One-line summary of the unsynced backlog, or None when there is nothing to report.
+pub fn backlog_summary(cfg: &Config) -> Option<String> {
+    let pending = pending_count(cfg).unwrap_or(0);
+    match (pending, blocker(cfg)) {
+        (0, _) => None,
+        (n, Some(reason)) => Some(format!("{n} memories waiting to sync (blocked: {reason})")),
+        (n, None) => Some(format!("{n} memories waiting to sync")),
+    }
+}
+
+/// Register `sync_excluded(tags_json, subject)` on a connection so push queries
+/// can skip memories matching `sync_exclude` patterns.
+fn register_sync_exclude(conn: &rusqlite::Connection, patterns: &[String]) -> Result<()> {
+    let patterns: Vec<String> = patterns.iter().map(|p| p.to_lowercase()).collect();
+    conn.create_scalar_function(
+        "sync_excluded",
+        2,
+        rusqlite::functions::FunctionFlags::SQLITE_UTF8 | rusqlite::functions::FunctionFlags::SQLITE_DETERMINISTIC,
+        move |ctx| {
+            if patterns.is_empty() {
+                return Ok(false);
+            }
+            let tags: Vec<String> = serde_json::from_str(&ctx.get::<String>(0)?).unwrap_or_default();
+            let subject: Option<String> = ctx.get(1)?;
+            Ok(is_sync_excluded(&patterns, &tags, subject.as_deref()))
+        },
+    )?;
+    Ok(())
+}
+
+/// Whether a memory with these tags/subject matches any (lowercased) exclude pattern.
+fn is_sync_excluded(patterns: &[String], tags: &[String], subject: Option<&str>) -> bool {
+    let tags: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+    let subject = subject.map(|s| s.to_lowercase());
+    let tag_match = |pat: &str| tags.iter().any(|t| glob_match(pat, t));
+    let subject_match = |pat: &str| subject.as_deref().is_some_and(|s| glob_match(pat, s));
+
+    patterns.iter().any(|p| {
+        if let Some(pat) = p.strip_prefix("tag:") {
+            tag_match(pat)
+        } else if let Some(pat) = p.strip_prefix("subject:") {
+            subject_match(pat)
+        } else {
+            tag_match(p) || subject_match(p)
+        }
+    })
+}
+
+/// Minimal glob: `*` matches any run of characters, `?` any single one, and
+/// everything else is literal. The whole text must match.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // The last `*` seen and where in the text it started matching
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the `*` swallow one more character and retry
+            star = Some((star_p, star_t + 1));
+            p = star_p + 1;
+            t = star_t + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// A local edit that hadn't been synced when a sync cycle started.
+struct LocalEdit {
+    content: String,
+    tags: String,
+    updated_at: String,
+}
+
+/// Snapshot local edits before pushing, so the pull that follows can still tell
+/// which memories were changed on both sides (the push marks them synced).
+fn pending_local_edits() -> Result<HashMap<String, LocalEdit>> {
+    let conn = db::open()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, content, tags, updated_at FROM memories
+         WHERE deleted = 0 AND synced_at IS NOT NULL AND datetime(updated_at) > datetime(synced_at)",
+    )?;
+    let edits = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                LocalEdit { content: row.get(1)?, tags: row.get(2)?, updated_at: row.get(3)? },
+            ))
+        })?
+        .collect::<std::result::Result<HashMap<_, _>, _>>()?;
+    Ok(edits)
+}
+
+/// Tombstones [`purge_tombstones`] removes (`?1` is the retention in days, `?2`
+/// the matching date modifier): ones whose deletion was pushed — synced at
+/// or after their last update — once past retention, and ones never synced at all
+/// after a day. A memory deleted since its last push keeps its tombstone until the
+/// deletion reaches the cloud, or other devices would never learn of it.
+const PURGEABLE_TOMBSTONES: &str = "deleted = 1
+    AND (
+      (synced_at IS NOT NULL AND julianday(synced_at) >= julianday(updated_at)
+        AND (?1 = 0 OR julianday(updated_at) <= julianday('now', ?2)))
+      OR (synced_at IS NULL AND julianday(updated_at) <= julianday('now', '-1 day'))
+    )";
+
+/// Purge tombstones (soft-deleted memories) whose deletion has been pushed and that
+/// are older than `tombstone_retention_days` (immediately when 0), or that were never
+/// synced and are older than 1 day.
 /// This permanently removes them from the local DB to reclaim space.
 /// Cloud-side cleanup happens separately via the cloud API's purge endpoint.
-fn purge_tombstones() -> Result<usize> {
+fn purge_tombstones(cfg: &Config) -> Result<usize> {
     let conn = db::open()?;
+    register_sync_exclude(&conn, &cfg.sync_exclude)?;
 
     // If too many unsynced tombstones, mark them as synced to unblock push queue
     let unsynced_count: i64 = conn.query_row(
-        "SELECT COUNT(*) FROM memories WHERE deleted = 1 AND synced_at IS NULL",
+        "SELECT COUNT(*) FROM memories WHERE deleted = 1 AND synced_at IS NULL AND NOT sync_excluded(tags, subject)",
         [],
         |r| r.get(0),
     )?;
     if unsynced_count > 100 {
         let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
         let _ = conn.execute(
-            "UPDATE memories SET synced_at = ?1 WHERE deleted = 1 AND synced_at IS NULL",
+            "UPDATE memories SET synced_at = ?1
+             WHERE deleted = 1 AND synced_at IS NULL AND NOT sync_excluded(tags, subject)",
             rusqlite::params![now],
         );
         tracing::warn!("Marked {unsynced_count} unsynced tombstones as synced to unblock push queue");
     }
 
+    // 0 skips the age check, so a tombstone is gone as soon as its deletion is pushed
+    let retention = cfg.tombstone_retention_days;
+    let cutoff = format!("-{retention} days");
+
     // Delete vectors first (FK-like cleanup)
-    conn.execute(
-        "DELETE FROM memory_vectors WHERE id IN (
-            SELECT id FROM memories
-            WHERE deleted = 1
-              AND (
-                (synced_at IS NOT NULL AND updated_at <= datetime('now', '-7 days'))
-                OR (synced_at IS NULL AND updated_at <= datetime('now', '-1 day'))
-              )
-        )",
-        [],
-    )?;
+    if db::vectors_available() {
+        conn.execute(
+            &format!("DELETE FROM memory_vectors WHERE id IN (SELECT id FROM memories WHERE {PURGEABLE_TOMBSTONES})"),
+            rusqlite::params![retention, cutoff],
+        )?;
+    }
 
     // Then permanently remove the tombstones
     let purged = conn.execute(
-        "DELETE FROM memories
-         WHERE deleted = 1
-           AND (
-             (synced_at IS NOT NULL AND updated_at <= datetime('now', '-7 days'))
-             OR (synced_at IS NULL AND updated_at <= datetime('now', '-1 day'))
-           )",
-        [],
+        &format!("DELETE FROM memories WHERE {PURGEABLE_TOMBSTONES}"),
+        rusqlite::params![retention, cutoff],
     )?;
 
     if purged > 0 {
-        tracing::info!("Purged {purged} tombstones (synced>7d or unsynced>1d)");
+        tracing::info!("Purged {purged} tombstones (synced>{retention}d or unsynced>1d)");
     }
 
     Ok(purged)
 }
 
-/// Encrypt a memory's content + tags for cloud storage.
+/// Encrypt a memory's content + tags for cloud storage, each bound to the memory
+/// id so the server can't swap ciphertext between records.
 /// Returns (encrypted_content, encrypted_tags_json, content_hash).
 fn encrypt_memory(
     key: &[u8; 32],
+    cipher: crypto::Cipher,
+    id: &str,
     content: &str,
     tags: &[String],
 ) -> Result<(String, String, String)> {
-    let enc_content = crypto::encrypt_string(key, content)?;
+    let enc_content = crypto::encrypt_string_bound(cipher, key, content, &record_aad("memory", id))?;
     let tags_json = serde_json::to_string(tags)?;
-    let enc_tags = crypto::encrypt_string(key, &tags_json)?;
+    let enc_tags = crypto::encrypt_string_bound(cipher, key, &tags_json, &record_aad("memory-tags", id))?;
     let hash = crypto::content_hash(content);
     Ok((enc_content, enc_tags, hash))
 }
 
+/// Associated data binding a synced record's ciphertext to its kind and id.
+fn record_aad(kind: &str, id: &str) -> String {
+    format!("ctxovrflw/{kind}/{id}")
+}
+
 /// Max memories to fetch at once (pre-batching)
 const FETCH_BATCH_SIZE: usize = 200;
 /// Target max payload size per push request (leave headroom below 1MB cloud limit)
@@ -191,12 +524,16 @@ async fn push(
     device_id: &str,
     enc_key: &[u8; 32],
 ) -> Result<usize> {
+    // Another process may have re-keyed since `cfg` was loaded
+    let verifier = Config::load().ok().and_then(|c| c.pin_verifier).or_else(|| cfg.pin_verifier.clone());
+    ensure_current_key(enc_key, verifier.as_deref())?;
+
     let conn = db::open()?;
-    let client = reqwest::Client::new();
+    register_sync_exclude(&conn, &cfg.sync_exclude)?;
     let mut total_synced: usize = 0;
 
     loop {
-        let all_unsynced = get_unsynced_memories(&conn, enc_key, FETCH_BATCH_SIZE)?;
+        let all_unsynced = get_unsynced_memories(&conn, enc_key, cfg.sync_cipher, FETCH_BATCH_SIZE)?;
         if all_unsynced.is_empty() {
             break;
         }
@@ -245,24 +582,7 @@ async fn push(
             .filter_map(|m| m.get("id").and_then(|v| v.as_str()).map(String::from))
             .collect();
 
-        let resp = client
-            .post(format!("{}/v1/sync/push", cfg.cloud_url))
-            .header("Authorization", format!("Bearer {api_key}"))
-            .json(&serde_json::json!({
-                "device_id": device_id,
-                "memories": batch,
-                "encrypted": true,
-            }))
-            .send()
-            .await?;
-
-        if !resp.status().is_success() {
-            let status = resp.status();
-            let body = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Push failed ({}): {}", status, body);
-        }
-
-        let result: PushResponse = resp.json().await?;
+        let result = push_records(cfg, api_key, device_id, &batch).await?;
 
         // Mark successfully pushed memories with synced_at timestamp
         if result.synced > 0 {
@@ -297,8 +617,14 @@ async fn pull(
     api_key: &str,
     device_id: &str,
     enc_key: &[u8; 32],
+    local_edits: &HashMap<String, LocalEdit>,
 ) -> Result<(usize, usize)> {
-    let client = reqwest::Client::new();
+    let result = fetch_pull(cfg, api_key, device_id).await?;
+    apply_pull(result, enc_key, local_edits)
+}
+
+async fn fetch_pull(cfg: &Config, api_key: &str, device_id: &str) -> Result<PullResponse> {
+    let client = crate::config::http_client()?;
     let resp = client
         .post(format!("{}/v1/sync/pull", cfg.cloud_url))
         .header("Authorization", format!("Bearer {api_key}"))
@@ -314,7 +640,16 @@ async fn pull(
         anyhow::bail!("Pull failed ({}): {}", status, body);
     }
 
-    let result: PullResponse = resp.json().await?;
+    Ok(resp.json().await?)
+}
+
+/// Merge a pull response into the local DB and apply its purges.
+/// Returns (pulled, purged).
+fn apply_pull(
+    result: PullResponse,
+    enc_key: &[u8; 32],
+    local_edits: &HashMap<String, LocalEdit>,
+) -> Result<(usize, usize)> {
     let count = result.memories.len();
 
     // Save capability token from pull response (refreshes every sync cycle)
@@ -328,7 +663,7 @@ async fn pull(
 
     if count > 0 {
         let conn = db::open()?;
-        merge_remote_memories(&conn, &result.memories, enc_key)?;
+        merge_remote_memories(&conn, &result.memories, enc_key, local_edits)?;
     }
 
     if !result.purge_ids.is_empty() {
@@ -354,6 +689,22 @@ async fn pull(
     Ok((count, purge_count))
 }
 
+/// Push a memory in the background right after a write, unless sync is
+/// deferred — per call via `defer`, else by `defer_sync` in config — in which
+/// case the next periodic sync pushes it along with the rest of the backlog.
+pub fn push_soon(cfg: &Config, memory_id: &str, defer: Option<bool>) {
+    if !cfg.is_logged_in() || defer.unwrap_or(cfg.defer_sync) {
+        return;
+    }
+    let cfg = cfg.clone();
+    let id = memory_id.to_string();
+    tokio::spawn(async move {
+        if let Err(e) = push_one(&cfg, &id).await {
+            tracing::warn!("Immediate sync of {id} failed: {e}");
+        }
+    });
+}
+
 /// Push a single memory to the cloud immediately.
 pub async fn push_one(cfg: &Config, memory_id: &str) -> Result<bool> {
     if !cfg.is_logged_in() {
@@ -364,11 +715,12 @@ pub async fn push_one(cfg: &Config, memory_id: &str) -> Result<bool> {
     let device_id = cfg.device_id.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in — no device ID"))?;
     let enc_key = get_encryption_key(cfg)?;
     let conn = db::open()?;
+    register_sync_exclude(&conn, &cfg.sync_exclude)?;
 
     let mem: Option<serde_json::Value> = conn
         .query_row(
-            "SELECT id, content, type, tags, subject, source, agent_id, deleted, created_at, updated_at, expires_at
-             FROM memories WHERE id = ?1",
+            "SELECT id, content, type, tags, subject, source, agent_id, deleted, created_at, updated_at, expires_at, workspace
+             FROM memories WHERE id = ?1 AND NOT sync_excluded(tags, subject)",
             rusqlite::params![memory_id],
             |row| {
                 let tags_str: String = row.get(3)?;
@@ -385,6 +737,7 @@ pub async fn push_one(cfg: &Config, memory_id: &str) -> Result<bool> {
                     "source": row.get::<_, Option<String>>(5)?,
                     "agent_id": row.get::<_, Option<String>>(6)?,
                     "expires_at": row.get::<_, Option<String>>(10)?,
+                    "workspace": row.get::<_, String>(11)?,
                     "deleted": deleted,
                     "created_at": row.get::<_, String>(8)?,
                     "updated_at": row.get::<_, String>(9)?,
@@ -400,18 +753,19 @@ pub async fn push_one(cfg: &Config, memory_id: &str) -> Result<bool> {
 
     // Encrypt content + tags before pushing
     {
+        let id = mem["id"].as_str().unwrap_or("").to_string();
         let content = mem["content"].as_str().unwrap_or("");
         let tags: Vec<String> = mem["tags"]
             .as_array()
             .map(|a| a.iter().filter_map(|v| v.as_str().map(String::from)).collect())
             .unwrap_or_default();
-        let (enc_content, enc_tags, hash) = encrypt_memory(&enc_key, content, &tags)?;
+        let (enc_content, enc_tags, hash) = encrypt_memory(&enc_key, cfg.sync_cipher, &id, content, &tags)?;
         mem["content"] = serde_json::Value::String(enc_content);
         mem["tags"] = serde_json::json!([enc_tags]);
         mem["content_hash"] = serde_json::Value::String(hash);
     }
 
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let resp = client
         .post(format!("{}/v1/sync/push", cfg.cloud_url))
         .header("Authorization", format!("Bearer {api_key}"))
@@ -436,17 +790,151 @@ pub async fn push_one(cfg: &Config, memory_id: &str) -> Result<bool> {
     Ok(false)
 }
 
+/// Tag on the throwaway memory `self_test` round-trips.
+const SELF_TEST_TAG: &str = "ctxovrflw:self-test";
+
+/// Round-trip a throwaway memory through the cloud (`sync --self-test`, and
+/// after login): encrypt, push, pull it back, decrypt and compare, then push
+/// a tombstone for it. Unlike the PIN verifier, which only proves the local
+/// key, this shows the key, server and record format all agree before a
+/// whole vault is pushed. Nothing local is pushed; anything else the pull
+/// returns is merged as a normal sync would, so it isn't skipped next time.
+pub async fn self_test(cfg: &Config) -> Result<()> {
+    if !cfg.is_logged_in() {
+        anyhow::bail!("Not logged in. Run `ctxovrflw login` first.");
+    }
+    let api_key = cfg.api_key.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in — no API key"))?;
+    let device_id = cfg.device_id.as_deref().ok_or_else(|| anyhow::anyhow!("Not logged in — no device ID"))?;
+    let enc_key = get_encryption_key(cfg)?;
+
+    println!("Sync self-test against {}", cfg.cloud_url);
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let content = format!("ctxovrflw sync self-test {id}");
+    let tags = vec![SELF_TEST_TAG.to_string()];
+    let now = chrono::Utc::now();
+    let created_at = now.format("%Y-%m-%d %H:%M:%S").to_string();
+    let (enc_content, enc_tags, hash) = encrypt_memory(&enc_key, cfg.sync_cipher, &id, &content, &tags)?;
+    let mut record = serde_json::json!({
+        "id": id,
+        "content": enc_content,
+        "memory_type": "semantic",
+        "tags": [enc_tags],
+        "subject": null,
+        "source": "self-test",
+        "agent_id": null,
+        "expires_at": null,
+        "workspace": db::DEFAULT_WORKSPACE,
+        "deleted": false,
+        "created_at": created_at,
+        "updated_at": created_at,
+        "content_hash": hash,
+    });
+    println!("  ✓ Encrypted test memory ({})", serde_json::to_value(cfg.sync_cipher)?.as_str().unwrap_or("?"));
+
+    let pushed = push_records(cfg, api_key, device_id, std::slice::from_ref(&record)).await?;
+    if pushed.synced == 0 {
+        println!("  ✗ Server accepted the push but stored nothing");
+        anyhow::bail!("sync self-test failed: test memory was rejected by {}", cfg.cloud_url);
+    }
+    println!("  ✓ Pushed");
+
+    let mut result = fetch_pull(cfg, api_key, device_id).await?;
+    let echoed = result.memories.iter().position(|m| m.id == id).map(|i| result.memories.remove(i));
+    let outcome = match echoed {
+        None => Err("the test memory didn't come back from the server".to_string()),
+        Some(remote) => verify_round_trip(&enc_key, &remote, &content, &tags),
+    };
+    let local_edits = pending_local_edits()?;
+    apply_pull(result, &enc_key, &local_edits)?;
+
+    // Delete it whatever the outcome; a tombstone for an id no device has is ignored on pull
+    record["deleted"] = serde_json::json!(true);
+    record["updated_at"] = serde_json::json!((now + chrono::Duration::seconds(1)).format("%Y-%m-%d %H:%M:%S").to_string());
+    let deleted = push_records(cfg, api_key, device_id, std::slice::from_ref(&record)).await;
+
+    match &outcome {
+        Ok(()) => println!("  ✓ Pulled back, decrypted and verified (content, tags, hash)"),
+        Err(problem) => {
+            println!("  ✗ Round trip failed: {problem}");
+            println!("    Check that every device uses the same account email and sync PIN, and that");
+            println!("    the server is compatible (`ctxovrflw sync --check`). Nothing else was pushed.");
+        }
+    }
+    match deleted {
+        Ok(_) => println!("  ✓ Test memory deleted"),
+        Err(e) => println!("  ⚠ Couldn't delete the test memory ({id}): {e}"),
+    }
+    if outcome.is_err() {
+        anyhow::bail!("sync self-test against {} failed", cfg.cloud_url);
+    }
+    Ok(())
+}
+
+/// Check a pulled copy of the self-test memory against what was pushed.
+fn verify_round_trip(
+    enc_key: &[u8; 32],
+    remote: &RemoteMemory,
+    content: &str,
+    tags: &[String],
+) -> std::result::Result<(), String> {
+    let decrypted = crypto::decrypt_string_bound(enc_key, &remote.content, &record_aad("memory", &remote.id))
+        .map_err(|e| format!("content didn't decrypt: {e}"))?;
+    if decrypted != content {
+        return Err("content decrypted to something other than what was pushed".to_string());
+    }
+    if remote.content_hash.as_deref().is_some_and(|h| !h.eq_ignore_ascii_case(&crypto::content_hash(content))) {
+        return Err("the server returned a different content hash".to_string());
+    }
+    let enc_tags = remote.tags.first().ok_or("tags were dropped by the server")?;
+    let tags_json = crypto::decrypt_string_bound(enc_key, enc_tags, &record_aad("memory-tags", &remote.id))
+        .map_err(|e| format!("tags didn't decrypt: {e}"))?;
+    if serde_json::from_str::<Vec<String>>(&tags_json).ok().as_deref() != Some(tags) {
+        return Err("tags decrypted to something other than what was pushed".to_string());
+    }
+    Ok(())
+}
+
+/// POST already-encrypted records to `/v1/sync/push` — the request behind
+/// both a regular push batch and the self-test.
+async fn push_records(
+    cfg: &Config,
+    api_key: &str,
+    device_id: &str,
+    memories: &[serde_json::Value],
+) -> Result<PushResponse> {
+    let resp = crate::config::http_client()?
+        .post(format!("{}/v1/sync/push", cfg.cloud_url))
+        .header("Authorization", format!("Bearer {api_key}"))
+        .json(&serde_json::json!({
+            "device_id": device_id,
+            "memories": memories,
+            "encrypted": true,
+        }))
+        .send()
+        .await?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let body = resp.text().await.unwrap_or_default();
+        anyhow::bail!("Push failed ({}): {}", status, body);
+    }
+    Ok(resp.json().await?)
+}
+
 /// Get memories that need to be pushed (never synced, or updated after last sync).
 /// Returns at most `limit` memories, encrypting content if key is provided.
 fn get_unsynced_memories(
     conn: &rusqlite::Connection,
     enc_key: &[u8; 32],
+    cipher: crypto::Cipher,
     limit: usize,
 ) -> Result<Vec<serde_json::Value>> {
     let mut stmt = conn.prepare(
-        "SELECT id, content, type, tags, subject, source, agent_id, deleted, created_at, updated_at, expires_at
+        "SELECT id, content, type, tags, subject, source, agent_id, deleted, created_at, updated_at, expires_at, workspace, skip_embedding
          FROM memories
-         WHERE synced_at IS NULL OR updated_at > synced_at
+         WHERE (synced_at IS NULL OR updated_at > synced_at)
+         AND NOT sync_excluded(tags, subject)
          ORDER BY updated_at ASC
          LIMIT ?1"
     )?;
@@ -470,13 +958,15 @@ fn get_unsynced_memories(
                 row.get::<_, String>(8)?,  // created_at
                 row.get::<_, String>(9)?,  // updated_at
                 row.get::<_, Option<String>>(10)?, // expires_at
+                row.get::<_, String>(11)?, // workspace
+                row.get::<_, bool>(12)?, // skip_embedding
             ))
         })?
         .collect::<std::result::Result<Vec<_>, _>>()?;
 
     let mut result = Vec::with_capacity(memories.len());
-    for (id, content, mtype, tags, subject, source, agent_id, deleted, created_at, updated_at, expires_at) in memories {
-        let (enc_content, enc_tags, hash) = encrypt_memory(enc_key, &content, &tags)
+    for (id, content, mtype, tags, subject, source, agent_id, deleted, created_at, updated_at, expires_at, workspace, skip_embedding) in memories {
+        let (enc_content, enc_tags, hash) = encrypt_memory(enc_key, cipher, &id, &content, &tags)
             .map_err(|e| anyhow::anyhow!("Encryption failed for {id}: {e}"))?;
         let mem = serde_json::json!({
             "id": id,
@@ -487,6 +977,8 @@ fn get_unsynced_memories(
             "source": source,
             "agent_id": agent_id,
             "expires_at": expires_at,
+            "workspace": workspace,
+            "skip_embedding": skip_embedding,
             "deleted": deleted,
             "created_at": created_at,
             "updated_at": updated_at,
@@ -504,22 +996,40 @@ fn merge_remote_memories(
     conn: &rusqlite::Connection,
     memories: &[RemoteMemory],
     enc_key: &[u8; 32],
+    local_edits: &HashMap<String, LocalEdit>,
 ) -> Result<()> {
-    // Use the global singleton embedder (loaded once at startup, shared everywhere)
+    // Use the global embedder pool (loaded once at startup, shared everywhere)
     let embedder = crate::embed::get_or_init().ok();
+    let mut undecryptable = 0usize;
 
     for mem in memories {
         // Decrypt content (all cloud data must be encrypted)
-        let decrypted_content = match crypto::decrypt_string(enc_key, &mem.content) {
+        let decrypted_content = match crypto::decrypt_string_bound(enc_key, &mem.content, &record_aad("memory", &mem.id)) {
             Ok(c) => c,
             Err(e) => {
                 tracing::warn!("Skipping memory {} — decryption failed: {e}", mem.id);
+                undecryptable += 1;
                 continue; // Don't store garbled data
             }
         };
 
+        // Verify the decrypted content against the pusher's hash — a mismatch means
+        // a key/format mismatch or server-side corruption, so don't store it
+        if !mem.deleted
+            && let Some(expected) = mem.content_hash.as_deref().filter(|h| !h.is_empty())
+        {
+            let actual = crypto::content_hash(&decrypted_content);
+            if !actual.eq_ignore_ascii_case(expected) {
+                tracing::warn!(
+                    "Skipping memory {} — content hash mismatch after decryption (expected {expected}, got {actual}); possible corruption",
+                    mem.id
+                );
+                continue;
+            }
+        }
+
         let decrypted_tags: Vec<String> = if let Some(enc_tags) = mem.tags.first() {
-            match crypto::decrypt_string(enc_key, enc_tags) {
+            match crypto::decrypt_string_bound(enc_key, enc_tags, &record_aad("memory-tags", &mem.id)) {
                 Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
                 Err(e) => {
                     tracing::warn!("Memory {} — tags decryption failed: {e}, using empty tags", mem.id);
@@ -544,6 +1054,32 @@ fn merge_remote_memories(
         let exists = local_state.is_some();
         let locally_deleted = local_state.map(|(d,)| d).unwrap_or(false);
 
+        let tags_json = serde_json::to_string(&tags)?;
+
+        // Both sides edited since the last sync with different content: keep a
+        // record of the losing version instead of overwriting it silently
+        if let Some(local) = local_edits.get(&mem.id)
+            && (local.content != content || local.tags != tags_json)
+        {
+            // Remote deletions always apply; edits are last-writer-wins
+            let winner = if mem.deleted || mem.updated_at > local.updated_at { "remote" } else { "local" };
+            if let Err(e) = crate::db::conflicts::record(
+                conn,
+                &mem.id,
+                &local.content,
+                &local.tags,
+                &local.updated_at,
+                &content,
+                &tags_json,
+                &mem.updated_at,
+                winner,
+            ) {
+                tracing::warn!("Failed to record sync conflict for {}: {e}", mem.id);
+            } else {
+                tracing::info!("Sync conflict on {} — {winner} version kept, other logged", mem.id);
+            }
+        }
+
         if mem.deleted {
             if exists {
                 conn.execute(
@@ -577,40 +1113,37 @@ fn merge_remote_memories(
             }
         }
 
-        let tags_json = serde_json::to_string(&tags)?;
-
         if exists {
             let rows = conn.execute(
                 "UPDATE memories SET content = ?1, type = ?2, tags = ?3, subject = ?4, source = ?5,
-                 agent_id = ?6, expires_at = ?7, updated_at = ?8, synced_at = ?8, deleted = 0
+                 agent_id = ?6, expires_at = ?7, updated_at = ?8, synced_at = ?8, deleted = 0,
+                 workspace = COALESCE(?10, workspace), skip_embedding = skip_embedding OR ?11
                  WHERE id = ?9 AND updated_at < ?8",
-                rusqlite::params![content, mem.memory_type, tags_json, mem.subject, mem.source, mem.agent_id, mem.expires_at, mem.updated_at, mem.id],
+                rusqlite::params![content, mem.memory_type, tags_json, mem.subject, mem.source, mem.agent_id, mem.expires_at, mem.updated_at, mem.id, mem.workspace, mem.skip_embedding],
             )?;
             // Re-embed if content was actually updated
-            if rows > 0 {
-                if let Some(ref emb) = embedder { let mut emb = emb.lock().unwrap_or_else(|e| e.into_inner());
+            if rows > 0 && !crate::db::memories::skips_embedding(conn, &mem.id) {
+                if let Some(ref emb) = embedder { let mut emb = emb.acquire();
                     if let Ok(embedding) = emb.embed(&content) {
-                        let _ = conn.execute(
-                            "INSERT OR REPLACE INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-                            rusqlite::params![mem.id, crate::db::memories::bytemuck_cast_pub(&embedding)],
-                        );
+                        let _ = crate::db::memories::upsert_vector(conn, &mem.id, &embedding);
                     }
                 }
             }
         } else {
             conn.execute(
-                "INSERT INTO memories (id, content, type, tags, subject, source, agent_id, expires_at, deleted, created_at, updated_at, synced_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?10, ?10)",
-                rusqlite::params![mem.id, content, mem.memory_type, tags_json, mem.subject, mem.source, mem.agent_id, mem.expires_at, mem.created_at, mem.updated_at],
+                "INSERT INTO memories (id, content, type, tags, subject, source, agent_id, expires_at, deleted, created_at, updated_at, synced_at, workspace, skip_embedding)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, 0, ?9, ?10, ?10, ?11, ?12)",
+                rusqlite::params![
+                    mem.id, content, mem.memory_type, tags_json, mem.subject, mem.source, mem.agent_id, mem.expires_at,
+                    mem.created_at, mem.updated_at, mem.workspace.as_deref().unwrap_or(crate::db::DEFAULT_WORKSPACE),
+                    mem.skip_embedding,
+                ],
             )?;
 
             // Generate embedding for the new memory
-            if let Some(ref emb) = embedder { let mut emb = emb.lock().unwrap_or_else(|e| e.into_inner());
+            if !mem.skip_embedding && let Some(ref emb) = embedder { let mut emb = emb.acquire();
                 if let Ok(embedding) = emb.embed(&content) {
-                    let _ = conn.execute(
-                        "INSERT OR REPLACE INTO memory_vectors (id, embedding) VALUES (?1, ?2)",
-                        rusqlite::params![mem.id, crate::db::memories::bytemuck_cast_pub(&embedding)],
-                    );
+                    let _ = crate::db::memories::upsert_vector(conn, &mem.id, &embedding);
                 }
             }
         }
@@ -626,6 +1159,12 @@ fn merge_remote_memories(
         );
     }
 
+    if undecryptable > 0 {
+        tracing::warn!(
+            "{undecryptable} pulled memories couldn't be decrypted — if the sync key was upgraded on another device, run `ctxovrflw logout` and `ctxovrflw login` here"
+        );
+    }
+
     Ok(())
 }
 
@@ -705,7 +1244,7 @@ async fn push_graph(
             "type": etype,
             "metadata": metadata.as_deref().and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok()),
         });
-        let encrypted = crypto::encrypt_string(enc_key, &plaintext.to_string())?;
+        let encrypted = crypto::encrypt_string_bound(cfg.sync_cipher, enc_key, &plaintext.to_string(), &record_aad("entity", id))?;
         remote_entities.push(RemoteEntity {
             id: id.clone(),
             content: encrypted,
@@ -751,7 +1290,7 @@ async fn push_graph(
             "source_memory_id": source_memory_id,
             "metadata": metadata.as_deref().and_then(|s| serde_json::from_str::<serde_json::Value>(s).ok()),
         });
-        let encrypted = crypto::encrypt_string(enc_key, &plaintext.to_string())?;
+        let encrypted = crypto::encrypt_string_bound(cfg.sync_cipher, enc_key, &plaintext.to_string(), &record_aad("relation", id))?;
         remote_relations.push(RemoteRelation {
             id: id.clone(),
             content: encrypted,
@@ -766,7 +1305,7 @@ async fn push_graph(
         return Ok((0, 0));
     }
 
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let resp = client
         .post(format!("{}/v1/graph/push", cfg.cloud_url))
         .header("Authorization", format!("Bearer {api_key}"))
@@ -824,7 +1363,7 @@ async fn pull_graph(
         .ok()
         .flatten();
 
-    let client = reqwest::Client::new();
+    let client = crate::config::http_client()?;
     let mut body = serde_json::json!({ "device_id": device_id });
     if let Some(since) = &last_sync {
         body["since"] = serde_json::Value::String(since.clone());
@@ -855,7 +1394,7 @@ async fn pull_graph(
 
     // Merge entities
     for ent in &result.entities {
-        let decrypted = match crypto::decrypt_string(enc_key, &ent.content) {
+        let decrypted = match crypto::decrypt_string_bound(enc_key, &ent.content, &record_aad("entity", &ent.id)) {
             Ok(c) => c,
             Err(e) => {
                 tracing::warn!("Skipping entity {} — decryption failed: {e}", ent.id);
@@ -902,7 +1441,7 @@ async fn pull_graph(
 
     // Merge relations
     for rel in &result.relations {
-        let decrypted = match crypto::decrypt_string(enc_key, &rel.content) {
+        let decrypted = match crypto::decrypt_string_bound(enc_key, &rel.content, &record_aad("relation", &rel.id)) {
             Ok(c) => c,
             Err(e) => {
                 tracing::warn!("Skipping relation {} — decryption failed: {e}", rel.id);
@@ -965,3 +1504,179 @@ async fn pull_graph(
 
     Ok((ent_count, rel_count))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A tombstone deleted `deleted_days_ago`, last synced `synced_days_ago`.
+    fn tombstone(conn: &rusqlite::Connection, deleted_days_ago: i64, synced_days_ago: Option<i64>) -> String {
+        let memory = db::memories::store(conn, "old note", &db::memories::MemoryType::Semantic, &[], None, None, None, None).unwrap();
+        let ago = |days: i64| (chrono::Utc::now() - chrono::Duration::days(days) - chrono::Duration::minutes(1)).to_rfc3339();
+        conn.execute(
+            "UPDATE memories SET deleted = 1, updated_at = ?1, synced_at = ?2 WHERE id = ?3",
+            rusqlite::params![ago(deleted_days_ago), synced_days_ago.map(|d| ago(d).replace('T', " ")), memory.id],
+        )
+        .unwrap();
+        memory.id
+    }
+
+    #[test]
+    fn test_push_refuses_a_replaced_key() {
+        let old_key = [1u8; 32];
+        let new_key = [2u8; 32];
+        let verifier = crypto::create_pin_verifier_with_iterations(&new_key, 900_000).unwrap();
+        assert!(ensure_current_key(&new_key, Some(&verifier)).is_ok());
+        assert!(ensure_current_key(&old_key, Some(&verifier)).is_err());
+        assert!(ensure_current_key(&old_key, None).is_ok());
+    }
+
+    #[test]
+    fn test_full_repush_queues_graph_rows() {
+        let _data = crate::config::test_data_dir();
+        let conn = db::open().unwrap();
+        let live = db::memories::store(&conn, "note", &db::memories::MemoryType::Semantic, &[], None, None, None, None).unwrap();
+        conn.execute("UPDATE memories SET synced_at = datetime('now') WHERE id = ?1", [&live.id]).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS entities (id TEXT PRIMARY KEY, name TEXT NOT NULL);
+             CREATE TABLE IF NOT EXISTS relations (id TEXT PRIMARY KEY, source_id TEXT, target_id TEXT, relation_type TEXT NOT NULL);
+             ALTER TABLE entities ADD COLUMN synced_at TEXT;
+             ALTER TABLE relations ADD COLUMN synced_at TEXT;
+             INSERT INTO entities (id, name, synced_at) VALUES ('e1', 'alice', datetime('now'));
+             INSERT INTO relations (id, source_id, target_id, relation_type, synced_at) VALUES ('r1', 'e1', 'e1', 'knows', datetime('now'));",
+        )
+        .unwrap();
+
+        assert_eq!(queue_full_repush().unwrap(), 3);
+        let unsynced = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {table} WHERE synced_at IS NULL"), [], |r| r.get(0)).unwrap()
+        };
+        assert_eq!(unsynced("memories"), 1);
+        assert_eq!(unsynced("entities"), 1);
+        assert_eq!(unsynced("relations"), 1);
+    }
+
+    /// What the server would hand back for a memory pushed with `key`.
+    fn echoed(key: &[u8; 32], content: &str, tags: &[String]) -> RemoteMemory {
+        let id = "11111111-2222-3333-4444-555555555555";
+        let (enc_content, enc_tags, hash) = encrypt_memory(key, crypto::Cipher::default(), id, content, tags).unwrap();
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "content": enc_content,
+            "memory_type": "semantic",
+            "tags": [enc_tags],
+            "source": "self-test",
+            "content_hash": hash,
+            "deleted": false,
+            "created_at": "2026-01-01 00:00:00",
+            "updated_at": "2026-01-01 00:00:00",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_round_trip_verifies() {
+        let key = [7u8; 32];
+        let tags = vec![SELF_TEST_TAG.to_string()];
+        let remote = echoed(&key, "probe", &tags);
+        assert_eq!(verify_round_trip(&key, &remote, "probe", &tags), Ok(()));
+    }
+
+    #[test]
+    fn test_round_trip_wrong_key() {
+        let tags = vec![SELF_TEST_TAG.to_string()];
+        let remote = echoed(&[7u8; 32], "probe", &tags);
+        let err = verify_round_trip(&[8u8; 32], &remote, "probe", &tags).unwrap_err();
+        assert!(err.starts_with("content didn't decrypt"), "{err}");
+    }
+
+    #[test]
+    fn test_round_trip_hash_mismatch() {
+        let key = [7u8; 32];
+        let tags = vec![SELF_TEST_TAG.to_string()];
+        let mut remote = echoed(&key, "probe", &tags);
+        remote.content_hash = Some(crypto::content_hash("something else"));
+        let err = verify_round_trip(&key, &remote, "probe", &tags).unwrap_err();
+        assert!(err.contains("content hash"), "{err}");
+    }
+
+    #[test]
+    fn test_round_trip_dropped_tags() {
+        let key = [7u8; 32];
+        let tags = vec![SELF_TEST_TAG.to_string()];
+        let mut remote = echoed(&key, "probe", &tags);
+        remote.tags.clear();
+        let err = verify_round_trip(&key, &remote, "probe", &tags).unwrap_err();
+        assert!(err.contains("tags were dropped"), "{err}");
+    }
+
+    fn exists(conn: &rusqlite::Connection, id: &str) -> bool {
+        conn.query_row("SELECT COUNT(*) FROM memories WHERE id = ?1", [id], |r| r.get::<_, i64>(0)).unwrap() > 0
+    }
+
+    #[test]
+    fn test_purge_with_zero_retention() {
+        let _data = crate::config::test_data_dir();
+        let conn = db::open().unwrap();
+        // Deleted, then the deletion pushed
+        let pushed = tombstone(&conn, 1, Some(0));
+        // Pushed earlier, deleted since: the cloud doesn't know yet
+        let unpushed = tombstone(&conn, 0, Some(2));
+        let never_synced = tombstone(&conn, 0, None);
+
+        let cfg = Config { tombstone_retention_days: 0, ..Config::default() };
+        assert_eq!(purge_tombstones(&cfg).unwrap(), 1);
+        assert!(!exists(&conn, &pushed));
+        assert!(exists(&conn, &unpushed));
+        assert!(exists(&conn, &never_synced));
+    }
+
+    #[test]
+    fn test_purge_with_retention_days() {
+        let _data = crate::config::test_data_dir();
+        let conn = db::open().unwrap();
+        let expired = tombstone(&conn, 8, Some(7));
+        let recent = tombstone(&conn, 3, Some(2));
+        let unpushed = tombstone(&conn, 30, Some(31));
+        let stale_local = tombstone(&conn, 2, None);
+        let fresh_local = tombstone(&conn, 0, None);
+
+        let cfg = Config { tombstone_retention_days: 7, ..Config::default() };
+        assert_eq!(purge_tombstones(&cfg).unwrap(), 2);
+        assert!(!exists(&conn, &expired));
+        assert!(exists(&conn, &recent));
+        assert!(exists(&conn, &unpushed));
+        assert!(!exists(&conn, &stale_local));
+        assert!(exists(&conn, &fresh_local));
+    }
+
+    #[test]
+    fn test_glob_star() {
+        assert!(glob_match("scratch*", "scratch"));
+        assert!(glob_match("scratch*", "scratchpad"));
+        assert!(glob_match("*-local", "notes-local"));
+        assert!(glob_match("a*b*c", "a-x-b-y-c"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("**", "anything"));
+        assert!(!glob_match("a*b*c", "a-x-c-y-b"));
+        assert!(!glob_match("a*a", "a"));
+    }
+
+    #[test]
+    fn test_glob_question_mark() {
+        assert!(glob_match("v?", "v1"));
+        assert!(glob_match("t?st*", "test-suite"));
+        assert!(glob_match("caf?", "café"));
+        assert!(!glob_match("v?", "v"));
+        assert!(!glob_match("v?", "v12"));
+    }
+
+    #[test]
+    fn test_glob_is_anchored() {
+        assert!(glob_match("local-only", "local-only"));
+        assert!(!glob_match("local", "local-only"));
+        assert!(!glob_match("only", "local-only"));
+        assert!(!glob_match("local*", "not-local"));
+        assert!(!glob_match("*local", "local-not"));
+    }
+}
@@ -30,6 +30,23 @@ pub fn parse_ttl(ttl: &str) -> Result<String, String> {
     Ok(expires.to_rfc3339())
 }
 
+/// Parse a point in time: RFC 3339, a plain date (midnight UTC), or a
+/// relative duration like "30m", "2h", "7d" meaning that long ago.
+pub fn parse_time(s: &str) -> Result<chrono::DateTime<Utc>, String> {
+    if let Ok(t) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Ok(t.to_utc());
+    }
+    if let Ok(d) = chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+        return Ok(d.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    // Relative: reuse the TTL parser and mirror the offset into the past
+    let future = parse_ttl(s)
+        .map_err(|_| format!("Invalid time '{s}'. Use RFC 3339, YYYY-MM-DD, or a duration like 2h / 7d"))?;
+    let future = chrono::DateTime::parse_from_rfc3339(&future).map_err(|e| e.to_string())?.to_utc();
+    let now = Utc::now();
+    Ok(now - (future - now))
+}
+
 /// Resolve expiry from ttl or expires_at. Returns Ok(Some(timestamp)) or Ok(None).
 pub fn resolve_expiry(ttl: Option<&str>, expires_at: Option<&str>) -> Result<Option<String>, String> {
     if let Some(t) = ttl {
@@ -120,3 +137,34 @@ pub fn hex_decode(s: &str) -> Option<Vec<u8>> {
         .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time_absolute() {
+        let t = parse_time("2025-03-04T10:30:00+02:00").unwrap();
+        assert_eq!(t.to_rfc3339(), "2025-03-04T08:30:00+00:00");
+        let d = parse_time("2025-03-04").unwrap();
+        assert_eq!(d.to_rfc3339(), "2025-03-04T00:00:00+00:00");
+        assert_eq!(parse_time("2024-02-29").unwrap().to_rfc3339(), "2024-02-29T00:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_time_relative() {
+        let now = Utc::now();
+        let week_ago = parse_time("7d").unwrap();
+        let drift = (now - chrono::Duration::days(7) - week_ago).num_seconds().abs();
+        assert!(drift <= 2, "7d was off by {drift}s");
+        assert!(parse_time("30m").unwrap() < now);
+        assert!(parse_time(" 2H ").unwrap() < now - chrono::Duration::minutes(119));
+    }
+
+    #[test]
+    fn test_parse_time_rejects_invalid() {
+        for bad in ["", "yesterday", "2025-02-30", "2025-13-01", "7w", "0d", "-3d", "d", "2025-03-04 10:30"] {
+            assert!(parse_time(bad).is_err(), "{bad:?} should be rejected");
+        }
+    }
+}